@@ -0,0 +1,93 @@
+use crate::AssetLoadError;
+use asset::{AssetKey, TypedAsset, WeakTypedAsset};
+use std::{cell::RefCell, collections::HashMap};
+
+/// Weak cache of loaded assets by their key, shared by the asset loaders.
+/// It also tracks the assets being loaded, to detect dependency cycles.
+pub(crate) struct AssetCache {
+    assets: RefCell<HashMap<AssetKey, WeakTypedAsset>>,
+    /// Keys of the assets currently being loaded, outermost first.
+    load_stack: RefCell<Vec<AssetKey>>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        Self {
+            assets: RefCell::new(HashMap::new()),
+            load_stack: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the cached asset if it is still alive.
+    pub fn find(&self, key: &AssetKey) -> Option<TypedAsset> {
+        self.assets
+            .borrow()
+            .get(key)
+            .and_then(|asset| asset.upgrade())
+    }
+
+    #[cfg(feature = "pipeline")]
+    pub fn contains(&self, key: &AssetKey) -> bool {
+        self.assets.borrow().contains_key(key)
+    }
+
+    #[cfg(all(test, feature = "pipeline"))]
+    pub fn is_empty(&self) -> bool {
+        self.assets.borrow().is_empty()
+    }
+
+    #[cfg(all(test, feature = "pipeline"))]
+    pub fn is_loading(&self) -> bool {
+        !self.load_stack.borrow().is_empty()
+    }
+
+    /// Caches an asset and removes entries whose assets have been dropped.
+    pub fn insert(&self, key: &AssetKey, asset: &TypedAsset) {
+        self.assets
+            .borrow_mut()
+            .insert(key.clone(), asset.downgrade());
+        self.purge();
+    }
+
+    #[cfg(feature = "pipeline")]
+    pub fn remove(&self, key: &AssetKey) {
+        self.assets.borrow_mut().remove(key);
+    }
+
+    /// Removes entries whose assets have been dropped.
+    pub fn purge(&self) {
+        self.assets.borrow_mut().retain(|_, asset| asset.is_alive());
+    }
+
+    /// Returns the cached asset, or loads and caches it with `load`.
+    /// Fails with [`AssetLoadError::DependencyCycle`] if the asset is requested again while it is being loaded.
+    pub fn load(
+        &self,
+        key: &AssetKey,
+        load: impl FnOnce() -> Result<TypedAsset, AssetLoadError>,
+    ) -> Result<TypedAsset, AssetLoadError> {
+        if let Some(asset) = self.find(key) {
+            return Ok(asset);
+        }
+
+        {
+            let mut load_stack = self.load_stack.borrow_mut();
+
+            if let Some(index) = load_stack.iter().position(|loading| loading == key) {
+                let mut chain = load_stack[index..].to_vec();
+                chain.push(key.clone());
+                return Err(AssetLoadError::DependencyCycle { chain });
+            }
+
+            load_stack.push(key.clone());
+        }
+
+        let result = load();
+        self.load_stack.borrow_mut().pop();
+
+        let asset = result?;
+        self.insert(key, &asset);
+
+        Ok(asset)
+    }
+}
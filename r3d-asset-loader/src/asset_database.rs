@@ -1,7 +1,14 @@
 use asset::AssetType;
-use asset_pipeline::{deduce_asset_type_from_path, AssetMetadata};
+#[cfg(feature = "pipeline")]
+use asset_pipeline::{asset_input_paths, generate_metadata};
+use asset_pipeline::{
+    deduce_asset_type, deduce_asset_type_from_path, metadata_path, AssetMetadata,
+    AssetTypeDeduceError,
+};
+use serde::Deserialize;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    fmt::Display,
     hash::Hash,
     path::{Path, PathBuf},
     sync::Arc,
@@ -15,6 +22,9 @@ pub enum AssetDatabaseError {
     IOError(#[from] std::io::Error),
     #[error("toml error: {0}")]
     TOMLError(#[from] toml::de::Error),
+    #[cfg(feature = "pipeline")]
+    #[error("toml serialization error: {0}")]
+    TOMLSerializeError(#[from] toml::ser::Error),
     #[error("failed to deduce asset type: {0}")]
     AssetTypeDeduceError(#[from] asset_pipeline::AssetTypeDeduceError),
 }
@@ -26,6 +36,9 @@ pub struct AssetData {
     pub path: PathBuf,
     pub asset_type: AssetType,
     pub metadata_content: String,
+    /// Other files that processing the asset reads, e.g. the skeleton of a motion and its metadata.
+    /// Cooked assets and the watcher track them along with the asset file.
+    pub input_paths: Vec<PathBuf>,
 }
 
 impl PartialEq for AssetData {
@@ -42,6 +55,95 @@ impl Hash for AssetData {
     }
 }
 
+/// The `asset` table of a metadata file. Other tables are up to each pipeline.
+#[derive(Deserialize)]
+struct MetadataHeader {
+    asset: AssetMetadata,
+}
+
+/// An asset whose path has changed between scans, e.g. a renamed or moved file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovedAsset {
+    pub id: Uuid,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// A problem found during a scan. The offending file is skipped.
+#[derive(Debug)]
+pub enum AssetScanWarning {
+    /// A file that no pipeline can process.
+    UnsupportedFile(PathBuf),
+    /// A metadata file that cannot be parsed.
+    InvalidMetadata {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    /// A metadata file that cannot be read, or cannot be written when it is missing.
+    InaccessibleMetadata {
+        path: PathBuf,
+        error: AssetDatabaseError,
+    },
+    /// An asset whose id is already taken by another asset, e.g. a copied file along with its metadata.
+    DuplicateId {
+        id: Uuid,
+        path: PathBuf,
+        existing_path: PathBuf,
+    },
+    /// A metadata file without its asset file.
+    OrphanMetadata(PathBuf),
+    /// An asset whose metadata imports it as a type its file cannot be imported as.
+    UnsupportedImportAs {
+        path: PathBuf,
+        asset_type: AssetType,
+    },
+}
+
+impl Display for AssetScanWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetScanWarning::UnsupportedFile(path) => {
+                write!(f, "unsupported file: {}", path.display())
+            }
+            AssetScanWarning::InvalidMetadata { path, error } => {
+                write!(f, "invalid metadata {}: {}", path.display(), error)
+            }
+            AssetScanWarning::InaccessibleMetadata { path, error } => {
+                write!(f, "inaccessible metadata {}: {}", path.display(), error)
+            }
+            AssetScanWarning::DuplicateId {
+                id,
+                path,
+                existing_path,
+            } => {
+                write!(
+                    f,
+                    "duplicate asset id {} of {}; already used by {}",
+                    id,
+                    path.display(),
+                    existing_path.display()
+                )
+            }
+            AssetScanWarning::OrphanMetadata(path) => {
+                write!(f, "metadata without asset: {}", path.display())
+            }
+            AssetScanWarning::UnsupportedImportAs { path, asset_type } => {
+                write!(f, "{} cannot be imported as {}", path.display(), asset_type)
+            }
+        }
+    }
+}
+
+/// Result of [`AssetDatabase::scan`].
+#[derive(Debug, Default)]
+pub struct AssetScanReport {
+    /// Metadata files that were missing and have been generated.
+    pub generated_metadata: Vec<PathBuf>,
+    /// Assets whose path has changed since the previous scan.
+    pub moved: Vec<MovedAsset>,
+    pub warnings: Vec<AssetScanWarning>,
+}
+
 #[derive(Debug, Clone)]
 pub struct AssetDatabase {
     base_path: PathBuf,
@@ -71,6 +173,7 @@ impl AssetDatabase {
     }
 
     /// Registers an asset from a path. It reads the metadata file; in other words, it performs IO oprations.
+    /// If the metadata file does not exist, it writes a new one with a fresh id.
     pub fn register(
         &mut self,
         path: impl AsRef<Path>,
@@ -83,21 +186,53 @@ impl AssetDatabase {
         path: impl Into<PathBuf>,
     ) -> Result<Arc<AssetData>, AssetDatabaseError> {
         let path = path.into();
-        let metadata_content = std::fs::read_to_string(path.with_extension("meta.toml"))?;
-        let metadata: AssetMetadata = toml::from_str(&metadata_content)?;
-        let asset_type = deduce_asset_type_from_path(&path)?;
+        let (metadata_content, _) =
+            read_or_generate_metadata(&path, deduce_asset_type_from_path(&path)?)?;
+        let metadata: MetadataHeader = toml::from_str(&metadata_content)?;
+        let asset_type = deduce_asset_type(&path, metadata.asset.import_as)?;
 
-        let asset_data = Arc::new(AssetData {
-            id: metadata.id,
-            path: path.clone(),
+        Ok(self.insert(AssetData {
+            id: metadata.asset.id,
+            input_paths: input_paths(&path, asset_type, &metadata_content),
+            path,
             asset_type,
             metadata_content,
-        });
+        }))
+    }
 
-        self.assets.insert(metadata.id, asset_data.clone());
-        self.asset_paths.insert(path, asset_data.clone());
+    fn insert(&mut self, asset_data: AssetData) -> Arc<AssetData> {
+        let asset_data = Arc::new(asset_data);
 
-        Ok(asset_data)
+        if let Some(previous) = self.assets.insert(asset_data.id, asset_data.clone()) {
+            if previous.path != asset_data.path {
+                self.asset_paths.remove(&previous.path);
+            }
+        }
+
+        if let Some(previous) = self
+            .asset_paths
+            .insert(asset_data.path.clone(), asset_data.clone())
+        {
+            if previous.id != asset_data.id {
+                self.assets.remove(&previous.id);
+            }
+        }
+
+        asset_data
+    }
+
+    /// Re-reads the metadata file of a registered asset. The asset keeps its path.
+    pub fn reload(&mut self, id: &Uuid) -> Result<Option<Arc<AssetData>>, AssetDatabaseError> {
+        let path = match self.assets.get(id) {
+            Some(data) => data.path.clone(),
+            None => return Ok(None),
+        };
+        Ok(Some(self.register_path(path)?))
+    }
+
+    /// Iterates over all registered assets.
+    pub fn assets(&self) -> impl Iterator<Item = &Arc<AssetData>> {
+        self.assets.values()
     }
 
     pub fn unregister(&mut self, id: &Uuid) -> Option<Arc<AssetData>> {
@@ -115,28 +250,327 @@ impl AssetDatabase {
         self.asset_paths.clear();
     }
 
-    /// Scans the base path for assets and registers them.
-    /// It first clears the database.
-    pub fn scan(&mut self) -> Result<(), AssetDatabaseError> {
+    /// Scans the base path for assets and registers them. It first clears the database.
+    /// Missing metadata files are generated. Files that cannot be registered are reported as warnings and skipped.
+    pub fn scan(&mut self) -> Result<AssetScanReport, AssetDatabaseError> {
         let path = self.base_path.clone();
+        let previous_assets = std::mem::take(&mut self.assets);
+        let mut report = AssetScanReport::default();
+        let mut metadata_paths = ScannedMetadataPaths::default();
 
-        self.clear();
-        self.scan_directory(&path)?;
+        self.asset_paths.clear();
+        self.scan_directory(&path, &mut report, &mut metadata_paths)?;
 
-        Ok(())
+        let mut moved = Vec::from_iter(self.assets.values().filter_map(|asset_data| {
+            let previous = previous_assets.get(&asset_data.id)?;
+
+            if previous.path == asset_data.path {
+                return None;
+            }
+
+            Some(MovedAsset {
+                id: asset_data.id,
+                from: previous.path.clone(),
+                to: asset_data.path.clone(),
+            })
+        }));
+        moved.sort_unstable_by(|a, b| a.to.cmp(&b.to));
+        report.moved = moved;
+
+        report.warnings.extend(
+            metadata_paths
+                .found
+                .into_iter()
+                .filter(|path| !metadata_paths.claimed.contains(path))
+                .map(AssetScanWarning::OrphanMetadata),
+        );
+
+        Ok(report)
     }
 
-    fn scan_directory(&mut self, path: impl AsRef<Path>) -> Result<(), AssetDatabaseError> {
-        for entry in std::fs::read_dir(path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                self.register_path(path)?;
-            } else if path.is_dir() {
-                self.scan_directory(path)?;
+    fn scan_directory(
+        &mut self,
+        path: impl AsRef<Path>,
+        report: &mut AssetScanReport,
+        metadata_paths: &mut ScannedMetadataPaths,
+    ) -> Result<(), AssetDatabaseError> {
+        let mut paths = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Sort the entries so that the first of duplicated assets is always the same one.
+        paths.sort_unstable();
+
+        for path in paths {
+            if path.is_dir() {
+                self.scan_directory(path, report, metadata_paths)?;
+                continue;
             }
+
+            if !path.is_file() {
+                continue;
+            }
+
+            if is_metadata_path(&path) {
+                metadata_paths.found.push(path);
+                continue;
+            }
+
+            metadata_paths.claimed.insert(metadata_path(&path));
+
+            let asset_type = match deduce_asset_type_from_path(&path) {
+                Ok(asset_type) => asset_type,
+                Err(_) => {
+                    report
+                        .warnings
+                        .push(AssetScanWarning::UnsupportedFile(path));
+                    continue;
+                }
+            };
+            let (metadata_content, is_generated) =
+                match read_or_generate_metadata(&path, asset_type) {
+                    Ok(metadata) => metadata,
+                    Err(error) => {
+                        report
+                            .warnings
+                            .push(AssetScanWarning::InaccessibleMetadata {
+                                path: metadata_path(&path),
+                                error,
+                            });
+                        continue;
+                    }
+                };
+
+            if is_generated {
+                report.generated_metadata.push(metadata_path(&path));
+            }
+
+            let metadata: MetadataHeader = match toml::from_str(&metadata_content) {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    report.warnings.push(AssetScanWarning::InvalidMetadata {
+                        path: metadata_path(&path),
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            let asset_type = match deduce_asset_type(&path, metadata.asset.import_as) {
+                Ok(asset_type) => asset_type,
+                Err(AssetTypeDeduceError::UnsupportedImportAs(path, asset_type)) => {
+                    report
+                        .warnings
+                        .push(AssetScanWarning::UnsupportedImportAs { path, asset_type });
+                    continue;
+                }
+                Err(_) => {
+                    report
+                        .warnings
+                        .push(AssetScanWarning::UnsupportedFile(path));
+                    continue;
+                }
+            };
+
+            if let Some(existing) = self.assets.get(&metadata.asset.id) {
+                report.warnings.push(AssetScanWarning::DuplicateId {
+                    id: metadata.asset.id,
+                    path,
+                    existing_path: existing.path.clone(),
+                });
+                continue;
+            }
+
+            self.insert(AssetData {
+                id: metadata.asset.id,
+                input_paths: input_paths(&path, asset_type, &metadata_content),
+                path,
+                asset_type,
+                metadata_content,
+            });
         }
 
         Ok(())
     }
 }
+
+#[derive(Default)]
+struct ScannedMetadataPaths {
+    /// All metadata files found.
+    found: Vec<PathBuf>,
+    /// Metadata paths of all other files found, whether they have been registered or not.
+    claimed: HashSet<PathBuf>,
+}
+
+fn is_metadata_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".meta.toml"))
+}
+
+/// Returns the other files that processing the asset reads. Without the pipelines, assets are never processed.
+#[cfg_attr(not(feature = "pipeline"), allow(unused_variables))]
+fn input_paths(path: &Path, asset_type: AssetType, metadata_content: &str) -> Vec<PathBuf> {
+    #[cfg(feature = "pipeline")]
+    return asset_input_paths(path, asset_type, metadata_content);
+    #[cfg(not(feature = "pipeline"))]
+    return Vec::new();
+}
+
+/// Reads the metadata file of an asset, or writes a new one if it does not exist.
+/// Returns the content and whether it has been generated.
+/// Metadata is generated by the pipelines; without them, a missing metadata file is an error.
+#[cfg_attr(not(feature = "pipeline"), allow(unused_variables))]
+fn read_or_generate_metadata(
+    path: &Path,
+    asset_type: AssetType,
+) -> Result<(String, bool), AssetDatabaseError> {
+    let metadata_path = metadata_path(path);
+
+    match std::fs::read_to_string(&metadata_path) {
+        Ok(content) => Ok((content, false)),
+        #[cfg(feature = "pipeline")]
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let content = generate_metadata(asset_type, Uuid::new_v4())?;
+            std::fs::write(&metadata_path, &content)?;
+            Ok((content, true))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(all(test, feature = "pipeline"))]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn check_scan_inaccessible_metadata() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+        std::fs::write(base_path.join("a.wgsl"), "").unwrap();
+        std::fs::write(base_path.join("b.wgsl"), "").unwrap();
+        // A directory in place of the metadata file can be neither read nor written.
+        std::fs::create_dir(base_path.join("a.meta.toml")).unwrap();
+
+        let mut database = AssetDatabase::new(base_path);
+        let report = database.scan().unwrap();
+
+        assert!(database
+            .find_asset_by_path(&base_path.join("a.wgsl"))
+            .is_none());
+        assert!(database
+            .find_asset_by_path(&base_path.join("b.wgsl"))
+            .is_some());
+        assert!(matches!(
+            report.warnings.as_slice(),
+            [AssetScanWarning::InaccessibleMetadata { path, .. }]
+                if path == &base_path.join("a.meta.toml")
+        ));
+    }
+
+    #[test]
+    fn check_scan() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+        std::fs::write(base_path.join("a.wgsl"), "").unwrap();
+        std::fs::write(base_path.join("readme.txt"), "").unwrap();
+        std::fs::write(base_path.join("orphan.meta.toml"), "").unwrap();
+
+        let mut database = AssetDatabase::new(base_path);
+        let report = database.scan().unwrap();
+        let a = database
+            .find_asset_by_path(&base_path.join("a.wgsl"))
+            .unwrap();
+
+        assert_eq!(
+            report.generated_metadata,
+            vec![base_path.join("a.meta.toml")]
+        );
+        assert_eq!(report.warnings.len(), 2);
+        assert!(report.warnings.iter().any(|warning| matches!(
+            warning,
+            AssetScanWarning::UnsupportedFile(path) if path == &base_path.join("readme.txt")
+        )));
+        assert!(report.warnings.iter().any(|warning| matches!(
+            warning,
+            AssetScanWarning::OrphanMetadata(path) if path == &base_path.join("orphan.meta.toml")
+        )));
+
+        // A copied asset keeps the id of the original.
+        std::fs::remove_file(base_path.join("readme.txt")).unwrap();
+        std::fs::remove_file(base_path.join("orphan.meta.toml")).unwrap();
+        std::fs::copy(base_path.join("a.wgsl"), base_path.join("b.wgsl")).unwrap();
+        std::fs::copy(base_path.join("a.meta.toml"), base_path.join("b.meta.toml")).unwrap();
+
+        let report = database.scan().unwrap();
+        assert!(report.generated_metadata.is_empty());
+        assert!(matches!(
+            report.warnings.as_slice(),
+            [AssetScanWarning::DuplicateId { id, path, .. }]
+                if *id == a.id && path == &base_path.join("b.wgsl")
+        ));
+
+        // A moved asset keeps its id, but changes its path.
+        std::fs::remove_file(base_path.join("b.wgsl")).unwrap();
+        std::fs::remove_file(base_path.join("b.meta.toml")).unwrap();
+        std::fs::create_dir(base_path.join("sub")).unwrap();
+        std::fs::rename(base_path.join("a.wgsl"), base_path.join("sub/c.wgsl")).unwrap();
+        std::fs::rename(
+            base_path.join("a.meta.toml"),
+            base_path.join("sub/c.meta.toml"),
+        )
+        .unwrap();
+
+        let report = database.scan().unwrap();
+        assert!(report.warnings.is_empty());
+        assert_eq!(
+            report.moved,
+            vec![MovedAsset {
+                id: a.id,
+                from: base_path.join("a.wgsl"),
+                to: base_path.join("sub/c.wgsl"),
+            }]
+        );
+        assert_eq!(
+            database.find_asset_by_id(a.id).unwrap().path,
+            base_path.join("sub/c.wgsl")
+        );
+    }
+
+    #[test]
+    fn check_scan_import_as() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+        let walk = Uuid::new_v4();
+        std::fs::write(base_path.join("walk.fbx"), "").unwrap();
+        std::fs::write(
+            base_path.join("walk.meta.toml"),
+            format!("[asset]\nid = \"{}\"\nimport_as = \"animation\"\n", walk),
+        )
+        .unwrap();
+        std::fs::write(base_path.join("model.pmx"), "").unwrap();
+        std::fs::write(
+            base_path.join("model.meta.toml"),
+            format!(
+                "[asset]\nid = \"{}\"\nimport_as = \"animation\"\n",
+                Uuid::new_v4()
+            ),
+        )
+        .unwrap();
+
+        let mut database = AssetDatabase::new(base_path);
+        let report = database.scan().unwrap();
+
+        assert_eq!(
+            database.find_asset_by_id(walk).unwrap().asset_type,
+            AssetType::Animation
+        );
+        // PMX models have no animations to import.
+        assert!(matches!(
+            report.warnings.as_slice(),
+            [AssetScanWarning::UnsupportedImportAs { path, asset_type: AssetType::Animation }]
+                if path == &base_path.join("model.pmx")
+        ));
+    }
+}
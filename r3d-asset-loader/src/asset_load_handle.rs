@@ -0,0 +1,81 @@
+use crate::AssetLoadError;
+use asset::{AssetKey, TypedAsset};
+use std::{cell::RefCell, rc::Rc};
+
+/// The state of an asynchronous load.
+#[derive(Clone)]
+pub enum AssetLoadState {
+    Pending,
+    Loaded(TypedAsset),
+    /// The error is shared with every load that failed for the same reason, e.g. its dependents.
+    Failed(Rc<AssetLoadError>),
+}
+
+/// A handle to an asynchronous load, returned by [`RuntimeAssetLoader::load_asset_async`](crate::asset_loaders::RuntimeAssetLoader::load_asset_async).
+/// Handles of the same asset share their state; it is updated when the loader is polled.
+#[derive(Clone)]
+pub struct AssetLoadHandle {
+    key: AssetKey,
+    state: Rc<RefCell<AssetLoadState>>,
+}
+
+impl AssetLoadHandle {
+    pub(crate) fn new(key: AssetKey, state: AssetLoadState) -> Self {
+        Self {
+            key,
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    pub fn key(&self) -> &AssetKey {
+        &self.key
+    }
+
+    pub fn state(&self) -> AssetLoadState {
+        self.state.borrow().clone()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(&*self.state.borrow(), AssetLoadState::Pending)
+    }
+
+    /// Returns the loaded asset, if the load has succeeded.
+    pub fn asset(&self) -> Option<TypedAsset> {
+        match &*self.state.borrow() {
+            AssetLoadState::Loaded(asset) => Some(asset.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_state(&self, state: AssetLoadState) {
+        *self.state.borrow_mut() = state;
+    }
+}
+
+/// Aggregate progress of asynchronous loads, including the dependencies they pulled in.
+/// It is reset when a load is requested while no other load is pending.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AssetLoadProgress {
+    pub total: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+impl AssetLoadProgress {
+    pub fn pending(&self) -> usize {
+        self.total - self.loaded - self.failed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending() == 0
+    }
+
+    /// Returns the ratio of finished loads, in `[0, 1]`. It is `1` if nothing has been requested.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        (self.loaded + self.failed) as f32 / self.total as f32
+    }
+}
@@ -1,4 +1,4 @@
-use crate::AssetDatabase;
+use crate::{AssetDatabase, AssetPackError};
 use asset::{AssetKey, TypedAsset};
 use thiserror::Error;
 use uuid::Uuid;
@@ -9,12 +9,30 @@ pub enum AssetLoadError {
     AssetNotFound(Uuid),
     #[error("failed to deduce asset type: {0}")]
     AssetTypeDeduceError(#[from] asset_pipeline::AssetTypeDeduceError),
+    #[cfg(feature = "pipeline")]
     #[error("failed to process asset: {0}")]
     ProcessError(#[from] asset_pipeline::AssetProcessError),
     #[error("failed to load asset: {0}")]
     LoadError(#[from] asset::AssetLoadError),
     #[error("io error: {0}")]
     IOError(#[from] std::io::Error),
+    #[error("asset not found in pack: {0}")]
+    AssetNotInPack(AssetKey),
+    #[error("failed to read asset pack: {0}")]
+    PackError(#[from] AssetPackError),
+    #[error("dependency cycle detected: {}", format_key_chain(.chain))]
+    DependencyCycle {
+        /// Keys forming the cycle, in load order. The first and the last key are the same.
+        chain: Vec<AssetKey>,
+    },
+}
+
+fn format_key_chain(chain: &[AssetKey]) -> String {
+    chain
+        .iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
 }
 
 pub trait AssetLoader {
@@ -1,3 +1,7 @@
+mod pack_asset_loader;
+#[cfg(feature = "pipeline")]
 mod runtime_asset_loader;
 
+pub use pack_asset_loader::*;
+#[cfg(feature = "pipeline")]
 pub use runtime_asset_loader::*;
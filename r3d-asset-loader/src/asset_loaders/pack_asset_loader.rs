@@ -0,0 +1,126 @@
+use crate::{AssetCache, AssetDatabase, AssetLoadError, AssetLoader, AssetPack};
+use asset::{AssetKey, GfxBridge, TypedAsset};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek},
+};
+
+/// Loads assets from an [`AssetPack`] instead of processing them, e.g. in shipped games.
+/// The database passed to [`AssetLoader::load_asset`] is not used, since the pack is self-contained.
+/// Loaded assets are cached weakly by their key, like [`RuntimeAssetLoader`](super::RuntimeAssetLoader).
+pub struct PackAssetLoader<R = BufReader<File>> {
+    pack: AssetPack<R>,
+    gfx_bridge: Box<dyn GfxBridge>,
+    cache: AssetCache,
+}
+
+impl<R> PackAssetLoader<R>
+where
+    R: Read + Seek,
+{
+    pub fn new(pack: AssetPack<R>, gfx_bridge: impl GfxBridge + 'static) -> Self {
+        Self {
+            pack,
+            gfx_bridge: Box::new(gfx_bridge),
+            cache: AssetCache::new(),
+        }
+    }
+
+    pub fn pack(&self) -> &AssetPack<R> {
+        &self.pack
+    }
+
+    /// Returns the cached asset if it is still alive.
+    pub fn find_cached(&self, key: &AssetKey) -> Option<TypedAsset> {
+        self.cache.find(key)
+    }
+
+    /// Removes cache entries whose assets have been dropped.
+    pub fn purge_cache(&self) {
+        self.cache.purge();
+    }
+
+    fn load_asset_uncached(
+        &self,
+        key: &AssetKey,
+        database: &AssetDatabase,
+    ) -> Result<TypedAsset, AssetLoadError> {
+        let entry = self
+            .pack
+            .resolve_entry(key)
+            .ok_or_else(|| AssetLoadError::AssetNotInPack(key.clone()))?;
+        let source = self.pack.read_source(entry)?;
+
+        // Resolve dependencies. NOTE: It can be recursive.
+        let deps = entry
+            .dependencies
+            .iter()
+            .map(|key| {
+                let asset = self.load_asset(key, database)?;
+
+                Ok((key.clone(), asset))
+            })
+            .collect::<Result<HashMap<_, _>, AssetLoadError>>()?;
+
+        Ok(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+    }
+}
+
+impl<R> AssetLoader for PackAssetLoader<R>
+where
+    R: Read + Seek,
+{
+    fn load_asset(
+        &self,
+        key: &AssetKey,
+        database: &AssetDatabase,
+    ) -> Result<TypedAsset, AssetLoadError> {
+        self.cache
+            .load(key, || self.load_asset_uncached(key, database))
+    }
+}
+
+#[cfg(all(test, feature = "pipeline"))]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{write_material, write_shader, NullGfxBridge, NullPipelineGfxBridge, TempDir},
+        AssetPackBuilder,
+    };
+    use std::io::Cursor;
+    use uuid::Uuid;
+
+    #[test]
+    fn check_load_from_pack() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+
+        let shader = Uuid::new_v4();
+        let material = Uuid::new_v4();
+        write_shader(base_path, "shader.wgsl", shader);
+        write_material(base_path, "material.mat", material, AssetKey::Id(shader));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let mut builder = AssetPackBuilder::new(&database, &NullPipelineGfxBridge);
+        builder.add(&AssetKey::Id(material)).unwrap();
+        let mut content = Vec::new();
+        builder.write(&mut content).unwrap();
+        drop(dir);
+
+        // The pack is self-contained, so the database is not needed anymore.
+        let loader = PackAssetLoader::new(
+            AssetPack::from_reader(Cursor::new(content)).unwrap(),
+            NullGfxBridge,
+        );
+        let asset = loader
+            .load_asset(&AssetKey::Id(material), &database)
+            .unwrap();
+        assert!(asset.is_material());
+        assert!(loader
+            .find_cached(&AssetKey::Id(shader))
+            .is_some_and(|asset| asset.is_shader()));
+    }
+}
@@ -1,70 +1,190 @@
-use crate::{AssetDatabase, AssetLoadError, AssetLoader};
-use asset::{AssetKey, AssetSource, GfxBridge, TypedAsset};
-use asset_pipeline::{
-    deduce_asset_type_from_path, process_asset, PipelineGfxBridge, TypedAssetSource,
+use crate::{
+    AssetCache, AssetDatabase, AssetLoadError, AssetLoadHandle, AssetLoadProgress, AssetLoadState,
+    AssetLoader, AssetProcessPool, AssetProcessRequest, CookedAssetCache,
+};
+use asset::{AssetKey, GfxBridge, TypedAsset};
+use asset_pipeline::{PipelineGfxBridge, TypedAssetSource};
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::Arc,
 };
-use std::collections::HashMap;
 
+/// Processes and uploads assets at runtime. Loaded assets are cached weakly by their key,
+/// so an asset shared by several dependents is loaded only once while it is alive.
+///
+/// Assets can also be loaded asynchronously with [`RuntimeAssetLoader::load_asset_async`].
+/// Sources are processed on worker threads, while uploads happen in [`RuntimeAssetLoader::poll_async`] on the calling thread.
 pub struct RuntimeAssetLoader {
     gfx_bridge: Box<dyn GfxBridge>,
-    pipeline_gfx_bridge: Box<dyn PipelineGfxBridge>,
+    pipeline_gfx_bridge: Arc<dyn PipelineGfxBridge + Send + Sync>,
+    cooked_cache: Option<CookedAssetCache>,
+    cache: AssetCache,
+    /// Maps each dependency to the cached assets that depend on it.
+    dependents: RefCell<HashMap<AssetKey, HashSet<AssetKey>>>,
+    /// Spawned on the first asynchronous load.
+    process_pool: OnceCell<AssetProcessPool>,
+    pending: RefCell<HashMap<AssetKey, PendingAsset>>,
+    progress: Cell<AssetLoadProgress>,
+}
+
+struct PendingAsset {
+    handle: AssetLoadHandle,
+    stage: PendingStage,
+}
+
+enum PendingStage {
+    /// The source is being processed by a worker.
+    Processing,
+    /// The source is processed and waits for its dependencies to be loaded.
+    Waiting {
+        source: Box<TypedAssetSource>,
+        deps: Vec<AssetLoadHandle>,
+    },
+}
+
+/// An asset that has been reloaded by [`RuntimeAssetLoader::reload_asset`].
+pub struct ReloadedAsset {
+    pub key: AssetKey,
+    /// The previous version, if it was still alive.
+    pub old: Option<TypedAsset>,
+    pub new: TypedAsset,
 }
 
 impl RuntimeAssetLoader {
     pub fn new(
         gfx_bridge: impl GfxBridge + 'static,
-        pipeline_gfx_bridge: impl PipelineGfxBridge + 'static,
+        pipeline_gfx_bridge: impl PipelineGfxBridge + Send + Sync + 'static,
     ) -> Self {
         Self {
             gfx_bridge: Box::new(gfx_bridge),
-            pipeline_gfx_bridge: Box::new(pipeline_gfx_bridge),
+            pipeline_gfx_bridge: Arc::new(pipeline_gfx_bridge),
+            cooked_cache: None,
+            cache: AssetCache::new(),
+            dependents: RefCell::new(HashMap::new()),
+            process_pool: OnceCell::new(),
+            pending: RefCell::new(HashMap::new()),
+            progress: Cell::new(AssetLoadProgress::default()),
         }
     }
-}
 
-impl AssetLoader for RuntimeAssetLoader {
-    fn load_asset(
+    /// Enables the cooked asset cache. Assets loaded by id are processed only when their cooked entry is missing or outdated.
+    pub fn with_cooked_cache(mut self, cooked_cache: CookedAssetCache) -> Self {
+        self.cooked_cache = Some(cooked_cache);
+        self
+    }
+
+    pub fn cooked_cache(&self) -> Option<&CookedAssetCache> {
+        self.cooked_cache.as_ref()
+    }
+
+    /// Returns the cached asset if it is still alive.
+    pub fn find_cached(&self, key: &AssetKey) -> Option<TypedAsset> {
+        self.cache.find(key)
+    }
+
+    /// Removes cache entries whose assets have been dropped.
+    pub fn purge_cache(&self) {
+        self.cache.purge();
+        self.purge_dependents();
+    }
+
+    /// Removes dependents which are not cached anymore.
+    fn purge_dependents(&self) {
+        self.dependents.borrow_mut().retain(|_, dependents| {
+            dependents.retain(|key| self.cache.contains(key));
+            !dependents.is_empty()
+        });
+    }
+
+    /// Reloads an asset and all alive assets depending on it, directly or indirectly.
+    /// Dependencies are always reloaded before their dependents, so that dependents observe the new versions.
+    /// The returned list is in reload order and starts with the given asset.
+    pub fn reload_asset(
+        &self,
+        key: &AssetKey,
+        database: &AssetDatabase,
+    ) -> Result<Vec<ReloadedAsset>, AssetLoadError> {
+        let mut order = Vec::new();
+        self.collect_dependents(key, &mut HashSet::new(), &mut order);
+        order.reverse();
+
+        let stale = order
+            .into_iter()
+            .filter_map(|stale_key| {
+                let old = self.find_cached(&stale_key);
+
+                if &stale_key != key && old.is_none() {
+                    return None;
+                }
+
+                Some((stale_key, old))
+            })
+            .collect::<Vec<_>>();
+
+        for (stale_key, _) in &stale {
+            self.cache.remove(stale_key);
+        }
+
+        stale
+            .into_iter()
+            .map(|(key, old)| {
+                let new = self.load_asset(&key, database)?;
+                Ok(ReloadedAsset { key, old, new })
+            })
+            .collect()
+    }
+
+    /// Collects the given key and its transitive dependents in post-order.
+    fn collect_dependents(
+        &self,
+        key: &AssetKey,
+        visited: &mut HashSet<AssetKey>,
+        order: &mut Vec<AssetKey>,
+    ) {
+        if !visited.insert(key.clone()) {
+            return;
+        }
+
+        let dependents = self
+            .dependents
+            .borrow()
+            .get(key)
+            .cloned()
+            .unwrap_or_default();
+
+        for dependent in &dependents {
+            self.collect_dependents(dependent, visited, order);
+        }
+
+        order.push(key.clone());
+    }
+
+    fn process_request(
+        key: &AssetKey,
+        database: &AssetDatabase,
+    ) -> Result<AssetProcessRequest, AssetLoadError> {
+        match key {
+            AssetKey::Id(id) => database
+                .find_asset_by_id(*id)
+                .map(AssetProcessRequest::Indexed)
+                .ok_or_else(|| AssetLoadError::AssetNotFound(*id)),
+            AssetKey::Path(path) => Ok(AssetProcessRequest::Path(path.clone())),
+        }
+    }
+
+    fn load_asset_uncached(
         &self,
         key: &AssetKey,
         database: &AssetDatabase,
     ) -> Result<TypedAsset, AssetLoadError> {
-        let processed = match key {
-            AssetKey::Id(id) => {
-                let id = *id;
-                let data = database
-                    .find_asset_by_id(id)
-                    .ok_or_else(|| AssetLoadError::AssetNotFound(id))?;
-                let processed = process_asset(
-                    &data.path,
-                    data.asset_type,
-                    Some(&data.metadata_content),
-                    &*self.pipeline_gfx_bridge,
-                )?;
-
-                processed
-            }
-            AssetKey::Path(path) => {
-                let asset_type = deduce_asset_type_from_path(path)?;
-                let processed = process_asset(
-                    path,
-                    asset_type,
-                    None as Option<&str>,
-                    &*self.pipeline_gfx_bridge,
-                )?;
-
-                processed
-            }
-        };
+        let processed = Self::process_request(key, database)?
+            .process(self.cooked_cache.as_ref(), &*self.pipeline_gfx_bridge)?;
 
         // Resolve dependencies. NOTE: It can be recursive.
-        let deps = match &processed {
-            TypedAssetSource::Font(source) => source.dependencies(),
-            TypedAssetSource::Material(source) => source.dependencies(),
-            TypedAssetSource::Model(source) => source.dependencies(),
-            TypedAssetSource::Shader(source) => source.dependencies(),
-            TypedAssetSource::Texture(source) => source.dependencies(),
-        };
-        let deps = deps
+        let deps = processed
+            .dependencies()
             .into_iter()
             .map(|key| {
                 let asset = self.load_asset(&key, database)?;
@@ -73,22 +193,482 @@ impl AssetLoader for RuntimeAssetLoader {
             })
             .collect::<Result<HashMap<_, _>, AssetLoadError>>()?;
 
-        Ok(match processed {
-            TypedAssetSource::Font(source) => {
-                TypedAsset::Font(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+        self.upload(key, processed, deps)
+    }
+
+    /// Uploads a processed source whose dependencies are loaded.
+    fn upload(
+        &self,
+        key: &AssetKey,
+        source: TypedAssetSource,
+        deps: HashMap<AssetKey, TypedAsset>,
+    ) -> Result<TypedAsset, AssetLoadError> {
+        {
+            let mut dependents = self.dependents.borrow_mut();
+
+            for dep in deps.keys() {
+                dependents
+                    .entry(dep.clone())
+                    .or_default()
+                    .insert(key.clone());
             }
-            TypedAssetSource::Material(source) => {
-                TypedAsset::Material(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+        }
+
+        Ok(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+    }
+
+    fn cache_asset(&self, key: &AssetKey, asset: &TypedAsset) {
+        self.cache.insert(key, asset);
+        self.purge_dependents();
+    }
+
+    /// Starts loading an asset and its dependencies in the background. The returned handle is pending
+    /// until [`RuntimeAssetLoader::poll_async`] finishes the load; it is already loaded if the asset is cached.
+    /// Requesting an asset that is already pending returns a handle sharing the same state.
+    pub fn load_asset_async(&self, key: &AssetKey, database: &AssetDatabase) -> AssetLoadHandle {
+        if let Some(asset) = self.find_cached(key) {
+            return AssetLoadHandle::new(key.clone(), AssetLoadState::Loaded(asset));
+        }
+
+        let mut pending = self.pending.borrow_mut();
+
+        if let Some(pending) = pending.get(key) {
+            return pending.handle.clone();
+        }
+
+        let mut progress = if pending.is_empty() {
+            AssetLoadProgress::default()
+        } else {
+            self.progress.get()
+        };
+        progress.total += 1;
+
+        let request = match Self::process_request(key, database) {
+            Ok(request) => request,
+            Err(err) => {
+                progress.failed += 1;
+                self.progress.set(progress);
+                return AssetLoadHandle::new(key.clone(), AssetLoadState::Failed(Rc::new(err)));
             }
-            TypedAssetSource::Model(source) => {
-                TypedAsset::Model(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+        };
+
+        let handle = AssetLoadHandle::new(key.clone(), AssetLoadState::Pending);
+        self.process_pool().submit(key.clone(), request);
+        pending.insert(
+            key.clone(),
+            PendingAsset {
+                handle: handle.clone(),
+                stage: PendingStage::Processing,
+            },
+        );
+        self.progress.set(progress);
+
+        handle
+    }
+
+    /// Returns the progress of the asynchronous loads requested since the loader was last idle.
+    pub fn async_progress(&self) -> AssetLoadProgress {
+        self.progress.get()
+    }
+
+    /// Finishes asynchronous loads whose sources have been processed and whose dependencies are loaded.
+    /// This uploads assets through the [`GfxBridge`], so it should be called on the thread owning it, e.g. once per frame.
+    /// Returns the handles finished by this call.
+    pub fn poll_async(&self, database: &AssetDatabase) -> Vec<AssetLoadHandle> {
+        let pool = match self.process_pool.get() {
+            Some(pool) => pool,
+            None => return vec![],
+        };
+        let mut finished = Vec::new();
+
+        while let Some((key, result)) = pool.try_recv() {
+            match result {
+                Ok(source) => {
+                    // Requesting dependencies may finish some of them immediately, e.g. cached ones.
+                    let deps = Vec::from_iter(
+                        source
+                            .dependencies()
+                            .iter()
+                            .map(|dep| self.load_asset_async(dep, database)),
+                    );
+
+                    if let Some(pending) = self.pending.borrow_mut().get_mut(&key) {
+                        pending.stage = PendingStage::Waiting {
+                            source: Box::new(source),
+                            deps,
+                        };
+                    }
+
+                    if let Some(chain) = self.find_pending_cycle(&key) {
+                        let error = Rc::new(AssetLoadError::DependencyCycle { chain });
+                        finished.extend(self.finish_async(&key, AssetLoadState::Failed(error)));
+                    }
+                }
+                Err(err) => {
+                    finished.extend(self.finish_async(&key, AssetLoadState::Failed(Rc::new(err))));
+                }
             }
-            TypedAssetSource::Shader(source) => {
-                TypedAsset::Shader(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+        }
+
+        // Finishing a load may unblock its dependents, so repeat until nothing changes.
+        loop {
+            let ready =
+                Vec::from_iter(self.pending.borrow().iter().filter_map(|(key, pending)| {
+                    let deps = match &pending.stage {
+                        PendingStage::Processing => return None,
+                        PendingStage::Waiting { deps, .. } => deps,
+                    };
+                    let mut is_ready = true;
+
+                    for dep in deps {
+                        match dep.state() {
+                            AssetLoadState::Pending => is_ready = false,
+                            AssetLoadState::Loaded(_) => {}
+                            AssetLoadState::Failed(err) => {
+                                return Some((key.clone(), Some(err)));
+                            }
+                        }
+                    }
+
+                    is_ready.then(|| (key.clone(), None))
+                }));
+
+            if ready.is_empty() {
+                break;
             }
-            TypedAssetSource::Texture(source) => {
-                TypedAsset::Texture(source.load(key.clone(), &deps, &*self.gfx_bridge)?)
+
+            for (key, dep_error) in ready {
+                let state = match dep_error {
+                    Some(err) => AssetLoadState::Failed(err),
+                    None => self.upload_pending(&key),
+                };
+
+                finished.extend(self.finish_async(&key, state));
             }
+        }
+
+        finished
+    }
+
+    fn process_pool(&self) -> &AssetProcessPool {
+        self.process_pool.get_or_init(|| {
+            let worker_count = std::thread::available_parallelism()
+                .map(|count| count.get().saturating_sub(1))
+                .unwrap_or(1);
+
+            AssetProcessPool::new(
+                worker_count,
+                self.cooked_cache.clone(),
+                self.pipeline_gfx_bridge.clone(),
+            )
         })
     }
+
+    fn upload_pending(&self, key: &AssetKey) -> AssetLoadState {
+        let (source, deps) = match self.pending.borrow_mut().get_mut(key) {
+            Some(pending) => {
+                match std::mem::replace(&mut pending.stage, PendingStage::Processing) {
+                    PendingStage::Waiting { source, deps } => (*source, deps),
+                    PendingStage::Processing => unreachable!(),
+                }
+            }
+            None => unreachable!(),
+        };
+
+        // The asset may have been loaded synchronously in the meantime.
+        if let Some(asset) = self.find_cached(key) {
+            return AssetLoadState::Loaded(asset);
+        }
+
+        let deps = HashMap::from_iter(
+            deps.into_iter()
+                .filter_map(|dep| Some((dep.key().clone(), dep.asset()?))),
+        );
+
+        match self.upload(key, source, deps) {
+            Ok(asset) => {
+                self.cache_asset(key, &asset);
+                AssetLoadState::Loaded(asset)
+            }
+            Err(err) => AssetLoadState::Failed(Rc::new(err)),
+        }
+    }
+
+    fn finish_async(&self, key: &AssetKey, state: AssetLoadState) -> Option<AssetLoadHandle> {
+        let mut progress = self.progress.get();
+
+        match &state {
+            AssetLoadState::Pending => return None,
+            AssetLoadState::Loaded(_) => progress.loaded += 1,
+            AssetLoadState::Failed(_) => progress.failed += 1,
+        }
+
+        let pending = self.pending.borrow_mut().remove(key)?;
+        self.progress.set(progress);
+        pending.handle.set_state(state);
+
+        Some(pending.handle)
+    }
+
+    /// Finds a chain of pending loads that leads from the given key back to itself.
+    fn find_pending_cycle(&self, key: &AssetKey) -> Option<Vec<AssetKey>> {
+        fn visit(
+            pending: &HashMap<AssetKey, PendingAsset>,
+            target: &AssetKey,
+            current: &AssetKey,
+            visited: &mut HashSet<AssetKey>,
+            chain: &mut Vec<AssetKey>,
+        ) -> bool {
+            let deps = match pending.get(current).map(|pending| &pending.stage) {
+                Some(PendingStage::Waiting { deps, .. }) => deps,
+                _ => return false,
+            };
+
+            for dep in deps {
+                if !dep.is_pending() {
+                    continue;
+                }
+
+                chain.push(dep.key().clone());
+
+                if dep.key() == target {
+                    return true;
+                }
+
+                if visited.insert(dep.key().clone())
+                    && visit(pending, target, dep.key(), visited, chain)
+                {
+                    return true;
+                }
+
+                chain.pop();
+            }
+
+            false
+        }
+
+        let mut chain = vec![key.clone()];
+
+        visit(
+            &self.pending.borrow(),
+            key,
+            key,
+            &mut HashSet::new(),
+            &mut chain,
+        )
+        .then_some(chain)
+    }
+}
+
+impl AssetLoader for RuntimeAssetLoader {
+    fn load_asset(
+        &self,
+        key: &AssetKey,
+        database: &AssetDatabase,
+    ) -> Result<TypedAsset, AssetLoadError> {
+        let asset = self
+            .cache
+            .load(key, || self.load_asset_uncached(key, database))?;
+        self.purge_dependents();
+
+        Ok(asset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        write_material, write_shader, NullGfxBridge, NullPipelineGfxBridge, TempDir,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn check_load_cached() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+
+        let shader = Uuid::new_v4();
+        let material = Uuid::new_v4();
+        write_shader(base_path, "shader.wgsl", shader);
+        write_material(base_path, "material.mat", material, AssetKey::Id(shader));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let loader = RuntimeAssetLoader::new(NullGfxBridge, NullPipelineGfxBridge);
+        let first = loader
+            .load_asset(&AssetKey::Id(material), &database)
+            .unwrap();
+        // Remove the sources, so that a second load can only be served from the cache.
+        std::fs::remove_dir_all(base_path).unwrap();
+        let second = loader
+            .load_asset(&AssetKey::Id(material), &database)
+            .unwrap();
+        assert!(Arc::ptr_eq(
+            first.as_material().unwrap(),
+            second.as_material().unwrap()
+        ));
+
+        // The shader is shared through the cache as well.
+        let shader = loader.find_cached(&AssetKey::Id(shader)).unwrap();
+        assert!(Arc::ptr_eq(
+            shader.as_shader().unwrap(),
+            &first.as_material().unwrap().preset().shader
+        ));
+    }
+
+    #[test]
+    fn check_evict_dropped() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+
+        let shader = Uuid::new_v4();
+        let material = Uuid::new_v4();
+        write_shader(base_path, "shader.wgsl", shader);
+        write_material(base_path, "material.mat", material, AssetKey::Id(shader));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let loader = RuntimeAssetLoader::new(NullGfxBridge, NullPipelineGfxBridge);
+        let asset = loader
+            .load_asset(&AssetKey::Id(material), &database)
+            .unwrap();
+        assert!(loader.find_cached(&AssetKey::Id(material)).is_some());
+        assert!(loader.find_cached(&AssetKey::Id(shader)).is_some());
+
+        drop(asset);
+        assert!(loader.find_cached(&AssetKey::Id(material)).is_none());
+        assert!(loader.find_cached(&AssetKey::Id(shader)).is_none());
+
+        loader.purge_cache();
+        assert!(loader.cache.is_empty());
+        assert!(loader.dependents.borrow().is_empty());
+
+        // Evicted assets are loaded again from their sources.
+        assert!(loader
+            .load_asset(&AssetKey::Id(material), &database)
+            .is_ok_and(|asset| asset.is_material()));
+    }
+
+    #[test]
+    fn check_reload_dependents() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+
+        let shader = Uuid::new_v4();
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        write_shader(base_path, "shader.wgsl", shader);
+        write_material(base_path, "a.mat", a, AssetKey::Id(shader));
+        write_material(base_path, "b.mat", b, AssetKey::Id(shader));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let loader = RuntimeAssetLoader::new(NullGfxBridge, NullPipelineGfxBridge);
+        let material = loader.load_asset(&AssetKey::Id(a), &database).unwrap();
+
+        // The material that has never been loaded is not reloaded.
+        let reloaded = loader
+            .reload_asset(&AssetKey::Id(shader), &database)
+            .unwrap();
+        assert_eq!(
+            Vec::from_iter(reloaded.iter().map(|reloaded| reloaded.key.clone())),
+            vec![AssetKey::Id(shader), AssetKey::Id(a)]
+        );
+        assert!(Arc::ptr_eq(
+            reloaded[1].old.as_ref().unwrap().as_material().unwrap(),
+            material.as_material().unwrap()
+        ));
+        assert!(Arc::ptr_eq(
+            &reloaded[1].new.as_material().unwrap().preset().shader,
+            reloaded[0].new.as_shader().unwrap()
+        ));
+        assert!(Arc::ptr_eq(
+            loader
+                .find_cached(&AssetKey::Id(a))
+                .unwrap()
+                .as_material()
+                .unwrap(),
+            reloaded[1].new.as_material().unwrap()
+        ));
+
+        // Dependencies are not reloaded along with their dependents.
+        let reloaded = loader.reload_asset(&AssetKey::Id(a), &database).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].key, AssetKey::Id(a));
+    }
+
+    #[test]
+    fn check_load_cycle() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        write_material(base_path, "a.mat", a, AssetKey::Id(b));
+        write_material(base_path, "b.mat", b, AssetKey::Id(a));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let loader = RuntimeAssetLoader::new(NullGfxBridge, NullPipelineGfxBridge);
+
+        match loader.load_asset(&AssetKey::Id(a), &database) {
+            Err(AssetLoadError::DependencyCycle { chain }) => {
+                assert_eq!(
+                    chain,
+                    vec![AssetKey::Id(a), AssetKey::Id(b), AssetKey::Id(a)]
+                );
+            }
+            _ => panic!("expected a dependency cycle"),
+        }
+
+        // The failed load must not leave anything behind.
+        assert!(!loader.cache.is_loading());
+        assert!(loader.find_cached(&AssetKey::Id(b)).is_none());
+    }
+
+    #[test]
+    fn check_load_async_cycle() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        write_material(base_path, "a.mat", a, AssetKey::Id(b));
+        write_material(base_path, "b.mat", b, AssetKey::Id(a));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let loader = RuntimeAssetLoader::new(NullGfxBridge, NullPipelineGfxBridge);
+        let handle = loader.load_asset_async(&AssetKey::Id(a), &database);
+        assert!(handle.is_pending());
+        assert!(loader
+            .load_asset_async(&AssetKey::Id(a), &database)
+            .is_pending());
+
+        let started = std::time::Instant::now();
+
+        while handle.is_pending() {
+            assert!(started.elapsed().as_secs() < 10, "load did not finish");
+            loader.poll_async(&database);
+            std::thread::yield_now();
+        }
+
+        match handle.state() {
+            AssetLoadState::Failed(err) => {
+                assert!(matches!(&*err, AssetLoadError::DependencyCycle { .. }));
+            }
+            _ => panic!("expected a dependency cycle"),
+        }
+
+        let progress = loader.async_progress();
+        assert_eq!(progress.total, 2);
+        assert_eq!(progress.failed, 2);
+        assert!(progress.is_done());
+    }
 }
@@ -0,0 +1,183 @@
+use asset::{AssetKey, AssetType};
+#[cfg(feature = "pipeline")]
+use asset_pipeline::AssetProcessError;
+use asset_pipeline::{AssetTypeDeduceError, TypedAssetSource};
+use flate2::read::DeflateDecoder;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+pub(crate) const ASSET_PACK_MAGIC: [u8; 8] = *b"R3DPACK\0";
+/// Bump this whenever the layout of the pack or any asset source changes.
+pub(crate) const ASSET_PACK_VERSION: u32 = 11;
+
+#[derive(Error, Debug)]
+pub enum AssetPackError {
+    #[error("io error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("bincode error: {0}")]
+    BincodeError(#[from] bincode::Error),
+    #[error("not an asset pack")]
+    InvalidMagic,
+    #[error("unsupported asset pack version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("asset not found: {0}")]
+    AssetNotFound(Uuid),
+    #[error("failed to deduce asset type: {0}")]
+    AssetTypeDeduceError(#[from] AssetTypeDeduceError),
+    #[cfg(feature = "pipeline")]
+    #[error("failed to process asset {key}: {error}")]
+    ProcessError {
+        key: AssetKey,
+        error: AssetProcessError,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AssetPackCompression {
+    #[default]
+    None,
+    Deflate,
+}
+
+/// An index entry of an [`AssetPack`], describing where the payload of an asset is stored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AssetPackEntry {
+    pub key: AssetKey,
+    /// The path of the asset relative to the base path of the database it is packed from, if any.
+    /// It uses `/` as a separator regardless of the platform.
+    pub path: Option<String>,
+    pub asset_type: AssetType,
+    /// The offset of the payload from the start of the pack.
+    pub offset: u64,
+    /// The size of the payload as stored, after compression.
+    pub size: u64,
+    pub uncompressed_size: u64,
+    pub compression: AssetPackCompression,
+    pub dependencies: Vec<AssetKey>,
+}
+
+/// A single-file bundle of processed asset sources. Only the index is kept in memory;
+/// payloads are read on demand.
+pub struct AssetPack<R = BufReader<File>> {
+    reader: RefCell<R>,
+    entries: Vec<AssetPackEntry>,
+    keys: HashMap<AssetKey, usize>,
+    paths: HashMap<String, usize>,
+    dependents: HashMap<AssetKey, Vec<AssetKey>>,
+}
+
+impl AssetPack {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AssetPackError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R> AssetPack<R>
+where
+    R: Read + Seek,
+{
+    pub fn from_reader(mut reader: R) -> Result<Self, AssetPackError> {
+        let mut magic = [0u8; 8];
+        let mut version = [0u8; 4];
+        let mut index_offset = [0u8; 8];
+
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut magic)?;
+
+        if magic != ASSET_PACK_MAGIC {
+            return Err(AssetPackError::InvalidMagic);
+        }
+
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+
+        if version != ASSET_PACK_VERSION {
+            return Err(AssetPackError::UnsupportedVersion(version));
+        }
+
+        reader.read_exact(&mut index_offset)?;
+        reader.seek(SeekFrom::Start(u64::from_le_bytes(index_offset)))?;
+
+        let entries: Vec<AssetPackEntry> = bincode::deserialize_from(&mut reader)?;
+        let mut keys = HashMap::with_capacity(entries.len());
+        let mut paths = HashMap::new();
+        let mut dependents = HashMap::<_, Vec<_>>::new();
+
+        for (index, entry) in entries.iter().enumerate() {
+            keys.insert(entry.key.clone(), index);
+
+            if let Some(path) = &entry.path {
+                paths.insert(path.clone(), index);
+            }
+
+            for dependency in &entry.dependencies {
+                dependents
+                    .entry(dependency.clone())
+                    .or_default()
+                    .push(entry.key.clone());
+            }
+        }
+
+        Ok(Self {
+            reader: RefCell::new(reader),
+            entries,
+            keys,
+            paths,
+            dependents,
+        })
+    }
+
+    pub fn entries(&self) -> &[AssetPackEntry] {
+        &self.entries
+    }
+
+    pub fn find_entry(&self, key: &AssetKey) -> Option<&AssetPackEntry> {
+        self.keys.get(key).map(|&index| &self.entries[index])
+    }
+
+    /// Finds an entry by its path relative to the base path of the database it was packed from.
+    pub fn find_entry_by_path(&self, path: &str) -> Option<&AssetPackEntry> {
+        self.paths.get(path).map(|&index| &self.entries[index])
+    }
+
+    /// Finds an entry by its key. Path keys that are not packed as-is fall back to the path aliases.
+    pub fn resolve_entry(&self, key: &AssetKey) -> Option<&AssetPackEntry> {
+        match (self.find_entry(key), key) {
+            (Some(entry), _) => Some(entry),
+            (None, AssetKey::Path(path)) => self.find_entry_by_path(path),
+            (None, AssetKey::Id(_)) => None,
+        }
+    }
+
+    /// Returns the keys of the packed assets that directly depend on the given asset.
+    pub fn dependents(&self, key: &AssetKey) -> &[AssetKey] {
+        self.dependents
+            .get(key)
+            .map(|dependents| dependents.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Reads and decodes the payload of an entry.
+    pub fn read_source(&self, entry: &AssetPackEntry) -> Result<TypedAssetSource, AssetPackError> {
+        let mut reader = self.reader.borrow_mut();
+        reader.seek(SeekFrom::Start(entry.offset))?;
+
+        let payload = (&mut *reader).take(entry.size);
+        let source = match entry.compression {
+            AssetPackCompression::None => bincode::deserialize_from(payload)?,
+            AssetPackCompression::Deflate => {
+                bincode::deserialize_from(DeflateDecoder::new(payload))?
+            }
+        };
+
+        Ok(source)
+    }
+}
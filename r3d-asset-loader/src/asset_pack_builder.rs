@@ -0,0 +1,229 @@
+use crate::{
+    AssetDatabase, AssetPackCompression, AssetPackEntry, AssetPackError, ASSET_PACK_MAGIC,
+    ASSET_PACK_VERSION,
+};
+use asset::AssetKey;
+use asset_pipeline::{deduce_asset_type_from_path, process_asset, PipelineGfxBridge};
+use flate2::{write::DeflateEncoder, Compression};
+use std::{collections::HashSet, fs::File, io::Write, path::Path};
+
+/// The magic, the version and the offset of the index.
+const ASSET_PACK_HEADER_SIZE: u64 = 8 + 4 + 8;
+
+/// Builds an asset pack from an [`AssetDatabase`]. Every added asset is processed once,
+/// and its dependencies are added along with it.
+pub struct AssetPackBuilder<'a> {
+    database: &'a AssetDatabase,
+    pipeline_gfx_bridge: &'a dyn PipelineGfxBridge,
+    compression: AssetPackCompression,
+    entries: Vec<(AssetPackEntry, Vec<u8>)>,
+    keys: HashSet<AssetKey>,
+    /// The offset of the next payload, which is also the offset of the index.
+    offset: u64,
+}
+
+impl<'a> AssetPackBuilder<'a> {
+    pub fn new(
+        database: &'a AssetDatabase,
+        pipeline_gfx_bridge: &'a dyn PipelineGfxBridge,
+    ) -> Self {
+        Self {
+            database,
+            pipeline_gfx_bridge,
+            compression: AssetPackCompression::None,
+            entries: Vec::new(),
+            keys: HashSet::new(),
+            offset: ASSET_PACK_HEADER_SIZE,
+        }
+    }
+
+    /// Sets the compression of the assets added afterwards.
+    pub fn with_compression(mut self, compression: AssetPackCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AssetPackEntry> {
+        self.entries.iter().map(|(entry, _)| entry)
+    }
+
+    /// Adds an asset and all of its dependencies, directly or indirectly. Already added assets are skipped.
+    /// Dependencies are stored before their dependents.
+    pub fn add(&mut self, key: &AssetKey) -> Result<(), AssetPackError> {
+        // Cycles are not an error here; they are reported when the pack is loaded.
+        if !self.keys.insert(key.clone()) {
+            return Ok(());
+        }
+
+        let (path, source) = match key {
+            AssetKey::Id(id) => {
+                let data = self
+                    .database
+                    .find_asset_by_id(*id)
+                    .ok_or(AssetPackError::AssetNotFound(*id))?;
+                let source = process_asset(
+                    &data.path,
+                    data.asset_type,
+                    Some(&data.metadata_content),
+                    self.pipeline_gfx_bridge,
+                )
+                .map_err(|error| AssetPackError::ProcessError {
+                    key: key.clone(),
+                    error,
+                })?;
+                let path =
+                    data.path
+                        .strip_prefix(self.database.base_path())
+                        .ok()
+                        .map(|path| {
+                            Vec::from_iter(path.components().map(|component| {
+                                component.as_os_str().to_string_lossy().into_owned()
+                            }))
+                            .join("/")
+                        });
+
+                (path, source)
+            }
+            AssetKey::Path(path) => {
+                let asset_type = deduce_asset_type_from_path(path)?;
+                let source = process_asset(
+                    path,
+                    asset_type,
+                    None as Option<&str>,
+                    self.pipeline_gfx_bridge,
+                )
+                .map_err(|error| AssetPackError::ProcessError {
+                    key: key.clone(),
+                    error,
+                })?;
+
+                (None, source)
+            }
+        };
+
+        let dependencies = source.dependencies();
+
+        for dependency in &dependencies {
+            self.add(dependency)?;
+        }
+
+        let payload = bincode::serialize(&source)?;
+        let uncompressed_size = payload.len() as u64;
+        let payload = match self.compression {
+            AssetPackCompression::None => payload,
+            AssetPackCompression::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&payload)?;
+                encoder.finish()?
+            }
+        };
+
+        let size = payload.len() as u64;
+        self.entries.push((
+            AssetPackEntry {
+                key: key.clone(),
+                path,
+                asset_type: source.asset_type(),
+                offset: self.offset,
+                size,
+                uncompressed_size,
+                compression: self.compression,
+                dependencies,
+            },
+            payload,
+        ));
+        self.offset += size;
+
+        Ok(())
+    }
+
+    /// Adds all assets of the database.
+    pub fn add_all(&mut self) -> Result<(), AssetPackError> {
+        let mut ids = Vec::from_iter(self.database.assets().map(|data| data.id));
+        // Keep the output stable across runs.
+        ids.sort();
+
+        for id in ids {
+            self.add(&AssetKey::Id(id))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the pack. The payloads come first and the index follows them.
+    pub fn write(&self, mut writer: impl Write) -> Result<(), AssetPackError> {
+        writer.write_all(&ASSET_PACK_MAGIC)?;
+        writer.write_all(&ASSET_PACK_VERSION.to_le_bytes())?;
+        writer.write_all(&self.offset.to_le_bytes())?;
+
+        for (_, payload) in &self.entries {
+            writer.write_all(payload)?;
+        }
+
+        bincode::serialize_into(&mut writer, &Vec::from_iter(self.entries()))?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the pack to a file, replacing it if it exists.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), AssetPackError> {
+        self.write(std::io::BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{write_material, NullPipelineGfxBridge, TempDir},
+        AssetPack,
+    };
+    use asset::AssetType;
+    use asset_pipeline::TypedAssetSource;
+    use std::io::Cursor;
+    use uuid::Uuid;
+
+    #[test]
+    fn check_write_and_read() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+        std::fs::create_dir(base_path.join("sub")).unwrap();
+
+        // The two materials depend on each other, which must not hang the builder.
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        write_material(base_path, "a.mat", a, AssetKey::Id(b));
+        write_material(base_path, "sub/b.mat", b, AssetKey::Id(a));
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let mut builder = AssetPackBuilder::new(&database, &NullPipelineGfxBridge)
+            .with_compression(AssetPackCompression::Deflate);
+        builder.add(&AssetKey::Id(a)).unwrap();
+        builder.add_all().unwrap();
+        assert_eq!(builder.entries().count(), 2);
+
+        let mut content = Vec::new();
+        builder.write(&mut content).unwrap();
+
+        let pack = AssetPack::from_reader(Cursor::new(content)).unwrap();
+        let entry = pack.find_entry_by_path("sub/b.mat").unwrap();
+        assert_eq!(entry.key, AssetKey::Id(b));
+        assert_eq!(entry.asset_type, AssetType::Material);
+        assert_eq!(entry.dependencies, vec![AssetKey::Id(a)]);
+        assert_eq!(pack.dependents(&AssetKey::Id(a)), &[AssetKey::Id(b)]);
+
+        match pack.read_source(entry).unwrap() {
+            TypedAssetSource::Material(source) => assert_eq!(source.shader, AssetKey::Id(a)),
+            _ => panic!("expected a packed material"),
+        }
+
+        assert_eq!(
+            pack.resolve_entry(&AssetKey::Path("a.mat".to_owned()))
+                .map(|entry| &entry.key),
+            Some(&AssetKey::Id(a))
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{AssetData, AssetLoadError, CookedAssetCache};
+use asset::AssetKey;
+use asset_pipeline::{
+    deduce_asset_type_from_path, process_asset, PipelineGfxBridge, TypedAssetSource,
+};
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Arc, Mutex,
+};
+
+/// What to process; the database lookup is done beforehand, so that workers do not need the database.
+pub(crate) enum AssetProcessRequest {
+    Indexed(Arc<AssetData>),
+    Path(String),
+}
+
+impl AssetProcessRequest {
+    /// Processes the asset, going through the cooked asset cache for indexed assets.
+    pub fn process(
+        &self,
+        cooked_cache: Option<&CookedAssetCache>,
+        pipeline_gfx_bridge: &dyn PipelineGfxBridge,
+    ) -> Result<TypedAssetSource, AssetLoadError> {
+        match self {
+            AssetProcessRequest::Indexed(data) => {
+                let process = || -> Result<_, AssetLoadError> {
+                    Ok(process_asset(
+                        &data.path,
+                        data.asset_type,
+                        Some(&data.metadata_content),
+                        pipeline_gfx_bridge,
+                    )?)
+                };
+
+                match cooked_cache {
+                    Some(cooked_cache) => cooked_cache.load_or_process(
+                        data,
+                        pipeline_gfx_bridge.semantic_signature(),
+                        process,
+                    ),
+                    None => process(),
+                }
+            }
+            AssetProcessRequest::Path(path) => {
+                let asset_type = deduce_asset_type_from_path(path)?;
+
+                Ok(process_asset(
+                    path,
+                    asset_type,
+                    None as Option<&str>,
+                    pipeline_gfx_bridge,
+                )?)
+            }
+        }
+    }
+}
+
+type AssetProcessResult = (AssetKey, Result<TypedAssetSource, AssetLoadError>);
+
+/// Worker threads that process asset sources in the background.
+/// Workers are detached; they exit once the pool is dropped and the request at hand is done.
+pub(crate) struct AssetProcessPool {
+    request_sender: Sender<(AssetKey, AssetProcessRequest)>,
+    result_receiver: Receiver<AssetProcessResult>,
+}
+
+impl AssetProcessPool {
+    pub fn new(
+        worker_count: usize,
+        cooked_cache: Option<CookedAssetCache>,
+        pipeline_gfx_bridge: Arc<dyn PipelineGfxBridge + Send + Sync>,
+    ) -> Self {
+        let (request_sender, request_receiver) = channel::<(AssetKey, AssetProcessRequest)>();
+        let (result_sender, result_receiver) = channel();
+        let request_receiver = Arc::new(Mutex::new(request_receiver));
+
+        for index in 0..worker_count.max(1) {
+            let request_receiver = request_receiver.clone();
+            let result_sender = result_sender.clone();
+            let cooked_cache = cooked_cache.clone();
+            let pipeline_gfx_bridge = pipeline_gfx_bridge.clone();
+
+            std::thread::Builder::new()
+                .name(format!("asset-process-{}", index))
+                .spawn(move || loop {
+                    // Release the lock before processing, so that other workers can take requests.
+                    let request = request_receiver.lock().unwrap().recv();
+                    let (key, request) = match request {
+                        Ok(request) => request,
+                        Err(_) => break,
+                    };
+                    let result = request.process(cooked_cache.as_ref(), &*pipeline_gfx_bridge);
+
+                    if result_sender.send((key, result)).is_err() {
+                        break;
+                    }
+                })
+                .expect("failed to spawn an asset process worker");
+        }
+
+        Self {
+            request_sender,
+            result_receiver,
+        }
+    }
+
+    pub fn submit(&self, key: AssetKey, request: AssetProcessRequest) {
+        // Workers live as long as the pool, so this cannot fail.
+        let _ = self.request_sender.send((key, request));
+    }
+
+    /// Returns a finished request, if any, without blocking.
+    pub fn try_recv(&self) -> Option<AssetProcessResult> {
+        self.result_receiver.try_recv().ok()
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{AssetData, AssetDatabase};
+use asset_pipeline::metadata_path;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileSnapshot {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileSnapshot {
+    fn take(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AssetSnapshot {
+    file: Option<FileSnapshot>,
+    metadata: Option<FileSnapshot>,
+    inputs: Vec<Option<FileSnapshot>>,
+}
+
+impl AssetSnapshot {
+    fn take(data: &AssetData) -> Self {
+        Self {
+            file: FileSnapshot::take(&data.path),
+            metadata: FileSnapshot::take(&metadata_path(&data.path)),
+            inputs: Vec::from_iter(data.input_paths.iter().map(|path| FileSnapshot::take(path))),
+        }
+    }
+}
+
+/// Detects changes of registered assets, their metadata files and their other inputs by polling the file system.
+/// It does not rely on any OS-specific file notification API.
+#[derive(Debug, Clone)]
+pub struct AssetWatcher {
+    interval: Duration,
+    last_poll: Option<Instant>,
+    snapshots: HashMap<Uuid, (PathBuf, AssetSnapshot)>,
+}
+
+impl AssetWatcher {
+    /// Creates a watcher that checks the file system at most once per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_poll: None,
+            snapshots: HashMap::new(),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Returns ids of the assets whose file, metadata file or other inputs have changed since the last poll.
+    /// Assets seen for the first time are only recorded, not reported.
+    pub fn poll(&mut self, database: &AssetDatabase) -> Vec<Uuid> {
+        let now = Instant::now();
+
+        if let Some(last_poll) = self.last_poll {
+            if now - last_poll < self.interval {
+                return vec![];
+            }
+        }
+
+        self.last_poll = Some(now);
+
+        let mut changed = Vec::new();
+        let mut snapshots = HashMap::with_capacity(self.snapshots.len());
+
+        for data in database.assets() {
+            let snapshot = AssetSnapshot::take(data);
+
+            match self.snapshots.remove(&data.id) {
+                Some((path, previous)) if path == data.path && previous != snapshot => {
+                    changed.push(data.id);
+                }
+                _ => {}
+            }
+
+            snapshots.insert(data.id, (data.path.clone(), snapshot));
+        }
+
+        self.snapshots = snapshots;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn check_poll() {
+        let dir = TempDir::new();
+        let base_path = dir.path();
+        let id = Uuid::new_v4();
+        std::fs::write(base_path.join("a.wgsl"), "").unwrap();
+        std::fs::write(
+            metadata_path(base_path.join("a.wgsl")),
+            format!("[asset]\nid = \"{}\"\n", id),
+        )
+        .unwrap();
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+
+        let mut watcher = AssetWatcher::new(Duration::ZERO);
+        assert!(watcher.poll(&database).is_empty());
+        assert!(watcher.poll(&database).is_empty());
+
+        std::fs::write(base_path.join("a.wgsl"), "// changed").unwrap();
+        assert_eq!(watcher.poll(&database), vec![id]);
+        assert!(watcher.poll(&database).is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+use crate::AssetData;
+use asset_pipeline::TypedAssetSource;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use uuid::Uuid;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Bump this whenever the layout of any asset source changes, to invalidate all cooked assets.
+const COOKED_ASSET_VERSION: u32 = 11;
+
+#[derive(Error, Debug)]
+pub enum CookedAssetCacheError {
+    #[error("io error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("bincode error: {0}")]
+    BincodeError(#[from] bincode::Error),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct CookedAssetHeader {
+    version: u32,
+    hash: u128,
+}
+
+/// On-disk cache of processed asset sources, serialized with bincode.
+/// Each entry is keyed by the asset id and validated against a content hash of the asset file, its metadata and its other inputs.
+#[derive(Debug, Clone)]
+pub struct CookedAssetCache {
+    path: PathBuf,
+}
+
+impl CookedAssetCache {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Creates a cache in a sibling directory of the given asset base path, e.g. `assets.cooked` for `assets`.
+    pub fn next_to(base_path: impl AsRef<Path>) -> Self {
+        let base_path = base_path.as_ref();
+        let path = match base_path.file_name() {
+            Some(name) => {
+                let mut name = name.to_os_string();
+                name.push(".cooked");
+                base_path.with_file_name(name)
+            }
+            None => base_path.join(".cooked"),
+        };
+
+        Self::new(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Computes the content hash of an indexed asset. It reads the asset file and its other inputs.
+    /// The semantic signature of the [`PipelineGfxBridge`](asset_pipeline::PipelineGfxBridge) processing the asset
+    /// is hashed as well, so that changes of the semantic tables invalidate cooked assets without a version bump.
+    pub fn compute_hash(asset: &AssetData, semantic_signature: &str) -> std::io::Result<u128> {
+        let mut hasher = Xxh3::new();
+        let content = std::fs::read(&asset.path)?;
+        hasher.update(&(content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+        hasher.update(&(asset.metadata_content.len() as u64).to_le_bytes());
+        hasher.update(asset.metadata_content.as_bytes());
+
+        for input_path in &asset.input_paths {
+            // Inputs may be optional, e.g. the metadata of a skeleton, so a missing one is hashed as such.
+            match std::fs::read(input_path) {
+                Ok(content) => {
+                    hasher.update(&[1]);
+                    hasher.update(&(content.len() as u64).to_le_bytes());
+                    hasher.update(&content);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => hasher.update(&[0]),
+                Err(err) => return Err(err),
+            }
+        }
+
+        hasher.update(semantic_signature.as_bytes());
+        Ok(hasher.digest128())
+    }
+
+    /// Returns the cooked asset if it is up to date, otherwise processes it and stores the result.
+    /// The cache is an optimization only; any failure of the cache itself falls back to processing.
+    pub fn load_or_process<E>(
+        &self,
+        asset: &AssetData,
+        semantic_signature: &str,
+        process: impl FnOnce() -> Result<TypedAssetSource, E>,
+    ) -> Result<TypedAssetSource, E>
+    where
+        E: From<std::io::Error>,
+    {
+        let hash = Self::compute_hash(asset, semantic_signature)?;
+
+        if let Ok(Some(processed)) = self.load(asset.id, hash) {
+            return Ok(processed);
+        }
+
+        let processed = process()?;
+        let _ = self.store(asset.id, hash, &processed);
+        Ok(processed)
+    }
+
+    /// Loads a cooked asset. Returns `None` if there is no entry or the entry is outdated.
+    pub fn load(
+        &self,
+        id: Uuid,
+        hash: u128,
+    ) -> Result<Option<TypedAssetSource>, CookedAssetCacheError> {
+        let file = match File::open(self.entry_path(id)) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut reader = BufReader::new(file);
+        let header: CookedAssetHeader = bincode::deserialize_from(&mut reader)?;
+
+        if header.version != COOKED_ASSET_VERSION || header.hash != hash {
+            return Ok(None);
+        }
+
+        Ok(Some(bincode::deserialize_from(&mut reader)?))
+    }
+
+    /// Stores a cooked asset, replacing the previous entry of the same id.
+    pub fn store(
+        &self,
+        id: Uuid,
+        hash: u128,
+        source: &TypedAssetSource,
+    ) -> Result<(), CookedAssetCacheError> {
+        std::fs::create_dir_all(&self.path)?;
+
+        // Write to a temporary file first, so that readers never observe a partially written entry.
+        let entry_path = self.entry_path(id);
+        let temp_path = entry_path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        bincode::serialize_into(
+            &mut writer,
+            &CookedAssetHeader {
+                version: COOKED_ASSET_VERSION,
+                hash,
+            },
+        )?;
+        bincode::serialize_into(&mut writer, source)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(temp_path, entry_path)?;
+
+        Ok(())
+    }
+
+    /// Removes the cooked asset of the given id, if any.
+    pub fn remove(&self, id: Uuid) -> std::io::Result<()> {
+        match std::fs::remove_file(self.entry_path(id)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes all cooked assets.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn entry_path(&self, id: Uuid) -> PathBuf {
+        self.path.join(format!("{}.bin", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+    use asset::{assets::MaterialSource, AssetKey};
+
+    fn material_source() -> TypedAssetSource {
+        TypedAssetSource::Material(MaterialSource {
+            shader: AssetKey::Path("shader.wgsl".to_owned()),
+            binding_props: vec![],
+            instance_props: vec![],
+        })
+    }
+
+    #[test]
+    fn check_store_and_load() {
+        let dir = TempDir::new();
+        let cache = CookedAssetCache::new(dir.path().join("cooked"));
+        let id = Uuid::new_v4();
+
+        assert!(cache.load(id, 1).unwrap().is_none());
+
+        cache.store(id, 1, &material_source()).unwrap();
+
+        match cache.load(id, 1).unwrap() {
+            Some(TypedAssetSource::Material(source)) => {
+                assert_eq!(source.shader, AssetKey::Path("shader.wgsl".to_owned()));
+            }
+            _ => panic!("expected a cooked material"),
+        }
+        assert!(cache.load(id, 2).unwrap().is_none());
+
+        cache.clear().unwrap();
+        assert!(cache.load(id, 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn check_hash_semantic_signature() {
+        let dir = TempDir::new();
+        let path = dir.path().join("a.wgsl");
+        std::fs::write(&path, "").unwrap();
+
+        let asset = AssetData {
+            id: Uuid::new_v4(),
+            path,
+            asset_type: asset::AssetType::Shader,
+            metadata_content: String::new(),
+            input_paths: vec![],
+        };
+        let hash = CookedAssetCache::compute_hash(&asset, "binding a 1").unwrap();
+        assert_eq!(
+            CookedAssetCache::compute_hash(&asset, "binding a 1").unwrap(),
+            hash
+        );
+        assert_ne!(
+            CookedAssetCache::compute_hash(&asset, "binding a 1\nbinding b 2").unwrap(),
+            hash
+        );
+    }
+
+    #[test]
+    fn check_hash_inputs() {
+        let dir = TempDir::new();
+        let path = dir.path().join("motion.vmd");
+        let skeleton_path = dir.path().join("model.pmx");
+        std::fs::write(&path, "").unwrap();
+        std::fs::write(&skeleton_path, "").unwrap();
+
+        let asset = AssetData {
+            id: Uuid::new_v4(),
+            path,
+            asset_type: asset::AssetType::Animation,
+            metadata_content: String::new(),
+            input_paths: vec![dir.path().join("model.meta.toml"), skeleton_path.clone()],
+        };
+        let hash = CookedAssetCache::compute_hash(&asset, "").unwrap();
+
+        std::fs::write(dir.path().join("model.meta.toml"), "[mesh]\nscale = 0.08\n").unwrap();
+        let with_metadata = CookedAssetCache::compute_hash(&asset, "").unwrap();
+        assert_ne!(with_metadata, hash);
+
+        std::fs::write(&skeleton_path, "changed").unwrap();
+        assert_ne!(
+            CookedAssetCache::compute_hash(&asset, "").unwrap(),
+            with_metadata
+        );
+    }
+
+    #[test]
+    fn check_load_or_process() {
+        let dir = TempDir::new();
+        let cache = CookedAssetCache::new(dir.path().join("cooked"));
+        let path = dir.path().join("a.mat");
+        std::fs::write(&path, "").unwrap();
+
+        let asset = AssetData {
+            id: Uuid::new_v4(),
+            path,
+            asset_type: asset::AssetType::Material,
+            metadata_content: String::new(),
+            input_paths: vec![],
+        };
+        let processed = cache
+            .load_or_process(&asset, "", || Ok::<_, std::io::Error>(material_source()))
+            .unwrap();
+        assert!(matches!(processed, TypedAssetSource::Material(_)));
+
+        // The second call is served from the cache without processing.
+        let cooked = cache
+            .load_or_process(&asset, "", || -> std::io::Result<_> {
+                panic!("the cooked asset should be up to date")
+            })
+            .unwrap();
+        assert!(matches!(cooked, TypedAssetSource::Material(_)));
+    }
+
+    #[test]
+    fn check_next_to() {
+        let cache = CookedAssetCache::next_to("project/assets");
+        assert_eq!(cache.path(), Path::new("project/assets.cooked"));
+    }
+}
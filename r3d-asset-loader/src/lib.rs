@@ -1,6 +1,30 @@
+mod asset_cache;
 mod asset_database;
+#[cfg(feature = "pipeline")]
+mod asset_load_handle;
 mod asset_loader;
 pub mod asset_loaders;
+mod asset_pack;
+#[cfg(feature = "pipeline")]
+mod asset_pack_builder;
+#[cfg(feature = "pipeline")]
+mod asset_process_pool;
+mod asset_watcher;
+#[cfg(feature = "pipeline")]
+mod cooked_asset_cache;
+#[cfg(test)]
+mod test_utils;
 
+pub(crate) use asset_cache::*;
 pub use asset_database::*;
+#[cfg(feature = "pipeline")]
+pub use asset_load_handle::*;
 pub use asset_loader::*;
+pub use asset_pack::*;
+#[cfg(feature = "pipeline")]
+pub use asset_pack_builder::*;
+#[cfg(feature = "pipeline")]
+pub(crate) use asset_process_pool::*;
+pub use asset_watcher::*;
+#[cfg(feature = "pipeline")]
+pub use cooked_asset_cache::*;
@@ -0,0 +1,196 @@
+// Only the temporary directories are used without the pipeline.
+#![cfg_attr(not(feature = "pipeline"), allow(dead_code, unused_imports))]
+
+use asset::{
+    assets::{
+        MaterialSource, SemanticShaderBindingKey, SemanticShaderInputKey, SemanticShaderOutputKey,
+        TextureAddressMode, TextureFilterMode, TextureFormat,
+    },
+    AssetKey, GfxBridge, GfxBuffer, GfxSampler, GfxShaderModule, GfxTexture, GfxTextureView,
+};
+use asset_pipeline::metadata_path;
+#[cfg(feature = "pipeline")]
+use asset_pipeline::PipelineGfxBridge;
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use uuid::Uuid;
+
+/// A bridge that knows no semantics.
+#[cfg(feature = "pipeline")]
+pub struct NullPipelineGfxBridge;
+
+#[cfg(feature = "pipeline")]
+impl PipelineGfxBridge for NullPipelineGfxBridge {
+    fn get_semantic_binding_key(
+        &self,
+        _module: &naga::Module,
+        _name: &str,
+    ) -> Option<SemanticShaderBindingKey> {
+        None
+    }
+
+    fn get_semantic_input_key(
+        &self,
+        _step_mode: wgpu::VertexStepMode,
+        _format: wgpu::VertexFormat,
+        _name: &str,
+    ) -> Option<SemanticShaderInputKey> {
+        None
+    }
+
+    fn get_semantic_output_key(
+        &self,
+        _location: u32,
+        _name: &str,
+    ) -> Option<SemanticShaderOutputKey> {
+        None
+    }
+
+    fn semantic_signature(&self) -> &str {
+        ""
+    }
+}
+
+/// A bridge for tests, creating empty placeholder resources of the requested shape instead of uploading any content.
+/// The placeholders live on a device shared by all tests, which is requested on first use.
+/// Any adapter will do, including software ones, but creating a placeholder panics if there is none.
+pub struct NullGfxBridge;
+
+impl NullGfxBridge {
+    fn device(&self) -> &'static wgpu::Device {
+        static DEVICE: OnceLock<wgpu::Device> = OnceLock::new();
+
+        DEVICE.get_or_init(|| {
+            let instance = wgpu::Instance::default();
+            let adapter = pollster::block_on(instance.request_adapter(&Default::default())).expect(
+                "NullGfxBridge requires a wgpu adapter; install a software one such as llvmpipe",
+            );
+            let (device, _) = pollster::block_on(adapter.request_device(&Default::default(), None))
+                .expect("failed to create the placeholder device of NullGfxBridge");
+            device
+        })
+    }
+}
+
+impl GfxBridge for NullGfxBridge {
+    fn upload_vertex_buffer(&self, usage: wgpu::BufferUsages, content: &[u8]) -> GfxBuffer {
+        self.device()
+            .create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: content.len() as wgpu::BufferAddress,
+                usage,
+                mapped_at_creation: false,
+            })
+            .into()
+    }
+
+    fn compile_shader(&self, source: wgpu::ShaderSource) -> GfxShaderModule {
+        self.device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source,
+            })
+            .into()
+    }
+
+    fn upload_texture(
+        &self,
+        width: u16,
+        height: u16,
+        _format: TextureFormat,
+        _generate_mipmaps: bool,
+        _texels: &[u8],
+    ) -> GfxTexture {
+        self.device()
+            .create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: width.max(1) as u32,
+                    height: height.max(1) as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .into()
+    }
+
+    fn create_texture_view(&self, texture: &wgpu::Texture) -> GfxTextureView {
+        texture.create_view(&Default::default()).into()
+    }
+
+    fn create_sampler(
+        &self,
+        _filter_mode: TextureFilterMode,
+        _address_mode: (TextureAddressMode, TextureAddressMode),
+    ) -> GfxSampler {
+        self.device().create_sampler(&Default::default()).into()
+    }
+}
+
+/// A uniquely named directory under the system temporary directory, removed along with its content on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes a material that depends on the given shader key, along with its metadata.
+pub fn write_material(base_path: &Path, name: &str, id: Uuid, shader: AssetKey) {
+    let source = MaterialSource {
+        shader,
+        binding_props: vec![],
+        instance_props: vec![],
+    };
+    let mut content = Vec::new();
+    source.serialize_into(&mut content).unwrap();
+    std::fs::write(base_path.join(name), content).unwrap();
+    std::fs::write(
+        metadata_path(base_path.join(name)),
+        format!("[asset]\nid = \"{}\"\n", id),
+    )
+    .unwrap();
+}
+
+/// Writes a minimal shader without any bindings, along with its metadata.
+pub fn write_shader(base_path: &Path, name: &str, id: Uuid) {
+    std::fs::write(
+        base_path.join(name),
+        "@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
+",
+    )
+    .unwrap();
+    std::fs::write(
+        metadata_path(base_path.join(name)),
+        format!("[asset]\nid = \"{}\"\n", id),
+    )
+    .unwrap();
+}
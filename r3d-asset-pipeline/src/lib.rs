@@ -1,20 +1,30 @@
 use asset::{
-    assets::{FontSource, MaterialSource, ModelSource, ShaderSource, TextureSource},
-    AssetType,
+    assets::{
+        AnimationSource, FontSource, MaterialSource, ModelSource, ShaderSource, TextureSource,
+    },
+    AssetDepsProvider, AssetKey, AssetLoadError, AssetSource, AssetType, GfxBridge, TypedAsset,
 };
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 mod metadata;
+#[cfg(feature = "importers")]
 mod pipeline;
+#[cfg(feature = "importers")]
 mod pipeline_gfx_bridge;
+#[cfg(feature = "importers")]
 pub mod pipelines;
 
 pub use metadata::*;
+#[cfg(feature = "importers")]
 pub use pipeline::*;
+#[cfg(feature = "importers")]
 pub use pipeline_gfx_bridge::*;
 
+#[derive(Serialize, Deserialize)]
 pub enum TypedAssetSource {
+    Animation(AnimationSource),
     Font(FontSource),
     Material(MaterialSource),
     Model(ModelSource),
@@ -22,6 +32,64 @@ pub enum TypedAssetSource {
     Texture(TextureSource),
 }
 
+impl TypedAssetSource {
+    pub fn asset_type(&self) -> AssetType {
+        match self {
+            Self::Animation(_) => AssetType::Animation,
+            Self::Font(_) => AssetType::Font,
+            Self::Material(_) => AssetType::Material,
+            Self::Model(_) => AssetType::Model,
+            Self::Shader(_) => AssetType::Shader,
+            Self::Texture(_) => AssetType::Texture,
+        }
+    }
+
+    /// Lists all dependencies of the asset. See [`AssetSource::dependencies`].
+    pub fn dependencies(&self) -> Vec<AssetKey> {
+        match self {
+            Self::Animation(source) => source.dependencies(),
+            Self::Font(source) => source.dependencies(),
+            Self::Material(source) => source.dependencies(),
+            Self::Model(source) => source.dependencies(),
+            Self::Shader(source) => source.dependencies(),
+            Self::Texture(source) => source.dependencies(),
+        }
+    }
+
+    /// Constructs an asset from the source. See [`AssetSource::load`].
+    pub fn load(
+        self,
+        key: AssetKey,
+        deps_provider: &dyn AssetDepsProvider,
+        gfx_bridge: &dyn GfxBridge,
+    ) -> Result<TypedAsset, AssetLoadError> {
+        Ok(match self {
+            Self::Animation(source) => {
+                TypedAsset::Animation(source.load(key, deps_provider, gfx_bridge)?)
+            }
+            Self::Font(source) => TypedAsset::Font(source.load(key, deps_provider, gfx_bridge)?),
+            Self::Material(source) => {
+                TypedAsset::Material(source.load(key, deps_provider, gfx_bridge)?)
+            }
+            Self::Model(source) => {
+                TypedAsset::Model(source.load(key, deps_provider, gfx_bridge)?)
+            }
+            Self::Shader(source) => {
+                TypedAsset::Shader(source.load(key, deps_provider, gfx_bridge)?)
+            }
+            Self::Texture(source) => {
+                TypedAsset::Texture(source.load(key, deps_provider, gfx_bridge)?)
+            }
+        })
+    }
+}
+
+impl From<AnimationSource> for TypedAssetSource {
+    fn from(value: AnimationSource) -> Self {
+        Self::Animation(value)
+    }
+}
+
 impl From<FontSource> for TypedAssetSource {
     fn from(value: FontSource) -> Self {
         Self::Font(value)
@@ -52,6 +120,7 @@ impl From<TextureSource> for TypedAssetSource {
     }
 }
 
+#[cfg(feature = "importers")]
 #[derive(Error, Debug)]
 pub enum AssetProcessError {
     #[error("io error: {0}")]
@@ -62,6 +131,21 @@ pub enum AssetProcessError {
     AssetPipelineError(#[from] anyhow::Error),
 }
 
+/// Returns the files other than the asset file and its metadata that processing the asset reads,
+/// e.g. the skeleton of a motion along with its metadata.
+#[cfg(feature = "importers")]
+pub fn asset_input_paths(
+    path: impl AsRef<Path>,
+    asset_type: AssetType,
+    metadata_content: &str,
+) -> Vec<PathBuf> {
+    match asset_type {
+        AssetType::Animation => pipelines::animation_input_paths(path.as_ref(), metadata_content),
+        _ => vec![],
+    }
+}
+
+#[cfg(feature = "importers")]
 pub fn process_asset(
     path: impl AsRef<Path>,
     asset_type: AssetType,
@@ -70,6 +154,15 @@ pub fn process_asset(
 ) -> Result<TypedAssetSource, AssetProcessError> {
     let path = path.as_ref();
     match asset_type {
+        AssetType::Animation => {
+            let metadata = metadata_content
+                .map(|content| Metadata::from_toml(content))
+                .transpose()?;
+            let metadata = metadata.map(|metadata| metadata.extra).unwrap_or_default();
+            let file_content = std::fs::read(path)?;
+            let asset = AnimationSource::process(path, file_content, &metadata, gfx_bridge)?;
+            Ok(asset.into())
+        }
         AssetType::Font => {
             let metadata = metadata_content
                 .map(|content| Metadata::from_toml(content))
@@ -126,8 +219,11 @@ pub enum AssetTypeDeduceError {
     NoExtension(PathBuf),
     #[error("unsupported extension: {0}")]
     UnsupportedExtension(PathBuf),
+    #[error("{0} cannot be imported as {1}")]
+    UnsupportedImportAs(PathBuf, AssetType),
 }
 
+/// Deduces the asset type from the extension of the path.
 pub fn deduce_asset_type_from_path(
     path: impl AsRef<Path>,
 ) -> Result<AssetType, AssetTypeDeduceError> {
@@ -140,8 +236,9 @@ pub fn deduce_asset_type_from_path(
     match extension.to_lowercase().as_str() {
         "ttf" | "otf" => Ok(AssetType::Font),
         "mat" => Ok(AssetType::Material),
-        "gltf" | "glb" | "fbx" | "obj" | "3ds" | "blender" => Ok(AssetType::Model),
-        "pmx" => Ok(AssetType::Model),
+        "vmd" => Ok(AssetType::Animation),
+        "gltf" | "glb" | "fbx" | "dae" | "obj" | "3ds" | "blender" => Ok(AssetType::Model),
+        "pmx" | "pmd" => Ok(AssetType::Model),
         "png" | "jpg" | "jpeg" | "gif" | "tif" | "tiff" | "tga" | "bmp" | "webp" => {
             Ok(AssetType::Texture)
         }
@@ -151,3 +248,27 @@ pub fn deduce_asset_type_from_path(
         )),
     }
 }
+
+/// Deduces the asset type from the extension of the path, unless the metadata imports the file as another type.
+/// Model files other than PMX and PMD can be imported as animations, which imports their clips instead of their meshes.
+pub fn deduce_asset_type(
+    path: impl AsRef<Path>,
+    import_as: Option<AssetType>,
+) -> Result<AssetType, AssetTypeDeduceError> {
+    let path = path.as_ref();
+    let asset_type = deduce_asset_type_from_path(path)?;
+    let is_mmd_model = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pmx") || ext.eq_ignore_ascii_case("pmd"));
+
+    match (asset_type, import_as) {
+        (asset_type, None) => Ok(asset_type),
+        (asset_type, Some(import_as)) if import_as == asset_type => Ok(asset_type),
+        (AssetType::Model, Some(AssetType::Animation)) if !is_mmd_model => Ok(AssetType::Animation),
+        (_, Some(import_as)) => Err(AssetTypeDeduceError::UnsupportedImportAs(
+            path.to_path_buf(),
+            import_as,
+        )),
+    }
+}
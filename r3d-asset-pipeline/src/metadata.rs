@@ -1,4 +1,11 @@
+#[cfg(feature = "importers")]
+use crate::pipelines::{
+    AnimationMetadata, FontMetadata, MaterialMetadata, MeshMetadata, ShaderMetadata,
+    TextureMetadata,
+};
+use asset::AssetType;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -23,6 +30,10 @@ where
 #[derive(Serialize, Deserialize)]
 pub struct AssetMetadata {
     pub id: Uuid,
+    /// Imports the file as another type than the one deduced from its extension, e.g. `import_as = "animation"`
+    /// to import the clips of a glTF or FBX model. See [`crate::deduce_asset_type`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_as: Option<AssetType>,
 }
 
 impl<T> Metadata<T>
@@ -33,3 +44,92 @@ where
         toml::from_str(content.as_ref()).map_err(MetadataLoadError::from)
     }
 }
+
+impl<T> Metadata<T>
+where
+    T: Serialize + Default,
+{
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+}
+
+/// Returns the path of the metadata file of an asset, which is placed next to it.
+pub fn metadata_path(asset_path: impl AsRef<Path>) -> PathBuf {
+    asset_path.as_ref().with_extension("meta.toml")
+}
+
+/// Generates the content of a metadata file for an asset of the given type.
+/// It contains the given id and the default table of the corresponding pipeline.
+#[cfg(feature = "importers")]
+pub fn generate_metadata(asset_type: AssetType, id: Uuid) -> Result<String, toml::ser::Error> {
+    fn generate<T: Serialize + Default>(id: Uuid) -> Result<String, toml::ser::Error> {
+        Metadata {
+            asset: AssetMetadata {
+                id,
+                import_as: None,
+            },
+            extra: T::default(),
+        }
+        .to_toml()
+    }
+
+    match asset_type {
+        AssetType::Animation => generate::<AnimationMetadata>(id),
+        AssetType::Font => generate::<FontMetadata>(id),
+        AssetType::Material => generate::<MaterialMetadata>(id),
+        AssetType::Model => generate::<MeshMetadata>(id),
+        AssetType::Shader => generate::<ShaderMetadata>(id),
+        AssetType::Texture => generate::<TextureMetadata>(id),
+    }
+}
+
+#[cfg(all(test, feature = "importers"))]
+mod tests {
+    use super::*;
+    use crate::pipelines::{MeshTable, MeshTableUpAxis};
+
+    #[test]
+    fn check_generate_metadata() {
+        for asset_type in [
+            AssetType::Animation,
+            AssetType::Font,
+            AssetType::Material,
+            AssetType::Model,
+            AssetType::Shader,
+            AssetType::Texture,
+        ] {
+            let id = Uuid::new_v4();
+            let content = generate_metadata(asset_type, id).unwrap();
+            let id_only = Metadata::<MaterialMetadata>::from_toml(&content).unwrap();
+            assert_eq!(id_only.asset.id, id);
+        }
+
+        let content = generate_metadata(AssetType::Texture, Uuid::new_v4()).unwrap();
+        let texture = Metadata::<TextureMetadata>::from_toml(&content).unwrap();
+        assert!(texture.extra.sprite.is_empty());
+
+        let content = generate_metadata(AssetType::Font, Uuid::new_v4()).unwrap();
+        let font = Metadata::<FontMetadata>::from_toml(&content).unwrap();
+        assert_eq!(font.extra.font.sdf_radius, 3);
+
+        let content = generate_metadata(AssetType::Model, Uuid::new_v4()).unwrap();
+        let model = Metadata::<MeshMetadata>::from_toml(&content).unwrap();
+        assert_eq!(model.extra.mesh, MeshTable::default());
+    }
+
+    #[test]
+    fn check_partial_mesh_table() {
+        let content = format!(
+            "[asset]\nid = \"{}\"\n\n[mesh]\nscale = 0.08\nflip_z = true\n\n[[mesh.lods]]\ntriangle_ratio = 0.5\nscreen_size = 0.25\n",
+            Uuid::new_v4()
+        );
+        let model = Metadata::<MeshMetadata>::from_toml(content).unwrap();
+        assert_eq!(model.extra.mesh.scale, 0.08);
+        assert!(model.extra.mesh.flip_z);
+        assert_eq!(model.extra.mesh.up_axis, MeshTableUpAxis::Y);
+        assert!(model.extra.mesh.generate_normals);
+        assert_eq!(model.extra.mesh.lods.len(), 1);
+        assert_eq!(model.extra.mesh.lods[0].screen_size, 0.25);
+    }
+}
@@ -22,4 +22,7 @@ pub trait PipelineGfxBridge {
     /// Gets the semantic output key of a output item.
     fn get_semantic_output_key(&self, location: u32, name: &str)
         -> Option<SemanticShaderOutputKey>;
+    /// Gets a stable description of the semantics the keys above are resolved against.
+    /// Processed assets depend on it, so cached results must be invalidated when it changes.
+    fn semantic_signature(&self) -> &str;
 }
@@ -1,9 +1,13 @@
+mod animation;
+mod coordinate_conversion;
 mod font;
 mod material;
+mod mesh_simplifier;
 mod model;
 mod shader;
 mod texture;
 
+pub use animation::*;
 pub use font::*;
 pub use material::*;
 pub use model::*;
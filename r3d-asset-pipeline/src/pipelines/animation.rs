@@ -0,0 +1,464 @@
+use super::{
+    coordinate_conversion::CoordinateConversion,
+    model::{
+        convert_pmx_coordinates, parse_mmd_model, pmx_bone_name, pmx_bone_parents,
+        pmx_bone_translation, pmx_morph_name,
+    },
+    MeshMetadata, MeshTable,
+};
+use crate::{metadata_path, AssetPipeline, Metadata, PipelineGfxBridge};
+use anyhow::{anyhow, Context};
+use asset::assets::{
+    AnimationClip, AnimationKeyframe, AnimationMorphTrack, AnimationSource, AnimationTrack,
+};
+use pmx::{Vmd, VmdBoneKeyframe, VmdMorphKeyframe, VMD_FRAME_RATE};
+use russimp::scene::Scene;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// Ticks per second assumed for files that do not specify it, following assimp.
+const DEFAULT_TICKS_PER_SECOND: f64 = 25.0;
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct AnimationMetadata {
+    pub animation: AnimationTable,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct AnimationTable {
+    /// PMX or PMD model the motion is played on, relative to the motion file.
+    /// Required for VMD motions, whose keyframes are relative to the rest pose of the model.
+    /// Motions are converted into our coordinate system as specified by the metadata of the model.
+    pub skeleton: Option<PathBuf>,
+}
+
+impl AssetPipeline for AnimationSource {
+    type Metadata = AnimationMetadata;
+
+    fn process(
+        file_path: &Path,
+        file_content: Vec<u8>,
+        metadata: &Self::Metadata,
+        _gfx_bridge: &dyn PipelineGfxBridge,
+    ) -> anyhow::Result<Self> {
+        if file_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("vmd"))
+        {
+            return process_vmd_animation(file_path, &file_content, metadata);
+        }
+
+        let conversion = match &metadata.animation.skeleton {
+            Some(skeleton) => skeleton_conversion(&skeleton_path(file_path, skeleton))?,
+            None => CoordinateConversion::new(&MeshTable::default()),
+        };
+        let scene = Scene::from_buffer(&file_content, vec![], "")
+            .with_context(|| "failed to load animation from file")
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(AnimationSource {
+            clips: Vec::from_iter(
+                scene
+                    .animations
+                    .iter()
+                    .map(|animation| convert_animation(animation, &conversion)),
+            ),
+        })
+    }
+}
+
+/// Returns the skeleton of a motion and its metadata, which processing the motion reads.
+pub(crate) fn animation_input_paths(file_path: &Path, metadata_content: &str) -> Vec<PathBuf> {
+    let skeleton = match Metadata::<AnimationMetadata>::from_toml(metadata_content) {
+        Ok(metadata) => metadata.extra.animation.skeleton,
+        Err(_) => None,
+    };
+
+    match skeleton {
+        Some(skeleton) => {
+            let skeleton_path = skeleton_path(file_path, &skeleton);
+            vec![metadata_path(&skeleton_path), skeleton_path]
+        }
+        None => vec![],
+    }
+}
+
+fn skeleton_path(file_path: &Path, skeleton: &Path) -> PathBuf {
+    file_path.parent().unwrap_or(Path::new("")).join(skeleton)
+}
+
+/// Reads the coordinate conversion of the skeleton from its metadata, if any.
+fn skeleton_conversion(skeleton_path: &Path) -> anyhow::Result<CoordinateConversion> {
+    let metadata_path = metadata_path(skeleton_path);
+    let table = match std::fs::read_to_string(&metadata_path) {
+        Ok(content) => Metadata::<MeshMetadata>::from_toml(content)?.extra.mesh,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => MeshTable::default(),
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!(
+                    "failed to read metadata `{}` of the skeleton",
+                    metadata_path.display()
+                )
+            })
+        }
+    };
+
+    Ok(CoordinateConversion::new(&table))
+}
+
+fn convert_animation(
+    animation: &russimp::animation::Animation,
+    conversion: &CoordinateConversion,
+) -> AnimationClip {
+    let ticks_per_second = if 0f64 < animation.ticks_per_second {
+        animation.ticks_per_second
+    } else {
+        DEFAULT_TICKS_PER_SECOND
+    };
+    let to_seconds = |ticks: f64| (ticks / ticks_per_second) as f32;
+
+    AnimationClip {
+        name: animation.name.clone(),
+        duration: to_seconds(animation.duration),
+        tracks: Vec::from_iter(animation.channels.iter().map(|channel| AnimationTrack {
+            node_name: channel.name.clone(),
+            translations: Vec::from_iter(channel.position_keys.iter().map(|key| {
+                AnimationKeyframe {
+                    time: to_seconds(key.time),
+                    value: conversion.position([key.value.x, key.value.y, key.value.z]),
+                }
+            })),
+            rotations: Vec::from_iter(channel.rotation_keys.iter().map(|key| AnimationKeyframe {
+                time: to_seconds(key.time),
+                value: conversion.rotation([key.value.x, key.value.y, key.value.z, key.value.w]),
+            })),
+            scales: Vec::from_iter(channel.scaling_keys.iter().map(|key| AnimationKeyframe {
+                time: to_seconds(key.time),
+                value: conversion.axis_scale([key.value.x, key.value.y, key.value.z]),
+            })),
+        })),
+        morph_tracks: vec![],
+    }
+}
+
+/// Converts the bone and morph keyframes of a VMD motion into a single clip named after the file.
+/// Bones and morphs missing in the skeleton are dropped. Bezier interpolation is baked into a keyframe per frame.
+/// Camera, light and IK keyframes are not imported, since they do not animate the model itself.
+fn process_vmd_animation(
+    file_path: &Path,
+    content: &[u8],
+    metadata: &AnimationMetadata,
+) -> anyhow::Result<AnimationSource> {
+    let skeleton = metadata.animation.skeleton.as_ref().ok_or_else(|| {
+        anyhow!("VMD motions require `animation.skeleton`, the PMX model they are played on")
+    })?;
+    let skeleton_path = skeleton_path(file_path, skeleton);
+    let skeleton_content = std::fs::read(&skeleton_path).with_context(|| {
+        format!(
+            "failed to read skeleton `{}` of the motion",
+            skeleton_path.display()
+        )
+    })?;
+    let mut pmx = parse_mmd_model(&skeleton_path, &skeleton_content)
+        .with_context(|| "failed to load skeleton from file")?;
+    let conversion = skeleton_conversion(&skeleton_path)?;
+    convert_pmx_coordinates(&mut pmx, &conversion);
+    let vmd = Vmd::parse(content).with_context(|| "failed to load motion from file")?;
+
+    // Later keyframes of the same frame override earlier ones.
+    let mut bone_keyframes = HashMap::<&str, BTreeMap<u32, &VmdBoneKeyframe>>::new();
+
+    for keyframe in &vmd.bone_keyframes {
+        bone_keyframes
+            .entry(keyframe.bone_name.as_str())
+            .or_default()
+            .insert(keyframe.frame, keyframe);
+    }
+
+    let mut last_frame = 0;
+    let mut tracks = Vec::with_capacity(bone_keyframes.len());
+    let parents = pmx_bone_parents(&pmx.bones);
+
+    for (bone_index, bone) in pmx.bones.iter().enumerate() {
+        let node_name = pmx_bone_name(bone);
+        let keyframes = match bone_keyframes.get(node_name.as_str()) {
+            Some(keyframes) => keyframes,
+            None => continue,
+        };
+
+        if let Some(&frame) = keyframes.keys().next_back() {
+            last_frame = last_frame.max(frame);
+        }
+
+        tracks.push(convert_vmd_track(
+            node_name,
+            pmx_bone_translation(&pmx.bones, &parents, bone_index),
+            &conversion,
+            keyframes.values().copied(),
+        ));
+    }
+
+    // Morph keyframes are grouped the same way as bone keyframes.
+    let mut morph_keyframes = HashMap::<&str, BTreeMap<u32, &VmdMorphKeyframe>>::new();
+
+    for keyframe in &vmd.morph_keyframes {
+        morph_keyframes
+            .entry(keyframe.morph_name.as_str())
+            .or_default()
+            .insert(keyframe.frame, keyframe);
+    }
+
+    let mut morph_tracks = Vec::with_capacity(morph_keyframes.len());
+
+    for morph in &pmx.morphs {
+        let morph_name = pmx_morph_name(morph);
+        let keyframes = match morph_keyframes.remove(morph_name.as_str()) {
+            Some(keyframes) => keyframes,
+            None => continue,
+        };
+
+        if let Some(&frame) = keyframes.keys().next_back() {
+            last_frame = last_frame.max(frame);
+        }
+
+        morph_tracks.push(convert_vmd_morph_track(
+            morph_name,
+            keyframes.values().copied(),
+        ));
+    }
+
+    Ok(AnimationSource {
+        clips: vec![AnimationClip {
+            name: file_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            duration: last_frame as f32 / VMD_FRAME_RATE,
+            tracks,
+            morph_tracks,
+        }],
+    })
+}
+
+/// VMD translations are offsets from the rest translation of the bone, which is already converted.
+fn convert_vmd_track<'a>(
+    node_name: String,
+    rest_translation: [f32; 3],
+    conversion: &CoordinateConversion,
+    keyframes: impl Iterator<Item = &'a VmdBoneKeyframe>,
+) -> AnimationTrack {
+    let translation = |keyframe: &VmdBoneKeyframe| {
+        let offset = conversion.position([
+            keyframe.translation.x,
+            keyframe.translation.y,
+            keyframe.translation.z,
+        ]);
+        [
+            rest_translation[0] + offset[0],
+            rest_translation[1] + offset[1],
+            rest_translation[2] + offset[2],
+        ]
+    };
+    let rotation = |keyframe: &VmdBoneKeyframe| {
+        conversion.rotation([
+            keyframe.rotation.x,
+            keyframe.rotation.y,
+            keyframe.rotation.z,
+            keyframe.rotation.w,
+        ])
+    };
+
+    let mut translations = Vec::new();
+    let mut rotations = Vec::new();
+    let mut previous: Option<&VmdBoneKeyframe> = None;
+
+    for keyframe in keyframes {
+        // The interpolation of a keyframe applies from the previous keyframe to it.
+        let interpolation = &keyframe.interpolation;
+        let is_linear = interpolation.translation_x.is_linear()
+            && interpolation.translation_y.is_linear()
+            && interpolation.translation_z.is_linear()
+            && interpolation.rotation.is_linear();
+
+        if let Some(previous) = previous.filter(|_| !is_linear) {
+            let from_translation = translation(previous);
+            let to_translation = translation(keyframe);
+            let from_rotation = rotation(previous);
+            let to_rotation = rotation(keyframe);
+            let span = (keyframe.frame - previous.frame) as f32;
+
+            for frame in previous.frame + 1..keyframe.frame {
+                let progress = (frame - previous.frame) as f32 / span;
+                let time = frame as f32 / VMD_FRAME_RATE;
+                let curves = [
+                    interpolation.translation_x,
+                    interpolation.translation_y,
+                    interpolation.translation_z,
+                ];
+                let mut value = [0f32; 3];
+
+                for (axis, curve) in curves.iter().enumerate() {
+                    let weight = curve.evaluate(progress);
+                    value[axis] = from_translation[axis]
+                        + (to_translation[axis] - from_translation[axis]) * weight;
+                }
+
+                translations.push(AnimationKeyframe { time, value });
+                rotations.push(AnimationKeyframe {
+                    time,
+                    value: slerp(
+                        from_rotation,
+                        to_rotation,
+                        interpolation.rotation.evaluate(progress),
+                    ),
+                });
+            }
+        }
+
+        let time = keyframe.frame as f32 / VMD_FRAME_RATE;
+        translations.push(AnimationKeyframe {
+            time,
+            value: translation(keyframe),
+        });
+        rotations.push(AnimationKeyframe {
+            time,
+            value: rotation(keyframe),
+        });
+        previous = Some(keyframe);
+    }
+
+    AnimationTrack {
+        node_name,
+        translations,
+        rotations,
+        scales: vec![],
+    }
+}
+
+/// VMD morph keyframes are interpolated linearly, so they map to keyframes one to one.
+fn convert_vmd_morph_track<'a>(
+    morph_name: String,
+    keyframes: impl Iterator<Item = &'a VmdMorphKeyframe>,
+) -> AnimationMorphTrack {
+    AnimationMorphTrack {
+        morph_name,
+        weights: Vec::from_iter(keyframes.map(|keyframe| AnimationKeyframe {
+            time: keyframe.frame as f32 / VMD_FRAME_RATE,
+            value: keyframe.weight,
+        })),
+    }
+}
+
+/// Spherical interpolation of `[x, y, z, w]` quaternions along the shortest path.
+fn slerp(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let mut cos = from
+        .iter()
+        .zip(&to)
+        .map(|(lhs, rhs)| lhs * rhs)
+        .sum::<f32>();
+    let to = if cos < 0.0 {
+        cos = -cos;
+        to.map(|value| -value)
+    } else {
+        to
+    };
+
+    let (lhs, rhs) = if 1.0 - f32::EPSILON <= cos {
+        (1.0 - t, t)
+    } else {
+        let angle = cos.acos();
+        let inv_sin = angle.sin().recip();
+        (
+            (angle * (1.0 - t)).sin() * inv_sin,
+            (angle * t).sin() * inv_sin,
+        )
+    };
+
+    let value = [0, 1, 2, 3].map(|index| from[index] * lhs + to[index] * rhs);
+    let len = value.iter().map(|value| value * value).sum::<f32>().sqrt();
+
+    if len == 0.0 {
+        value
+    } else {
+        value.map(|value| value / len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pmx::{PmxVec3, PmxVec4, VmdBezierCurve, VmdBoneInterpolation};
+
+    fn keyframe(frame: u32, x: f32, interpolation: VmdBoneInterpolation) -> VmdBoneKeyframe {
+        VmdBoneKeyframe {
+            bone_name: "bone".to_owned(),
+            frame,
+            translation: PmxVec3 { x, y: 0.0, z: 0.0 },
+            rotation: PmxVec4 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 1.0,
+            },
+            interpolation,
+        }
+    }
+
+    #[test]
+    fn check_vmd_track_bakes_bezier_interpolation() {
+        let ease_in = VmdBoneInterpolation {
+            translation_x: VmdBezierCurve {
+                x1: 127,
+                y1: 0,
+                x2: 127,
+                y2: 127,
+            },
+            ..Default::default()
+        };
+        let keyframes = [
+            keyframe(0, 0.0, Default::default()),
+            keyframe(4, 4.0, ease_in),
+            keyframe(6, 0.0, Default::default()),
+        ];
+        let track = convert_vmd_track(
+            "bone".to_owned(),
+            [1.0, 2.0, 3.0],
+            &CoordinateConversion::new(&MeshTable::default()),
+            keyframes.iter(),
+        );
+
+        // frames 1 to 3 are baked; the linear segment from 4 to 6 is not
+        let times = Vec::from_iter(track.translations.iter().map(|keyframe| keyframe.time));
+        assert_eq!(
+            times,
+            [0.0, 1.0, 2.0, 3.0, 4.0, 6.0].map(|frame| frame / VMD_FRAME_RATE)
+        );
+        assert_eq!(track.translations[0].value, [1.0, 2.0, 3.0]);
+        assert_eq!(track.translations[4].value, [5.0, 2.0, 3.0]);
+        // eased in, behind the linear value at the midpoint
+        assert!(track.translations[2].value[0] < 3.0);
+        assert_eq!(track.rotations.len(), track.translations.len());
+    }
+
+    #[test]
+    fn check_vmd_morph_track() {
+        let keyframes = [(0, 0.0), (10, 1.0), (15, 0.5)].map(|(frame, weight)| VmdMorphKeyframe {
+            morph_name: "あ".to_owned(),
+            frame,
+            weight,
+        });
+        let track = convert_vmd_morph_track("あ".to_owned(), keyframes.iter());
+
+        assert_eq!(track.morph_name, "あ");
+        assert_eq!(
+            track.weights,
+            [(0.0, 0.0), (10.0, 1.0), (15.0, 0.5)].map(|(frame, value)| AnimationKeyframe {
+                time: frame / VMD_FRAME_RATE,
+                value,
+            })
+        );
+    }
+}
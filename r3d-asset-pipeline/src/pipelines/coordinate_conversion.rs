@@ -0,0 +1,163 @@
+use super::{MeshTable, MeshTableUpAxis};
+
+/// Conversion from the coordinate system of a source file into ours, which is right handed and +Y up.
+/// It is a uniform scale combined with a signed permutation of the axes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CoordinateConversion {
+    scale: f32,
+    /// Source axis and sign of each axis.
+    axes: [(usize, f32); 3],
+}
+
+impl CoordinateConversion {
+    pub fn new(table: &MeshTable) -> Self {
+        let mut axes = match table.up_axis {
+            MeshTableUpAxis::Y => [(0, 1.0), (1, 1.0), (2, 1.0)],
+            // Rotates -90 degrees around the X axis, bringing +Z up and +Y forward.
+            MeshTableUpAxis::Z => [(0, 1.0), (2, 1.0), (1, -1.0)],
+        };
+
+        if table.flip_z {
+            axes[2].1 = -axes[2].1;
+        }
+
+        Self {
+            scale: table.scale,
+            axes,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.scale == 1.0 && self.axes == [(0, 1.0), (1, 1.0), (2, 1.0)]
+    }
+
+    /// `true` if the handedness changes, which reverses the winding order of triangles.
+    pub fn is_mirrored(&self) -> bool {
+        // The sign of the determinant, where each swap of axes negates it as well.
+        let swaps = (0..3)
+            .flat_map(|i| (i + 1..3).map(move |j| (i, j)))
+            .filter(|&(i, j)| self.axes[j].0 < self.axes[i].0)
+            .count();
+        let signs = self.axes.iter().map(|&(_, sign)| sign).product::<f32>();
+
+        (swaps % 2 == 1) != (signs < 0.0)
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Converts a direction, such as a normal. Directions are not scaled.
+    pub fn direction(&self, vec: [f32; 3]) -> [f32; 3] {
+        self.axes.map(|(axis, sign)| vec[axis] * sign)
+    }
+
+    pub fn position(&self, vec: [f32; 3]) -> [f32; 3] {
+        self.direction(vec).map(|value| value * self.scale)
+    }
+
+    /// Converts a per axis scale, which has no sign to convert.
+    pub fn axis_scale(&self, vec: [f32; 3]) -> [f32; 3] {
+        self.axes.map(|(axis, _)| vec[axis])
+    }
+
+    /// Converts rotation angles around each axis. Mirroring reverses the direction of rotations.
+    /// The order of Euler angles is kept, which is exact unless the up axis changes.
+    pub fn angles(&self, vec: [f32; 3]) -> [f32; 3] {
+        let handedness = if self.is_mirrored() { -1.0 } else { 1.0 };
+        self.direction(vec).map(|value| value * handedness)
+    }
+
+    /// Converts a range of rotation angles, keeping `min <= max` on each axis.
+    pub fn angle_range(&self, min: [f32; 3], max: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+        sort_range(self.angles(min), self.angles(max))
+    }
+
+    /// Converts a range of positions, keeping `min <= max` on each axis.
+    pub fn position_range(&self, min: [f32; 3], max: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+        sort_range(self.position(min), self.position(max))
+    }
+
+    /// Converts an `[x, y, z, w]` quaternion.
+    pub fn rotation(&self, quat: [f32; 4]) -> [f32; 4] {
+        let [x, y, z] = self.angles([quat[0], quat[1], quat[2]]);
+        [x, y, z, quat[3]]
+    }
+
+    /// Converts a column-major affine matrix, which maps from and into the source coordinate system.
+    pub fn matrix(&self, matrix: [f32; 16]) -> [f32; 16] {
+        let mut converted = [0f32; 16];
+
+        for column in 0..3 {
+            let (source_column, column_sign) = self.axes[column];
+
+            for row in 0..3 {
+                let (source_row, row_sign) = self.axes[row];
+                converted[column * 4 + row] =
+                    matrix[source_column * 4 + source_row] * column_sign * row_sign;
+            }
+        }
+
+        let [x, y, z] = self.position([matrix[12], matrix[13], matrix[14]]);
+        converted[12] = x;
+        converted[13] = y;
+        converted[14] = z;
+        converted[15] = 1.0;
+        converted
+    }
+}
+
+fn sort_range(lhs: [f32; 3], rhs: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+    (
+        [0, 1, 2].map(|axis| lhs[axis].min(rhs[axis])),
+        [0, 1, 2].map(|axis| lhs[axis].max(rhs[axis])),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversion(scale: f32, up_axis: MeshTableUpAxis, flip_z: bool) -> CoordinateConversion {
+        CoordinateConversion::new(&MeshTable {
+            scale,
+            up_axis,
+            flip_z,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn check_coordinate_conversion() {
+        let identity = conversion(1.0, MeshTableUpAxis::Y, false);
+        assert!(identity.is_identity());
+        assert!(!identity.is_mirrored());
+
+        let mmd = conversion(0.5, MeshTableUpAxis::Y, true);
+        assert!(mmd.is_mirrored());
+        assert_eq!(mmd.position([2.0, 4.0, 6.0]), [1.0, 2.0, -3.0]);
+        assert_eq!(mmd.direction([0.0, 0.0, 1.0]), [0.0, 0.0, -1.0]);
+        assert_eq!(mmd.rotation([1.0, 2.0, 3.0, 4.0]), [-1.0, -2.0, 3.0, 4.0]);
+        assert_eq!(
+            mmd.angle_range([-1.0, 0.0, -3.0], [2.0, 0.5, 0.0]),
+            ([-2.0, -0.5, -3.0], [1.0, 0.0, 0.0])
+        );
+
+        let z_up = conversion(1.0, MeshTableUpAxis::Z, false);
+        assert!(!z_up.is_mirrored());
+        assert_eq!(z_up.position([0.0, 0.0, 1.0]), [0.0, 1.0, 0.0]);
+        assert_eq!(z_up.position([0.0, 1.0, 0.0]), [0.0, 0.0, -1.0]);
+        assert_eq!(z_up.axis_scale([1.0, 2.0, 3.0]), [1.0, 3.0, 2.0]);
+
+        // a translation by +Z, converted into a translation by +Y
+        let mut matrix = [0f32; 16];
+        matrix[0] = 1.0;
+        matrix[5] = 1.0;
+        matrix[10] = 1.0;
+        matrix[14] = 2.0;
+        matrix[15] = 1.0;
+        let converted = z_up.matrix(matrix);
+        assert_eq!(&converted[12..], [0.0, 2.0, 0.0, 1.0]);
+        assert_eq!([converted[0], converted[5], converted[10]], [1.0, 1.0, 1.0]);
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 #[derive(Default, Serialize, Deserialize)]
-pub struct MaterialMetadata;
+pub struct MaterialMetadata {}
 
 impl AssetPipeline for MaterialSource {
     type Metadata = MaterialMetadata;
@@ -0,0 +1,441 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+/// Simplifies a triangle list by collapsing edges in the order of their quadric error.
+/// An edge is collapsed into one of its vertices, so the result refers to the same vertices and
+/// vertex attributes do not have to be interpolated.
+///
+/// Vertices on open borders and vertices sharing their position with others, such as those on UV seams,
+/// are never collapsed away. Collapses flipping a triangle are rejected. Simplification stops once
+/// at most `target_index_count` indices remain, or no edge can be collapsed.
+pub(crate) fn simplify_mesh(
+    positions: &[[f32; 3]],
+    indices: &[u32],
+    target_index_count: usize,
+) -> Vec<u32> {
+    let mut simplifier = MeshSimplifier::new(positions, indices);
+    simplifier.simplify(target_index_count);
+    simplifier.indices()
+}
+
+/// Symmetric 4x4 matrix measuring the squared distance to a set of planes.
+#[derive(Debug, Default, Clone, Copy)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: [f64; 3], distance: f64, weight: f64) -> Self {
+        let [a, b, c] = normal;
+        let d = distance;
+
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|value| value * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (lhs, rhs) in self.0.iter_mut().zip(&other.0) {
+            *lhs += rhs;
+        }
+    }
+
+    fn error(&self, position: [f32; 3]) -> f64 {
+        let [x, y, z] = position.map(|value| value as f64);
+        let q = &self.0;
+
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// Collapse of the vertex `from` into the vertex `to`.
+#[derive(Debug, Clone, Copy)]
+struct Collapse {
+    error: f64,
+    from: u32,
+    to: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// Reversed, so that the collapse of the least error is at the top of a [`BinaryHeap`].
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .error
+            .total_cmp(&self.error)
+            .then_with(|| (other.from, other.to).cmp(&(self.from, self.to)))
+    }
+}
+
+struct MeshSimplifier<'a> {
+    positions: &'a [[f32; 3]],
+    triangles: Vec<[u32; 3]>,
+    is_triangle_removed: Vec<bool>,
+    triangle_count: usize,
+    /// Triangles referring to each vertex, including removed ones.
+    vertex_triangles: Vec<Vec<u32>>,
+    quadrics: Vec<Quadric>,
+    is_locked: Vec<bool>,
+    is_vertex_removed: Vec<bool>,
+    collapses: BinaryHeap<Collapse>,
+}
+
+impl<'a> MeshSimplifier<'a> {
+    fn new(positions: &'a [[f32; 3]], indices: &[u32]) -> Self {
+        let vertex_count = positions.len();
+        let triangles = Vec::from_iter(
+            indices
+                .chunks_exact(3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .filter(|triangle| {
+                    triangle
+                        .iter()
+                        .all(|&index| (index as usize) < vertex_count)
+                }),
+        );
+        let mut vertex_triangles = vec![Vec::new(); vertex_count];
+        let mut quadrics = vec![Quadric::default(); vertex_count];
+        let mut is_locked = vec![false; vertex_count];
+        let mut edge_counts = HashMap::<(u32, u32), u32>::new();
+
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            let [p0, p1, p2] = triangle.map(|index| positions[index as usize].map(f64::from));
+            let normal = cross(sub(p1, p0), sub(p2, p0));
+            let area = dot(normal, normal).sqrt();
+
+            if 0.0 < area {
+                let normal = normal.map(|value| value / area);
+                let quadric = Quadric::from_plane(normal, -dot(normal, p0), area);
+
+                for &index in triangle {
+                    quadrics[index as usize].add(&quadric);
+                }
+            }
+
+            for (corner, &index) in triangle.iter().enumerate() {
+                vertex_triangles[index as usize].push(triangle_index as u32);
+
+                let next = triangle[(corner + 1) % 3];
+                *edge_counts
+                    .entry((index.min(next), index.max(next)))
+                    .or_default() += 1;
+            }
+        }
+
+        for (&(lhs, rhs), &count) in &edge_counts {
+            if count == 1 {
+                is_locked[lhs as usize] = true;
+                is_locked[rhs as usize] = true;
+            }
+        }
+
+        let mut vertices_by_position = HashMap::<[u32; 3], Vec<u32>>::new();
+
+        for (index, position) in positions.iter().enumerate() {
+            vertices_by_position
+                .entry(position.map(f32::to_bits))
+                .or_default()
+                .push(index as u32);
+        }
+
+        for indices in vertices_by_position
+            .values()
+            .filter(|indices| 1 < indices.len())
+        {
+            for &index in indices {
+                is_locked[index as usize] = true;
+            }
+        }
+
+        let triangle_count = triangles.len();
+        let mut simplifier = Self {
+            positions,
+            is_triangle_removed: vec![false; triangle_count],
+            triangle_count,
+            triangles,
+            vertex_triangles,
+            quadrics,
+            is_locked,
+            is_vertex_removed: vec![false; vertex_count],
+            collapses: BinaryHeap::new(),
+        };
+
+        for &(lhs, rhs) in edge_counts.keys() {
+            simplifier.push_collapse(lhs, rhs);
+            simplifier.push_collapse(rhs, lhs);
+        }
+
+        simplifier
+    }
+
+    fn collapse_error(&self, from: u32, to: u32) -> f64 {
+        let mut quadric = self.quadrics[from as usize];
+        quadric.add(&self.quadrics[to as usize]);
+        quadric.error(self.positions[to as usize])
+    }
+
+    fn push_collapse(&mut self, from: u32, to: u32) {
+        if self.is_locked[from as usize] {
+            return;
+        }
+
+        self.collapses.push(Collapse {
+            error: self.collapse_error(from, to),
+            from,
+            to,
+        });
+    }
+
+    fn simplify(&mut self, target_index_count: usize) {
+        while target_index_count < self.triangle_count * 3 {
+            let collapse = match self.collapses.pop() {
+                Some(collapse) => collapse,
+                None => break,
+            };
+            let (from, to) = (collapse.from, collapse.to);
+
+            if self.is_vertex_removed[from as usize] || self.is_vertex_removed[to as usize] {
+                continue;
+            }
+
+            // The quadrics may have grown since the collapse was queued.
+            let error = self.collapse_error(from, to);
+
+            if collapse.error < error {
+                self.collapses.push(Collapse { error, ..collapse });
+                continue;
+            }
+
+            if !self.is_edge(from, to) || self.flips_triangle(from, to) {
+                continue;
+            }
+
+            self.collapse(from, to);
+        }
+    }
+
+    fn live_triangles(&self, vertex: u32) -> impl Iterator<Item = u32> + '_ {
+        self.vertex_triangles[vertex as usize]
+            .iter()
+            .copied()
+            .filter(|&triangle| !self.is_triangle_removed[triangle as usize])
+    }
+
+    fn is_edge(&self, from: u32, to: u32) -> bool {
+        self.live_triangles(from)
+            .any(|triangle| self.triangles[triangle as usize].contains(&to))
+    }
+
+    /// `true` if moving `from` onto `to` flips a triangle that is not removed by the collapse.
+    fn flips_triangle(&self, from: u32, to: u32) -> bool {
+        self.live_triangles(from).any(|triangle| {
+            let triangle = self.triangles[triangle as usize];
+
+            if triangle.contains(&to) {
+                return false;
+            }
+
+            let position = |index: u32| self.positions[index as usize].map(f64::from);
+            let normal = |[p0, p1, p2]: [[f64; 3]; 3]| cross(sub(p1, p0), sub(p2, p0));
+            let before = normal(triangle.map(position));
+            let after =
+                normal(triangle.map(|index| position(if index == from { to } else { index })));
+
+            dot(before, after) <= 0.0
+        })
+    }
+
+    fn collapse(&mut self, from: u32, to: u32) {
+        let quadric = self.quadrics[from as usize];
+        self.quadrics[to as usize].add(&quadric);
+        self.is_vertex_removed[from as usize] = true;
+
+        for triangle_index in std::mem::take(&mut self.vertex_triangles[from as usize]) {
+            if self.is_triangle_removed[triangle_index as usize] {
+                continue;
+            }
+
+            let triangle = &mut self.triangles[triangle_index as usize];
+
+            if triangle.contains(&to) {
+                self.is_triangle_removed[triangle_index as usize] = true;
+                self.triangle_count -= 1;
+                continue;
+            }
+
+            for index in triangle.iter_mut() {
+                if *index == from {
+                    *index = to;
+                }
+            }
+
+            self.vertex_triangles[to as usize].push(triangle_index);
+        }
+
+        let mut neighbors = Vec::from_iter(
+            self.live_triangles(to)
+                .flat_map(|triangle| self.triangles[triangle as usize])
+                .filter(|&index| index != to),
+        );
+        neighbors.sort_unstable();
+        neighbors.dedup();
+
+        for neighbor in neighbors {
+            self.push_collapse(neighbor, to);
+            self.push_collapse(to, neighbor);
+        }
+    }
+
+    fn indices(&self) -> Vec<u32> {
+        Vec::from_iter(
+            self.triangles
+                .iter()
+                .zip(&self.is_triangle_removed)
+                .filter(|(_, &is_removed)| !is_removed)
+                .flat_map(|(triangle, _)| *triangle),
+        )
+    }
+}
+
+fn sub(lhs: [f64; 3], rhs: [f64; 3]) -> [f64; 3] {
+    [lhs[0] - rhs[0], lhs[1] - rhs[1], lhs[2] - rhs[2]]
+}
+
+fn dot(lhs: [f64; 3], rhs: [f64; 3]) -> f64 {
+    lhs[0] * rhs[0] + lhs[1] * rhs[1] + lhs[2] * rhs[2]
+}
+
+fn cross(lhs: [f64; 3], rhs: [f64; 3]) -> [f64; 3] {
+    [
+        lhs[1] * rhs[2] - lhs[2] * rhs[1],
+        lhs[2] * rhs[0] - lhs[0] * rhs[2],
+        lhs[0] * rhs[1] - lhs[1] * rhs[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A closed box made of `n` by `n` quads per face, with vertices shared between faces.
+    fn subdivided_cube(n: usize) -> (Vec<[f32; 3]>, Vec<u32>) {
+        let mut positions = Vec::new();
+        let mut vertex_indices = HashMap::new();
+        let mut indices = Vec::new();
+        let mut vertex = |position: [f32; 3]| -> u32 {
+            *vertex_indices
+                .entry(position.map(f32::to_bits))
+                .or_insert_with(|| {
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+        };
+
+        for axis in 0..3 {
+            for side in [0.0, 1.0] {
+                let point = |u: usize, v: usize| {
+                    let (u, v) = (u as f32 / n as f32, v as f32 / n as f32);
+                    let mut position = [0.0; 3];
+                    position[axis] = side;
+                    position[(axis + 1) % 3] = u;
+                    position[(axis + 2) % 3] = v;
+                    position
+                };
+
+                for u in 0..n {
+                    for v in 0..n {
+                        let quad = [
+                            vertex(point(u, v)),
+                            vertex(point(u + 1, v)),
+                            vertex(point(u + 1, v + 1)),
+                            vertex(point(u, v + 1)),
+                        ];
+                        // Faces on the positive side wind the other way, so all of them face outwards.
+                        let quad = if side == 0.0 {
+                            [quad[0], quad[3], quad[2], quad[1]]
+                        } else {
+                            quad
+                        };
+
+                        indices.extend_from_slice(&[quad[0], quad[1], quad[2]]);
+                        indices.extend_from_slice(&[quad[0], quad[2], quad[3]]);
+                    }
+                }
+            }
+        }
+
+        (positions, indices)
+    }
+
+    #[test]
+    fn check_simplify_flat_faces() {
+        let (positions, indices) = subdivided_cube(4);
+        let simplified = simplify_mesh(&positions, &indices, 0);
+        assert_eq!(simplified.len() % 3, 0);
+        assert!(simplified.len() < indices.len());
+
+        // Every face is flat, so the box collapses into its 12 triangles without any error.
+        let simplified = simplify_mesh(&positions, &indices, 36);
+        assert_eq!(simplified.len(), 36);
+
+        for index in simplified {
+            let position = positions[index as usize];
+            assert!(position.iter().all(|&value| value == 0.0 || value == 1.0));
+        }
+    }
+
+    #[test]
+    fn check_simplify_keeps_borders_and_seams() {
+        // a fan of 4 triangles around a center vertex, whose outer vertices are on the border
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, -1.0, 0.0],
+        ];
+        let indices = [0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 1];
+        let simplified = simplify_mesh(&positions, &indices, 0);
+        assert_eq!(simplified.len(), 6);
+        assert!(!simplified.contains(&0));
+
+        // the center is duplicated, as if it were on a seam
+        let positions = [positions.to_vec(), vec![[0.0, 0.0, 0.0]]].concat();
+        let indices = [0, 1, 2, 0, 2, 3, 5, 3, 4, 5, 4, 1];
+        assert_eq!(simplify_mesh(&positions, &indices, 0), indices);
+    }
+}
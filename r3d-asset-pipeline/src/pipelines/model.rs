@@ -1,12 +1,26 @@
-use crate::{AssetPipeline, PipelineGfxBridge};
+use super::{coordinate_conversion::CoordinateConversion, mesh_simplifier::simplify_mesh};
+use crate::{deduce_asset_type_from_path, AssetPipeline, PipelineGfxBridge};
 use anyhow::{anyhow, Context};
-use asset::assets::{
-    MeshAABB, MeshSource, ModelSource, NodeSource, NodeTransform, VertexAttribute,
-    VertexAttributeKind, VertexIndexType,
+use asset::{
+    assets::{
+        BoneAngleLimit, BoneIK, BoneIKLink, BoneInheritance, BoneLocalCoordinate, GroupMorphOffset,
+        MaterialMorphOffset, MaterialMorphOperation, MaterialMorphValues, MeshAABB, MeshLodSource,
+        MeshMaterialEnvironmentBlendMode, MeshMaterialSource, MeshMorphOffset, MeshMorphTarget,
+        MeshSkin, MeshSource, ModelSource, MorphKind, MorphSource, NodeBone, NodeSource,
+        NodeTransform, PbrMaterialSource, RigidBodyJointSource, RigidBodyMode, RigidBodyShape,
+        RigidBodySource, VertexAttribute, VertexAttributeKind, VertexIndexType,
+    },
+    AssetKey, AssetType,
 };
 use byteorder::ByteOrder;
-use pmx::Pmx;
+use pmx::{
+    Pmx, PmxBone, PmxBoneIndex, PmxBoneInheritanceMode, PmxBoneTailPosition, PmxMaterial,
+    PmxMaterialEnvironmentBlendMode, PmxMaterialToonMode, PmxMorph, PmxMorphMaterialOperation,
+    PmxMorphOffset, PmxRigidbodyPhysicsMode, PmxRigidbodyShapeKind, PmxVec3, PmxVec4,
+    PmxVertexDeformKind,
+};
 use russimp::{
+    material::{Material, PropertyTypeInfo, TextureType},
     mesh::PrimitiveType,
     scene::{PostProcess, Scene},
     Color4D, Vector3D,
@@ -24,8 +38,58 @@ pub struct MeshMetadata {
     pub mesh: MeshTable,
 }
 
-#[derive(Default, Serialize, Deserialize)]
-pub struct MeshTable {}
+/// Import options of a model. Missing fields take their default values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MeshTable {
+    /// Uniform scale applied to the model, such as `0.08` for PMX models authored in units of 8 cm.
+    pub scale: f32,
+    /// Up axis of the source file, converted into +Y.
+    pub up_axis: MeshTableUpAxis,
+    /// `true` if the Z axis should be negated, converting left handed models such as PMX into right handed.
+    pub flip_z: bool,
+    /// `true` if missing normals should be generated. Ignored for PMX models.
+    pub generate_normals: bool,
+    /// `true` if tangents and bitangents should be generated. Ignored for PMX models.
+    pub generate_tangents: bool,
+    /// `true` if the node hierarchy should be collapsed where possible. Ignored for PMX models.
+    pub optimize_graph: bool,
+    /// `true` if meshes with too many vertices or triangles should be split. Ignored for PMX models.
+    pub split_large_meshes: bool,
+    /// Simplified levels of detail generated for every sub mesh, in addition to the full one.
+    pub lods: Vec<MeshTableLod>,
+}
+
+impl Default for MeshTable {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            up_axis: MeshTableUpAxis::Y,
+            flip_z: false,
+            generate_normals: true,
+            generate_tangents: true,
+            optimize_graph: true,
+            split_large_meshes: true,
+            lods: vec![],
+        }
+    }
+}
+
+/// A level of detail to generate, under `[[mesh.lods]]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MeshTableLod {
+    /// Fraction of the triangles of the full mesh to keep, between `0` and `1`.
+    pub triangle_ratio: f32,
+    /// The level is drawn while the mesh covers at most this fraction of the viewport height.
+    pub screen_size: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MeshTableUpAxis {
+    Y,
+    Z,
+}
 
 impl AssetPipeline for ModelSource {
     type Metadata = MeshMetadata;
@@ -33,126 +97,180 @@ impl AssetPipeline for ModelSource {
     fn process(
         file_path: &Path,
         file_content: Vec<u8>,
-        _metadata: &Self::Metadata,
+        metadata: &Self::Metadata,
         _gfx_bridge: &dyn PipelineGfxBridge,
     ) -> anyhow::Result<Self> {
-        if file_path
-            .extension()
-            .map_or(false, |ext| ext.to_ascii_lowercase() == "pmx")
-        {
-            process_pmx_model(&file_content)
+        let mut model = if is_mmd_model(file_path) {
+            process_pmx_model(file_path, &file_content, &metadata.mesh)?
         } else {
-            process_assimp_model(&file_content)
+            process_assimp_model(file_path, &file_content, &metadata.mesh)?
+        };
+
+        if !metadata.mesh.lods.is_empty() {
+            for mesh in &mut model.meshes {
+                generate_lods(mesh, &metadata.mesh.lods);
+            }
         }
+
+        Ok(model)
     }
 }
 
-fn process_pmx_model(content: &[u8]) -> anyhow::Result<ModelSource> {
-    let pmx = Pmx::parse(content).with_context(|| "failed to load mesh from file")?;
-
-    let mut material_offset = 0;
-    let mut meshes = Vec::with_capacity(pmx.materials.len());
+/// `true` if the file is a MikuMikuDance model, PMX or the legacy PMD.
+fn is_mmd_model(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pmx") || ext.eq_ignore_ascii_case("pmd"))
+}
 
-    for material_index in 0..pmx.materials.len() {
-        let material = &pmx.materials[material_index];
-        let surfaces = &pmx.surfaces[material_offset..material.surface_count as usize];
-        material_offset += material.surface_count as usize;
+/// Parses a PMX model. PMD models are converted into PMX.
+pub(crate) fn parse_mmd_model(file_path: &Path, content: &[u8]) -> anyhow::Result<Pmx> {
+    let is_pmd = file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pmd"));
 
-        let aabb = if surfaces.len() == 0 {
-            MeshAABB {
-                min: [0f32; 3],
-                max: [0f32; 3],
-            }
-        } else {
-            let mut aabb = MeshAABB {
-                min: [f32::MAX; 3],
-                max: [f32::MIN; 3],
-            };
-
-            for surface in surfaces {
-                let vertex = &pmx.vertices[surface.vertex_indices[0].get() as usize];
-                aabb.min[0] = aabb.min[0].min(vertex.position.x);
-                aabb.min[1] = aabb.min[1].min(vertex.position.y);
-                aabb.min[2] = aabb.min[2].min(vertex.position.z);
-
-                aabb.max[0] = aabb.max[0].max(vertex.position.x);
-                aabb.max[1] = aabb.max[1].max(vertex.position.y);
-                aabb.max[2] = aabb.max[2].max(vertex.position.z);
-
-                let vertex = &pmx.vertices[surface.vertex_indices[1].get() as usize];
-                aabb.min[0] = aabb.min[0].min(vertex.position.x);
-                aabb.min[1] = aabb.min[1].min(vertex.position.y);
-                aabb.min[2] = aabb.min[2].min(vertex.position.z);
-
-                aabb.max[0] = aabb.max[0].max(vertex.position.x);
-                aabb.max[1] = aabb.max[1].max(vertex.position.y);
-                aabb.max[2] = aabb.max[2].max(vertex.position.z);
-
-                let vertex = &pmx.vertices[surface.vertex_indices[2].get() as usize];
-                aabb.min[0] = aabb.min[0].min(vertex.position.x);
-                aabb.min[1] = aabb.min[1].min(vertex.position.y);
-                aabb.min[2] = aabb.min[2].min(vertex.position.z);
-
-                aabb.max[0] = aabb.max[0].max(vertex.position.x);
-                aabb.max[1] = aabb.max[1].max(vertex.position.y);
-                aabb.max[2] = aabb.max[2].max(vertex.position.z);
-            }
+    if is_pmd {
+        Ok(Pmx::parse_pmd(content)?)
+    } else {
+        Ok(Pmx::parse(content)?)
+    }
+}
 
-            aabb
-        };
+fn process_pmx_model(
+    file_path: &Path,
+    content: &[u8],
+    table: &MeshTable,
+) -> anyhow::Result<ModelSource> {
+    let mut pmx =
+        parse_mmd_model(file_path, content).with_context(|| "failed to load mesh from file")?;
+    convert_pmx_coordinates(&mut pmx, &CoordinateConversion::new(table));
+
+    let additional_vec4_count = pmx.header.config.additional_vec4_count;
+    let is_skinned = !pmx.bones.is_empty();
+    let mut vertex_attributes = Vec::with_capacity(5 + additional_vec4_count);
+    vertex_attributes.push(VertexAttribute {
+        offset: 0,
+        kind: VertexAttributeKind::Position,
+    });
+    vertex_attributes.push(VertexAttribute {
+        offset: size_of::<[f32; 3]>() as u32,
+        kind: VertexAttributeKind::Normal,
+    });
+    vertex_attributes.push(VertexAttribute {
+        offset: size_of::<[f32; 6]>() as u32,
+        kind: VertexAttributeKind::TexCoord { index: 0 },
+    });
 
-        let additional_vec4_count = pmx.header.config.additional_vec4_count;
-        let mut vertex_attributes = Vec::with_capacity(3 + additional_vec4_count);
+    for index in 0..additional_vec4_count {
         vertex_attributes.push(VertexAttribute {
-            offset: 0,
-            kind: VertexAttributeKind::Position,
+            offset: size_of::<[f32; 8]>() as u32 + (size_of::<[f32; 4]>() * index) as u32,
+            kind: VertexAttributeKind::Extra {
+                index: index as u32,
+            },
         });
+    }
+
+    if is_skinned {
+        let offset =
+            size_of::<[f32; 8]>() as u32 + (size_of::<[f32; 4]>() * additional_vec4_count) as u32;
         vertex_attributes.push(VertexAttribute {
-            offset: size_of::<[f32; 3]>() as u32,
-            kind: VertexAttributeKind::Normal,
+            offset,
+            kind: VertexAttributeKind::Joints,
         });
         vertex_attributes.push(VertexAttribute {
-            offset: size_of::<[f32; 6]>() as u32,
-            kind: VertexAttributeKind::TexCoord { index: 0 },
+            offset: offset + size_of::<[u32; 4]>() as u32,
+            kind: VertexAttributeKind::Weights,
         });
+    }
 
-        for index in 0..additional_vec4_count {
-            vertex_attributes.push(VertexAttribute {
-                offset: size_of::<[f32; 8]>() as u32 + (size_of::<[f32; 4]>() * index) as u32,
-                kind: VertexAttributeKind::Extra {
-                    index: index as u32,
-                },
-            });
-        }
-
-        let mut vertices = Vec::<u8>::with_capacity(
-            surfaces.len() * 3 * (8 + additional_vec4_count * 4) * size_of::<f32>(),
-        );
+    let mut material_offset = 0;
+    let mut meshes = Vec::with_capacity(pmx.materials.len());
+    // Sub mesh and vertex index of each PMX vertex, which may be shared by several sub meshes.
+    let mut vertex_locations = vec![Vec::new(); pmx.vertices.len()];
+
+    for (material_index, material) in pmx.materials.iter().enumerate() {
+        // The surface count of a material is the number of its vertex indices.
+        let material_end =
+            (material_offset + material.surface_count as usize / 3).min(pmx.surfaces.len());
+        let surfaces = &pmx.surfaces[material_offset..material_end];
+        material_offset = material_end;
+
+        let mut aabb = MeshAABB {
+            min: [f32::MAX; 3],
+            max: [f32::MIN; 3],
+        };
+        let mut vertices = Vec::<u8>::new();
+        let mut vertex_count = 0u32;
         let mut indices = Vec::with_capacity(surfaces.len() * 3);
         let mut index_map = HashMap::new();
+        // Only the bones used by this mesh become its joints, to keep the bone palette small.
+        let mut joints = Vec::new();
+        let mut joint_map = HashMap::new();
 
-        for (surface_index, surface) in surfaces.iter().enumerate() {
-            for (surface_sub_index, vertex_index) in surface.vertex_indices.iter().enumerate() {
-                let index = match index_map.entry(surface_index * 3 + surface_sub_index) {
+        for surface in surfaces {
+            for vertex_index in &surface.vertex_indices {
+                let index = match index_map.entry(vertex_index.get()) {
                     Entry::Occupied(entry) => *entry.get(),
                     Entry::Vacant(entry) => {
-                        let vertex = &pmx.vertices[vertex_index.get() as usize];
+                        let vertex =
+                            pmx.vertices
+                                .get(vertex_index.get() as usize)
+                                .ok_or_else(|| {
+                                    anyhow!("vertex index `{}` is out of range", vertex_index.get())
+                                })?;
+                        let position = [vertex.position.x, vertex.position.y, vertex.position.z];
+
+                        for (axis, &value) in position.iter().enumerate() {
+                            aabb.min[axis] = aabb.min[axis].min(value);
+                            aabb.max[axis] = aabb.max[axis].max(value);
+                        }
 
+                        vertices.extend_from_slice(position.as_bytes());
                         vertices.extend_from_slice(
-                            &[vertex.position.x, vertex.position.y, vertex.position.z].as_bytes(),
-                        );
-                        vertices.extend_from_slice(
-                            &[vertex.normal.x, vertex.normal.y, vertex.normal.z].as_bytes(),
+                            [vertex.normal.x, vertex.normal.y, vertex.normal.z].as_bytes(),
                         );
-                        vertices.extend_from_slice(&[vertex.uv.x, vertex.uv.y].as_bytes());
+                        vertices.extend_from_slice([vertex.uv.x, vertex.uv.y].as_bytes());
 
                         for index in 0..additional_vec4_count {
                             let vec4 = &vertex.additional_vec4s[index];
-                            vertices
-                                .extend_from_slice(&[vec4.x, vec4.y, vec4.z, vec4.w].as_bytes());
+                            vertices.extend_from_slice([vec4.x, vec4.y, vec4.z, vec4.w].as_bytes());
                         }
 
-                        let index = vertices.len() as u32;
+                        if is_skinned {
+                            let mut joint_indices = [0u32; 4];
+                            let mut weights = [0f32; 4];
+
+                            for (slot, (bone_index, weight)) in
+                                pmx_vertex_influences(&vertex.deform_kind)
+                                    .into_iter()
+                                    .enumerate()
+                            {
+                                let bone_index = bone_index.get();
+
+                                if weight <= 0f32
+                                    || bone_index < 0
+                                    || pmx.bones.len() <= bone_index as usize
+                                {
+                                    continue;
+                                }
+
+                                joint_indices[slot] =
+                                    *joint_map.entry(bone_index).or_insert_with(|| {
+                                        joints.push(bone_index as usize);
+                                        joints.len() as u32 - 1
+                                    });
+                                weights[slot] = weight;
+                            }
+
+                            normalize_weights(&mut weights);
+                            vertices.extend_from_slice(joint_indices.as_bytes());
+                            vertices.extend_from_slice(weights.as_bytes());
+                        }
+
+                        let index = vertex_count;
+                        vertex_count += 1;
+                        vertex_locations[vertex_index.get() as usize]
+                            .push((material_index as u32, index));
                         entry.insert(index);
                         index
                     }
@@ -162,11 +280,18 @@ fn process_pmx_model(content: &[u8]) -> anyhow::Result<ModelSource> {
             }
         }
 
+        if vertex_count == 0 {
+            aabb = MeshAABB {
+                min: [0f32; 3],
+                max: [0f32; 3],
+            };
+        }
+
         // reduce vertex indices if possible
-        let (index_type, indices) = if index_map.is_empty() {
+        let (index_type, indices) = if vertex_count == 0 {
             (VertexIndexType::U8, vec![])
         } else {
-            let max = *index_map.values().max().unwrap();
+            let max = vertex_count - 1;
 
             if max <= u8::MAX as u32 {
                 let mut raw_indices = Vec::with_capacity(indices.len());
@@ -196,69 +321,799 @@ fn process_pmx_model(content: &[u8]) -> anyhow::Result<ModelSource> {
             }
         };
 
+        // Meshes are placed at the model origin, so the bind pose of a bone is its position.
+        let skin = is_skinned.then(|| MeshSkin {
+            joints: Vec::from_iter(joints.iter().map(|&bone_index| bone_index as u32 + 1)),
+            inverse_bind_matrices: Vec::from_iter(joints.iter().map(|&bone_index| {
+                let position = &pmx.bones[bone_index].position;
+                translation_matrix([-position.x, -position.y, -position.z])
+            })),
+        });
+
         meshes.push(MeshSource {
             index: material_index as u32,
             aabb,
             index_type,
             index_buffer: indices,
-            vertex_attributes,
+            vertex_attributes: vertex_attributes.clone(),
             vertex_buffer: vertices,
-            vertex_count: surfaces.len() as u32 * 3,
-            material: None,
+            vertex_count,
+            material: Some(convert_pmx_material(&pmx, material, file_path)),
+            skin,
+            morph_targets: vec![],
+            lods: vec![],
         });
     }
 
-    // test: drop all bones and attach all meshes to root node
-    let nodes = vec![NodeSource {
+    let nodes = convert_pmx_bones(&pmx.bones, meshes.len() as u32);
+    let morphs = convert_pmx_morphs(&pmx, &vertex_locations, &mut meshes);
+    let rigid_bodies = convert_pmx_rigid_bodies(&pmx);
+    let rigid_body_joints = convert_pmx_joints(&pmx);
+
+    Ok(ModelSource {
+        root_node_index: Some(0),
+        nodes,
+        meshes,
+        morphs,
+        rigid_bodies,
+        rigid_body_joints,
+    })
+}
+
+/// Converts everything positioned in the PMX model into our coordinate system.
+/// Surfaces are reversed if the conversion mirrors the model, keeping their faces visible.
+pub(crate) fn convert_pmx_coordinates(pmx: &mut Pmx, conversion: &CoordinateConversion) {
+    if conversion.is_identity() {
+        return;
+    }
+
+    let position = |vec: &mut PmxVec3| set_pmx_vec3(vec, conversion.position(pmx_vec3(vec)));
+    let direction = |vec: &mut PmxVec3| set_pmx_vec3(vec, conversion.direction(pmx_vec3(vec)));
+    let angles = |vec: &mut PmxVec3| set_pmx_vec3(vec, conversion.angles(pmx_vec3(vec)));
+    let axis_scale = |vec: &mut PmxVec3| set_pmx_vec3(vec, conversion.axis_scale(pmx_vec3(vec)));
+    let position_range = |min: &mut PmxVec3, max: &mut PmxVec3| {
+        let (new_min, new_max) = conversion.position_range(pmx_vec3(min), pmx_vec3(max));
+        set_pmx_vec3(min, new_min);
+        set_pmx_vec3(max, new_max);
+    };
+    let angle_range = |min: &mut PmxVec3, max: &mut PmxVec3| {
+        let (new_min, new_max) = conversion.angle_range(pmx_vec3(min), pmx_vec3(max));
+        set_pmx_vec3(min, new_min);
+        set_pmx_vec3(max, new_max);
+    };
+
+    for vertex in &mut pmx.vertices {
+        position(&mut vertex.position);
+        direction(&mut vertex.normal);
+
+        if let PmxVertexDeformKind::Sdef { c, r0, r1, .. } = &mut vertex.deform_kind {
+            position(c);
+            position(r0);
+            position(r1);
+        }
+    }
+
+    if conversion.is_mirrored() {
+        for surface in &mut pmx.surfaces {
+            surface.vertex_indices.swap(1, 2);
+        }
+    }
+
+    for bone in &mut pmx.bones {
+        position(&mut bone.position);
+
+        if let PmxBoneTailPosition::Vec3 { position: tail } = &mut bone.tail_position {
+            position(tail);
+        }
+
+        if let Some(fixed_axis) = &mut bone.fixed_axis {
+            direction(&mut fixed_axis.direction);
+        }
+
+        if let Some(local_coordinate) = &mut bone.local_coordinate {
+            direction(&mut local_coordinate.x_axis);
+            direction(&mut local_coordinate.z_axis);
+        }
+
+        for link in bone.ik.iter_mut().flat_map(|ik| &mut ik.links) {
+            if let Some(limit) = &mut link.angle_limit {
+                angle_range(&mut limit.min, &mut limit.max);
+            }
+        }
+    }
+
+    for morph in &mut pmx.morphs {
+        match &mut morph.offset {
+            PmxMorphOffset::Vertex(offsets) => {
+                for offset in offsets {
+                    position(&mut offset.translation);
+                }
+            }
+            PmxMorphOffset::Bone(offsets) => {
+                for offset in offsets {
+                    position(&mut offset.translation);
+                    let rotation = conversion.rotation(pmx_vec4(&offset.rotation));
+                    offset.rotation = PmxVec4 {
+                        x: rotation[0],
+                        y: rotation[1],
+                        z: rotation[2],
+                        w: rotation[3],
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for rigidbody in &mut pmx.rigidbodies {
+        let shape = &mut rigidbody.shape;
+        let size = pmx_vec3(&shape.size);
+        // Spheres and capsules store their radius and height rather than extents along axes.
+        let size = match shape.kind {
+            PmxRigidbodyShapeKind::Box => conversion.axis_scale(size),
+            PmxRigidbodyShapeKind::Sphere | PmxRigidbodyShapeKind::Capsule => size,
+        };
+        set_pmx_vec3(
+            &mut shape.size,
+            size.map(|value| value * conversion.scale()),
+        );
+        position(&mut shape.position);
+        angles(&mut shape.rotation);
+    }
+
+    for joint in &mut pmx.joints {
+        position(&mut joint.position);
+        angles(&mut joint.rotation);
+        position_range(&mut joint.position_limit_min, &mut joint.position_limit_max);
+        angle_range(&mut joint.rotation_limit_min, &mut joint.rotation_limit_max);
+        axis_scale(&mut joint.spring_position);
+        axis_scale(&mut joint.spring_rotation);
+    }
+}
+
+/// Returns up to 4 bones influencing the vertex, along with their weights.
+/// Sdef and Qdef are approximated by linear blend skinning.
+fn pmx_vertex_influences(deform_kind: &PmxVertexDeformKind) -> [(PmxBoneIndex, f32); 4] {
+    let none = (PmxBoneIndex::new(-1), 0f32);
+
+    match deform_kind {
+        &PmxVertexDeformKind::Bdef1 { bone_index } => [(bone_index, 1f32), none, none, none],
+        &PmxVertexDeformKind::Bdef2 {
+            bone_index_1,
+            bone_index_2,
+            bone_weight,
+        }
+        | &PmxVertexDeformKind::Sdef {
+            bone_index_1,
+            bone_index_2,
+            bone_weight,
+            ..
+        } => [
+            (bone_index_1, bone_weight),
+            (bone_index_2, 1f32 - bone_weight),
+            none,
+            none,
+        ],
+        &PmxVertexDeformKind::Bdef4 {
+            bone_index_1,
+            bone_index_2,
+            bone_index_3,
+            bone_index_4,
+            bone_weight_1,
+            bone_weight_2,
+            bone_weight_3,
+            bone_weight_4,
+        }
+        | &PmxVertexDeformKind::Qdef {
+            bone_index_1,
+            bone_index_2,
+            bone_index_3,
+            bone_index_4,
+            bone_weight_1,
+            bone_weight_2,
+            bone_weight_3,
+            bone_weight_4,
+        } => [
+            (bone_index_1, bone_weight_1),
+            (bone_index_2, bone_weight_2),
+            (bone_index_3, bone_weight_3),
+            (bone_index_4, bone_weight_4),
+        ],
+    }
+}
+
+/// Scales the weights so that they sum up to `1`. Weights summing up to `0` are left as is.
+fn normalize_weights(weights: &mut [f32; 4]) {
+    let sum = weights.iter().sum::<f32>();
+
+    if sum <= 0f32 {
+        return;
+    }
+
+    for weight in weights {
+        *weight /= sum;
+    }
+}
+
+/// Converts PMX bones into nodes. The node at index `0` is an identity root holding all meshes,
+/// and the bone at index `N` becomes the node at index `N + 1`. Bones without a valid parent are attached to the root,
+/// as are bones closing a parent cycle.
+fn convert_pmx_bones(bones: &[PmxBone], mesh_count: u32) -> Vec<NodeSource> {
+    let bone_node_index = |index: PmxBoneIndex| -> Option<u32> {
+        let index = index.get();
+
+        if index < 0 || bones.len() <= index as usize {
+            return None;
+        }
+
+        Some(index as u32 + 1)
+    };
+    let to_array = |vec: &PmxVec3| [vec.x, vec.y, vec.z];
+    let parents = pmx_bone_parents(bones);
+
+    let mut nodes = Vec::with_capacity(1 + bones.len());
+    nodes.push(NodeSource {
         index: 0,
         parent_index: None,
         children_indices: vec![],
         name: "root".to_owned(),
         transform: NodeTransform {
-            matrix: [
-                1.0, 0.0, 0.0, 0.0, //
-                0.0, 1.0, 0.0, 0.0, //
-                0.0, 0.0, 1.0, 0.0, //
-                0.0, 0.0, 0.0, 1.0, //
-            ],
+            matrix: translation_matrix([0f32; 3]),
         },
-        mesh_indices: (0..meshes.len() as u32).collect(),
-    }];
+        mesh_indices: (0..mesh_count).collect(),
+        bone: None,
+    });
 
-    Ok(ModelSource {
-        root_node_index: Some(0),
-        nodes,
-        meshes,
-    })
+    for (bone_index, bone) in bones.iter().enumerate() {
+        let index = bone_index as u32 + 1;
+        let parent_index = parents[bone_index].map_or(0, |parent_index| parent_index as u32 + 1);
+        let translation = pmx_bone_translation(bones, &parents, bone_index);
+        let ik = bone.ik.as_ref().and_then(|ik| {
+            Some(BoneIK {
+                target_index: bone_node_index(ik.index)?,
+                loop_count: ik.loop_count.max(0) as u32,
+                limit_angle: ik.limit_angle,
+                links: Vec::from_iter(ik.links.iter().filter_map(|link| {
+                    Some(BoneIKLink {
+                        node_index: bone_node_index(link.index)?,
+                        angle_limit: link.angle_limit.as_ref().map(|limit| BoneAngleLimit {
+                            min: to_array(&limit.min),
+                            max: to_array(&limit.max),
+                        }),
+                    })
+                })),
+            })
+        });
+        let inheritance = bone.inheritance.as_ref().and_then(|inheritance| {
+            Some(BoneInheritance {
+                node_index: bone_node_index(inheritance.index)?,
+                coefficient: inheritance.coefficient,
+                inherit_rotation: inheritance.inheritance_mode
+                    != PmxBoneInheritanceMode::TranslationOnly,
+                inherit_translation: inheritance.inheritance_mode
+                    != PmxBoneInheritanceMode::RotationOnly,
+            })
+        });
+
+        nodes.push(NodeSource {
+            index,
+            parent_index: Some(parent_index),
+            children_indices: vec![],
+            name: pmx_bone_name(bone),
+            transform: NodeTransform {
+                matrix: translation_matrix(translation),
+            },
+            mesh_indices: vec![],
+            bone: Some(NodeBone {
+                layer: bone.layer as i32,
+                deform_after_physics: bone.flags.physics_after_deform,
+                is_rotatable: bone.flags.is_rotatable,
+                is_translatable: bone.flags.is_translatable,
+                is_visible: bone.flags.is_visible,
+                ik,
+                inheritance,
+                fixed_axis: bone
+                    .fixed_axis
+                    .as_ref()
+                    .map(|fixed_axis| to_array(&fixed_axis.direction)),
+                local_coordinate: bone.local_coordinate.as_ref().map(|local_coordinate| {
+                    BoneLocalCoordinate {
+                        x_axis: to_array(&local_coordinate.x_axis),
+                        z_axis: to_array(&local_coordinate.z_axis),
+                    }
+                }),
+            }),
+        });
+    }
+
+    for index in 1..nodes.len() {
+        if let Some(parent_index) = nodes[index].parent_index {
+            nodes[parent_index as usize]
+                .children_indices
+                .push(index as u32);
+        }
+    }
+
+    nodes
+}
+
+/// Returns the parent bone index of each bone, or `None` if the bone is attached to the root.
+/// Invalid parents are dropped, and parents closing a cycle are dropped too, so that the bones form a tree.
+pub(crate) fn pmx_bone_parents(bones: &[PmxBone]) -> Vec<Option<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        Unvisited,
+        OnPath,
+        Done,
+    }
+
+    let mut parents = Vec::from_iter(bones.iter().enumerate().map(|(bone_index, bone)| {
+        usize::try_from(bone.parent_index.get())
+            .ok()
+            .filter(|&parent_index| parent_index != bone_index && parent_index < bones.len())
+    }));
+    let mut visits = vec![Visit::Unvisited; bones.len()];
+
+    for bone_index in 0..bones.len() {
+        let mut path = Vec::new();
+        let mut current = Some(bone_index);
+
+        while let Some(index) = current {
+            match visits[index] {
+                Visit::Unvisited => {
+                    visits[index] = Visit::OnPath;
+                    path.push(index);
+                    current = parents[index];
+                }
+                Visit::OnPath => {
+                    // The last bone of the path points back into it; reattach it to the root.
+                    parents[*path.last().unwrap()] = None;
+                    break;
+                }
+                Visit::Done => break,
+            }
+        }
+
+        for index in path {
+            visits[index] = Visit::Done;
+        }
+    }
+
+    parents
 }
 
-fn process_assimp_model(content: &[u8]) -> anyhow::Result<ModelSource> {
-    let scene = Scene::from_buffer(
-        &content,
-        vec![
-            PostProcess::JoinIdenticalVertices,
-            PostProcess::Triangulate,
-            PostProcess::SortByPrimitiveType,
-            PostProcess::SplitLargeMeshes,
-            PostProcess::GenerateNormals,
-            PostProcess::FixInfacingNormals,
-            PostProcess::CalculateTangentSpace,
-            PostProcess::GenerateUVCoords,
-            PostProcess::GenerateBoundingBoxes,
-            PostProcess::ImproveCacheLocality,
-            PostProcess::OptimizeGraph,
-            PostProcess::OptimizeMeshes,
+/// Returns the translation of the bone relative to its parent, which is its rest pose as a node.
+/// PMX bone positions are in model space, while node transforms are relative to the parent.
+/// `parents` are the ones returned by [`pmx_bone_parents`].
+pub(crate) fn pmx_bone_translation(
+    bones: &[PmxBone],
+    parents: &[Option<usize>],
+    bone_index: usize,
+) -> [f32; 3] {
+    let bone = &bones[bone_index];
+
+    match parents[bone_index].map(|parent_index| &bones[parent_index]) {
+        Some(parent) => [
+            bone.position.x - parent.position.x,
+            bone.position.y - parent.position.y,
+            bone.position.z - parent.position.z,
         ],
-        "",
-    )
-    .with_context(|| "failed to load mesh from file")
-    .map_err(|err| anyhow!(err))?;
-    let mut extractor = SceneExtractor::new();
+        None => [bone.position.x, bone.position.y, bone.position.z],
+    }
+}
+
+/// Converts vertex, UV, material and group morphs. Vertex and UV offsets are distributed to the sub meshes
+/// containing the vertices. Other kinds of morphs, and UV morphs of additional UVs, are dropped;
+/// group offsets referring to them are dropped as well.
+fn convert_pmx_morphs(
+    pmx: &Pmx,
+    vertex_locations: &[Vec<(u32, u32)>],
+    meshes: &mut [MeshSource],
+) -> Vec<MorphSource> {
+    let is_supported = |morph: &PmxMorph| match &morph.offset {
+        PmxMorphOffset::Vertex(_) | PmxMorphOffset::Material(_) | PmxMorphOffset::Group(_) => true,
+        PmxMorphOffset::Uv { uv_index, .. } => *uv_index == 0,
+        PmxMorphOffset::Bone(_) | PmxMorphOffset::Flip(_) | PmxMorphOffset::Impulse(_) => false,
+    };
+
+    let mut morph_indices = Vec::with_capacity(pmx.morphs.len());
+    let mut morph_count = 0u32;
+
+    for morph in &pmx.morphs {
+        if is_supported(morph) {
+            morph_indices.push(Some(morph_count));
+            morph_count += 1;
+        } else {
+            morph_indices.push(None);
+        }
+    }
+
+    let mut morphs = Vec::with_capacity(morph_count as usize);
+
+    for (morph, morph_index) in pmx.morphs.iter().zip(&morph_indices) {
+        let morph_index = match morph_index {
+            Some(morph_index) => *morph_index,
+            None => continue,
+        };
+        let mut targets = HashMap::<u32, Vec<MeshMorphOffset>>::new();
+        let mut add_offset = |vertex_index: u32, position: [f32; 3], uv: [f32; 2]| {
+            let locations = match vertex_locations.get(vertex_index as usize) {
+                Some(locations) => locations,
+                None => return,
+            };
+
+            for &(mesh_index, vertex_index) in locations {
+                targets
+                    .entry(mesh_index)
+                    .or_default()
+                    .push(MeshMorphOffset {
+                        vertex_index,
+                        position,
+                        uv,
+                    });
+            }
+        };
+
+        let kind = match &morph.offset {
+            PmxMorphOffset::Vertex(offsets) => {
+                for offset in offsets {
+                    let translation = &offset.translation;
+                    add_offset(
+                        offset.index.get(),
+                        [translation.x, translation.y, translation.z],
+                        [0.0, 0.0],
+                    );
+                }
+
+                MorphKind::Vertex
+            }
+            PmxMorphOffset::Uv { offsets, .. } => {
+                for offset in offsets {
+                    add_offset(
+                        offset.index.get(),
+                        [0.0, 0.0, 0.0],
+                        [offset.vec4.x, offset.vec4.y],
+                    );
+                }
+
+                MorphKind::Vertex
+            }
+            PmxMorphOffset::Material(offsets) => {
+                MorphKind::Material(Vec::from_iter(offsets.iter().map(|offset| {
+                    let index = offset.index.get();
+
+                    MaterialMorphOffset {
+                        mesh_index: (0 <= index).then_some(index as u32),
+                        operation: match offset.operation {
+                            PmxMorphMaterialOperation::Multiply => MaterialMorphOperation::Multiply,
+                            PmxMorphMaterialOperation::Add => MaterialMorphOperation::Add,
+                        },
+                        values: MaterialMorphValues {
+                            diffuse_color: pmx_vec4(&offset.diffuse_color),
+                            specular_color: pmx_vec3(&offset.specular_color),
+                            specular_strength: offset.specular_strength,
+                            ambient_color: pmx_vec3(&offset.ambient_color),
+                            edge_color: pmx_vec4(&offset.edge_color),
+                            edge_size: offset.edge_size,
+                            texture_tint_color: pmx_vec4(&offset.texture_tint_color),
+                            environment_tint_color: pmx_vec4(&offset.environment_tint_color),
+                            toon_tint_color: pmx_vec4(&offset.toon_tint_color),
+                        },
+                    }
+                })))
+            }
+            PmxMorphOffset::Group(offsets) => {
+                MorphKind::Group(Vec::from_iter(offsets.iter().filter_map(|offset| {
+                    let index = offset.index.get();
+
+                    if index < 0 {
+                        return None;
+                    }
+
+                    let morph_index = (*morph_indices.get(index as usize)?)?;
+
+                    Some(GroupMorphOffset {
+                        morph_index,
+                        coefficient: offset.coefficient,
+                    })
+                })))
+            }
+            PmxMorphOffset::Bone(_) | PmxMorphOffset::Flip(_) | PmxMorphOffset::Impulse(_) => {
+                unreachable!()
+            }
+        };
+
+        for (mesh_index, offsets) in targets {
+            meshes[mesh_index as usize]
+                .morph_targets
+                .push(MeshMorphTarget {
+                    morph_index,
+                    offsets,
+                });
+        }
+
+        morphs.push(MorphSource {
+            name: pmx_morph_name(morph),
+            kind,
+        });
+    }
+
+    morphs
+}
+
+fn convert_pmx_material(
+    pmx: &Pmx,
+    material: &PmxMaterial,
+    model_path: &Path,
+) -> MeshMaterialSource {
+    let texture = |index: i32| {
+        let texture = pmx.textures.get(usize::try_from(index).ok()?)?;
+        texture_key(model_path, &texture.path)
+    };
+    let environment_blend_mode = match material.environment_blend_mode {
+        PmxMaterialEnvironmentBlendMode::Disabled => None,
+        PmxMaterialEnvironmentBlendMode::Multiplicative => {
+            Some(MeshMaterialEnvironmentBlendMode::Multiply)
+        }
+        PmxMaterialEnvironmentBlendMode::Additive => Some(MeshMaterialEnvironmentBlendMode::Add),
+        PmxMaterialEnvironmentBlendMode::AdditionalVec4UV => {
+            Some(MeshMaterialEnvironmentBlendMode::SubTexture)
+        }
+    };
+
+    MeshMaterialSource {
+        name: if material.name_local.is_empty() {
+            material.name_universal.clone()
+        } else {
+            material.name_local.clone()
+        },
+        diffuse_color: pmx_vec4(&material.diffuse_color),
+        specular_color: pmx_vec3(&material.specular_color),
+        specular_strength: material.specular_strength,
+        ambient_color: pmx_vec3(&material.ambient_color),
+        edge_color: pmx_vec4(&material.edge_color),
+        edge_size: material.edge_size,
+        // PMX names the flag after culling, but it is set on double sided materials.
+        is_double_sided: material.flags.cull_back_face,
+        casts_shadow: material.flags.cast_shadow_on_ground || material.flags.cast_shadow_on_object,
+        receives_shadow: material.flags.receive_shadow,
+        has_edge: material.flags.has_edge,
+        texture: texture(material.texture_index.get()),
+        environment_texture: environment_blend_mode
+            .and_then(|_| texture(material.environment_texture_index.get())),
+        environment_blend_mode: environment_blend_mode
+            .unwrap_or(MeshMaterialEnvironmentBlendMode::Multiply),
+        // The shared toon textures are not shipped with models, so they are left to the renderer.
+        toon_texture: match material.toon_mode {
+            PmxMaterialToonMode::Texture { index } => texture(index.get()),
+            PmxMaterialToonMode::InternalTexture { .. } => None,
+        },
+        pbr: None,
+    }
+}
+
+fn convert_pmx_rigid_bodies(pmx: &Pmx) -> Vec<RigidBodySource> {
+    Vec::from_iter(pmx.rigidbodies.iter().map(|rigidbody| {
+        let bone_index = rigidbody.bone_index.get();
+        let size = &rigidbody.shape.size;
+
+        RigidBodySource {
+            name: if rigidbody.name_local.is_empty() {
+                rigidbody.name_universal.clone()
+            } else {
+                rigidbody.name_local.clone()
+            },
+            // Bones are placed after the root node.
+            node_index: if 0 <= bone_index && (bone_index as usize) < pmx.bones.len() {
+                Some(bone_index as u32 + 1)
+            } else {
+                None
+            },
+            shape: match rigidbody.shape.kind {
+                PmxRigidbodyShapeKind::Sphere => RigidBodyShape::Sphere { radius: size.x },
+                PmxRigidbodyShapeKind::Box => RigidBodyShape::Box {
+                    half_extents: pmx_vec3(size),
+                },
+                PmxRigidbodyShapeKind::Capsule => RigidBodyShape::Capsule {
+                    radius: size.x,
+                    height: size.y,
+                },
+            },
+            position: pmx_vec3(&rigidbody.shape.position),
+            rotation: pmx_vec3(&rigidbody.shape.rotation),
+            group: rigidbody.group_id.clamp(0, 15) as u8,
+            // PMX stores the groups the body collides with, despite the name.
+            collision_mask: rigidbody.non_collision_group as u16,
+            mass: rigidbody.mass,
+            linear_damping: rigidbody.linear_damping,
+            angular_damping: rigidbody.angular_damping,
+            restitution: rigidbody.restitution_coefficient,
+            friction: rigidbody.friction_coefficient,
+            mode: match rigidbody.physics_mode {
+                PmxRigidbodyPhysicsMode::Static => RigidBodyMode::FollowBone,
+                PmxRigidbodyPhysicsMode::Dynamic => RigidBodyMode::Physics,
+                PmxRigidbodyPhysicsMode::DynamicWithBone => RigidBodyMode::PhysicsWithBone,
+            },
+        }
+    }))
+}
+
+/// Joints referring to missing rigid bodies are dropped.
+fn convert_pmx_joints(pmx: &Pmx) -> Vec<RigidBodyJointSource> {
+    let rigid_body_index = |index: i32| {
+        if 0 <= index && (index as usize) < pmx.rigidbodies.len() {
+            Some(index as u32)
+        } else {
+            None
+        }
+    };
+
+    Vec::from_iter(pmx.joints.iter().filter_map(|joint| {
+        let (first, second) = joint.rigidbody_index_pair;
+
+        Some(RigidBodyJointSource {
+            name: if joint.name_local.is_empty() {
+                joint.name_universal.clone()
+            } else {
+                joint.name_local.clone()
+            },
+            rigid_body_indices: [
+                rigid_body_index(first.get())?,
+                rigid_body_index(second.get())?,
+            ],
+            position: pmx_vec3(&joint.position),
+            rotation: pmx_vec3(&joint.rotation),
+            translation_limit_min: pmx_vec3(&joint.position_limit_min),
+            translation_limit_max: pmx_vec3(&joint.position_limit_max),
+            rotation_limit_min: pmx_vec3(&joint.rotation_limit_min),
+            rotation_limit_max: pmx_vec3(&joint.rotation_limit_max),
+            translation_stiffness: pmx_vec3(&joint.spring_position),
+            rotation_stiffness: pmx_vec3(&joint.spring_rotation),
+        })
+    }))
+}
+
+fn pmx_vec3(vec: &PmxVec3) -> [f32; 3] {
+    [vec.x, vec.y, vec.z]
+}
+
+fn pmx_vec4(vec: &PmxVec4) -> [f32; 4] {
+    [vec.x, vec.y, vec.z, vec.w]
+}
+
+fn set_pmx_vec3(vec: &mut PmxVec3, value: [f32; 3]) {
+    [vec.x, vec.y, vec.z] = value;
+}
+
+/// Prefers the local name, since motions refer to morphs by it.
+pub(crate) fn pmx_morph_name(morph: &PmxMorph) -> String {
+    if morph.name_local.is_empty() {
+        morph.name_universal.clone()
+    } else {
+        morph.name_local.clone()
+    }
+}
+
+/// Prefers the local name, since motions refer to bones by it.
+pub(crate) fn pmx_bone_name(bone: &PmxBone) -> String {
+    if bone.name_local.is_empty() {
+        bone.name_universal.clone()
+    } else {
+        bone.name_local.clone()
+    }
+}
+
+/// A column-major 4x4 matrix with the given translation.
+#[rustfmt::skip]
+fn translation_matrix(translation: [f32; 3]) -> [f32; 16] {
+    [
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        translation[0], translation[1], translation[2], 1.0,
+    ]
+}
+
+/// Generates simplified levels of detail of the mesh, sharing its vertices.
+/// Levels not reducing the number of triangles of the previous level are skipped.
+fn generate_lods(mesh: &mut MeshSource, lods: &[MeshTableLod]) {
+    let position_offset = match mesh
+        .vertex_attributes
+        .iter()
+        .find(|attribute| attribute.kind == VertexAttributeKind::Position)
+    {
+        Some(attribute) => attribute.offset as usize,
+        None => return,
+    };
+
+    if mesh.vertex_count == 0 {
+        return;
+    }
+
+    let stride = mesh.vertex_buffer.len() / mesh.vertex_count as usize;
+    let positions = Vec::from_iter(mesh.vertex_buffer.chunks_exact(stride).map(|vertex| {
+        let mut position = [0f32; 3];
+        byteorder::LE::read_f32_into(
+            &vertex[position_offset..position_offset + size_of::<[f32; 3]>()],
+            &mut position,
+        );
+        position
+    }));
+    let full_indices = decode_indices(mesh.index_type, &mesh.index_buffer);
+
+    let mut lods = lods.to_vec();
+    lods.sort_by(|lhs, rhs| rhs.screen_size.total_cmp(&lhs.screen_size));
+
+    let mut indices = full_indices.clone();
+
+    for lod in lods {
+        let ratio = lod.triangle_ratio.clamp(0.0, 1.0) as f64;
+        let target_index_count = (full_indices.len() / 3) as f64 * ratio;
+        let simplified = simplify_mesh(&positions, &indices, target_index_count as usize * 3);
+
+        if simplified.len() == indices.len() {
+            continue;
+        }
+
+        indices = simplified;
+        mesh.lods.push(MeshLodSource {
+            screen_size: lod.screen_size,
+            index_type: mesh.index_type,
+            index_buffer: encode_indices(mesh.index_type, &indices),
+        });
+    }
+}
+
+fn decode_indices(index_type: VertexIndexType, index_buffer: &[u8]) -> Vec<u32> {
+    match index_type {
+        VertexIndexType::U8 => Vec::from_iter(index_buffer.iter().map(|&index| index as u32)),
+        VertexIndexType::U16 => Vec::from_iter(
+            index_buffer
+                .chunks_exact(2)
+                .map(|index| byteorder::LE::read_u16(index) as u32),
+        ),
+        VertexIndexType::U32 => {
+            Vec::from_iter(index_buffer.chunks_exact(4).map(byteorder::LE::read_u32))
+        }
+    }
+}
+
+fn encode_indices(index_type: VertexIndexType, indices: &[u32]) -> Vec<u8> {
+    match index_type {
+        VertexIndexType::U8 => Vec::from_iter(indices.iter().map(|&index| index as u8)),
+        VertexIndexType::U16 => Vec::from_iter(
+            indices
+                .iter()
+                .flat_map(|&index| (index as u16).to_le_bytes()),
+        ),
+        VertexIndexType::U32 => {
+            Vec::from_iter(indices.iter().flat_map(|index| index.to_le_bytes()))
+        }
+    }
+}
+
+fn process_assimp_model(
+    file_path: &Path,
+    content: &[u8],
+    table: &MeshTable,
+) -> anyhow::Result<ModelSource> {
+    let conversion = CoordinateConversion::new(table);
+    let scene = Scene::from_buffer(&content, assimp_post_process(table, &conversion), "")
+        .with_context(|| "failed to load mesh from file")
+        .map_err(|err| anyhow!(err))?;
+    let mut extractor = SceneExtractor::new(conversion);
+    extractor.materials = Vec::from_iter(
+        scene
+            .materials
+            .iter()
+            .map(|material| convert_assimp_material(material, file_path)),
+    );
 
     let root_node_index = scene
         .root
         .as_ref()
         .map(|root| extractor.extract_node(&scene, root, None));
+    extractor.resolve_joints()?;
     let nodes = extractor.nodes;
     let meshes = extractor.meshes;
 
@@ -266,18 +1121,72 @@ fn process_assimp_model(content: &[u8]) -> anyhow::Result<ModelSource> {
         root_node_index,
         nodes,
         meshes,
+        morphs: vec![],
+        rigid_bodies: vec![],
+        rigid_body_joints: vec![],
     })
 }
 
-#[derive(Default)]
+fn assimp_post_process(table: &MeshTable, conversion: &CoordinateConversion) -> Vec<PostProcess> {
+    let mut post_process = vec![
+        PostProcess::JoinIdenticalVertices,
+        PostProcess::Triangulate,
+        PostProcess::SortByPrimitiveType,
+    ];
+
+    if table.split_large_meshes {
+        post_process.push(PostProcess::SplitLargeMeshes);
+    }
+
+    if table.generate_normals {
+        post_process.push(PostProcess::GenerateNormals);
+        post_process.push(PostProcess::FixInfacingNormals);
+    }
+
+    if table.generate_tangents {
+        post_process.push(PostProcess::CalculateTangentSpace);
+    }
+
+    post_process.extend([
+        PostProcess::GenerateUVCoords,
+        PostProcess::GenerateBoundingBoxes,
+        PostProcess::ImproveCacheLocality,
+    ]);
+
+    if table.optimize_graph {
+        post_process.push(PostProcess::OptimizeGraph);
+    }
+
+    post_process.extend([PostProcess::OptimizeMeshes, PostProcess::LimitBoneWeights]);
+
+    // Mirroring the vertices reverses the winding order, which is reversed back here.
+    if conversion.is_mirrored() {
+        post_process.push(PostProcess::FlipWindingOrder);
+    }
+
+    post_process
+}
+
 struct SceneExtractor {
     pub nodes: Vec<NodeSource>,
     pub meshes: Vec<MeshSource>,
+    /// Materials of the scene, assigned to the meshes referring to them.
+    pub materials: Vec<MeshMaterialSource>,
+    /// Bone names of each skinned mesh, resolved into node indices once all nodes are extracted.
+    pub joint_names: Vec<(u32, Vec<String>)>,
+    /// Applied to node transforms and meshes as they are extracted.
+    pub conversion: CoordinateConversion,
 }
 
 impl SceneExtractor {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(conversion: CoordinateConversion) -> Self {
+        Self {
+            nodes: vec![],
+            meshes: vec![],
+            materials: vec![],
+            joint_names: vec![],
+            conversion,
+        }
     }
 
     pub fn extract_node(
@@ -293,26 +1202,10 @@ impl SceneExtractor {
             children_indices: vec![],
             name: node.name.clone(),
             transform: NodeTransform {
-                matrix: [
-                    node.transformation.a1,
-                    node.transformation.b1,
-                    node.transformation.c1,
-                    node.transformation.d1,
-                    node.transformation.a2,
-                    node.transformation.b2,
-                    node.transformation.c2,
-                    node.transformation.d2,
-                    node.transformation.a3,
-                    node.transformation.b3,
-                    node.transformation.c3,
-                    node.transformation.d3,
-                    node.transformation.a4,
-                    node.transformation.b4,
-                    node.transformation.c4,
-                    node.transformation.d4,
-                ],
+                matrix: self.conversion.matrix(convert_matrix(&node.transformation)),
             },
             mesh_indices: vec![],
+            bone: None,
         });
 
         let children_indices = Vec::from_iter(
@@ -338,13 +1231,53 @@ impl SceneExtractor {
 
     fn extract_mesh(&mut self, mesh: &russimp::mesh::Mesh) -> u32 {
         let index = self.meshes.len() as u32;
-        let mesh = convert_mesh(index, mesh);
-        self.meshes.push(mesh);
+
+        if !mesh.bones.is_empty() {
+            self.joint_names.push((
+                index,
+                Vec::from_iter(mesh.bones.iter().map(|bone| bone.name.clone())),
+            ));
+        }
+
+        let mut converted = convert_mesh(index, mesh, &self.conversion);
+        converted.material = self.materials.get(mesh.material_index as usize).cloned();
+        self.meshes.push(converted);
         index
     }
+
+    /// Maps bones to the nodes of the same name.
+    pub fn resolve_joints(&mut self) -> anyhow::Result<()> {
+        let mut node_indices = HashMap::with_capacity(self.nodes.len());
+
+        for node in &self.nodes {
+            node_indices.entry(node.name.as_str()).or_insert(node.index);
+        }
+
+        for (mesh_index, joint_names) in &self.joint_names {
+            let joints = joint_names
+                .iter()
+                .map(|name| {
+                    node_indices
+                        .get(name.as_str())
+                        .copied()
+                        .ok_or_else(|| anyhow!("bone `{}` has no matching node", name))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            if let Some(skin) = &mut self.meshes[*mesh_index as usize].skin {
+                skin.joints = joints;
+            }
+        }
+
+        Ok(())
+    }
 }
 
-fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
+fn convert_mesh(
+    index: u32,
+    mesh: &russimp::mesh::Mesh,
+    conversion: &CoordinateConversion,
+) -> MeshSource {
     let mut vertex_attributes = Vec::with_capacity(8);
     let mut offset = 0;
 
@@ -361,8 +1294,8 @@ fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
             offset,
             kind: VertexAttributeKind::Normal,
         });
+        offset += size_of::<[f32; 3]>() as u32;
     }
-    offset += size_of::<[f32; 3]>() as u32;
 
     // Colors
     for (index, colors) in mesh.colors.iter().enumerate() {
@@ -411,6 +1344,22 @@ fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
         offset += size_of::<[f32; 3]>() as u32;
     }
 
+    // Joints and weights
+    let joint_offset = offset;
+
+    if !mesh.bones.is_empty() {
+        vertex_attributes.push(VertexAttribute {
+            offset,
+            kind: VertexAttributeKind::Joints,
+        });
+        offset += size_of::<[u32; 4]>() as u32;
+        vertex_attributes.push(VertexAttribute {
+            offset,
+            kind: VertexAttributeKind::Weights,
+        });
+        offset += size_of::<[f32; 4]>() as u32;
+    }
+
     let stride = (offset / size_of::<f32>() as u32) as usize;
     let mut vertex_buffer = vec![0f32; mesh.vertices.len() * stride];
 
@@ -426,6 +1375,8 @@ fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
             ),
             VertexAttributeKind::Tangent => VertexDataCopySource::Vector3D(&mesh.tangents),
             VertexAttributeKind::Bitangent => VertexDataCopySource::Vector3D(&mesh.bitangents),
+            // Written below, since joint indices are not floats.
+            VertexAttributeKind::Joints | VertexAttributeKind::Weights => continue,
             _ => unreachable!(),
         };
 
@@ -437,10 +1388,56 @@ fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
         }
     }
 
+    if !conversion.is_identity() {
+        for attribute in &vertex_attributes {
+            let convert = match attribute.kind {
+                VertexAttributeKind::Position => CoordinateConversion::position,
+                VertexAttributeKind::Normal
+                | VertexAttributeKind::Tangent
+                | VertexAttributeKind::Bitangent => CoordinateConversion::direction,
+                _ => continue,
+            };
+
+            for index in 0..mesh.vertices.len() {
+                let offset = index * stride + attribute.offset as usize / size_of::<f32>();
+                let value = &mut vertex_buffer[offset..offset + 3];
+                value.copy_from_slice(&convert(conversion, [value[0], value[1], value[2]]));
+            }
+        }
+    }
+
     let mut raw_vertex_buffer = vec![0u8; vertex_buffer.len() * size_of::<f32>()];
     byteorder::LE::write_f32_into(&vertex_buffer, &mut raw_vertex_buffer);
     drop(vertex_buffer);
 
+    let skin = if mesh.bones.is_empty() {
+        None
+    } else {
+        let stride = offset as usize;
+
+        for (index, (joints, mut weights)) in assimp_vertex_influences(mesh).into_iter().enumerate()
+        {
+            normalize_weights(&mut weights);
+
+            let offset = index * stride + joint_offset as usize;
+            byteorder::LE::write_u32_into(&joints, &mut raw_vertex_buffer[offset..offset + 16]);
+            byteorder::LE::write_f32_into(
+                &weights,
+                &mut raw_vertex_buffer[offset + 16..offset + 32],
+            );
+        }
+
+        Some(MeshSkin {
+            // Filled by `SceneExtractor::resolve_joints`.
+            joints: vec![],
+            inverse_bind_matrices: Vec::from_iter(
+                mesh.bones
+                    .iter()
+                    .map(|bone| conversion.matrix(convert_matrix(&bone.offset_matrix))),
+            ),
+        })
+    };
+
     let vertex_count = mesh.vertices.len();
     let (index_type, raw_index_buffer) = if vertex_count < u8::MAX as usize {
         let mut index_buffer = Vec::with_capacity(mesh.faces.len() * 3);
@@ -483,10 +1480,11 @@ fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
         (VertexIndexType::U32, raw_index_buffer)
     };
 
-    let aabb = MeshAABB {
-        min: [mesh.aabb.min.x, mesh.aabb.min.y, mesh.aabb.min.z],
-        max: [mesh.aabb.max.x, mesh.aabb.max.y, mesh.aabb.max.z],
-    };
+    let (min, max) = conversion.position_range(
+        [mesh.aabb.min.x, mesh.aabb.min.y, mesh.aabb.min.z],
+        [mesh.aabb.max.x, mesh.aabb.max.y, mesh.aabb.max.z],
+    );
+    let aabb = MeshAABB { min, max };
 
     MeshSource {
         index,
@@ -497,7 +1495,168 @@ fn convert_mesh(index: u32, mesh: &russimp::mesh::Mesh) -> MeshSource {
         vertex_buffer: raw_vertex_buffer,
         vertex_count: vertex_count as u32,
         material: None,
+        skin,
+        morph_targets: vec![],
+        lods: vec![],
+    }
+}
+
+fn convert_assimp_material(material: &Material, model_path: &Path) -> MeshMaterialSource {
+    let find = |key: &str, semantic: TextureType| {
+        material
+            .properties
+            .iter()
+            .find(|property| {
+                property.key == key && property.semantic == semantic && property.index == 0
+            })
+            .map(|property| &property.data)
+    };
+    let texture_float = |key: &str, semantic: TextureType| match find(key, semantic) {
+        Some(PropertyTypeInfo::FloatArray(values)) => values.first().copied(),
+        _ => None,
+    };
+    let float = |key: &str| texture_float(key, TextureType::None);
+    let color = |key: &str| match find(key, TextureType::None) {
+        Some(PropertyTypeInfo::FloatArray(values)) if 3 <= values.len() => Some([
+            values[0],
+            values[1],
+            values[2],
+            values.get(3).copied().unwrap_or(1.0),
+        ]),
+        _ => None,
+    };
+    let texture = |semantic: TextureType| match find("$tex.file", semantic) {
+        Some(PropertyTypeInfo::String(path)) => texture_key(model_path, path),
+        _ => None,
+    };
+
+    // Physically based formats such as glTF store the base color apart from the diffuse color.
+    let mut diffuse_color = color("$clr.base")
+        .or_else(|| color("$clr.diffuse"))
+        .unwrap_or([1.0; 4]);
+    diffuse_color[3] *= float("$mat.opacity").unwrap_or(1.0);
+    let specular_color = color("$clr.specular").unwrap_or([0.0; 4]);
+    let ambient_color = color("$clr.ambient").unwrap_or([0.0; 4]);
+    let base_color_texture =
+        texture(TextureType::BaseColor).or_else(|| texture(TextureType::Diffuse));
+
+    // glTF materials carry metallic-roughness parameters; the combined texture is reported as unknown.
+    let metallic_factor = float("$mat.metallicFactor");
+    let roughness_factor = float("$mat.roughnessFactor");
+    let metallic_roughness_texture = texture(TextureType::Unknown)
+        .or_else(|| texture(TextureType::Metalness))
+        .or_else(|| texture(TextureType::Roughness));
+    let pbr = (metallic_factor.is_some()
+        || roughness_factor.is_some()
+        || metallic_roughness_texture.is_some())
+    .then(|| {
+        let defaults = PbrMaterialSource::default();
+        let emissive_color = color("$clr.emissive").unwrap_or([0.0; 4]);
+
+        PbrMaterialSource {
+            base_color_factor: diffuse_color,
+            metallic_factor: metallic_factor.unwrap_or(defaults.metallic_factor),
+            roughness_factor: roughness_factor.unwrap_or(defaults.roughness_factor),
+            normal_scale: texture_float("$tex.scale", TextureType::Normals)
+                .unwrap_or(defaults.normal_scale),
+            occlusion_strength: texture_float("$tex.strength", TextureType::LightMap)
+                .unwrap_or(defaults.occlusion_strength),
+            emissive_factor: [emissive_color[0], emissive_color[1], emissive_color[2]],
+            base_color_texture: base_color_texture.clone(),
+            metallic_roughness_texture,
+            normal_texture: texture(TextureType::Normals),
+            occlusion_texture: texture(TextureType::LightMap)
+                .or_else(|| texture(TextureType::AmbientOcclusion)),
+            emissive_texture: texture(TextureType::Emissive),
+        }
+    });
+
+    MeshMaterialSource {
+        name: match find("?mat.name", TextureType::None) {
+            Some(PropertyTypeInfo::String(name)) => name.clone(),
+            _ => String::new(),
+        },
+        diffuse_color,
+        specular_color: [specular_color[0], specular_color[1], specular_color[2]],
+        specular_strength: float("$mat.shininess").unwrap_or(0.0),
+        ambient_color: [ambient_color[0], ambient_color[1], ambient_color[2]],
+        edge_color: [0.0, 0.0, 0.0, 1.0],
+        edge_size: 0.0,
+        is_double_sided: match find("$mat.twosided", TextureType::None) {
+            Some(PropertyTypeInfo::IntegerArray(values)) => values.first().is_some_and(|&v| v != 0),
+            _ => false,
+        },
+        casts_shadow: true,
+        receives_shadow: true,
+        has_edge: false,
+        texture: base_color_texture,
+        environment_texture: None,
+        environment_blend_mode: MeshMaterialEnvironmentBlendMode::Multiply,
+        toon_texture: None,
+        pbr,
+    }
+}
+
+/// Resolves a texture path found in a model into a key, relative to the directory of the model.
+/// Returns `None` for embedded textures, files that do not exist, and unsupported formats.
+fn texture_key(model_path: &Path, texture_path: &str) -> Option<AssetKey> {
+    // Models authored on Windows separate directories with backslashes.
+    let texture_path = texture_path.trim().replace('\\', "/");
+
+    // Embedded textures are named `*0`, `*1`, and so on.
+    if texture_path.is_empty() || texture_path.starts_with('*') {
+        return None;
+    }
+
+    let path = model_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(texture_path);
+
+    if !path.is_file()
+        || !deduce_asset_type_from_path(&path).is_ok_and(|ty| ty == AssetType::Texture)
+    {
+        return None;
+    }
+
+    Some(AssetKey::Path(path.to_string_lossy().into_owned()))
+}
+
+/// Returns the 4 most influential bones of each vertex, along with their weights.
+fn assimp_vertex_influences(mesh: &russimp::mesh::Mesh) -> Vec<([u32; 4], [f32; 4])> {
+    let mut influences = vec![([0u32; 4], [0f32; 4]); mesh.vertices.len()];
+
+    for (bone_index, bone) in mesh.bones.iter().enumerate() {
+        for weight in &bone.weights {
+            let (joints, weights) = match influences.get_mut(weight.vertex_id as usize) {
+                Some(influence) => influence,
+                None => continue,
+            };
+            // Replace the weakest influence, if this one is stronger.
+            let (slot, &min) = weights
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+
+            if min < weight.weight {
+                joints[slot] = bone_index as u32;
+                weights[slot] = weight.weight;
+            }
+        }
     }
+
+    influences
+}
+
+/// Converts a row-major assimp matrix into the layout of [`NodeTransform`].
+fn convert_matrix(matrix: &russimp::Matrix4x4) -> [f32; 16] {
+    [
+        matrix.a1, matrix.b1, matrix.c1, matrix.d1, //
+        matrix.a2, matrix.b2, matrix.c2, matrix.d2, //
+        matrix.a3, matrix.b3, matrix.c3, matrix.d3, //
+        matrix.a4, matrix.b4, matrix.c4, matrix.d4, //
+    ]
 }
 
 #[derive(Clone, Copy)]
@@ -531,3 +1690,302 @@ impl<'a> VertexDataCopySource<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pmx::{PmxBoneFlags, PmxBoneIK, PmxBoneIKLink, PmxBoneInheritance};
+
+    #[test]
+    fn check_sdef_falls_back_to_linear_blend() {
+        let zero = PmxVec3 {
+            x: 0f32,
+            y: 0f32,
+            z: 0f32,
+        };
+        let influences = pmx_vertex_influences(&PmxVertexDeformKind::Sdef {
+            bone_index_1: PmxBoneIndex::new(3),
+            bone_index_2: PmxBoneIndex::new(5),
+            bone_weight: 0.25,
+            c: zero,
+            r0: zero,
+            r1: zero,
+        });
+
+        assert_eq!(influences[0], (PmxBoneIndex::new(3), 0.25));
+        assert_eq!(influences[1], (PmxBoneIndex::new(5), 0.75));
+        assert_eq!(influences[2].1, 0f32);
+        assert_eq!(influences[3].1, 0f32);
+
+        let mut weights = [1f32, 3f32, 0f32, 0f32];
+        normalize_weights(&mut weights);
+        assert_eq!(weights, [0.25, 0.75, 0.0, 0.0]);
+    }
+
+    fn pmx_bone(
+        name_local: &str,
+        name_universal: &str,
+        position: [f32; 3],
+        parent: i32,
+    ) -> PmxBone {
+        let [x, y, z] = position;
+
+        PmxBone {
+            name_local: name_local.to_owned(),
+            name_universal: name_universal.to_owned(),
+            position: PmxVec3 { x, y, z },
+            parent_index: PmxBoneIndex::new(parent),
+            layer: 0,
+            flags: PmxBoneFlags {
+                indexed_tail_position: false,
+                is_rotatable: true,
+                is_translatable: false,
+                is_visible: true,
+                is_enabled: true,
+                supports_ik: false,
+                inherit_rotation: false,
+                inherit_translation: false,
+                fixed_axis: false,
+                local_coordinate: false,
+                physics_after_deform: false,
+                external_parent_deform: false,
+            },
+            tail_position: PmxBoneTailPosition::Vec3 {
+                position: PmxVec3 {
+                    x: 0f32,
+                    y: 0f32,
+                    z: 0f32,
+                },
+            },
+            inheritance: None,
+            fixed_axis: None,
+            local_coordinate: None,
+            external_parent: None,
+            ik: None,
+        }
+    }
+
+    fn node_translation(node: &NodeSource) -> [f32; 3] {
+        [
+            node.transform.matrix[12],
+            node.transform.matrix[13],
+            node.transform.matrix[14],
+        ]
+    }
+
+    #[test]
+    fn check_pmx_bones_are_parent_relative() {
+        let bones = vec![
+            pmx_bone("センター", "center", [0.0, 8.0, 0.0], -1),
+            pmx_bone("", "upper body", [0.0, 12.0, 1.0], 0),
+            pmx_bone("首", "neck", [0.0, 16.0, 0.5], 1),
+        ];
+        let nodes = convert_pmx_bones(&bones, 2);
+
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[0].mesh_indices, vec![0, 1]);
+        assert_eq!(nodes[0].children_indices, vec![1]);
+        assert_eq!(nodes[1].parent_index, Some(0));
+        assert_eq!(nodes[2].parent_index, Some(1));
+        assert_eq!(nodes[3].parent_index, Some(2));
+        assert_eq!(node_translation(&nodes[1]), [0.0, 8.0, 0.0]);
+        assert_eq!(node_translation(&nodes[2]), [0.0, 4.0, 1.0]);
+        assert_eq!(node_translation(&nodes[3]), [0.0, 4.0, -0.5]);
+
+        // the local name is preferred, falling back to the universal one
+        assert_eq!(nodes[1].name, "センター");
+        assert_eq!(nodes[2].name, "upper body");
+    }
+
+    #[test]
+    fn check_pmx_bone_parent_cycles_are_broken() {
+        let bones = vec![
+            pmx_bone("a", "", [1.0, 0.0, 0.0], 2),
+            pmx_bone("b", "", [2.0, 0.0, 0.0], 0),
+            pmx_bone("c", "", [3.0, 0.0, 0.0], 1),
+            pmx_bone("d", "", [4.0, 0.0, 0.0], 3),
+            pmx_bone("e", "", [5.0, 0.0, 0.0], 7),
+        ];
+        let parents = pmx_bone_parents(&bones);
+
+        // the bone closing the cycle is reattached to the root; self and invalid parents are dropped
+        assert_eq!(parents, vec![Some(2), None, Some(1), None, None]);
+
+        let nodes = convert_pmx_bones(&bones, 0);
+        assert_eq!(nodes[2].parent_index, Some(0));
+        assert_eq!(node_translation(&nodes[2]), [2.0, 0.0, 0.0]);
+        assert_eq!(node_translation(&nodes[3]), [1.0, 0.0, 0.0]);
+        assert_eq!(node_translation(&nodes[1]), [-2.0, 0.0, 0.0]);
+
+        // every node reaches the root
+        for node in &nodes {
+            let mut current = node;
+            let mut steps = 0;
+
+            while let Some(parent_index) = current.parent_index {
+                current = &nodes[parent_index as usize];
+                steps += 1;
+                assert!(steps <= nodes.len());
+            }
+
+            assert_eq!(current.index, 0);
+        }
+    }
+
+    #[test]
+    fn check_pmx_bone_references_are_node_indices() {
+        let mut bones = vec![
+            pmx_bone("leg", "", [0.0, 8.0, 0.0], -1),
+            pmx_bone("knee", "", [0.0, 4.0, 0.0], 0),
+            pmx_bone("ankle", "", [0.0, 0.0, 0.0], 1),
+            pmx_bone("leg IK", "", [0.0, 0.0, 0.0], -1),
+            pmx_bone("twist", "", [0.0, 4.0, 0.0], 0),
+        ];
+        bones[3].ik = Some(PmxBoneIK {
+            index: PmxBoneIndex::new(2),
+            loop_count: 40,
+            limit_angle: 2.0,
+            links: vec![
+                PmxBoneIKLink {
+                    index: PmxBoneIndex::new(1),
+                    angle_limit: None,
+                },
+                PmxBoneIKLink {
+                    index: PmxBoneIndex::new(9),
+                    angle_limit: None,
+                },
+                PmxBoneIKLink {
+                    index: PmxBoneIndex::new(0),
+                    angle_limit: None,
+                },
+            ],
+        });
+        bones[4].inheritance = Some(PmxBoneInheritance {
+            index: PmxBoneIndex::new(1),
+            coefficient: 0.5,
+            inheritance_mode: PmxBoneInheritanceMode::RotationOnly,
+        });
+        let nodes = convert_pmx_bones(&bones, 0);
+
+        let ik = nodes[4].bone.as_ref().unwrap().ik.as_ref().unwrap();
+        assert_eq!(ik.target_index, 3);
+        assert_eq!(ik.loop_count, 40);
+        // links referring to missing bones are dropped
+        assert_eq!(
+            Vec::from_iter(ik.links.iter().map(|link| link.node_index)),
+            vec![2, 1]
+        );
+
+        let inheritance = nodes[5]
+            .bone
+            .as_ref()
+            .unwrap()
+            .inheritance
+            .as_ref()
+            .unwrap();
+        assert_eq!(inheritance.node_index, 2);
+        assert_eq!(inheritance.coefficient, 0.5);
+        assert!(inheritance.inherit_rotation);
+        assert!(!inheritance.inherit_translation);
+    }
+
+    #[test]
+    fn check_texture_keys_are_relative_to_model() {
+        let base_path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(base_path.join("tex")).unwrap();
+        std::fs::write(base_path.join("tex").join("body.png"), []).unwrap();
+        std::fs::write(base_path.join("tex").join("body.spa"), []).unwrap();
+        let model_path = base_path.join("model.pmx");
+
+        assert_eq!(
+            texture_key(&model_path, "tex\\body.png"),
+            Some(AssetKey::Path(
+                base_path
+                    .join("tex/body.png")
+                    .to_string_lossy()
+                    .into_owned()
+            ))
+        );
+        // unsupported formats, missing files and embedded textures are dropped
+        assert_eq!(texture_key(&model_path, "tex/body.spa"), None);
+        assert_eq!(texture_key(&model_path, "tex/face.png"), None);
+        assert_eq!(texture_key(&model_path, "*0"), None);
+
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+
+    #[test]
+    fn check_gltf_material_is_metallic_roughness() {
+        let base_path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&base_path).unwrap();
+        std::fs::write(base_path.join("normal.png"), []).unwrap();
+        let model_path = base_path.join("model.gltf");
+
+        let property = |key: &str, semantic: TextureType, data: PropertyTypeInfo| {
+            russimp::material::MaterialProperty {
+                key: key.to_owned(),
+                data,
+                index: 0,
+                semantic,
+            }
+        };
+        let mut material = Material {
+            properties: vec![
+                property(
+                    "$clr.base",
+                    TextureType::None,
+                    PropertyTypeInfo::FloatArray(vec![0.5, 0.25, 1.0, 0.75]),
+                ),
+                property(
+                    "$mat.metallicFactor",
+                    TextureType::None,
+                    PropertyTypeInfo::FloatArray(vec![0.0]),
+                ),
+                property(
+                    "$mat.roughnessFactor",
+                    TextureType::None,
+                    PropertyTypeInfo::FloatArray(vec![0.5]),
+                ),
+                property(
+                    "$clr.emissive",
+                    TextureType::None,
+                    PropertyTypeInfo::FloatArray(vec![1.0, 0.0, 0.0]),
+                ),
+                property(
+                    "$tex.file",
+                    TextureType::Normals,
+                    PropertyTypeInfo::String("normal.png".to_owned()),
+                ),
+                property(
+                    "$tex.scale",
+                    TextureType::Normals,
+                    PropertyTypeInfo::FloatArray(vec![2.0]),
+                ),
+            ],
+            textures: Default::default(),
+        };
+
+        let pbr = convert_assimp_material(&material, &model_path).pbr.unwrap();
+        assert_eq!(pbr.base_color_factor, [0.5, 0.25, 1.0, 0.75]);
+        assert_eq!((pbr.metallic_factor, pbr.roughness_factor), (0.0, 0.5));
+        assert_eq!(pbr.emissive_factor, [1.0, 0.0, 0.0]);
+        assert_eq!(
+            pbr.normal_texture,
+            Some(AssetKey::Path(
+                base_path.join("normal.png").to_string_lossy().into_owned()
+            ))
+        );
+        assert_eq!(pbr.surface_factors(), [0.0, 0.5, 2.0, 1.0]);
+        assert_eq!(pbr.base_color_texture, None);
+
+        // materials of other formats have no metallic-roughness parameters
+        material.properties.retain(|property| {
+            property.key != "$mat.metallicFactor" && property.key != "$mat.roughnessFactor"
+        });
+        assert!(convert_assimp_material(&material, &model_path)
+            .pbr
+            .is_none());
+
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+}
@@ -31,7 +31,7 @@ pub enum ShaderReflectionError {
 }
 
 #[derive(Default, Serialize, Deserialize)]
-pub struct ShaderMetadata;
+pub struct ShaderMetadata {}
 
 impl AssetPipeline for ShaderSource {
     type Metadata = ShaderMetadata;
@@ -309,7 +309,11 @@ fn shader_ty_to_global_item_kind(module: &Module, ty: &Type) -> Option<ShaderGlo
         TypeInner::Struct { span, .. } => Some(ShaderGlobalItemKind::Buffer {
             size: unsafe { NonZeroU64::new_unchecked(*span as u64) },
         }),
-        TypeInner::Image { dim, class, .. } => {
+        TypeInner::Image {
+            dim,
+            arrayed,
+            class,
+        } => {
             let (sample_type, multisampled) = match *class {
                 ImageClass::Sampled { kind, multi } => {
                     let sample_type = match kind {
@@ -330,11 +334,13 @@ fn shader_ty_to_global_item_kind(module: &Module, ty: &Type) -> Option<ShaderGlo
 
             Some(ShaderGlobalItemKind::Texture {
                 sample_type,
-                view_dimension: match *dim {
-                    ImageDimension::D1 => TextureViewDimension::D1,
-                    ImageDimension::D2 => TextureViewDimension::D2,
-                    ImageDimension::D3 => TextureViewDimension::D3,
-                    ImageDimension::Cube => TextureViewDimension::Cube,
+                view_dimension: match (*dim, *arrayed) {
+                    (ImageDimension::D1, _) => TextureViewDimension::D1,
+                    (ImageDimension::D2, false) => TextureViewDimension::D2,
+                    (ImageDimension::D2, true) => TextureViewDimension::D2Array,
+                    (ImageDimension::D3, _) => TextureViewDimension::D3,
+                    (ImageDimension::Cube, false) => TextureViewDimension::Cube,
+                    (ImageDimension::Cube, true) => TextureViewDimension::CubeArray,
                 },
                 multisampled,
                 array_size: None,
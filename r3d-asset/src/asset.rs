@@ -1,11 +1,20 @@
 use crate::{
-    assets::{Font, Material, Model, Shader, Texture},
+    assets::{
+        Animation, AnimationAsset, Font, FontAsset, Material, MaterialAsset, Model, ModelAsset,
+        Shader, ShaderAsset, Texture, TextureAsset,
+    },
     AssetKey,
 };
-use std::{fmt::Display, sync::Arc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    sync::{Arc, Weak},
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum AssetType {
+    Animation,
     Font,
     Material,
     Model,
@@ -16,6 +25,7 @@ pub enum AssetType {
 impl Display for AssetType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            AssetType::Animation => write!(f, "animation"),
             AssetType::Font => write!(f, "font"),
             AssetType::Material => write!(f, "material"),
             AssetType::Model => write!(f, "model"),
@@ -27,6 +37,7 @@ impl Display for AssetType {
 
 #[derive(Clone)]
 pub enum TypedAsset {
+    Animation(Animation),
     Font(Font),
     Material(Material),
     Model(Model),
@@ -37,6 +48,7 @@ pub enum TypedAsset {
 impl TypedAsset {
     pub fn ty(&self) -> AssetType {
         match self {
+            TypedAsset::Animation(_) => AssetType::Animation,
             TypedAsset::Font(_) => AssetType::Font,
             TypedAsset::Material(_) => AssetType::Material,
             TypedAsset::Model(_) => AssetType::Model,
@@ -45,6 +57,10 @@ impl TypedAsset {
         }
     }
 
+    pub fn is_animation(&self) -> bool {
+        matches!(self, TypedAsset::Animation(_))
+    }
+
     pub fn is_font(&self) -> bool {
         matches!(self, TypedAsset::Font(_))
     }
@@ -65,6 +81,13 @@ impl TypedAsset {
         matches!(self, TypedAsset::Texture(_))
     }
 
+    pub fn as_animation(&self) -> Option<&Animation> {
+        match self {
+            TypedAsset::Animation(animation) => Some(animation),
+            _ => None,
+        }
+    }
+
     pub fn as_font(&self) -> Option<&Font> {
         match self {
             TypedAsset::Font(font) => Some(font),
@@ -99,6 +122,68 @@ impl TypedAsset {
             _ => None,
         }
     }
+
+    /// Creates a [`WeakTypedAsset`] that does not keep the asset alive.
+    pub fn downgrade(&self) -> WeakTypedAsset {
+        match self {
+            TypedAsset::Animation(animation) => {
+                WeakTypedAsset::Animation(Arc::downgrade(animation))
+            }
+            TypedAsset::Font(font) => WeakTypedAsset::Font(Arc::downgrade(font)),
+            TypedAsset::Material(material) => WeakTypedAsset::Material(Arc::downgrade(material)),
+            TypedAsset::Model(model) => WeakTypedAsset::Model(Arc::downgrade(model)),
+            TypedAsset::Shader(shader) => WeakTypedAsset::Shader(Arc::downgrade(shader)),
+            TypedAsset::Texture(texture) => WeakTypedAsset::Texture(Arc::downgrade(texture)),
+        }
+    }
+}
+
+/// A weak counterpart of [`TypedAsset`].
+#[derive(Clone)]
+pub enum WeakTypedAsset {
+    Animation(Weak<dyn AnimationAsset>),
+    Font(Weak<dyn FontAsset>),
+    Material(Weak<dyn MaterialAsset>),
+    Model(Weak<dyn ModelAsset>),
+    Shader(Weak<dyn ShaderAsset>),
+    Texture(Weak<dyn TextureAsset>),
+}
+
+impl WeakTypedAsset {
+    pub fn ty(&self) -> AssetType {
+        match self {
+            WeakTypedAsset::Animation(_) => AssetType::Animation,
+            WeakTypedAsset::Font(_) => AssetType::Font,
+            WeakTypedAsset::Material(_) => AssetType::Material,
+            WeakTypedAsset::Model(_) => AssetType::Model,
+            WeakTypedAsset::Shader(_) => AssetType::Shader,
+            WeakTypedAsset::Texture(_) => AssetType::Texture,
+        }
+    }
+
+    /// Returns `None` if the asset has already been dropped.
+    pub fn upgrade(&self) -> Option<TypedAsset> {
+        Some(match self {
+            WeakTypedAsset::Animation(animation) => TypedAsset::Animation(animation.upgrade()?),
+            WeakTypedAsset::Font(font) => TypedAsset::Font(font.upgrade()?),
+            WeakTypedAsset::Material(material) => TypedAsset::Material(material.upgrade()?),
+            WeakTypedAsset::Model(model) => TypedAsset::Model(model.upgrade()?),
+            WeakTypedAsset::Shader(shader) => TypedAsset::Shader(shader.upgrade()?),
+            WeakTypedAsset::Texture(texture) => TypedAsset::Texture(texture.upgrade()?),
+        })
+    }
+
+    /// Returns `true` if the asset is still alive.
+    pub fn is_alive(&self) -> bool {
+        match self {
+            WeakTypedAsset::Animation(animation) => animation.strong_count() != 0,
+            WeakTypedAsset::Font(font) => font.strong_count() != 0,
+            WeakTypedAsset::Material(material) => material.strong_count() != 0,
+            WeakTypedAsset::Model(model) => model.strong_count() != 0,
+            WeakTypedAsset::Shader(shader) => shader.strong_count() != 0,
+            WeakTypedAsset::Texture(texture) => texture.strong_count() != 0,
+        }
+    }
 }
 
 pub trait Asset {
@@ -1,9 +1,11 @@
+mod animation_asset;
 mod font_asset;
 mod material_asset;
 mod model_asset;
 mod shader_asset;
 mod texture_asset;
 
+pub use animation_asset::*;
 pub use font_asset::*;
 pub use material_asset::*;
 pub use model_asset::*;
@@ -12,6 +14,7 @@ pub use texture_asset::*;
 
 use std::sync::Arc;
 
+pub type Animation = Arc<dyn AnimationAsset>;
 pub type Font = Arc<dyn FontAsset>;
 pub type Material = Arc<dyn MaterialAsset>;
 pub type Model = Arc<dyn ModelAsset>;
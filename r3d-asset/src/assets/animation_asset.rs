@@ -0,0 +1,97 @@
+use crate::{
+    Asset, AssetDepsProvider, AssetKey, AssetLoadError, AssetSource, GfxBridge, TypedAsset,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A value of a track at a point in time.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct AnimationKeyframe<T> {
+    /// In seconds.
+    pub time: f32,
+    pub value: T,
+}
+
+/// Keyframes of a single node. Keyframes of each channel are sorted by time;
+/// an empty channel leaves the corresponding property of the node untouched.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnimationTrack {
+    /// Name of the animated node.
+    pub node_name: String,
+    pub translations: Vec<AnimationKeyframe<[f32; 3]>>,
+    /// Quaternions in `[x, y, z, w]` order.
+    pub rotations: Vec<AnimationKeyframe<[f32; 4]>>,
+    pub scales: Vec<AnimationKeyframe<[f32; 3]>>,
+}
+
+/// Weight keyframes of a single morph, sorted by time. See [`crate::assets::ModelAsset::morphs`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnimationMorphTrack {
+    /// Name of the animated morph.
+    pub morph_name: String,
+    pub weights: Vec<AnimationKeyframe<f32>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnimationClip {
+    pub name: String,
+    /// In seconds.
+    pub duration: f32,
+    pub tracks: Vec<AnimationTrack>,
+    pub morph_tracks: Vec<AnimationMorphTrack>,
+}
+
+/// Represents an animation asset, holding one or more clips.
+pub trait AnimationAsset: Asset {
+    fn clips(&self) -> &[AnimationClip];
+
+    fn find_clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips().iter().find(|clip| clip.name == name)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AnimationSource {
+    pub clips: Vec<AnimationClip>,
+}
+
+impl AssetSource for AnimationSource {
+    type Asset = dyn AnimationAsset;
+
+    fn dependencies(&self) -> Vec<AssetKey> {
+        vec![]
+    }
+
+    fn load(
+        self,
+        key: AssetKey,
+        _deps_provider: &dyn AssetDepsProvider,
+        _gfx_bridge: &dyn GfxBridge,
+    ) -> Result<Arc<Self::Asset>, AssetLoadError> {
+        Ok(Arc::new(Animation {
+            key,
+            clips: self.clips,
+        }))
+    }
+}
+
+struct Animation {
+    key: AssetKey,
+    clips: Vec<AnimationClip>,
+}
+
+impl Asset for Animation {
+    fn key(&self) -> &AssetKey {
+        &self.key
+    }
+
+    fn as_typed(self: Arc<Self>) -> TypedAsset {
+        TypedAsset::Animation(self)
+    }
+}
+
+impl AnimationAsset for Animation {
+    fn clips(&self) -> &[AnimationClip] {
+        &self.clips
+    }
+}
@@ -61,6 +61,7 @@ impl From<FontDueMetrics> for GlyphMetrics {
 /// Represents a font asset. It supplies SDF generation parameters.
 /// It also provides glyph metrics and rasterization.
 pub trait FontAsset: Asset {
+    fn data(&self) -> &FontDueFont;
     fn sdf_font_size(&self) -> f32;
     fn sdf_inset(&self) -> u32;
     fn sdf_radius(&self) -> u32;
@@ -124,6 +125,10 @@ impl Asset for Font {
 }
 
 impl FontAsset for Font {
+    fn data(&self) -> &FontDueFont {
+        &self.font
+    }
+
     fn sdf_font_size(&self) -> f32 {
         self.sdf_font_size
     }
@@ -164,6 +164,9 @@ pub struct MaterialBindingPropSource {
 
 pub type MaterialInstancePropSource = MaterialInstanceProp;
 
+/// The minimum uniform buffer offset alignment guaranteed by wgpu.
+const BINDING_OFFSET_ALIGNMENT: usize = 256;
+
 #[derive(Serialize, Deserialize)]
 pub struct MaterialSource {
     pub shader: AssetKey,
@@ -181,6 +184,171 @@ impl MaterialSource {
     }
 }
 
+/// Names of the bindings taken by the built-in metallic-roughness shader, filled by [`PbrMaterialSource`].
+pub mod pbr_bindings {
+    /// `vec4<f32>`, multiplied with the base color texture.
+    pub const BASE_COLOR_FACTOR: &str = "base_color_factor";
+    /// `vec4<f32>`; x: metallic, y: roughness, z: normal scale, w: occlusion strength.
+    pub const SURFACE_FACTORS: &str = "surface_factors";
+    /// `vec4<f32>`; rgb: multiplied with the emissive texture.
+    pub const EMISSIVE_FACTOR: &str = "emissive_factor";
+    pub const BASE_COLOR_TEXTURE: &str = "base_color_texture";
+    pub const BASE_COLOR_SAMPLER: &str = "base_color_sampler";
+    /// Roughness in the green channel and metalness in the blue channel, as in glTF.
+    pub const METALLIC_ROUGHNESS_TEXTURE: &str = "metallic_roughness_texture";
+    pub const METALLIC_ROUGHNESS_SAMPLER: &str = "metallic_roughness_sampler";
+    /// Tangent space normals.
+    pub const NORMAL_TEXTURE: &str = "normal_texture";
+    pub const NORMAL_SAMPLER: &str = "normal_sampler";
+    /// Ambient occlusion in the red channel.
+    pub const OCCLUSION_TEXTURE: &str = "occlusion_texture";
+    pub const OCCLUSION_SAMPLER: &str = "occlusion_sampler";
+    pub const EMISSIVE_TEXTURE: &str = "emissive_texture";
+    pub const EMISSIVE_SAMPLER: &str = "emissive_sampler";
+    /// `texture_cube<f32>` of the surroundings used for image-based lighting.
+    /// Texture assets are 2D only, so this is bound at runtime.
+    pub const ENVIRONMENT_TEXTURE: &str = "environment_texture";
+    pub const ENVIRONMENT_SAMPLER: &str = "environment_sampler";
+}
+
+/// A metallic-roughness material as defined by glTF, for the built-in PBR shader.
+/// Textures refer to texture assets; missing ones are expected to be bound to a white texture at runtime.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PbrMaterialSource {
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    /// Scales the X and Y of the normals sampled from the normal texture.
+    pub normal_scale: f32,
+    /// How much the occlusion texture darkens indirect light, from `0` to `1`.
+    pub occlusion_strength: f32,
+    pub emissive_factor: [f32; 3],
+    pub base_color_texture: Option<AssetKey>,
+    pub metallic_roughness_texture: Option<AssetKey>,
+    pub normal_texture: Option<AssetKey>,
+    pub occlusion_texture: Option<AssetKey>,
+    pub emissive_texture: Option<AssetKey>,
+}
+
+impl Default for PbrMaterialSource {
+    fn default() -> Self {
+        Self {
+            base_color_factor: [1.0; 4],
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            emissive_factor: [0.0; 3],
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+impl PbrMaterialSource {
+    pub fn textures(&self) -> impl Iterator<Item = &AssetKey> {
+        [
+            &self.base_color_texture,
+            &self.metallic_roughness_texture,
+            &self.normal_texture,
+            &self.occlusion_texture,
+            &self.emissive_texture,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Packs the factors in the layout of [`pbr_bindings::SURFACE_FACTORS`].
+    /// Normal mapping is turned off without a normal texture, so that a white texture can stand in for it.
+    pub fn surface_factors(&self) -> [f32; 4] {
+        [
+            self.metallic_factor,
+            self.roughness_factor,
+            if self.normal_texture.is_some() {
+                self.normal_scale
+            } else {
+                0.0
+            },
+            self.occlusion_strength,
+        ]
+    }
+
+    /// Converts the material into a material source of the given shader, which should take [`pbr_bindings`].
+    pub fn to_material_source(&self, shader: AssetKey) -> MaterialSource {
+        let mut binding_props = vec![
+            MaterialBindingPropSource {
+                key: MaterialBindingKey::Named(pbr_bindings::BASE_COLOR_FACTOR.to_owned()),
+                value: MaterialBindingValueSource::Float32x4(self.base_color_factor),
+            },
+            MaterialBindingPropSource {
+                key: MaterialBindingKey::Named(pbr_bindings::SURFACE_FACTORS.to_owned()),
+                value: MaterialBindingValueSource::Float32x4(self.surface_factors()),
+            },
+            MaterialBindingPropSource {
+                key: MaterialBindingKey::Named(pbr_bindings::EMISSIVE_FACTOR.to_owned()),
+                value: MaterialBindingValueSource::Float32x4([
+                    self.emissive_factor[0],
+                    self.emissive_factor[1],
+                    self.emissive_factor[2],
+                    1.0,
+                ]),
+            },
+        ];
+
+        for (texture, texture_name, sampler_name) in [
+            (
+                &self.base_color_texture,
+                pbr_bindings::BASE_COLOR_TEXTURE,
+                pbr_bindings::BASE_COLOR_SAMPLER,
+            ),
+            (
+                &self.metallic_roughness_texture,
+                pbr_bindings::METALLIC_ROUGHNESS_TEXTURE,
+                pbr_bindings::METALLIC_ROUGHNESS_SAMPLER,
+            ),
+            (
+                &self.normal_texture,
+                pbr_bindings::NORMAL_TEXTURE,
+                pbr_bindings::NORMAL_SAMPLER,
+            ),
+            (
+                &self.occlusion_texture,
+                pbr_bindings::OCCLUSION_TEXTURE,
+                pbr_bindings::OCCLUSION_SAMPLER,
+            ),
+            (
+                &self.emissive_texture,
+                pbr_bindings::EMISSIVE_TEXTURE,
+                pbr_bindings::EMISSIVE_SAMPLER,
+            ),
+        ] {
+            if let Some(texture) = texture {
+                binding_props.push(MaterialBindingPropSource {
+                    key: MaterialBindingKey::Named(texture_name.to_owned()),
+                    value: MaterialBindingValueSource::TextureView {
+                        texture: texture.clone(),
+                    },
+                });
+                binding_props.push(MaterialBindingPropSource {
+                    key: MaterialBindingKey::Named(sampler_name.to_owned()),
+                    value: MaterialBindingValueSource::SamplerTexture {
+                        texture: texture.clone(),
+                    },
+                });
+            }
+        }
+
+        MaterialSource {
+            shader,
+            binding_props,
+            instance_props: Vec::new(),
+        }
+    }
+}
+
 impl AssetSource for MaterialSource {
     type Asset = dyn MaterialAsset;
 
@@ -289,8 +457,15 @@ impl AssetSource for MaterialSource {
 
             debug_assert_eq!(bytes.is_empty(), false);
 
-            binding_data.extend_from_slice(bytes);
+            // Each value is bound at its own offset, which must be aligned for uniform buffers.
+            binding_data.resize(
+                binding_data
+                    .len()
+                    .next_multiple_of(BINDING_OFFSET_ALIGNMENT),
+                0,
+            );
             binding_offsets.push(binding_data.len() as BufferAddress);
+            binding_data.extend_from_slice(bytes);
             binding_sizes.push(BufferSize::new(bytes.len() as u64).unwrap());
         }
 
@@ -1,6 +1,7 @@
+use super::PbrMaterialSource;
 use crate::{
-    Asset, AssetDepsProvider, AssetKey, AssetLoadError, AssetSource, GfxBridge, GfxBuffer,
-    TypedAsset,
+    Asset, AssetDepsProvider, AssetKey, AssetLoadError, AssetSource, AssetType, GfxBridge,
+    GfxBuffer, GfxSampler, GfxTextureView, TypedAsset,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -14,6 +15,17 @@ pub enum VertexIndexType {
     U32,
 }
 
+impl VertexIndexType {
+    /// Size of a single index in bytes.
+    pub fn size(&self) -> u32 {
+        match self {
+            VertexIndexType::U8 => 1,
+            VertexIndexType::U16 => 2,
+            VertexIndexType::U32 => 4,
+        }
+    }
+}
+
 /// Type of a vertex attribute.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum VertexAttributeKind {
@@ -31,6 +43,27 @@ pub enum VertexAttributeKind {
     Bitangent,
     /// vec4
     Extra { index: u32 },
+    /// uvec4, indices into [`MeshSkin::joints`]
+    Joints,
+    /// vec4, sums up to `1`
+    Weights,
+}
+
+impl VertexAttributeKind {
+    /// Size of the attribute in bytes.
+    pub fn size(&self) -> u32 {
+        match self {
+            VertexAttributeKind::Position => 12,
+            VertexAttributeKind::Normal => 12,
+            VertexAttributeKind::Color { .. } => 16,
+            VertexAttributeKind::TexCoord { .. } => 8,
+            VertexAttributeKind::Tangent => 12,
+            VertexAttributeKind::Bitangent => 12,
+            VertexAttributeKind::Extra { .. } => 16,
+            VertexAttributeKind::Joints => 16,
+            VertexAttributeKind::Weights => 16,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -61,23 +94,394 @@ pub struct Node {
     pub name: String,
     pub transform: NodeTransform,
     pub mesh_indices: Vec<u32>,
+    /// Present if the node is a bone of a skeleton.
+    pub bone: Option<NodeBone>,
+}
+
+/// Skeleton data of a bone node. All node indices refer to [`ModelAsset::nodes`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NodeBone {
+    /// Bones are deformed in ascending order of their layer, then of their node index.
+    pub layer: i32,
+    /// `true` if the bone is deformed after physics is simulated.
+    pub deform_after_physics: bool,
+    pub is_rotatable: bool,
+    pub is_translatable: bool,
+    pub is_visible: bool,
+    pub ik: Option<BoneIK>,
+    pub inheritance: Option<BoneInheritance>,
+    /// The only direction the bone may point to, in model space.
+    pub fixed_axis: Option<[f32; 3]>,
+    pub local_coordinate: Option<BoneLocalCoordinate>,
+}
+
+/// An IK chain driven by the bone it belongs to; the bone itself is the IK target position.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BoneIK {
+    /// The effector, which is moved towards the IK bone.
+    pub target_index: u32,
+    pub loop_count: u32,
+    /// The maximum rotation per iteration, in radians.
+    pub limit_angle: f32,
+    /// Links from the effector towards the root of the chain.
+    pub links: Vec<BoneIKLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BoneIKLink {
+    pub node_index: u32,
+    pub angle_limit: Option<BoneAngleLimit>,
+}
+
+/// Euler angle limits, in radians.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BoneAngleLimit {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+/// Makes the bone follow a portion of the local rotation and/or translation of another bone.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BoneInheritance {
+    pub node_index: u32,
+    pub coefficient: f32,
+    pub inherit_rotation: bool,
+    pub inherit_translation: bool,
+}
+
+/// Local axes of the bone, in model space.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BoneLocalCoordinate {
+    pub x_axis: [f32; 3],
+    pub z_axis: [f32; 3],
 }
 
+/// A sub mesh uploaded to the GPU. Its index buffer never uses [`VertexIndexType::U8`];
+/// such indices are widened to [`VertexIndexType::U16`] while loading, since GPUs cannot consume them.
 #[derive(Debug)]
 pub struct Mesh {
     pub index: u32,
     pub aabb: MeshAABB,
     pub index_type: VertexIndexType,
     pub index_buffer: GfxBuffer,
+    pub index_count: u32,
     pub vertex_attributes: Vec<VertexAttribute>,
     pub vertex_buffer: GfxBuffer,
     pub vertex_count: u32,
     pub material: Option<MeshMaterial>,
+    pub skin: Option<MeshSkin>,
+    pub morph_targets: Vec<MeshMorphTarget>,
+    /// Simplified levels of detail, in descending order of [`MeshLod::screen_size`].
+    pub lods: Vec<MeshLod>,
+}
+
+impl Mesh {
+    /// Size of a single vertex in bytes. All vertex attributes are interleaved.
+    pub fn vertex_stride(&self) -> u32 {
+        self.vertex_attributes
+            .iter()
+            .map(|attribute| attribute.offset + attribute.kind.size())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A simplified level of detail of a sub mesh. It shares the vertex buffer of the sub mesh,
+/// referring to a subset of its vertices. Index buffers follow the same rules as [`Mesh::index_buffer`].
+#[derive(Debug)]
+pub struct MeshLod {
+    /// The level is drawn while the [`MeshAABB`] of the sub mesh covers at most this fraction of the viewport height.
+    pub screen_size: f32,
+    pub index_type: VertexIndexType,
+    pub index_buffer: GfxBuffer,
+    pub index_count: u32,
+}
+
+/// Skinning data of a sub mesh. Its vertices are deformed by the joints referenced by
+/// [`VertexAttributeKind::Joints`], blended by [`VertexAttributeKind::Weights`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MeshSkin {
+    /// Node index of each joint.
+    pub joints: Vec<u32>,
+    /// Transforms from the space of the sub mesh into the local space of each joint in bind pose.
+    /// Laid out the same way as [`NodeTransform`].
+    pub inverse_bind_matrices: Vec<[f32; 16]>,
+}
+
+/// Sparse vertex offsets of a [`MorphKind::Vertex`] morph, restricted to the vertices of a sub mesh.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MeshMorphTarget {
+    /// Index into [`ModelAsset::morphs`].
+    pub morph_index: u32,
+    pub offsets: Vec<MeshMorphOffset>,
+}
+
+/// Offset of a single vertex at full weight.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MeshMorphOffset {
+    pub vertex_index: u32,
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+/// A blend shape of a model, driven by a weight that is usually in the range of `[0, 1]`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Morph {
+    pub name: String,
+    pub kind: MorphKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum MorphKind {
+    /// Offsets vertex positions and texture coordinates. The offsets are stored per sub mesh,
+    /// in [`Mesh::morph_targets`].
+    Vertex,
+    /// Modulates material parameters of sub meshes.
+    Material(Vec<MaterialMorphOffset>),
+    /// Drives other morphs, each weighted by its coefficient. Groups may contain groups.
+    Group(Vec<GroupMorphOffset>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GroupMorphOffset {
+    /// Index into [`ModelAsset::morphs`].
+    pub morph_index: u32,
+    pub coefficient: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MaterialMorphOffset {
+    /// The sub mesh whose material is modulated, or `None` for all of them.
+    pub mesh_index: Option<u32>,
+    pub operation: MaterialMorphOperation,
+    pub values: MaterialMorphValues,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaterialMorphOperation {
+    /// Multiplies the parameters by the values, interpolated from `1` by the weight.
+    Multiply,
+    /// Adds the values, scaled by the weight, to the parameters.
+    Add,
+}
+
+/// Material parameters affected by material morphs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MaterialMorphValues {
+    pub diffuse_color: [f32; 4],
+    pub specular_color: [f32; 3],
+    pub specular_strength: f32,
+    pub ambient_color: [f32; 3],
+    pub edge_color: [f32; 4],
+    pub edge_size: f32,
+    pub texture_tint_color: [f32; 4],
+    pub environment_tint_color: [f32; 4],
+    pub toon_tint_color: [f32; 4],
+}
+
+impl MaterialMorphValues {
+    /// All values set to `0`, which is the identity of [`MaterialMorphOperation::Add`].
+    pub const ZERO: Self = Self::splat(0.0);
+    /// All values set to `1`, which is the identity of [`MaterialMorphOperation::Multiply`].
+    pub const ONE: Self = Self::splat(1.0);
+
+    pub const fn splat(value: f32) -> Self {
+        Self {
+            diffuse_color: [value; 4],
+            specular_color: [value; 3],
+            specular_strength: value,
+            ambient_color: [value; 3],
+            edge_color: [value; 4],
+            edge_size: value,
+            texture_tint_color: [value; 4],
+            environment_tint_color: [value; 4],
+            toon_tint_color: [value; 4],
+        }
+    }
+
+    /// Combines each value with the corresponding value of `other`.
+    pub fn zip_with(&self, other: &Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        fn zip<const N: usize>(
+            lhs: [f32; N],
+            rhs: [f32; N],
+            f: impl Fn(f32, f32) -> f32,
+        ) -> [f32; N] {
+            std::array::from_fn(|index| f(lhs[index], rhs[index]))
+        }
+
+        Self {
+            diffuse_color: zip(self.diffuse_color, other.diffuse_color, &f),
+            specular_color: zip(self.specular_color, other.specular_color, &f),
+            specular_strength: f(self.specular_strength, other.specular_strength),
+            ambient_color: zip(self.ambient_color, other.ambient_color, &f),
+            edge_color: zip(self.edge_color, other.edge_color, &f),
+            edge_size: f(self.edge_size, other.edge_size),
+            texture_tint_color: zip(self.texture_tint_color, other.texture_tint_color, &f),
+            environment_tint_color: zip(
+                self.environment_tint_color,
+                other.environment_tint_color,
+                &f,
+            ),
+            toon_tint_color: zip(self.toon_tint_color, other.toon_tint_color, &f),
+        }
+    }
+}
+
+/// A rigid body of the physics simulation, usually attached to a bone.
+/// Transforms are in model space, in the rest pose.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RigidBody {
+    pub name: String,
+    /// The bone node the body is attached to.
+    pub node_index: Option<u32>,
+    pub shape: RigidBodyShape,
+    pub position: [f32; 3],
+    /// Euler angles in radians, applied around the Z axis, then X, then Y.
+    pub rotation: [f32; 3],
+    /// The collision group, in the range of `[0, 16)`.
+    pub group: u8,
+    /// Bit `n` is set if the body collides with bodies of group `n`.
+    pub collision_mask: u16,
+    pub mass: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    pub mode: RigidBodyMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RigidBodyShape {
+    Sphere {
+        radius: f32,
+    },
+    Box {
+        half_extents: [f32; 3],
+    },
+    /// Aligned to the Y axis. `height` excludes the hemispherical caps.
+    Capsule {
+        radius: f32,
+        height: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RigidBodyMode {
+    /// Follows the bone, pushing simulated bodies away.
+    FollowBone,
+    /// Simulated, driving the bone.
+    Physics,
+    /// Simulated, driving the rotation of the bone. The bone keeps its animated translation.
+    PhysicsWithBone,
 }
 
+/// A 6-DOF spring constraint between two rigid bodies.
+/// Limits are relative to the joint frame in the rest pose, which is attached to the first body.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RigidBodyJoint {
+    pub name: String,
+    /// Indices into [`ModelAsset::rigid_bodies`].
+    pub rigid_body_indices: [u32; 2],
+    /// In model space.
+    pub position: [f32; 3],
+    /// Euler angles in radians, in the order of [`RigidBody::rotation`].
+    pub rotation: [f32; 3],
+    pub translation_limit_min: [f32; 3],
+    pub translation_limit_max: [f32; 3],
+    /// Euler angles in radians, applied around the Z axis, then Y, then X.
+    pub rotation_limit_min: [f32; 3],
+    pub rotation_limit_max: [f32; 3],
+    /// Stiffness of the spring pulling each axis back to the rest pose. `0` disables the spring.
+    pub translation_stiffness: [f32; 3],
+    pub rotation_stiffness: [f32; 3],
+}
+
+/// Surface parameters of a sub mesh, imported along with the model.
+#[derive(Debug, Clone)]
 pub struct MeshMaterial {
-    // TODO: Add more fields.
+    pub name: String,
+    pub diffuse_color: [f32; 4],
+    pub specular_color: [f32; 3],
+    pub specular_strength: f32,
+    pub ambient_color: [f32; 3],
+    pub edge_color: [f32; 4],
+    pub edge_size: f32,
+    /// `true` if back faces should be drawn too.
+    pub is_double_sided: bool,
+    pub casts_shadow: bool,
+    pub receives_shadow: bool,
+    pub has_edge: bool,
+    /// The base color texture, multiplied with the diffuse color.
+    pub texture: Option<MeshMaterialTexture>,
+    /// The sphere map texture, sampled with view space normals.
+    pub environment_texture: Option<MeshMaterialTexture>,
+    pub environment_blend_mode: MeshMaterialEnvironmentBlendMode,
+    pub toon_texture: Option<MeshMaterialTexture>,
+    /// Metallic-roughness parameters, present for physically based formats such as glTF.
+    pub pbr: Option<MeshMaterialPbr>,
+}
+
+impl MeshMaterial {
+    /// The parameters modulated by material morphs. Tint colors are white.
+    pub fn morph_values(&self) -> MaterialMorphValues {
+        MaterialMorphValues {
+            diffuse_color: self.diffuse_color,
+            specular_color: self.specular_color,
+            specular_strength: self.specular_strength,
+            ambient_color: self.ambient_color,
+            edge_color: self.edge_color,
+            edge_size: self.edge_size,
+            ..MaterialMorphValues::ONE
+        }
+    }
+}
+
+/// Metallic-roughness parameters of a [`MeshMaterial`], resolved from a [`PbrMaterialSource`].
+#[derive(Debug, Clone)]
+pub struct MeshMaterialPbr {
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    pub emissive_factor: [f32; 3],
+    pub base_color_texture: Option<MeshMaterialTexture>,
+    pub metallic_roughness_texture: Option<MeshMaterialTexture>,
+    pub normal_texture: Option<MeshMaterialTexture>,
+    pub occlusion_texture: Option<MeshMaterialTexture>,
+    pub emissive_texture: Option<MeshMaterialTexture>,
+}
+
+impl MeshMaterialPbr {
+    /// See [`PbrMaterialSource::surface_factors`].
+    pub fn surface_factors(&self) -> [f32; 4] {
+        [
+            self.metallic_factor,
+            self.roughness_factor,
+            if self.normal_texture.is_some() {
+                self.normal_scale
+            } else {
+                0.0
+            },
+            self.occlusion_strength,
+        ]
+    }
+}
+
+/// A texture of a [`MeshMaterial`], resolved from its dependency.
+#[derive(Debug, Clone)]
+pub struct MeshMaterialTexture {
+    pub key: AssetKey,
+    pub view: GfxTextureView,
+    pub sampler: GfxSampler,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeshMaterialEnvironmentBlendMode {
+    Multiply,
+    Add,
+    /// Blended like a regular texture, using the first extra vertex attribute as texture coordinates.
+    SubTexture,
 }
 
 /// Represents a mesy asset.
@@ -85,6 +489,16 @@ pub trait ModelAsset: Asset {
     fn root_node_index(&self) -> Option<u32>;
     fn nodes(&self) -> &[Node];
     fn meshes(&self) -> &[Mesh];
+    fn morphs(&self) -> &[Morph];
+    fn rigid_bodies(&self) -> &[RigidBody];
+    fn rigid_body_joints(&self) -> &[RigidBodyJoint];
+
+    fn find_morph(&self, name: &str) -> Option<u32> {
+        self.morphs()
+            .iter()
+            .position(|morph| morph.name == name)
+            .map(|index| index as u32)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -99,29 +513,158 @@ pub struct MeshSource {
     pub vertex_buffer: Vec<u8>,
     pub vertex_count: u32,
     pub material: Option<MeshMaterialSource>,
+    pub skin: Option<MeshSkinSource>,
+    pub morph_targets: Vec<MeshMorphTargetSource>,
+    /// In descending order of [`MeshLodSource::screen_size`].
+    pub lods: Vec<MeshLodSource>,
+}
+
+/// Serialized form of [`MeshLod`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MeshLodSource {
+    pub screen_size: f32,
+    pub index_type: VertexIndexType,
+    /// Little-endian.
+    pub index_buffer: Vec<u8>,
+}
+
+/// Serialized form of [`MeshMaterial`]. Textures refer to texture assets, usually by paths relative to the model.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MeshMaterialSource {
+    pub name: String,
+    pub diffuse_color: [f32; 4],
+    pub specular_color: [f32; 3],
+    pub specular_strength: f32,
+    pub ambient_color: [f32; 3],
+    pub edge_color: [f32; 4],
+    pub edge_size: f32,
+    pub is_double_sided: bool,
+    pub casts_shadow: bool,
+    pub receives_shadow: bool,
+    pub has_edge: bool,
+    pub texture: Option<AssetKey>,
+    pub environment_texture: Option<AssetKey>,
+    pub environment_blend_mode: MeshMaterialEnvironmentBlendMode,
+    pub toon_texture: Option<AssetKey>,
+    pub pbr: Option<PbrMaterialSource>,
+}
+
+impl MeshMaterialSource {
+    pub fn textures(&self) -> impl Iterator<Item = &AssetKey> {
+        [&self.texture, &self.environment_texture, &self.toon_texture]
+            .into_iter()
+            .flatten()
+            .chain(self.pbr.iter().flat_map(|pbr| pbr.textures()))
+    }
+
+    fn load(self, deps_provider: &dyn AssetDepsProvider) -> Result<MeshMaterial, AssetLoadError> {
+        let load_texture = |key: Option<AssetKey>| -> Result<_, AssetLoadError> {
+            key.map(|key| {
+                let texture = deps_provider.find_dependency(&key).ok_or_else(|| {
+                    AssetLoadError::MissingDependency {
+                        expected_key: key.clone(),
+                        expected_ty: AssetType::Texture,
+                    }
+                })?;
+                let texture =
+                    texture
+                        .as_texture()
+                        .ok_or_else(|| AssetLoadError::DependencyTypeMismatch {
+                            expected_key: key.clone(),
+                            expected_ty: AssetType::Texture,
+                            actual_ty: texture.ty(),
+                        })?;
+
+                Ok(MeshMaterialTexture {
+                    view: texture.view_handle().clone(),
+                    sampler: texture.sampler_handle().clone(),
+                    key,
+                })
+            })
+            .transpose()
+        };
+
+        let pbr = self
+            .pbr
+            .map(|pbr| -> Result<_, AssetLoadError> {
+                Ok(MeshMaterialPbr {
+                    base_color_factor: pbr.base_color_factor,
+                    metallic_factor: pbr.metallic_factor,
+                    roughness_factor: pbr.roughness_factor,
+                    normal_scale: pbr.normal_scale,
+                    occlusion_strength: pbr.occlusion_strength,
+                    emissive_factor: pbr.emissive_factor,
+                    base_color_texture: load_texture(pbr.base_color_texture)?,
+                    metallic_roughness_texture: load_texture(pbr.metallic_roughness_texture)?,
+                    normal_texture: load_texture(pbr.normal_texture)?,
+                    occlusion_texture: load_texture(pbr.occlusion_texture)?,
+                    emissive_texture: load_texture(pbr.emissive_texture)?,
+                })
+            })
+            .transpose()?;
+
+        Ok(MeshMaterial {
+            texture: load_texture(self.texture)?,
+            environment_texture: load_texture(self.environment_texture)?,
+            toon_texture: load_texture(self.toon_texture)?,
+            pbr,
+            name: self.name,
+            diffuse_color: self.diffuse_color,
+            specular_color: self.specular_color,
+            specular_strength: self.specular_strength,
+            ambient_color: self.ambient_color,
+            edge_color: self.edge_color,
+            edge_size: self.edge_size,
+            is_double_sided: self.is_double_sided,
+            casts_shadow: self.casts_shadow,
+            receives_shadow: self.receives_shadow,
+            has_edge: self.has_edge,
+            environment_blend_mode: self.environment_blend_mode,
+        })
+    }
 }
 
-pub type MeshMaterialSource = MeshMaterial;
+pub type MeshSkinSource = MeshSkin;
+pub type MeshMorphTargetSource = MeshMorphTarget;
+pub type MorphSource = Morph;
 pub type NodeSource = Node;
+pub type RigidBodySource = RigidBody;
+pub type RigidBodyJointSource = RigidBodyJoint;
 
 #[derive(Serialize, Deserialize)]
 pub struct ModelSource {
     pub root_node_index: Option<u32>,
     pub nodes: Vec<NodeSource>,
     pub meshes: Vec<MeshSource>,
+    pub morphs: Vec<MorphSource>,
+    pub rigid_bodies: Vec<RigidBodySource>,
+    pub rigid_body_joints: Vec<RigidBodyJointSource>,
 }
 
 impl AssetSource for ModelSource {
     type Asset = dyn ModelAsset;
 
     fn dependencies(&self) -> Vec<AssetKey> {
-        vec![]
+        let mut deps = Vec::new();
+
+        for key in self
+            .meshes
+            .iter()
+            .filter_map(|mesh| mesh.material.as_ref())
+            .flat_map(|material| material.textures())
+        {
+            if !deps.contains(key) {
+                deps.push(key.clone());
+            }
+        }
+
+        deps
     }
 
     fn load(
         self,
         key: AssetKey,
-        _deps_provider: &dyn AssetDepsProvider,
+        deps_provider: &dyn AssetDepsProvider,
         gfx_bridge: &dyn GfxBridge,
     ) -> Result<Arc<Self::Asset>, AssetLoadError> {
         Ok(Arc::new(Model {
@@ -131,28 +674,82 @@ impl AssetSource for ModelSource {
             meshes: self
                 .meshes
                 .into_iter()
-                .map(|mesh| Mesh {
-                    index: mesh.index,
-                    aabb: mesh.aabb,
-                    index_type: mesh.index_type,
-                    index_buffer: gfx_bridge
-                        .upload_vertex_buffer(BufferUsages::INDEX, &mesh.index_buffer),
-                    vertex_attributes: mesh.vertex_attributes,
-                    vertex_buffer: gfx_bridge
-                        .upload_vertex_buffer(BufferUsages::VERTEX, &mesh.vertex_buffer),
-                    vertex_count: mesh.vertex_count,
-                    material: mesh.material,
+                .map(|mesh| {
+                    let material = mesh
+                        .material
+                        .map(|material| material.load(deps_provider))
+                        .transpose()?;
+                    let (index_type, index_buffer, index_count) =
+                        upload_index_buffer(gfx_bridge, mesh.index_type, mesh.index_buffer);
+
+                    Ok(Mesh {
+                        index: mesh.index,
+                        aabb: mesh.aabb,
+                        index_type,
+                        index_buffer,
+                        index_count,
+                        vertex_attributes: mesh.vertex_attributes,
+                        vertex_buffer: gfx_bridge
+                            .upload_vertex_buffer(BufferUsages::VERTEX, &mesh.vertex_buffer),
+                        vertex_count: mesh.vertex_count,
+                        material,
+                        skin: mesh.skin,
+                        morph_targets: mesh.morph_targets,
+                        lods: Vec::from_iter(mesh.lods.into_iter().map(|lod| {
+                            let (index_type, index_buffer, index_count) =
+                                upload_index_buffer(gfx_bridge, lod.index_type, lod.index_buffer);
+
+                            MeshLod {
+                                screen_size: lod.screen_size,
+                                index_type,
+                                index_buffer,
+                                index_count,
+                            }
+                        })),
+                    })
                 })
-                .collect(),
+                .collect::<Result<_, AssetLoadError>>()?,
+            morphs: self.morphs,
+            rigid_bodies: self.rigid_bodies,
+            rigid_body_joints: self.rigid_body_joints,
         }))
     }
 }
 
+/// Uploads little-endian indices, widening [`VertexIndexType::U8`] ones.
+/// Returns the uploaded index type, the buffer and the number of indices.
+fn upload_index_buffer(
+    gfx_bridge: &dyn GfxBridge,
+    index_type: VertexIndexType,
+    index_buffer: Vec<u8>,
+) -> (VertexIndexType, GfxBuffer, u32) {
+    let index_count = index_buffer.len() as u32 / index_type.size();
+    let (index_type, index_buffer) = match index_type {
+        VertexIndexType::U8 => (
+            VertexIndexType::U16,
+            index_buffer
+                .iter()
+                .flat_map(|&index| (index as u16).to_le_bytes())
+                .collect(),
+        ),
+        index_type => (index_type, index_buffer),
+    };
+
+    (
+        index_type,
+        gfx_bridge.upload_vertex_buffer(BufferUsages::INDEX, &index_buffer),
+        index_count,
+    )
+}
+
 struct Model {
     key: AssetKey,
     root_node_index: Option<u32>,
     nodes: Vec<Node>,
     meshes: Vec<Mesh>,
+    morphs: Vec<Morph>,
+    rigid_bodies: Vec<RigidBody>,
+    rigid_body_joints: Vec<RigidBodyJoint>,
 }
 
 impl Asset for Model {
@@ -177,4 +774,16 @@ impl ModelAsset for Model {
     fn meshes(&self) -> &[Mesh] {
         &self.meshes
     }
+
+    fn morphs(&self) -> &[Morph] {
+        &self.morphs
+    }
+
+    fn rigid_bodies(&self) -> &[RigidBody] {
+        &self.rigid_bodies
+    }
+
+    fn rigid_body_joints(&self) -> &[RigidBodyJoint] {
+        &self.rigid_body_joints
+    }
 }
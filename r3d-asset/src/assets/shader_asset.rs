@@ -16,12 +16,42 @@ use wgpu::{
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SemanticShaderBindingKey(NonZeroU32);
 
+impl SemanticShaderBindingKey {
+    pub const fn new(key: NonZeroU32) -> Self {
+        Self(key)
+    }
+
+    pub const fn get(self) -> NonZeroU32 {
+        self.0
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SemanticShaderInputKey(NonZeroU32);
 
+impl SemanticShaderInputKey {
+    pub const fn new(key: NonZeroU32) -> Self {
+        Self(key)
+    }
+
+    pub const fn get(self) -> NonZeroU32 {
+        self.0
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SemanticShaderOutputKey(NonZeroU32);
 
+impl SemanticShaderOutputKey {
+    pub const fn new(key: NonZeroU32) -> Self {
+        Self(key)
+    }
+
+    pub const fn get(self) -> NonZeroU32 {
+        self.0
+    }
+}
+
 /// A fully reflected shader.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ShaderReflection {
@@ -1,3 +1,5 @@
+mod pack;
+
 use pollster::FutureExt;
 use r3d::{
     event::{event_types, EventHandler},
@@ -31,14 +33,24 @@ pub enum Error {
     EngineInitError(#[from] EngineInitError),
     #[error("engine exec error: {0}")]
     EngineExecError(#[from] EngineExecError),
+    #[error("pack error: {0}")]
+    PackCommandError(#[from] pack::PackCommandError),
 }
 
 fn main() -> Result<(), Error> {
+    let args = Vec::from_iter(std::env::args().skip(1));
+
+    if args.first().map(String::as_str) == Some("pack") {
+        pack::run(&args[1..])?;
+        return Ok(());
+    }
+
     let engine = Engine::new(EngineConfig {
         title: format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
         resizable: true,
         width: 800,
         height: 600,
+        asset_base_path: "assets".into(),
     })
     .block_on()?;
 
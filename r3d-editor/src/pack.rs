@@ -0,0 +1,72 @@
+use pollster::FutureExt;
+use r3d::{
+    asset_loader::AssetPackCompression,
+    asset_mgr::{build_asset_pack, AssetPackBuildError},
+    EngineInitError, HeadlessEngine, HeadlessEngineConfig,
+};
+use thiserror::Error;
+
+const USAGE: &str = "usage: editor pack <asset-dir> <output-file> [--compress]";
+
+#[derive(Error, Debug)]
+pub enum PackCommandError {
+    #[error("{USAGE}")]
+    InvalidArguments,
+    #[error("engine init error: {0}")]
+    EngineInitError(#[from] EngineInitError),
+    #[error("{0}")]
+    BuildError(#[from] AssetPackBuildError),
+}
+
+/// Runs `editor pack`, which packs all assets under a directory into a single file.
+pub fn run(args: &[String]) -> Result<(), PackCommandError> {
+    let mut paths = Vec::new();
+    let mut compression = AssetPackCompression::None;
+
+    for arg in args {
+        match arg.as_str() {
+            "--compress" => compression = AssetPackCompression::Deflate,
+            _ if arg.starts_with("--") => return Err(PackCommandError::InvalidArguments),
+            _ => paths.push(arg),
+        }
+    }
+
+    let (base_path, output_path) = match paths.as_slice() {
+        [base_path, output_path] => (base_path, output_path),
+        _ => return Err(PackCommandError::InvalidArguments),
+    };
+
+    // Shaders are processed against the semantic tables of a live shader manager, as they are at runtime.
+    let engine = HeadlessEngine::new(HeadlessEngineConfig {
+        width: 1,
+        height: 1,
+        asset_base_path: base_path.into(),
+    })
+    .block_on()?;
+    let summary = build_asset_pack(
+        base_path.as_str(),
+        output_path,
+        compression,
+        engine.context().shader_mgr(),
+    )?;
+
+    for warning in &summary.scan_report.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    for entry in &summary.entries {
+        println!(
+            "{} {} ({} bytes)",
+            entry.asset_type,
+            entry.path.as_deref().unwrap_or("-"),
+            entry.size
+        );
+    }
+
+    println!(
+        "packed {} assets into {}",
+        summary.entries.len(),
+        output_path
+    );
+    Ok(())
+}
@@ -1,5 +1,7 @@
 mod cursor;
 mod parse;
+mod pmd;
+mod pmd_to_pmx;
 mod pmx_bone;
 mod pmx_display;
 mod pmx_header;
@@ -8,25 +10,52 @@ mod pmx_material;
 mod pmx_morph;
 mod pmx_primitives;
 mod pmx_rigidbody;
+mod pmx_soft_body;
 mod pmx_surface;
 mod pmx_texture;
 mod pmx_vertex;
 mod primitives;
+mod shift_jis;
+mod shift_jis_table;
+mod vmd;
+mod vmd_bone_keyframe;
+mod vmd_camera_keyframe;
+mod vmd_header;
+mod vmd_ik_keyframe;
+mod vmd_light_keyframe;
+mod vmd_morph_keyframe;
+mod vmd_primitives;
+mod write;
+
+pub use pmd::PmdParseError;
+pub use pmx_bone::*;
+pub use pmx_display::*;
+pub use pmx_header::*;
+pub use pmx_joint::*;
+pub use pmx_material::*;
+pub use pmx_morph::*;
+pub use pmx_primitives::*;
+pub use pmx_rigidbody::*;
+pub use pmx_soft_body::*;
+pub use pmx_surface::*;
+pub use pmx_texture::*;
+pub use pmx_vertex::*;
+pub use shift_jis::*;
+pub use vmd::*;
+pub use vmd_bone_keyframe::*;
+pub use vmd_camera_keyframe::*;
+pub use vmd_header::*;
+pub use vmd_ik_keyframe::*;
+pub use vmd_light_keyframe::*;
+pub use vmd_morph_keyframe::*;
+pub use vmd_primitives::*;
+pub use write::PmxWriteError;
 
 use cursor::Cursor;
 use parse::Parse;
-use pmx_bone::PmxBone;
-use pmx_display::PmxDisplay;
-use pmx_header::PmxHeader;
-use pmx_joint::PmxJoint;
-use pmx_material::PmxMaterial;
-use pmx_morph::PmxMorph;
-use pmx_rigidbody::PmxRigidbody;
-use pmx_surface::PmxSurface;
-use pmx_texture::PmxTexture;
-use pmx_vertex::PmxVertex;
 use std::fmt::Display;
 use thiserror::Error;
+use write::Write;
 
 #[derive(Error, Debug)]
 pub enum PmxParseError {
@@ -50,9 +79,11 @@ pub enum PmxParseError {
     PmxRigidbodyParseError(#[from] pmx_rigidbody::PmxRigidbodyParseError),
     #[error("failed to parse PMX joint: {0}")]
     PmxJointParseError(#[from] pmx_joint::PmxJointParseError),
+    #[error("failed to parse PMX soft body: {0}")]
+    PmxSoftBodyParseError(#[from] pmx_soft_body::PmxSoftBodyParseError),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pmx {
     pub header: PmxHeader,
     pub vertices: Vec<PmxVertex>,
@@ -64,6 +95,15 @@ pub struct Pmx {
     pub displays: Vec<PmxDisplay>,
     pub rigidbodies: Vec<PmxRigidbody>,
     pub joints: Vec<PmxJoint>,
+    /// Always empty before PMX 2.1.
+    pub soft_bodies: Vec<PmxSoftBody>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PmxWriteOptions {
+    /// `true` if the index sizes should be the smallest ones able to index all items,
+    /// otherwise `false` (the index sizes in the header are used as is).
+    pub fit_index_sizes: bool,
 }
 
 impl Pmx {
@@ -80,6 +120,10 @@ impl Pmx {
         let displays = Vec::parse(&header.config, &mut cursor)?;
         let rigidbodies = Vec::parse(&header.config, &mut cursor)?;
         let joints = Vec::parse(&header.config, &mut cursor)?;
+        let soft_bodies = match header.is_v2_1() {
+            true => Vec::parse(&header.config, &mut cursor)?,
+            false => Vec::new(),
+        };
 
         Ok(Self {
             header,
@@ -92,8 +136,58 @@ impl Pmx {
             displays,
             rigidbodies,
             joints,
+            soft_bodies,
         })
     }
+
+    /// Writes the model using the configuration in the header.
+    pub fn write(&self) -> Result<Vec<u8>, PmxWriteError> {
+        self.write_with_options(&PmxWriteOptions::default())
+    }
+
+    pub fn write_with_options(&self, options: &PmxWriteOptions) -> Result<Vec<u8>, PmxWriteError> {
+        if !self.soft_bodies.is_empty() && !self.header.is_v2_1() {
+            return Err(PmxWriteError::SoftBodiesUnsupported {
+                version: self.header.version,
+            });
+        }
+
+        let config = match options.fit_index_sizes {
+            true => self.fitted_config(),
+            false => self.header.config.clone(),
+        };
+        let mut buffer = Vec::new();
+
+        self.header.write(&config, &mut buffer)?;
+        self.vertices.write(&config, &mut buffer)?;
+        self.surfaces.write(&config, &mut buffer)?;
+        self.textures.write(&config, &mut buffer)?;
+        self.materials.write(&config, &mut buffer)?;
+        self.bones.write(&config, &mut buffer)?;
+        self.morphs.write(&config, &mut buffer)?;
+        self.displays.write(&config, &mut buffer)?;
+        self.rigidbodies.write(&config, &mut buffer)?;
+        self.joints.write(&config, &mut buffer)?;
+
+        if self.header.is_v2_1() {
+            self.soft_bodies.write(&config, &mut buffer)?;
+        }
+
+        Ok(buffer)
+    }
+
+    /// Returns the header's configuration with the smallest index sizes able to index all items.
+    pub fn fitted_config(&self) -> PmxConfig {
+        PmxConfig {
+            vertex_index_size: PmxIndexSize::fit_vertices(self.vertices.len()),
+            texture_index_size: PmxIndexSize::fit(self.textures.len()),
+            material_index_size: PmxIndexSize::fit(self.materials.len()),
+            bone_index_size: PmxIndexSize::fit(self.bones.len()),
+            morph_index_size: PmxIndexSize::fit(self.morphs.len()),
+            rigidbody_index_size: PmxIndexSize::fit(self.rigidbodies.len()),
+            ..self.header.config.clone()
+        }
+    }
 }
 
 impl Display for Pmx {
@@ -124,6 +218,521 @@ impl Display for Pmx {
         writeln!(f, "  displays: {}", self.displays.len())?;
         writeln!(f, "  rigidbodies: {}", self.rigidbodies.len())?;
         writeln!(f, "  joints: {}", self.joints.len())?;
+        writeln!(f, "  soft bodies: {}", self.soft_bodies.len())?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vec3(x: f32, y: f32, z: f32) -> PmxVec3 {
+        PmxVec3 { x, y, z }
+    }
+
+    fn vec4(x: f32, y: f32, z: f32, w: f32) -> PmxVec4 {
+        PmxVec4 { x, y, z, w }
+    }
+
+    fn sample_pmx(text_encoding: PmxTextEncoding, version: f32) -> Pmx {
+        let config = PmxConfig {
+            text_encoding,
+            additional_vec4_count: 1,
+            vertex_index_size: PmxIndexSize::U32,
+            texture_index_size: PmxIndexSize::U32,
+            material_index_size: PmxIndexSize::U32,
+            bone_index_size: PmxIndexSize::U32,
+            morph_index_size: PmxIndexSize::U32,
+            rigidbody_index_size: PmxIndexSize::U32,
+        };
+        let header = PmxHeader {
+            signature: *b"PMX ",
+            version,
+            config,
+            model_name_local: "モデル".to_owned(),
+            model_name_universal: "model".to_owned(),
+            model_comment_local: "コメント".to_owned(),
+            model_comment_universal: "comment".to_owned(),
+        };
+
+        let bone = PmxBoneIndex::new;
+        let deform_kinds = [
+            PmxVertexDeformKind::Bdef1 {
+                bone_index: bone(0),
+            },
+            PmxVertexDeformKind::Bdef2 {
+                bone_index_1: bone(0),
+                bone_index_2: bone(1),
+                bone_weight: 0.25,
+            },
+            PmxVertexDeformKind::Bdef4 {
+                bone_index_1: bone(0),
+                bone_index_2: bone(1),
+                bone_index_3: bone(0),
+                bone_index_4: bone(-1),
+                bone_weight_1: 0.5,
+                bone_weight_2: 0.25,
+                bone_weight_3: 0.25,
+                bone_weight_4: 0.0,
+            },
+            PmxVertexDeformKind::Sdef {
+                bone_index_1: bone(0),
+                bone_index_2: bone(1),
+                bone_weight: 0.75,
+                c: vec3(0.0, 1.0, 0.0),
+                r0: vec3(0.0, 0.5, 0.0),
+                r1: vec3(0.0, 1.5, 0.0),
+            },
+        ];
+        let vertices = Vec::from_iter(deform_kinds.into_iter().enumerate().map(
+            |(index, deform_kind)| PmxVertex {
+                position: vec3(index as f32, 0.0, 1.0),
+                normal: vec3(0.0, 1.0, 0.0),
+                uv: PmxVec2 { x: 0.5, y: 0.25 },
+                additional_vec4s: [
+                    vec4(1.0, 0.5, 0.25, 1.0),
+                    vec4(0.0, 0.0, 0.0, 0.0),
+                    vec4(0.0, 0.0, 0.0, 0.0),
+                    vec4(0.0, 0.0, 0.0, 0.0),
+                ],
+                deform_kind,
+                edge_size: 1.0,
+            },
+        ));
+        let surfaces = vec![
+            PmxSurface {
+                vertex_indices: [0, 1, 2].map(PmxVertexIndex::new),
+            },
+            PmxSurface {
+                vertex_indices: [2, 1, 3].map(PmxVertexIndex::new),
+            },
+        ];
+        let textures = vec![
+            PmxTexture {
+                path: "tex\\body.png".to_owned(),
+            },
+            PmxTexture {
+                path: "toon.bmp".to_owned(),
+            },
+        ];
+        let material = PmxMaterial {
+            name_local: "体".to_owned(),
+            name_universal: "body".to_owned(),
+            diffuse_color: vec4(1.0, 0.5, 0.5, 1.0),
+            specular_color: vec3(0.1, 0.1, 0.1),
+            specular_strength: 5.0,
+            ambient_color: vec3(0.5, 0.25, 0.25),
+            flags: PmxMaterialFlags {
+                cull_back_face: true,
+                cast_shadow_on_ground: false,
+                cast_shadow_on_object: true,
+                receive_shadow: true,
+                has_edge: true,
+                uses_vertex_color: false,
+                draws_points: false,
+                draws_lines: false,
+            },
+            edge_color: vec4(0.0, 0.0, 0.0, 1.0),
+            edge_size: 0.5,
+            texture_index: PmxTextureIndex::new(0),
+            environment_texture_index: PmxTextureIndex::new(-1),
+            environment_blend_mode: PmxMaterialEnvironmentBlendMode::Disabled,
+            toon_mode: PmxMaterialToonMode::Texture {
+                index: PmxTextureIndex::new(1),
+            },
+            metadata: "memo".to_owned(),
+            surface_count: 3,
+        };
+        let materials = vec![
+            material.clone(),
+            PmxMaterial {
+                environment_blend_mode: PmxMaterialEnvironmentBlendMode::Multiplicative,
+                toon_mode: PmxMaterialToonMode::InternalTexture { index: 3 },
+                ..material
+            },
+        ];
+
+        let flags = PmxBoneFlags {
+            indexed_tail_position: false,
+            is_rotatable: true,
+            is_translatable: false,
+            is_visible: true,
+            is_enabled: true,
+            supports_ik: false,
+            inherit_rotation: false,
+            inherit_translation: false,
+            fixed_axis: false,
+            local_coordinate: false,
+            physics_after_deform: false,
+            external_parent_deform: false,
+        };
+        let bones = vec![
+            PmxBone {
+                name_local: "センター".to_owned(),
+                name_universal: "center".to_owned(),
+                position: vec3(0.0, 0.0, 0.0),
+                parent_index: bone(-1),
+                layer: 0,
+                flags: PmxBoneFlags {
+                    indexed_tail_position: true,
+                    is_translatable: true,
+                    ..flags
+                },
+                tail_position: PmxBoneTailPosition::BoneIndex { index: bone(1) },
+                inheritance: None,
+                fixed_axis: None,
+                local_coordinate: None,
+                external_parent: None,
+                ik: None,
+            },
+            PmxBone {
+                name_local: "腕".to_owned(),
+                name_universal: "arm".to_owned(),
+                position: vec3(0.0, 1.0, 0.0),
+                parent_index: bone(0),
+                layer: 1,
+                flags: PmxBoneFlags {
+                    inherit_rotation: true,
+                    fixed_axis: true,
+                    local_coordinate: true,
+                    physics_after_deform: true,
+                    external_parent_deform: true,
+                    ..flags
+                },
+                tail_position: PmxBoneTailPosition::Vec3 {
+                    position: vec3(0.0, 0.5, 0.0),
+                },
+                inheritance: Some(PmxBoneInheritance {
+                    index: bone(0),
+                    coefficient: 0.5,
+                    inheritance_mode: PmxBoneInheritanceMode::RotationOnly,
+                }),
+                fixed_axis: Some(PmxBoneFixedAxis {
+                    direction: vec3(1.0, 0.0, 0.0),
+                }),
+                local_coordinate: Some(PmxBoneLocalCoordinate {
+                    x_axis: vec3(1.0, 0.0, 0.0),
+                    z_axis: vec3(0.0, 0.0, 1.0),
+                }),
+                external_parent: Some(PmxBoneExternalParent { index: 7 }),
+                ik: None,
+            },
+            PmxBone {
+                name_local: "足IK".to_owned(),
+                name_universal: "leg IK".to_owned(),
+                position: vec3(0.0, -1.0, 0.0),
+                parent_index: bone(0),
+                layer: 0,
+                flags: PmxBoneFlags {
+                    supports_ik: true,
+                    ..flags
+                },
+                tail_position: PmxBoneTailPosition::Vec3 {
+                    position: vec3(0.0, 0.0, 0.5),
+                },
+                inheritance: None,
+                fixed_axis: None,
+                local_coordinate: None,
+                external_parent: None,
+                ik: Some(PmxBoneIK {
+                    index: bone(1),
+                    loop_count: 40,
+                    limit_angle: 2.0,
+                    links: vec![
+                        PmxBoneIKLink {
+                            index: bone(1),
+                            angle_limit: Some(PmxBoneIKAngleLimit {
+                                min: vec3(-3.0, 0.0, 0.0),
+                                max: vec3(-0.01, 0.0, 0.0),
+                            }),
+                        },
+                        PmxBoneIKLink {
+                            index: bone(0),
+                            angle_limit: None,
+                        },
+                    ],
+                }),
+            },
+        ];
+
+        let morph = |name: &str, offset| PmxMorph {
+            name_local: name.to_owned(),
+            name_universal: name.to_owned(),
+            panel_kind: PmxMorphPanelKind::Other,
+            offset,
+        };
+        let morphs = vec![
+            morph(
+                "group",
+                PmxMorphOffset::Group(vec![PmxMorphOffsetGroup {
+                    index: PmxMorphIndex::new(1),
+                    coefficient: 0.5,
+                }]),
+            ),
+            morph(
+                "vertex",
+                PmxMorphOffset::Vertex(vec![PmxMorphOffsetVertex {
+                    index: PmxVertexIndex::new(2),
+                    translation: vec3(0.0, 0.1, 0.0),
+                }]),
+            ),
+            morph(
+                "bone",
+                PmxMorphOffset::Bone(vec![PmxMorphOffsetBone {
+                    index: bone(1),
+                    translation: vec3(0.0, 0.0, 0.1),
+                    rotation: vec4(0.0, 0.0, 0.0, 1.0),
+                }]),
+            ),
+            morph(
+                "uv",
+                PmxMorphOffset::Uv {
+                    offsets: vec![PmxMorphOffsetUv {
+                        index: PmxVertexIndex::new(3),
+                        vec4: vec4(0.1, 0.2, 0.0, 0.0),
+                    }],
+                    uv_index: 1,
+                },
+            ),
+            morph(
+                "material",
+                PmxMorphOffset::Material(vec![PmxMorphOffsetMaterial {
+                    index: PmxMaterialIndex::new(-1),
+                    operation: PmxMorphMaterialOperation::Add,
+                    diffuse_color: vec4(0.1, 0.0, 0.0, 0.0),
+                    specular_color: vec3(0.0, 0.0, 0.0),
+                    specular_strength: 0.0,
+                    ambient_color: vec3(0.0, 0.0, 0.0),
+                    edge_color: vec4(0.0, 0.0, 0.0, 0.0),
+                    edge_size: 0.0,
+                    texture_tint_color: vec4(1.0, 1.0, 1.0, 1.0),
+                    environment_tint_color: vec4(1.0, 1.0, 1.0, 1.0),
+                    toon_tint_color: vec4(1.0, 1.0, 1.0, 1.0),
+                }]),
+            ),
+            morph(
+                "flip",
+                PmxMorphOffset::Flip(vec![PmxMorphOffsetFlip {
+                    index: PmxMorphIndex::new(0),
+                    coefficient: 1.0,
+                }]),
+            ),
+            morph(
+                "impulse",
+                PmxMorphOffset::Impulse(vec![PmxMorphOffsetImpulse {
+                    index: PmxRigidbodyIndex::new(0),
+                    is_local: true,
+                    velocity: vec3(0.0, 1.0, 0.0),
+                    torque: vec3(0.0, 0.0, 0.0),
+                }]),
+            ),
+        ];
+
+        let displays = vec![PmxDisplay {
+            name_local: "Root".to_owned(),
+            name_universal: "Root".to_owned(),
+            is_special: true,
+            frames: vec![
+                PmxDisplayFrame::Bone { index: bone(0) },
+                PmxDisplayFrame::Morph {
+                    index: PmxMorphIndex::new(1),
+                },
+            ],
+        }];
+
+        let rigidbody = PmxRigidbody {
+            name_local: "頭".to_owned(),
+            name_universal: "head".to_owned(),
+            bone_index: bone(1),
+            group_id: 2,
+            non_collision_group: -2,
+            shape: PmxRigidbodyShape {
+                kind: PmxRigidbodyShapeKind::Capsule,
+                size: vec3(0.5, 1.0, 0.0),
+                position: vec3(0.0, 1.0, 0.0),
+                rotation: vec3(0.0, 0.0, 0.5),
+            },
+            mass: 1.0,
+            linear_damping: 0.5,
+            angular_damping: 0.5,
+            restitution_coefficient: 0.0,
+            friction_coefficient: 0.5,
+            physics_mode: PmxRigidbodyPhysicsMode::DynamicWithBone,
+        };
+        let rigidbodies = vec![
+            rigidbody.clone(),
+            PmxRigidbody {
+                bone_index: bone(-1),
+                physics_mode: PmxRigidbodyPhysicsMode::Static,
+                ..rigidbody
+            },
+        ];
+
+        let joints = vec![PmxJoint {
+            name_local: "首".to_owned(),
+            name_universal: "neck".to_owned(),
+            kind: PmxJointKind::Spring6Dof,
+            rigidbody_index_pair: (PmxRigidbodyIndex::new(1), PmxRigidbodyIndex::new(0)),
+            position: vec3(0.0, 1.0, 0.0),
+            rotation: vec3(0.0, 0.0, 0.0),
+            position_limit_min: vec3(0.0, 0.0, 0.0),
+            position_limit_max: vec3(0.0, 0.0, 0.0),
+            rotation_limit_min: vec3(-0.5, -0.5, -0.5),
+            rotation_limit_max: vec3(0.5, 0.5, 0.5),
+            spring_position: vec3(0.0, 0.0, 0.0),
+            spring_rotation: vec3(10.0, 10.0, 10.0),
+        }];
+
+        Pmx {
+            header,
+            vertices,
+            surfaces,
+            textures,
+            materials,
+            bones,
+            morphs,
+            displays,
+            rigidbodies,
+            joints,
+            soft_bodies: Vec::new(),
+        }
+    }
+
+    fn sample_soft_body() -> PmxSoftBody {
+        PmxSoftBody {
+            name_local: "スカート".to_owned(),
+            name_universal: "skirt".to_owned(),
+            shape: PmxSoftBodyShape::TriMesh,
+            material_index: PmxMaterialIndex::new(1),
+            group_id: 3,
+            non_collision_group: 0xfff0,
+            flags: PmxSoftBodyFlags {
+                generates_bending_links: true,
+                generates_clusters: false,
+                randomizes_links: true,
+            },
+            bending_link_distance: 2,
+            cluster_count: 0,
+            total_mass: 1.0,
+            collision_margin: 0.05,
+            aero_model: PmxSoftBodyAeroModel::VertexTwoSided,
+            config: PmxSoftBodyConfig {
+                velocity_correction: 1.0,
+                damping: 0.1,
+                drag: 0.0,
+                lift: 0.0,
+                pressure: 0.0,
+                volume_conservation: 0.0,
+                dynamic_friction: 0.2,
+                pose_matching: 0.0,
+                rigid_contact_hardness: 1.0,
+                kinetic_contact_hardness: 0.1,
+                soft_contact_hardness: 1.0,
+                anchor_hardness: 0.7,
+            },
+            cluster: PmxSoftBodyCluster {
+                soft_rigid_hardness: 0.1,
+                soft_kinetic_hardness: 1.0,
+                soft_soft_hardness: 0.5,
+                soft_rigid_impulse_split: 0.5,
+                soft_kinetic_impulse_split: 0.5,
+                soft_soft_impulse_split: 0.5,
+            },
+            iteration: PmxSoftBodyIteration {
+                velocity: 0,
+                position: 1,
+                drift: 0,
+                cluster: 4,
+            },
+            material: PmxSoftBodyMaterial {
+                linear_stiffness: 1.0,
+                angular_stiffness: 1.0,
+                volume_stiffness: 1.0,
+            },
+            anchors: vec![PmxSoftBodyAnchor {
+                rigidbody_index: PmxRigidbodyIndex::new(0),
+                vertex_index: PmxVertexIndex::new(2),
+                is_near_mode: true,
+            }],
+            pin_vertex_indices: vec![PmxVertexIndex::new(0), PmxVertexIndex::new(3)],
+        }
+    }
+
+    fn assert_round_trip(pmx: &Pmx) {
+        let bytes = pmx.write().unwrap();
+        let parsed = Pmx::parse(&bytes).unwrap();
+        assert_eq!(&parsed, pmx);
+        assert_eq!(parsed.write().unwrap(), bytes);
+    }
+
+    #[test]
+    fn check_round_trip() {
+        for text_encoding in [PmxTextEncoding::Utf16le, PmxTextEncoding::Utf8] {
+            assert_round_trip(&sample_pmx(text_encoding, 2.0));
+        }
+    }
+
+    #[test]
+    fn check_round_trip_v2_1() {
+        let mut pmx = sample_pmx(PmxTextEncoding::Utf8, 2.1);
+        pmx.vertices[2].deform_kind = PmxVertexDeformKind::Qdef {
+            bone_index_1: PmxBoneIndex::new(0),
+            bone_index_2: PmxBoneIndex::new(1),
+            bone_index_3: PmxBoneIndex::new(-1),
+            bone_index_4: PmxBoneIndex::new(-1),
+            bone_weight_1: 0.5,
+            bone_weight_2: 0.5,
+            bone_weight_3: 0.0,
+            bone_weight_4: 0.0,
+        };
+        pmx.materials[0].flags.uses_vertex_color = true;
+        pmx.materials[1].flags.draws_lines = true;
+        pmx.joints[0].kind = PmxJointKind::ConeTwist;
+        pmx.soft_bodies.push(sample_soft_body());
+
+        assert_round_trip(&pmx);
+
+        // soft bodies cannot be stored in PMX 2.0
+        pmx.header.version = 2.0;
+        assert!(matches!(
+            pmx.write(),
+            Err(PmxWriteError::SoftBodiesUnsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn check_fit_index_sizes() {
+        let pmx = sample_pmx(PmxTextEncoding::Utf16le, 2.0);
+        let options = PmxWriteOptions {
+            fit_index_sizes: true,
+        };
+        let bytes = pmx.write_with_options(&options).unwrap();
+        let parsed = Pmx::parse(&bytes).unwrap();
+
+        assert_eq!(parsed.header.config.vertex_index_size, PmxIndexSize::U8);
+        assert_eq!(parsed.header.config.bone_index_size, PmxIndexSize::U8);
+        assert!(bytes.len() < pmx.write().unwrap().len());
+
+        let mut expected = pmx.clone();
+        expected.header.config = pmx.fitted_config();
+        assert_eq!(parsed, expected);
+
+        // bone flags follow the data, not the stale flags
+        let mut pmx = pmx;
+        pmx.bones[1].flags.fixed_axis = false;
+        pmx.bones[1].fixed_axis = None;
+        pmx.bones[2].ik = None;
+        let parsed = Pmx::parse(pmx.write().unwrap()).unwrap();
+        assert!(!parsed.bones[2].flags.supports_ik);
+        assert!(parsed.bones[2].ik.is_none());
+
+        // indices that do not fit in the configured size are rejected
+        pmx.header.config.vertex_index_size = PmxIndexSize::U8;
+        pmx.surfaces[0].vertex_indices[0] = PmxVertexIndex::new(256);
+        assert!(matches!(
+            pmx.write(),
+            Err(PmxWriteError::IndexOutOfRange { kind: "vertex", .. })
+        ));
+    }
+}
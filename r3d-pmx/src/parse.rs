@@ -1,9 +1,11 @@
 use crate::{cursor::Cursor, pmx_header::PmxConfig};
 
-pub trait Parse: Sized {
+/// Parses a value from the cursor. `C` is the configuration of the file format being parsed;
+/// values that do not depend on it are implemented for any configuration.
+pub trait Parse<C = PmxConfig>: Sized {
     type Error: ParseError;
 
-    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error>;
+    fn parse(config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error>;
 }
 
 pub trait ParseError {
@@ -0,0 +1,599 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    pmx_primitives::{PmxVec2, PmxVec3},
+    shift_jis::parse_shift_jis_name,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PmdParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+    #[error("failed to parse a PMX primitive: {0}")]
+    PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
+    #[error("`{signature:?}` is not a valid PMD signature")]
+    InvalidSignature { signature: [u8; 3] },
+    #[error("index count `{count}` is invalid; it must be a multiple of 3")]
+    InvalidIndexCount { count: usize },
+    #[error("morph vertex index `{index}` is invalid; the base morph has `{count}` vertices")]
+    InvalidMorphVertexIndex { index: u32, count: usize },
+    #[error("rigidbody shape kind `{kind}` is invalid: must be in the range of [0, 2]")]
+    InvalidRigidbodyShapeKind { kind: u8 },
+    #[error("rigidbody physics mode `{mode}` is invalid: must be in the range of [0, 2]")]
+    InvalidRigidbodyPhysicsMode { mode: u8 },
+}
+
+impl ParseError for PmdParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+/// Index meaning "none" in 2 bytes PMD indices.
+pub(crate) const PMD_NONE_INDEX: u16 = 0xFFFF;
+
+/// A PMD model as stored in the file. It is only used to build a [`crate::Pmx`].
+#[derive(Debug, Clone)]
+pub(crate) struct Pmd {
+    pub model_name: String,
+    pub comment: String,
+    pub vertices: Vec<PmdVertex>,
+    pub indices: Vec<u16>,
+    pub materials: Vec<PmdMaterial>,
+    pub bones: Vec<PmdBone>,
+    pub iks: Vec<PmdIK>,
+    pub morphs: Vec<PmdMorph>,
+    /// Indices of the morphs shown in the expression frame.
+    pub morph_display: Vec<u16>,
+    pub bone_display_names: Vec<String>,
+    /// Pairs of a bone index and a 1-based index of `bone_display_names`.
+    pub bone_display: Vec<(u16, u8)>,
+    pub english: Option<PmdEnglish>,
+    /// 10 toon texture names; `None` if the file predates the section.
+    pub toon_textures: Option<Vec<String>>,
+    pub rigidbodies: Vec<PmdRigidbody>,
+    pub joints: Vec<PmdJoint>,
+}
+
+impl Pmd {
+    pub fn parse(buf: &[u8]) -> Result<Self, PmdParseError> {
+        let mut cursor = Cursor::new(buf);
+
+        // signature (3 bytes)
+        // version (4 bytes)
+        // model name (20 bytes)
+        // comment (256 bytes)
+        let size = 3 + 4 + 20 + 256;
+        cursor.ensure_bytes::<PmdParseError>(size)?;
+
+        let signature = *cursor.read::<PmdParseError, 3>()?;
+        if &signature != b"Pmd" {
+            return Err(PmdParseError::InvalidSignature { signature });
+        }
+
+        // version is always 1.0
+        let _version = f32::parse(&(), &mut cursor)?;
+        let model_name = parse_shift_jis_name::<PmdParseError, 20>(&mut cursor)?;
+        let comment = parse_shift_jis_name::<PmdParseError, 256>(&mut cursor)?;
+
+        let vertices = parse_list::<PmdVertex>(&mut cursor, u32_count, 38)?;
+
+        let indices = parse_list::<u16>(&mut cursor, u32_count, 2)?;
+        if indices.len() % 3 != 0 {
+            return Err(PmdParseError::InvalidIndexCount {
+                count: indices.len(),
+            });
+        }
+
+        let materials = parse_list::<PmdMaterial>(&mut cursor, u32_count, 70)?;
+        let bones = parse_list::<PmdBone>(&mut cursor, u16_count, 39)?;
+        let iks = parse_list::<PmdIK>(&mut cursor, u16_count, 11)?;
+        let morphs = parse_list::<PmdMorph>(&mut cursor, u16_count, 25)?;
+        let morph_display = parse_list::<u16>(&mut cursor, u8_count, 2)?;
+
+        let count = u8_count(&mut cursor)?;
+        let bone_display_names = parse_names::<50>(&mut cursor, count)?;
+
+        // bone display (4 bytes + 3 bytes * count)
+        let count = u32_count(&mut cursor)?;
+        cursor.ensure_bytes::<PmdParseError>(count * 3)?;
+
+        let mut bone_display = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let bone_index = u16::parse(&(), &mut cursor)?;
+            let frame_index = u8::parse(&(), &mut cursor)?;
+            bone_display.push((bone_index, frame_index));
+        }
+
+        // the sections below are extensions, which older files do not have
+        let english = match cursor.has_bytes(1) && u8::parse(&(), &mut cursor)? != 0 {
+            true => Some(PmdEnglish::parse(
+                &mut cursor,
+                bones.len(),
+                morphs.len().saturating_sub(1),
+                bone_display_names.len(),
+            )?),
+            false => None,
+        };
+
+        let toon_textures = match cursor.has_bytes(100 * 10) {
+            true => Some(parse_names::<100>(&mut cursor, 10)?),
+            false => None,
+        };
+
+        let (rigidbodies, joints) = match cursor.has_bytes(4) {
+            true => (
+                parse_list::<PmdRigidbody>(&mut cursor, u32_count, 83)?,
+                parse_list::<PmdJoint>(&mut cursor, u32_count, 124)?,
+            ),
+            false => (Vec::new(), Vec::new()),
+        };
+
+        Ok(Self {
+            model_name,
+            comment,
+            vertices,
+            indices,
+            materials,
+            bones,
+            iks,
+            morphs,
+            morph_display,
+            bone_display_names,
+            bone_display,
+            english,
+            toon_textures,
+            rigidbodies,
+            joints,
+        })
+    }
+}
+
+fn u8_count(cursor: &mut Cursor) -> Result<usize, PmdParseError> {
+    cursor.ensure_bytes::<PmdParseError>(1)?;
+    Ok(u8::parse(&(), cursor)? as usize)
+}
+
+fn u16_count(cursor: &mut Cursor) -> Result<usize, PmdParseError> {
+    cursor.ensure_bytes::<PmdParseError>(2)?;
+    Ok(u16::parse(&(), cursor)? as usize)
+}
+
+fn u32_count(cursor: &mut Cursor) -> Result<usize, PmdParseError> {
+    cursor.ensure_bytes::<PmdParseError>(4)?;
+    Ok(u32::parse(&(), cursor)? as usize)
+}
+
+/// Parses a list prefixed with its count, whose width differs per section in PMD.
+/// `min_size` is the smallest size of an item, which is checked for all items up front.
+fn parse_list<T>(
+    cursor: &mut Cursor,
+    count: fn(&mut Cursor) -> Result<usize, PmdParseError>,
+    min_size: usize,
+) -> Result<Vec<T>, PmdParseError>
+where
+    T: Parse<()>,
+    T::Error: Into<PmdParseError>,
+{
+    let count = count(cursor)?;
+    cursor.ensure_bytes::<PmdParseError>(count * min_size)?;
+
+    let mut items = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        items.push(T::parse(&(), cursor).map_err(Into::into)?);
+    }
+
+    Ok(items)
+}
+
+/// Parses `count` names of `L` bytes.
+fn parse_names<const L: usize>(
+    cursor: &mut Cursor,
+    count: usize,
+) -> Result<Vec<String>, PmdParseError> {
+    cursor.ensure_bytes::<PmdParseError>(count * L)?;
+
+    let mut names = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        names.push(parse_shift_jis_name::<PmdParseError, L>(cursor)?);
+    }
+
+    Ok(names)
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdVertex {
+    pub position: PmxVec3,
+    pub normal: PmxVec3,
+    pub uv: PmxVec2,
+    pub bone_indices: [u16; 2],
+    /// weight of the first bone in the range of [0, 100]
+    pub bone_weight: u8,
+    pub has_edge: bool,
+}
+
+impl Parse<()> for PmdVertex {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // position (12 bytes)
+        // normal (12 bytes)
+        // uv (8 bytes)
+        // bone indices (2 bytes * 2)
+        // bone weight (1 byte)
+        // edge flag (1 byte)
+        let size = 12 + 12 + 8 + 2 * 2 + 1 + 1;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let position = PmxVec3::parse(config, cursor)?;
+        let normal = PmxVec3::parse(config, cursor)?;
+        let uv = PmxVec2::parse(config, cursor)?;
+        let bone_indices = [u16::parse(config, cursor)?, u16::parse(config, cursor)?];
+        let bone_weight = u8::parse(config, cursor)?;
+        // the flag disables the edge, unlike the other flags
+        let has_edge = u8::parse(config, cursor)? == 0;
+
+        Ok(Self {
+            position,
+            normal,
+            uv,
+            bone_indices,
+            bone_weight,
+            has_edge,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdMaterial {
+    pub diffuse_color: PmxVec3,
+    pub alpha: f32,
+    pub specular_strength: f32,
+    pub specular_color: PmxVec3,
+    pub ambient_color: PmxVec3,
+    /// index of the toon textures; `0xFF` if none
+    pub toon_index: u8,
+    pub has_edge: bool,
+    pub index_count: u32,
+    /// texture and sphere map paths, separated by `*`
+    pub texture_path: String,
+}
+
+impl Parse<()> for PmdMaterial {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // diffuse color (12 bytes)
+        // alpha (4 bytes)
+        // specular strength (4 bytes)
+        // specular color (12 bytes)
+        // ambient color (12 bytes)
+        // toon index (1 byte)
+        // edge flag (1 byte)
+        // index count (4 bytes)
+        // texture path (20 bytes)
+        let size = 12 + 4 + 4 + 12 + 12 + 1 + 1 + 4 + 20;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let diffuse_color = PmxVec3::parse(config, cursor)?;
+        let alpha = f32::parse(config, cursor)?;
+        let specular_strength = f32::parse(config, cursor)?;
+        let specular_color = PmxVec3::parse(config, cursor)?;
+        let ambient_color = PmxVec3::parse(config, cursor)?;
+        let toon_index = u8::parse(config, cursor)?;
+        let has_edge = bool::parse(config, cursor)?;
+        let index_count = u32::parse(config, cursor)?;
+        let texture_path = parse_shift_jis_name::<Self::Error, 20>(cursor)?;
+
+        Ok(Self {
+            diffuse_color,
+            alpha,
+            specular_strength,
+            specular_color,
+            ambient_color,
+            toon_index,
+            has_edge,
+            index_count,
+            texture_path,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum PmdBoneKind {
+    Rotate,
+    RotateAndMove,
+    IK,
+    Unknown,
+    /// Rotated by an IK.
+    UnderIK,
+    /// Copies the rotation of the bone at `ik_parent_index`.
+    UnderRotate,
+    IKTarget,
+    Invisible,
+    Twist,
+    /// Rotates the bone at `tail_index` by `ik_parent_index` percent of its own rotation.
+    FollowRotate,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdBone {
+    pub name: String,
+    pub parent_index: u16,
+    pub tail_index: u16,
+    pub kind: PmdBoneKind,
+    /// Meaning depends on `kind`.
+    pub ik_parent_index: u16,
+    pub position: PmxVec3,
+}
+
+impl Parse<()> for PmdBone {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // name (20 bytes)
+        // parent index (2 bytes)
+        // tail index (2 bytes)
+        // kind (1 byte)
+        // IK parent index (2 bytes)
+        // position (12 bytes)
+        let size = 20 + 2 + 2 + 1 + 2 + 12;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let name = parse_shift_jis_name::<Self::Error, 20>(cursor)?;
+        let parent_index = u16::parse(config, cursor)?;
+        let tail_index = u16::parse(config, cursor)?;
+        let kind = match u8::parse(config, cursor)? {
+            0 => PmdBoneKind::Rotate,
+            1 => PmdBoneKind::RotateAndMove,
+            2 => PmdBoneKind::IK,
+            4 => PmdBoneKind::UnderIK,
+            5 => PmdBoneKind::UnderRotate,
+            6 => PmdBoneKind::IKTarget,
+            7 => PmdBoneKind::Invisible,
+            8 => PmdBoneKind::Twist,
+            9 => PmdBoneKind::FollowRotate,
+            _ => PmdBoneKind::Unknown,
+        };
+        let ik_parent_index = u16::parse(config, cursor)?;
+        let position = PmxVec3::parse(config, cursor)?;
+
+        Ok(Self {
+            name,
+            parent_index,
+            tail_index,
+            kind,
+            ik_parent_index,
+            position,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdIK {
+    pub bone_index: u16,
+    pub target_index: u16,
+    pub loop_count: u16,
+    /// in radians per 4 in PMX
+    pub limit_angle: f32,
+    pub link_indices: Vec<u16>,
+}
+
+impl Parse<()> for PmdIK {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // bone index (2 bytes)
+        // target index (2 bytes)
+        // link count (1 byte)
+        // loop count (2 bytes)
+        // limit angle (4 bytes)
+        let size = 2 + 2 + 1 + 2 + 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let bone_index = u16::parse(config, cursor)?;
+        let target_index = u16::parse(config, cursor)?;
+        let link_count = u8::parse(config, cursor)? as usize;
+        let loop_count = u16::parse(config, cursor)?;
+        let limit_angle = f32::parse(config, cursor)?;
+
+        // link indices (2 bytes * count)
+        let size = 2 * link_count;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let mut link_indices = Vec::with_capacity(link_count);
+
+        for _ in 0..link_count {
+            link_indices.push(u16::parse(config, cursor)?);
+        }
+
+        Ok(Self {
+            bone_index,
+            target_index,
+            loop_count,
+            limit_angle,
+            link_indices,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdMorph {
+    pub name: String,
+    /// `0` for the base morph, otherwise the panel in the range of [1, 4]
+    pub kind: u8,
+    /// The base morph holds vertex indices and positions, the others hold
+    /// indices of the base morph's vertices and offsets.
+    pub vertices: Vec<(u32, PmxVec3)>,
+}
+
+impl Parse<()> for PmdMorph {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // name (20 bytes)
+        // vertex count (4 bytes)
+        // kind (1 byte)
+        let size = 20 + 4 + 1;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let name = parse_shift_jis_name::<Self::Error, 20>(cursor)?;
+        let count = u32::parse(config, cursor)? as usize;
+        let kind = u8::parse(config, cursor)?;
+
+        // vertices (16 bytes * count)
+        let size = 16 * count;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let mut vertices = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let index = u32::parse(config, cursor)?;
+            let position = PmxVec3::parse(config, cursor)?;
+            vertices.push((index, position));
+        }
+
+        Ok(Self {
+            name,
+            kind,
+            vertices,
+        })
+    }
+}
+
+/// English names, stored in the same order as the local names.
+#[derive(Debug, Clone)]
+pub(crate) struct PmdEnglish {
+    pub model_name: String,
+    pub comment: String,
+    pub bone_names: Vec<String>,
+    /// names of the morphs except the base morph
+    pub morph_names: Vec<String>,
+    pub bone_display_names: Vec<String>,
+}
+
+impl PmdEnglish {
+    fn parse(
+        cursor: &mut Cursor,
+        bone_count: usize,
+        morph_count: usize,
+        bone_display_name_count: usize,
+    ) -> Result<Self, PmdParseError> {
+        // model name (20 bytes)
+        // comment (256 bytes)
+        let size = 20 + 256;
+        cursor.ensure_bytes::<PmdParseError>(size)?;
+
+        let model_name = parse_shift_jis_name::<PmdParseError, 20>(cursor)?;
+        let comment = parse_shift_jis_name::<PmdParseError, 256>(cursor)?;
+        let bone_names = parse_names::<20>(cursor, bone_count)?;
+        let morph_names = parse_names::<20>(cursor, morph_count)?;
+        let bone_display_names = parse_names::<50>(cursor, bone_display_name_count)?;
+
+        Ok(Self {
+            model_name,
+            comment,
+            bone_names,
+            morph_names,
+            bone_display_names,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdRigidbody {
+    pub name: String,
+    pub bone_index: u16,
+    pub group_id: u8,
+    pub non_collision_group: u16,
+    pub shape_kind: u8,
+    pub size: PmxVec3,
+    /// relative to the bone
+    pub position: PmxVec3,
+    pub rotation: PmxVec3,
+    pub mass: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    pub restitution_coefficient: f32,
+    pub friction_coefficient: f32,
+    pub physics_mode: u8,
+}
+
+impl Parse<()> for PmdRigidbody {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // name (20 bytes)
+        // bone index (2 bytes)
+        // group id (1 byte)
+        // non collision group (2 bytes)
+        // shape kind (1 byte)
+        // size, position, rotation (12 bytes * 3)
+        // mass, dampings, coefficients (4 bytes * 5)
+        // physics mode (1 byte)
+        let size = 20 + 2 + 1 + 2 + 1 + 12 * 3 + 4 * 5 + 1;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        Ok(Self {
+            name: parse_shift_jis_name::<Self::Error, 20>(cursor)?,
+            bone_index: u16::parse(config, cursor)?,
+            group_id: u8::parse(config, cursor)?,
+            non_collision_group: u16::parse(config, cursor)?,
+            shape_kind: u8::parse(config, cursor)?,
+            size: PmxVec3::parse(config, cursor)?,
+            position: PmxVec3::parse(config, cursor)?,
+            rotation: PmxVec3::parse(config, cursor)?,
+            mass: f32::parse(config, cursor)?,
+            linear_damping: f32::parse(config, cursor)?,
+            angular_damping: f32::parse(config, cursor)?,
+            restitution_coefficient: f32::parse(config, cursor)?,
+            friction_coefficient: f32::parse(config, cursor)?,
+            physics_mode: u8::parse(config, cursor)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PmdJoint {
+    pub name: String,
+    pub rigidbody_index_pair: (u32, u32),
+    pub position: PmxVec3,
+    pub rotation: PmxVec3,
+    pub position_limit_min: PmxVec3,
+    pub position_limit_max: PmxVec3,
+    pub rotation_limit_min: PmxVec3,
+    pub rotation_limit_max: PmxVec3,
+    pub spring_position: PmxVec3,
+    pub spring_rotation: PmxVec3,
+}
+
+impl Parse<()> for PmdJoint {
+    type Error = PmdParseError;
+
+    fn parse(config: &(), cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // name (20 bytes)
+        // rigidbody index pair (4 bytes * 2)
+        // vectors (12 bytes * 8)
+        let size = 20 + 4 * 2 + 12 * 8;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        Ok(Self {
+            name: parse_shift_jis_name::<Self::Error, 20>(cursor)?,
+            rigidbody_index_pair: (u32::parse(config, cursor)?, u32::parse(config, cursor)?),
+            position: PmxVec3::parse(config, cursor)?,
+            rotation: PmxVec3::parse(config, cursor)?,
+            position_limit_min: PmxVec3::parse(config, cursor)?,
+            position_limit_max: PmxVec3::parse(config, cursor)?,
+            rotation_limit_min: PmxVec3::parse(config, cursor)?,
+            rotation_limit_max: PmxVec3::parse(config, cursor)?,
+            spring_position: PmxVec3::parse(config, cursor)?,
+            spring_rotation: PmxVec3::parse(config, cursor)?,
+        })
+    }
+}
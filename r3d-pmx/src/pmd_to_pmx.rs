@@ -0,0 +1,798 @@
+use crate::{
+    pmd::{Pmd, PmdBoneKind, PmdMaterial, PmdParseError, PMD_NONE_INDEX},
+    Pmx, PmxBone, PmxBoneFixedAxis, PmxBoneFlags, PmxBoneIK, PmxBoneIKAngleLimit, PmxBoneIKLink,
+    PmxBoneIndex, PmxBoneInheritance, PmxBoneInheritanceMode, PmxBoneTailPosition, PmxConfig,
+    PmxDisplay, PmxDisplayFrame, PmxHeader, PmxIndexSize, PmxJoint, PmxJointKind, PmxMaterial,
+    PmxMaterialEnvironmentBlendMode, PmxMaterialFlags, PmxMaterialToonMode, PmxMorph,
+    PmxMorphIndex, PmxMorphOffset, PmxMorphOffsetVertex, PmxMorphPanelKind, PmxRigidbody,
+    PmxRigidbodyIndex, PmxRigidbodyPhysicsMode, PmxRigidbodyShape, PmxRigidbodyShapeKind,
+    PmxSurface, PmxTextEncoding, PmxTexture, PmxTextureIndex, PmxVec3, PmxVec4, PmxVertex,
+    PmxVertexDeformKind, PmxVertexIndex,
+};
+use std::collections::HashMap;
+
+impl Pmx {
+    /// Parses a PMD model, the format preceding PMX, and converts it into PMX 2.0.
+    /// English names, toon textures and physics are optional in PMD; missing ones are left empty.
+    pub fn parse_pmd(buf: impl AsRef<[u8]>) -> Result<Self, PmdParseError> {
+        let pmd = Pmd::parse(buf.as_ref())?;
+        let english = pmd.english.as_ref();
+
+        let mut textures = TextureList::default();
+        let toon_textures = pmd.toon_textures.clone().unwrap_or_default();
+        let materials =
+            Vec::from_iter(pmd.materials.iter().enumerate().map(|(index, material)| {
+                convert_material(index, material, &toon_textures, &mut textures)
+            }));
+
+        let morphs = convert_morphs(&pmd)?;
+        let rigidbodies = convert_rigidbodies(&pmd)?;
+
+        let mut pmx = Pmx {
+            header: PmxHeader {
+                signature: *b"PMX ",
+                version: 2.0,
+                config: PmxConfig {
+                    text_encoding: PmxTextEncoding::Utf16le,
+                    additional_vec4_count: 0,
+                    vertex_index_size: PmxIndexSize::U32,
+                    texture_index_size: PmxIndexSize::U32,
+                    material_index_size: PmxIndexSize::U32,
+                    bone_index_size: PmxIndexSize::U32,
+                    morph_index_size: PmxIndexSize::U32,
+                    rigidbody_index_size: PmxIndexSize::U32,
+                },
+                model_name_local: pmd.model_name.clone(),
+                model_name_universal: english
+                    .map(|english| english.model_name.clone())
+                    .unwrap_or_default(),
+                model_comment_local: pmd.comment.clone(),
+                model_comment_universal: english
+                    .map(|english| english.comment.clone())
+                    .unwrap_or_default(),
+            },
+            vertices: convert_vertices(&pmd),
+            surfaces: Vec::from_iter(pmd.indices.chunks_exact(3).map(|indices| PmxSurface {
+                vertex_indices: [0, 1, 2].map(|i| PmxVertexIndex::new(indices[i] as u32)),
+            })),
+            textures: textures.textures,
+            materials,
+            bones: convert_bones(&pmd),
+            displays: convert_displays(&pmd),
+            morphs,
+            rigidbodies,
+            joints: convert_joints(&pmd),
+            soft_bodies: Vec::new(),
+        };
+        pmx.header.config = pmx.fitted_config();
+
+        Ok(pmx)
+    }
+}
+
+fn bone_index(index: u16) -> PmxBoneIndex {
+    match index {
+        PMD_NONE_INDEX => PmxBoneIndex::new(-1),
+        index => PmxBoneIndex::new(index as i32),
+    }
+}
+
+fn convert_vertices(pmd: &Pmd) -> Vec<PmxVertex> {
+    let zero = PmxVec4 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 0.0,
+    };
+
+    Vec::from_iter(pmd.vertices.iter().map(|vertex| PmxVertex {
+        position: vertex.position,
+        normal: vertex.normal,
+        uv: vertex.uv,
+        additional_vec4s: [zero; 4],
+        deform_kind: PmxVertexDeformKind::Bdef2 {
+            bone_index_1: bone_index(vertex.bone_indices[0]),
+            bone_index_2: bone_index(vertex.bone_indices[1]),
+            bone_weight: vertex.bone_weight.min(100) as f32 / 100.0,
+        },
+        edge_size: if vertex.has_edge { 1.0 } else { 0.0 },
+    }))
+}
+
+/// Deduplicated textures referenced by the materials.
+#[derive(Default)]
+struct TextureList {
+    textures: Vec<PmxTexture>,
+    indices: HashMap<String, PmxTextureIndex>,
+}
+
+impl TextureList {
+    fn index(&mut self, path: &str) -> PmxTextureIndex {
+        if path.is_empty() {
+            return PmxTextureIndex::new(-1);
+        }
+
+        *self.indices.entry(path.to_owned()).or_insert_with(|| {
+            self.textures.push(PmxTexture {
+                path: path.to_owned(),
+            });
+            PmxTextureIndex::new(self.textures.len() as i32 - 1)
+        })
+    }
+}
+
+fn convert_material(
+    index: usize,
+    material: &PmdMaterial,
+    toon_textures: &[String],
+    textures: &mut TextureList,
+) -> PmxMaterial {
+    // the texture path is either a texture, a sphere map or both separated by `*`
+    let mut texture_index = PmxTextureIndex::new(-1);
+    let mut environment_texture_index = PmxTextureIndex::new(-1);
+    let mut environment_blend_mode = PmxMaterialEnvironmentBlendMode::Disabled;
+
+    for path in material.texture_path.split('*') {
+        let extension = path.rsplit_once('.').map(|(_, extension)| extension);
+
+        match extension
+            .map(|extension| extension.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("sph") => {
+                environment_texture_index = textures.index(path);
+                environment_blend_mode = PmxMaterialEnvironmentBlendMode::Multiplicative;
+            }
+            Some("spa") => {
+                environment_texture_index = textures.index(path);
+                environment_blend_mode = PmxMaterialEnvironmentBlendMode::Additive;
+            }
+            _ => {
+                if !path.is_empty() {
+                    texture_index = textures.index(path);
+                }
+            }
+        }
+    }
+
+    // toon textures named as the shared ones refer to the shared ones
+    let toon_mode = match toon_textures.get(material.toon_index as usize) {
+        Some(path)
+            if !path.is_empty()
+                && !path
+                    .eq_ignore_ascii_case(&format!("toon{:02}.bmp", material.toon_index + 1)) =>
+        {
+            PmxMaterialToonMode::Texture {
+                index: textures.index(path),
+            }
+        }
+        _ if material.toon_index < 10 => PmxMaterialToonMode::InternalTexture {
+            index: material.toon_index,
+        },
+        _ => PmxMaterialToonMode::Texture {
+            index: PmxTextureIndex::new(-1),
+        },
+    };
+
+    // MikuMikuDance draws translucent materials double-sided, and disables self shadows of
+    // materials with the alpha of exactly 0.98
+    let is_self_shadowed = material.alpha != 0.98;
+
+    PmxMaterial {
+        name_local: format!("材質{}", index + 1),
+        name_universal: format!("material{}", index + 1),
+        diffuse_color: PmxVec4 {
+            x: material.diffuse_color.x,
+            y: material.diffuse_color.y,
+            z: material.diffuse_color.z,
+            w: material.alpha,
+        },
+        specular_color: material.specular_color,
+        specular_strength: material.specular_strength,
+        ambient_color: material.ambient_color,
+        flags: PmxMaterialFlags {
+            cull_back_face: material.alpha < 1.0,
+            cast_shadow_on_ground: true,
+            cast_shadow_on_object: is_self_shadowed,
+            receive_shadow: is_self_shadowed,
+            has_edge: material.has_edge,
+            uses_vertex_color: false,
+            draws_points: false,
+            draws_lines: false,
+        },
+        edge_color: PmxVec4 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        },
+        edge_size: 1.0,
+        texture_index,
+        environment_texture_index,
+        environment_blend_mode,
+        toon_mode,
+        metadata: String::new(),
+        surface_count: material.index_count,
+    }
+}
+
+fn convert_bones(pmd: &Pmd) -> Vec<PmxBone> {
+    let english = pmd.english.as_ref();
+    // a tail index of zero means none, since the root bone cannot be a tail
+    let tail_index = |index: u16| match index {
+        0 | PMD_NONE_INDEX => None,
+        index => pmd.bones.get(index as usize).map(|_| index),
+    };
+
+    let mut bones = Vec::from_iter(pmd.bones.iter().enumerate().map(|(index, bone)| {
+        let kind = bone.kind;
+        let mut flags = PmxBoneFlags {
+            indexed_tail_position: true,
+            is_rotatable: true,
+            is_translatable: matches!(kind, PmdBoneKind::RotateAndMove | PmdBoneKind::IK),
+            is_visible: !matches!(
+                kind,
+                PmdBoneKind::IKTarget | PmdBoneKind::Invisible | PmdBoneKind::FollowRotate
+            ),
+            is_enabled: true,
+            supports_ik: false,
+            inherit_rotation: false,
+            inherit_translation: false,
+            fixed_axis: false,
+            local_coordinate: false,
+            physics_after_deform: false,
+            external_parent_deform: false,
+        };
+
+        let inheritance = match kind {
+            PmdBoneKind::UnderRotate if (bone.ik_parent_index as usize) < pmd.bones.len() => {
+                flags.inherit_rotation = true;
+                Some(PmxBoneInheritance {
+                    index: bone_index(bone.ik_parent_index),
+                    coefficient: 1.0,
+                    inheritance_mode: PmxBoneInheritanceMode::RotationOnly,
+                })
+            }
+            _ => None,
+        };
+
+        let fixed_axis = match (kind, tail_index(bone.tail_index)) {
+            (PmdBoneKind::Twist, Some(tail)) => {
+                let tail = &pmd.bones[tail as usize].position;
+                let direction = [
+                    tail.x - bone.position.x,
+                    tail.y - bone.position.y,
+                    tail.z - bone.position.z,
+                ];
+                let len = direction.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+                (f32::EPSILON < len).then(|| {
+                    flags.fixed_axis = true;
+                    PmxBoneFixedAxis {
+                        direction: PmxVec3 {
+                            x: direction[0] / len,
+                            y: direction[1] / len,
+                            z: direction[2] / len,
+                        },
+                    }
+                })
+            }
+            _ => None,
+        };
+
+        PmxBone {
+            name_local: bone.name.clone(),
+            name_universal: english
+                .and_then(|english| english.bone_names.get(index).cloned())
+                .unwrap_or_default(),
+            position: bone.position,
+            parent_index: bone_index(bone.parent_index),
+            layer: 0,
+            flags,
+            tail_position: PmxBoneTailPosition::BoneIndex {
+                index: tail_index(bone.tail_index).map_or(PmxBoneIndex::new(-1), bone_index),
+            },
+            inheritance,
+            fixed_axis,
+            local_coordinate: None,
+            external_parent: None,
+            ik: None,
+        }
+    }));
+
+    // a follow-rotate bone rotates its tail bone by a ratio of its own rotation
+    for (index, bone) in pmd.bones.iter().enumerate() {
+        let tail = match (bone.kind, tail_index(bone.tail_index)) {
+            (PmdBoneKind::FollowRotate, Some(tail)) => &mut bones[tail as usize],
+            _ => continue,
+        };
+
+        if tail.inheritance.is_some() {
+            continue;
+        }
+
+        tail.flags.inherit_rotation = true;
+        tail.inheritance = Some(PmxBoneInheritance {
+            index: PmxBoneIndex::new(index as i32),
+            coefficient: bone.ik_parent_index as f32 * 0.01,
+            inheritance_mode: PmxBoneInheritanceMode::RotationOnly,
+        });
+    }
+
+    for ik in &pmd.iks {
+        let is_valid = |index: u16| (index as usize) < bones.len();
+
+        if !is_valid(ik.bone_index) || !is_valid(ik.target_index) {
+            continue;
+        }
+
+        let links = Vec::from_iter(
+            ik.link_indices
+                .iter()
+                .copied()
+                .filter(|&index| is_valid(index))
+                .map(|index| PmxBoneIKLink {
+                    index: bone_index(index),
+                    // knees only bend backwards in MikuMikuDance
+                    angle_limit: pmd.bones[index as usize].name.contains("ひざ").then_some(
+                        PmxBoneIKAngleLimit {
+                            min: PmxVec3 {
+                                x: -std::f32::consts::PI,
+                                y: 0.0,
+                                z: 0.0,
+                            },
+                            max: PmxVec3 {
+                                x: -0.5f32.to_radians(),
+                                y: 0.0,
+                                z: 0.0,
+                            },
+                        },
+                    ),
+                }),
+        );
+
+        let bone = &mut bones[ik.bone_index as usize];
+        bone.flags.supports_ik = true;
+        bone.ik = Some(PmxBoneIK {
+            index: bone_index(ik.target_index),
+            loop_count: ik.loop_count as i32,
+            // PMD stores the limit in the unit of 4 radians
+            limit_angle: ik.limit_angle * 4.0,
+            links,
+        });
+    }
+
+    bones
+}
+
+fn convert_morphs(pmd: &Pmd) -> Result<Vec<PmxMorph>, PmdParseError> {
+    let english = pmd.english.as_ref();
+    let base = match pmd.morphs.iter().find(|morph| morph.kind == 0) {
+        Some(base) => base,
+        None => return Ok(Vec::new()),
+    };
+    let mut morphs = Vec::with_capacity(pmd.morphs.len().saturating_sub(1));
+
+    for morph in pmd.morphs.iter().filter(|morph| morph.kind != 0) {
+        let mut offsets = Vec::with_capacity(morph.vertices.len());
+
+        for &(index, translation) in &morph.vertices {
+            let &(vertex_index, _) = base.vertices.get(index as usize).ok_or(
+                PmdParseError::InvalidMorphVertexIndex {
+                    index,
+                    count: base.vertices.len(),
+                },
+            )?;
+
+            offsets.push(PmxMorphOffsetVertex {
+                index: PmxVertexIndex::new(vertex_index),
+                translation,
+            });
+        }
+
+        morphs.push(PmxMorph {
+            name_local: morph.name.clone(),
+            name_universal: english
+                .and_then(|english| english.morph_names.get(morphs.len()).cloned())
+                .unwrap_or_default(),
+            panel_kind: match morph.kind {
+                1 => PmxMorphPanelKind::Eyebrows,
+                2 => PmxMorphPanelKind::Eyes,
+                3 => PmxMorphPanelKind::Mouth,
+                _ => PmxMorphPanelKind::Other,
+            },
+            offset: PmxMorphOffset::Vertex(offsets),
+        });
+    }
+
+    Ok(morphs)
+}
+
+fn convert_displays(pmd: &Pmd) -> Vec<PmxDisplay> {
+    let english = pmd.english.as_ref();
+    let mut displays = Vec::with_capacity(2 + pmd.bone_display_names.len());
+
+    // PMX models have the special root and expression frames first
+    displays.push(PmxDisplay {
+        name_local: "Root".to_owned(),
+        name_universal: "Root".to_owned(),
+        is_special: true,
+        frames: match pmd.bones.is_empty() {
+            true => Vec::new(),
+            false => vec![PmxDisplayFrame::Bone {
+                index: PmxBoneIndex::new(0),
+            }],
+        },
+    });
+
+    // morph indices shift by one, since the base morph is not converted
+    let morph_indices = HashMap::<usize, usize>::from_iter(
+        pmd.morphs
+            .iter()
+            .enumerate()
+            .filter(|(_, morph)| morph.kind != 0)
+            .enumerate()
+            .map(|(pmx_index, (pmd_index, _))| (pmd_index, pmx_index)),
+    );
+    displays.push(PmxDisplay {
+        name_local: "表情".to_owned(),
+        name_universal: "Exp".to_owned(),
+        is_special: true,
+        frames: Vec::from_iter(pmd.morph_display.iter().filter_map(|&index| {
+            morph_indices
+                .get(&(index as usize))
+                .map(|&index| PmxDisplayFrame::Morph {
+                    index: PmxMorphIndex::new(index as i32),
+                })
+        })),
+    });
+
+    for (index, name) in pmd.bone_display_names.iter().enumerate() {
+        // names are usually terminated by a line feed
+        let name_universal = english
+            .and_then(|english| english.bone_display_names.get(index))
+            .map_or("", |name| name.trim_end());
+
+        displays.push(PmxDisplay {
+            name_local: name.trim_end().to_owned(),
+            name_universal: name_universal.to_owned(),
+            is_special: false,
+            frames: Vec::from_iter(
+                pmd.bone_display
+                    .iter()
+                    .filter(|&&(_, frame_index)| frame_index as usize == index + 1)
+                    .map(|&(bone, _)| PmxDisplayFrame::Bone {
+                        index: bone_index(bone),
+                    }),
+            ),
+        });
+    }
+
+    displays
+}
+
+fn convert_rigidbodies(pmd: &Pmd) -> Result<Vec<PmxRigidbody>, PmdParseError> {
+    let mut rigidbodies = Vec::with_capacity(pmd.rigidbodies.len());
+
+    for rigidbody in &pmd.rigidbodies {
+        let kind = match rigidbody.shape_kind {
+            0 => PmxRigidbodyShapeKind::Sphere,
+            1 => PmxRigidbodyShapeKind::Box,
+            2 => PmxRigidbodyShapeKind::Capsule,
+            kind => return Err(PmdParseError::InvalidRigidbodyShapeKind { kind }),
+        };
+        let physics_mode = match rigidbody.physics_mode {
+            0 => PmxRigidbodyPhysicsMode::Static,
+            1 => PmxRigidbodyPhysicsMode::Dynamic,
+            2 => PmxRigidbodyPhysicsMode::DynamicWithBone,
+            mode => return Err(PmdParseError::InvalidRigidbodyPhysicsMode { mode }),
+        };
+
+        // positions are relative to the bone in PMD; rigidbodies without a bone follow the first bone
+        let origin = match rigidbody.bone_index {
+            PMD_NONE_INDEX => pmd.bones.first(),
+            index => pmd.bones.get(index as usize),
+        }
+        .map_or(
+            PmxVec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            |bone| bone.position,
+        );
+
+        rigidbodies.push(PmxRigidbody {
+            name_local: rigidbody.name.clone(),
+            name_universal: String::new(),
+            bone_index: bone_index(rigidbody.bone_index),
+            group_id: rigidbody.group_id as i8,
+            non_collision_group: rigidbody.non_collision_group as i16,
+            shape: PmxRigidbodyShape {
+                kind,
+                size: rigidbody.size,
+                position: PmxVec3 {
+                    x: origin.x + rigidbody.position.x,
+                    y: origin.y + rigidbody.position.y,
+                    z: origin.z + rigidbody.position.z,
+                },
+                rotation: rigidbody.rotation,
+            },
+            mass: rigidbody.mass,
+            linear_damping: rigidbody.linear_damping,
+            angular_damping: rigidbody.angular_damping,
+            restitution_coefficient: rigidbody.restitution_coefficient,
+            friction_coefficient: rigidbody.friction_coefficient,
+            physics_mode,
+        });
+    }
+
+    Ok(rigidbodies)
+}
+
+fn convert_joints(pmd: &Pmd) -> Vec<PmxJoint> {
+    Vec::from_iter(pmd.joints.iter().map(|joint| PmxJoint {
+        name_local: joint.name.clone(),
+        name_universal: String::new(),
+        kind: PmxJointKind::Spring6Dof,
+        rigidbody_index_pair: (
+            PmxRigidbodyIndex::new(joint.rigidbody_index_pair.0 as i32),
+            PmxRigidbodyIndex::new(joint.rigidbody_index_pair.1 as i32),
+        ),
+        position: joint.position,
+        rotation: joint.rotation,
+        position_limit_min: joint.position_limit_min,
+        position_limit_max: joint.position_limit_max,
+        rotation_limit_min: joint.rotation_limit_min,
+        rotation_limit_max: joint.rotation_limit_max,
+        spring_position: joint.spring_position,
+        spring_rotation: joint.spring_rotation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_name<const L: usize>(buf: &mut Vec<u8>, name: &[u8]) {
+        let mut bytes = [0u8; L];
+        bytes[..name.len()].copy_from_slice(name);
+        buf.extend_from_slice(&bytes);
+    }
+
+    fn push_f32s(buf: &mut Vec<u8>, values: &[f32]) {
+        for value in values {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn push_bone(buf: &mut Vec<u8>, name: &[u8], parent: u16, tail: u16, kind: u8, ik_parent: u16) {
+        push_name::<20>(buf, name);
+        buf.extend_from_slice(&parent.to_le_bytes());
+        buf.extend_from_slice(&tail.to_le_bytes());
+        buf.push(kind);
+        buf.extend_from_slice(&ik_parent.to_le_bytes());
+        push_f32s(buf, &[0.0, 10.0, 0.0]);
+    }
+
+    fn sample_pmd() -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"Pmd");
+        push_f32s(&mut buf, &[1.0]);
+        push_name::<20>(&mut buf, b"model");
+        push_name::<256>(&mut buf, b"comment");
+
+        // vertices
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        for index in 0..3 {
+            push_f32s(&mut buf, &[index as f32, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5]);
+            buf.extend_from_slice(&0u16.to_le_bytes());
+            buf.extend_from_slice(&1u16.to_le_bytes());
+            buf.push(75);
+            buf.push(index as u8 % 2);
+        }
+
+        // indices
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        for index in [0u16, 1, 2] {
+            buf.extend_from_slice(&index.to_le_bytes());
+        }
+
+        // a translucent material with a texture and a sphere map
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_f32s(
+            &mut buf,
+            &[1.0, 0.5, 0.5, 0.5, 5.0, 0.1, 0.1, 0.1, 0.2, 0.2, 0.2],
+        );
+        buf.push(0);
+        buf.push(1);
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        push_name::<20>(&mut buf, b"body.bmp*light.sph");
+
+        // bones: center, "左ひざ" (left knee), IK, and a follow-rotate bone driving the knee by half
+        buf.extend_from_slice(&4u16.to_le_bytes());
+        push_bone(&mut buf, b"center", 0xFFFF, 1, 1, 0);
+        push_bone(&mut buf, &[0x8D, 0xB6, 0x82, 0xD0, 0x82, 0xB4], 0, 0, 0, 0);
+        push_bone(&mut buf, b"ik", 0, 0, 2, 0);
+        push_bone(&mut buf, b"follow", 0, 1, 9, 50);
+
+        // IK
+        buf.extend_from_slice(&1u16.to_le_bytes());
+        buf.extend_from_slice(&2u16.to_le_bytes());
+        buf.extend_from_slice(&1u16.to_le_bytes());
+        buf.push(1);
+        buf.extend_from_slice(&40u16.to_le_bytes());
+        push_f32s(&mut buf, &[0.5]);
+        buf.extend_from_slice(&1u16.to_le_bytes());
+
+        // morphs: the base morph and a mouth morph moving the vertex 2
+        buf.extend_from_slice(&2u16.to_le_bytes());
+        push_name::<20>(&mut buf, b"base");
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        push_f32s(&mut buf, &[0.0, 0.0, 0.0]);
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        push_f32s(&mut buf, &[2.0, 0.0, 0.0]);
+        push_name::<20>(&mut buf, b"a");
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.push(3);
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_f32s(&mut buf, &[0.0, 0.1, 0.0]);
+
+        // morph display
+        buf.push(1);
+        buf.extend_from_slice(&1u16.to_le_bytes());
+
+        // bone display
+        buf.push(1);
+        push_name::<50>(&mut buf, b"legs\n");
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&1u16.to_le_bytes());
+        buf.push(1);
+
+        // English names
+        buf.push(1);
+        push_name::<20>(&mut buf, b"model_en");
+        push_name::<256>(&mut buf, b"comment_en");
+        for name in [&b"center"[..], b"left knee", b"leg IK", b"follow"] {
+            push_name::<20>(&mut buf, name);
+        }
+        push_name::<20>(&mut buf, b"a_en");
+        push_name::<50>(&mut buf, b"legs_en\n");
+
+        // toon textures, where the first one is custom
+        push_name::<100>(&mut buf, b"custom.bmp");
+        for index in 2..=10 {
+            push_name::<100>(&mut buf, format!("toon{:02}.bmp", index).as_bytes());
+        }
+
+        // physics
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_name::<20>(&mut buf, b"body");
+        buf.extend_from_slice(&0u16.to_le_bytes());
+        buf.push(1);
+        buf.extend_from_slice(&0xFFFEu16.to_le_bytes());
+        buf.push(2);
+        push_f32s(&mut buf, &[1.0, 2.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        push_f32s(&mut buf, &[1.0, 0.5, 0.5, 0.0, 0.5]);
+        buf.push(1);
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_name::<20>(&mut buf, b"joint");
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        push_f32s(&mut buf, &[0.0; 24]);
+
+        buf
+    }
+
+    #[test]
+    fn check_parse_pmd() {
+        let pmx = Pmx::parse_pmd(sample_pmd()).unwrap();
+
+        assert_eq!(pmx.header.model_name_universal, "model_en");
+        assert_eq!(pmx.header.config.vertex_index_size, PmxIndexSize::U8);
+        assert_eq!(pmx.vertices.len(), 3);
+        assert_eq!(pmx.vertices[1].edge_size, 0.0);
+        assert_eq!(
+            pmx.vertices[0].deform_kind,
+            PmxVertexDeformKind::Bdef2 {
+                bone_index_1: PmxBoneIndex::new(0),
+                bone_index_2: PmxBoneIndex::new(1),
+                bone_weight: 0.75,
+            }
+        );
+        assert_eq!(pmx.surfaces.len(), 1);
+
+        let paths = Vec::from_iter(pmx.textures.iter().map(|texture| texture.path.as_str()));
+        assert_eq!(paths, ["body.bmp", "light.sph", "custom.bmp"]);
+
+        let material = &pmx.materials[0];
+        assert_eq!(material.texture_index, PmxTextureIndex::new(0));
+        assert_eq!(material.environment_texture_index, PmxTextureIndex::new(1));
+        assert_eq!(
+            material.environment_blend_mode,
+            PmxMaterialEnvironmentBlendMode::Multiplicative
+        );
+        assert_eq!(
+            material.toon_mode,
+            PmxMaterialToonMode::Texture {
+                index: PmxTextureIndex::new(2)
+            }
+        );
+        assert!(material.flags.cull_back_face);
+        assert!(material.flags.has_edge);
+        assert_eq!(material.surface_count, 3);
+
+        let knee = &pmx.bones[1];
+        assert_eq!(knee.name_local, "左ひざ");
+        assert_eq!(knee.name_universal, "left knee");
+        let inheritance = knee.inheritance.as_ref().unwrap();
+        assert_eq!(inheritance.index, PmxBoneIndex::new(3));
+        assert_eq!(inheritance.coefficient, 0.5);
+        assert!(knee.flags.inherit_rotation);
+
+        let ik = pmx.bones[2].ik.as_ref().unwrap();
+        assert!(pmx.bones[2].flags.supports_ik);
+        assert_eq!(ik.index, PmxBoneIndex::new(1));
+        assert_eq!(ik.limit_angle, 2.0);
+        assert!(ik.links[0].angle_limit.is_some());
+        assert!(!pmx.bones[3].flags.is_visible);
+
+        assert_eq!(pmx.morphs.len(), 1);
+        assert_eq!(pmx.morphs[0].name_universal, "a_en");
+        assert_eq!(pmx.morphs[0].panel_kind, PmxMorphPanelKind::Mouth);
+        match &pmx.morphs[0].offset {
+            PmxMorphOffset::Vertex(offsets) => {
+                assert_eq!(offsets[0].index, PmxVertexIndex::new(2));
+            }
+            offset => panic!("unexpected morph offset {:?}", offset),
+        }
+
+        assert_eq!(pmx.displays.len(), 3);
+        assert_eq!(
+            pmx.displays[1].frames,
+            [PmxDisplayFrame::Morph {
+                index: PmxMorphIndex::new(0)
+            }]
+        );
+        assert_eq!(pmx.displays[2].name_local, "legs");
+        assert_eq!(pmx.displays[2].name_universal, "legs_en");
+        assert_eq!(
+            pmx.displays[2].frames,
+            [PmxDisplayFrame::Bone {
+                index: PmxBoneIndex::new(1)
+            }]
+        );
+
+        // rigidbody positions are relative to the bone in PMD
+        let rigidbody = &pmx.rigidbodies[0];
+        assert_eq!(rigidbody.shape.kind, PmxRigidbodyShapeKind::Capsule);
+        assert_eq!(rigidbody.shape.position.y, 11.0);
+        assert_eq!(rigidbody.non_collision_group, -2);
+        assert_eq!(pmx.joints.len(), 1);
+
+        // the converted model is a valid PMX model
+        let parsed = Pmx::parse(pmx.write().unwrap()).unwrap();
+        assert_eq!(parsed, pmx);
+    }
+
+    #[test]
+    fn check_parse_pmd_without_extensions() {
+        let mut buf = sample_pmd();
+        // cut the file right after the bone display section
+        let len = buf.len() - (1 + 20 + 256 + 20 * 4 + 20 + 50) - 100 * 10 - (4 + 83 + 4 + 124);
+        buf.truncate(len);
+
+        let pmx = Pmx::parse_pmd(&buf).unwrap();
+        assert_eq!(pmx.header.model_name_universal, "");
+        assert_eq!(pmx.bones[1].name_universal, "");
+        assert_eq!(
+            pmx.materials[0].toon_mode,
+            PmxMaterialToonMode::InternalTexture { index: 0 }
+        );
+        assert!(pmx.rigidbodies.is_empty());
+
+        assert!(Pmx::parse_pmd(&buf[..buf.len() - 1]).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
     pmx_primitives::{PmxBoneIndex, PmxVec3},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -22,7 +23,7 @@ impl ParseError for PmxBoneParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBone {
     pub name_local: String,
     pub name_universal: String,
@@ -136,6 +137,71 @@ impl Parse for PmxBone {
     }
 }
 
+impl Write for PmxBone {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+        self.position.write(config, buffer)?;
+        self.parent_index.write(config, buffer)?;
+        self.layer.write(config, buffer)?;
+
+        // the flags that decide the layout follow the data, so that edited bones are written consistently
+        let inheritance_mode = self
+            .inheritance
+            .as_ref()
+            .map(|inheritance| inheritance.inheritance_mode);
+        let flags = PmxBoneFlags {
+            indexed_tail_position: matches!(
+                self.tail_position,
+                PmxBoneTailPosition::BoneIndex { .. }
+            ),
+            supports_ik: self.ik.is_some(),
+            inherit_rotation: matches!(
+                inheritance_mode,
+                Some(PmxBoneInheritanceMode::Both | PmxBoneInheritanceMode::RotationOnly)
+            ),
+            inherit_translation: matches!(
+                inheritance_mode,
+                Some(PmxBoneInheritanceMode::Both | PmxBoneInheritanceMode::TranslationOnly)
+            ),
+            fixed_axis: self.fixed_axis.is_some(),
+            local_coordinate: self.local_coordinate.is_some(),
+            external_parent_deform: self.external_parent.is_some(),
+            ..self.flags
+        };
+        flags.write(config, buffer)?;
+
+        match &self.tail_position {
+            PmxBoneTailPosition::Vec3 { position } => position.write(config, buffer)?,
+            PmxBoneTailPosition::BoneIndex { index } => index.write(config, buffer)?,
+        }
+
+        if let Some(inheritance) = &self.inheritance {
+            inheritance.index.write(config, buffer)?;
+            inheritance.coefficient.write(config, buffer)?;
+        }
+
+        if let Some(fixed_axis) = &self.fixed_axis {
+            fixed_axis.direction.write(config, buffer)?;
+        }
+
+        if let Some(local_coordinate) = &self.local_coordinate {
+            local_coordinate.x_axis.write(config, buffer)?;
+            local_coordinate.z_axis.write(config, buffer)?;
+        }
+
+        if let Some(external_parent) = &self.external_parent {
+            external_parent.index.write(config, buffer)?;
+        }
+
+        if let Some(ik) = &self.ik {
+            ik.write(config, buffer)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxBone> {
     type Error = PmxBoneParseError;
 
@@ -221,13 +287,45 @@ impl Parse for PmxBoneFlags {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxBoneFlags {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let to_bits = |flags: [bool; 6]| {
+            flags
+                .into_iter()
+                .enumerate()
+                .fold(0u8, |bits, (bit, flag)| bits | ((flag as u8) << bit))
+        };
+
+        let flag_1 = to_bits([
+            self.indexed_tail_position,
+            self.is_rotatable,
+            self.is_translatable,
+            self.is_visible,
+            self.is_enabled,
+            self.supports_ik,
+        ]);
+        let flag_2 = to_bits([
+            self.inherit_rotation,
+            self.inherit_translation,
+            self.fixed_axis,
+            self.local_coordinate,
+            self.physics_after_deform,
+            self.external_parent_deform,
+        ]);
+
+        flag_1.write(config, buffer)?;
+        flag_2.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum PmxBoneTailPosition {
     Vec3 { position: PmxVec3 },
     BoneIndex { index: PmxBoneIndex },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneInheritance {
     pub index: PmxBoneIndex,
     pub coefficient: f32,
@@ -241,7 +339,7 @@ pub enum PmxBoneInheritanceMode {
     TranslationOnly,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneFixedAxis {
     pub direction: PmxVec3,
 }
@@ -260,7 +358,7 @@ impl Parse for PmxBoneFixedAxis {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneLocalCoordinate {
     pub x_axis: PmxVec3,
     pub z_axis: PmxVec3,
@@ -282,7 +380,7 @@ impl Parse for PmxBoneLocalCoordinate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneExternalParent {
     /// 4 bytes signed integer, not bone index
     pub index: i32,
@@ -302,7 +400,7 @@ impl Parse for PmxBoneExternalParent {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneIK {
     pub index: PmxBoneIndex,
     pub loop_count: i32,
@@ -337,7 +435,17 @@ impl Parse for PmxBoneIK {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxBoneIK {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.loop_count.write(config, buffer)?;
+        self.limit_angle.write(config, buffer)?;
+        self.links.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneIKLink {
     pub index: PmxBoneIndex,
     pub angle_limit: Option<PmxBoneIKAngleLimit>,
@@ -360,6 +468,23 @@ impl Parse for PmxBoneIKLink {
     }
 }
 
+impl Write for PmxBoneIKLink {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+
+        match &self.angle_limit {
+            Some(angle_limit) => {
+                true.write(config, buffer)?;
+                angle_limit.min.write(config, buffer)?;
+                angle_limit.max.write(config, buffer)?;
+            }
+            None => false.write(config, buffer)?,
+        }
+
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxBoneIKLink> {
     type Error = PmxBoneParseError;
 
@@ -379,7 +504,7 @@ impl Parse for Vec<PmxBoneIKLink> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxBoneIKAngleLimit {
     /// in radians
     pub min: PmxVec3,
@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
     pmx_primitives::{PmxBoneIndex, PmxMorphIndex},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -24,7 +25,7 @@ impl ParseError for PmxDisplayParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxDisplay {
     pub name_local: String,
     pub name_universal: String,
@@ -58,6 +59,16 @@ impl Parse for PmxDisplay {
     }
 }
 
+impl Write for PmxDisplay {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+        self.is_special.write(config, buffer)?;
+        self.frames.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxDisplay> {
     type Error = PmxDisplayParseError;
 
@@ -77,7 +88,7 @@ impl Parse for Vec<PmxDisplay> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PmxDisplayFrame {
     Bone { index: PmxBoneIndex },
     Morph { index: PmxMorphIndex },
@@ -117,6 +128,23 @@ impl Parse for PmxDisplayFrame {
     }
 }
 
+impl Write for PmxDisplayFrame {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        match self {
+            Self::Bone { index } => {
+                0u8.write(config, buffer)?;
+                index.write(config, buffer)?;
+            }
+            Self::Morph { index } => {
+                1u8.write(config, buffer)?;
+                index.write(config, buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxDisplayFrame> {
     type Error = PmxDisplayParseError;
 
@@ -1,6 +1,7 @@
 use crate::{
     cursor::Cursor,
     parse::{Parse, ParseError},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -32,7 +33,7 @@ impl ParseError for PmxHeaderParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxHeader {
     pub signature: [u8; 4],
     pub version: f32,
@@ -59,10 +60,10 @@ impl PmxHeader {
             return Err(PmxHeaderParseError::InvalidSignature { signature });
         }
 
-        // version should be 2.0, with some tolerance
+        // version should be 2.0 or 2.1, with some tolerance
         let version = cursor.read::<PmxHeaderParseError, 4>()?;
         let version = f32::from_le_bytes(*version);
-        if version < 1.95 || 2.05 < version {
+        if !(1.95..=2.15).contains(&version) {
             return Err(PmxHeaderParseError::UnsupportedVersion { version });
         }
 
@@ -83,9 +84,25 @@ impl PmxHeader {
             model_comment_universal,
         })
     }
+
+    /// `true` if the version is 2.1 or later, which adds soft bodies among others.
+    pub fn is_v2_1(&self) -> bool {
+        2.05 <= self.version
+    }
+
+    pub fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        buffer.extend_from_slice(&self.signature);
+        self.version.write(config, buffer)?;
+        config.write(buffer)?;
+        self.model_name_local.write(config, buffer)?;
+        self.model_name_universal.write(config, buffer)?;
+        self.model_comment_local.write(config, buffer)?;
+        self.model_comment_universal.write(config, buffer)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxConfig {
     pub text_encoding: PmxTextEncoding,
     pub additional_vec4_count: usize,
@@ -138,6 +155,29 @@ impl PmxConfig {
             rigidbody_index_size,
         })
     }
+
+    pub fn write(&self, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        if 4 < self.additional_vec4_count {
+            return Err(PmxWriteError::InvalidAdditionalVec4Count {
+                count: self.additional_vec4_count,
+            });
+        }
+
+        // global count is fixed to 8 in PMX 2.0
+        buffer.push(8);
+        buffer.push(match self.text_encoding {
+            PmxTextEncoding::Utf16le => 0,
+            PmxTextEncoding::Utf8 => 1,
+        });
+        buffer.push(self.additional_vec4_count as u8);
+        buffer.push(self.vertex_index_size.size() as u8);
+        buffer.push(self.texture_index_size.size() as u8);
+        buffer.push(self.material_index_size.size() as u8);
+        buffer.push(self.bone_index_size.size() as u8);
+        buffer.push(self.morph_index_size.size() as u8);
+        buffer.push(self.rigidbody_index_size.size() as u8);
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -162,6 +202,28 @@ impl PmxIndexSize {
         }
     }
 
+    /// The smallest size able to index `count` vertices. Vertex indices are unsigned.
+    pub fn fit_vertices(count: usize) -> Self {
+        if count <= u8::MAX as usize + 1 {
+            Self::U8
+        } else if count <= u16::MAX as usize + 1 {
+            Self::U16
+        } else {
+            Self::U32
+        }
+    }
+
+    /// The smallest size able to index `count` items other than vertices. Those indices are signed.
+    pub fn fit(count: usize) -> Self {
+        if count <= i8::MAX as usize + 1 {
+            Self::U8
+        } else if count <= i16::MAX as usize + 1 {
+            Self::U16
+        } else {
+            Self::U32
+        }
+    }
+
     pub fn parse(globals: &[u8; 8], index: usize) -> Result<Self, PmxHeaderParseError> {
         match globals[index] {
             1 => Ok(Self::U8),
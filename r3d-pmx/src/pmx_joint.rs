@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
     pmx_primitives::{PmxRigidbodyIndex, PmxVec3},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -14,7 +15,7 @@ pub enum PmxJointParseError {
     RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
     #[error("failed to parse a PMX primitive: {0}")]
     PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
-    #[error("joint kind `{kind}` is invalid; must be zero")]
+    #[error("joint kind `{kind}` is invalid; it must be in the range of [0, 5] in PMX 2.1")]
     InvalidJointKind { kind: u8 },
 }
 
@@ -24,7 +25,7 @@ impl ParseError for PmxJointParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxJoint {
     pub name_local: String,
     pub name_universal: String,
@@ -92,6 +93,25 @@ impl Parse for PmxJoint {
     }
 }
 
+impl Write for PmxJoint {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+        self.kind.write(config, buffer)?;
+        self.rigidbody_index_pair.0.write(config, buffer)?;
+        self.rigidbody_index_pair.1.write(config, buffer)?;
+        self.position.write(config, buffer)?;
+        self.rotation.write(config, buffer)?;
+        self.position_limit_min.write(config, buffer)?;
+        self.position_limit_max.write(config, buffer)?;
+        self.rotation_limit_min.write(config, buffer)?;
+        self.rotation_limit_max.write(config, buffer)?;
+        self.spring_position.write(config, buffer)?;
+        self.spring_rotation.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxJoint> {
     type Error = PmxJointParseError;
 
@@ -114,6 +134,12 @@ impl Parse for Vec<PmxJoint> {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PmxJointKind {
     Spring6Dof,
+    /// Added in PMX 2.1, as are the kinds below.
+    SixDof,
+    PointToPoint,
+    ConeTwist,
+    Slider,
+    Hinge,
 }
 
 impl Parse for PmxJointKind {
@@ -125,7 +151,27 @@ impl Parse for PmxJointKind {
 
         match kind {
             0 => Ok(Self::Spring6Dof),
+            1 => Ok(Self::SixDof),
+            2 => Ok(Self::PointToPoint),
+            3 => Ok(Self::ConeTwist),
+            4 => Ok(Self::Slider),
+            5 => Ok(Self::Hinge),
             kind => Err(PmxJointParseError::InvalidJointKind { kind }),
         }
     }
 }
+
+impl Write for PmxJointKind {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let kind: u8 = match self {
+            Self::Spring6Dof => 0,
+            Self::SixDof => 1,
+            Self::PointToPoint => 2,
+            Self::ConeTwist => 3,
+            Self::Slider => 4,
+            Self::Hinge => 5,
+        };
+
+        kind.write(config, buffer)
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
     pmx_primitives::{PmxTextureIndex, PmxVec3, PmxVec4},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -26,7 +27,7 @@ impl ParseError for PmxMaterialParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMaterial {
     pub name_local: String,
     pub name_universal: String,
@@ -119,6 +120,27 @@ impl Parse for PmxMaterial {
     }
 }
 
+impl Write for PmxMaterial {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+        self.diffuse_color.write(config, buffer)?;
+        self.specular_color.write(config, buffer)?;
+        self.specular_strength.write(config, buffer)?;
+        self.ambient_color.write(config, buffer)?;
+        self.flags.write(config, buffer)?;
+        self.edge_color.write(config, buffer)?;
+        self.edge_size.write(config, buffer)?;
+        self.texture_index.write(config, buffer)?;
+        self.environment_texture_index.write(config, buffer)?;
+        self.environment_blend_mode.write(config, buffer)?;
+        self.toon_mode.write(config, buffer)?;
+        self.metadata.write(config, buffer)?;
+        self.surface_count.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxMaterial> {
     type Error = PmxMaterialParseError;
 
@@ -150,6 +172,12 @@ pub struct PmxMaterialFlags {
     pub receive_shadow: bool,
     /// `true` if it should be drawn with pencil-like outline otherwise `false`.
     pub has_edge: bool,
+    /// `true` if `vertex.additional_vec4s[0]` is used as vertex color otherwise `false`. (PMX 2.1)
+    pub uses_vertex_color: bool,
+    /// `true` if it should be drawn as points otherwise `false`. (PMX 2.1)
+    pub draws_points: bool,
+    /// `true` if it should be drawn as lines otherwise `false`. (PMX 2.1)
+    pub draws_lines: bool,
 }
 
 impl Parse for PmxMaterialFlags {
@@ -164,6 +192,9 @@ impl Parse for PmxMaterialFlags {
         let cast_shadow_on_object = flags & 0b0000_0100 != 0;
         let receive_shadow = flags & 0b0000_1000 != 0;
         let has_edge = flags & 0b0001_0000 != 0;
+        let uses_vertex_color = flags & 0b0010_0000 != 0;
+        let draws_points = flags & 0b0100_0000 != 0;
+        let draws_lines = flags & 0b1000_0000 != 0;
 
         Ok(Self {
             cull_back_face,
@@ -171,10 +202,33 @@ impl Parse for PmxMaterialFlags {
             cast_shadow_on_object,
             receive_shadow,
             has_edge,
+            uses_vertex_color,
+            draws_points,
+            draws_lines,
         })
     }
 }
 
+impl Write for PmxMaterialFlags {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let flags = [
+            self.cull_back_face,
+            self.cast_shadow_on_ground,
+            self.cast_shadow_on_object,
+            self.receive_shadow,
+            self.has_edge,
+            self.uses_vertex_color,
+            self.draws_points,
+            self.draws_lines,
+        ]
+        .into_iter()
+        .enumerate()
+        .fold(0u8, |flags, (bit, flag)| flags | ((flag as u8) << bit));
+
+        flags.write(config, buffer)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PmxMaterialEnvironmentBlendMode {
     Disabled,
@@ -201,6 +255,19 @@ impl Parse for PmxMaterialEnvironmentBlendMode {
     }
 }
 
+impl Write for PmxMaterialEnvironmentBlendMode {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let mode: u8 = match self {
+            Self::Disabled => 0,
+            Self::Multiplicative => 1,
+            Self::Additive => 2,
+            Self::AdditionalVec4UV => 3,
+        };
+
+        mode.write(config, buffer)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PmxMaterialToonMode {
     /// Refers to `textures[index]`.
@@ -243,3 +310,20 @@ impl Parse for PmxMaterialToonMode {
         })
     }
 }
+
+impl Write for PmxMaterialToonMode {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        match self {
+            Self::Texture { index } => {
+                0u8.write(config, buffer)?;
+                index.write(config, buffer)?;
+            }
+            Self::InternalTexture { index } => {
+                1u8.write(config, buffer)?;
+                index.write(config, buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
         PmxBoneIndex, PmxMaterialIndex, PmxMorphIndex, PmxRigidbodyIndex, PmxVec3, PmxVec4,
         PmxVertexIndex,
     },
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -21,6 +22,10 @@ pub enum PmxMorphParseError {
     InvalidMorphPanelKind { kind: u8 },
     #[error("morph offset kind `{kind}` is invalid; it must be in the range of [0, 10]")]
     InvalidMorphOffsetKind { kind: u8 },
+    #[error(
+        "material morph operation `{operation}` is invalid; it must be in the range of [0, 1]"
+    )]
+    InvalidMaterialOperation { operation: u8 },
 }
 
 impl ParseError for PmxMorphParseError {
@@ -29,7 +34,7 @@ impl ParseError for PmxMorphParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorph {
     pub name_local: String,
     pub name_universal: String,
@@ -63,6 +68,16 @@ impl Parse for PmxMorph {
     }
 }
 
+impl Write for PmxMorph {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+        self.panel_kind.write(config, buffer)?;
+        self.offset.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxMorph> {
     type Error = PmxMorphParseError;
 
@@ -112,7 +127,21 @@ impl Parse for PmxMorphPanelKind {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphPanelKind {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let kind: u8 = match self {
+            Self::Hidden => 0,
+            Self::Eyebrows => 1,
+            Self::Eyes => 2,
+            Self::Mouth => 3,
+            Self::Other => 4,
+        };
+
+        kind.write(config, buffer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum PmxMorphOffset {
     Group(Vec<PmxMorphOffsetGroup>),
     Vertex(Vec<PmxMorphOffsetVertex>),
@@ -155,6 +184,47 @@ impl Parse for PmxMorphOffset {
     }
 }
 
+impl Write for PmxMorphOffset {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        match self {
+            Self::Group(offsets) => {
+                0u8.write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+            Self::Vertex(offsets) => {
+                1u8.write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+            Self::Bone(offsets) => {
+                2u8.write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+            Self::Uv { offsets, uv_index } => {
+                if 4 < *uv_index {
+                    return Err(PmxWriteError::InvalidUvMorphIndex {
+                        uv_index: *uv_index,
+                    });
+                }
+
+                (uv_index + 3).write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+            Self::Material(offsets) => {
+                8u8.write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+            Self::Flip(offsets) => {
+                9u8.write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+            Self::Impulse(offsets) => {
+                10u8.write(config, buffer)?;
+                offsets.write(config, buffer)
+            }
+        }
+    }
+}
+
 pub trait PmxMorphOffsetSizeHint {
     fn size_hint(config: &PmxConfig) -> usize;
 }
@@ -183,7 +253,7 @@ impl<T: Parse<Error = PmxMorphParseError> + PmxMorphOffsetSizeHint> Parse for Ve
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetGroup {
     pub index: PmxMorphIndex,
     pub coefficient: f32,
@@ -207,7 +277,15 @@ impl Parse for PmxMorphOffsetGroup {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphOffsetGroup {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.coefficient.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetVertex {
     pub index: PmxVertexIndex,
     pub translation: PmxVec3,
@@ -231,7 +309,15 @@ impl Parse for PmxMorphOffsetVertex {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphOffsetVertex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.translation.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetBone {
     pub index: PmxBoneIndex,
     pub translation: PmxVec3,
@@ -261,7 +347,16 @@ impl Parse for PmxMorphOffsetBone {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphOffsetBone {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.translation.write(config, buffer)?;
+        self.rotation.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetUv {
     pub index: PmxVertexIndex,
     pub vec4: PmxVec4,
@@ -285,10 +380,19 @@ impl Parse for PmxMorphOffsetUv {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphOffsetUv {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.vec4.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetMaterial {
     /// -1 for all materials
     pub index: PmxMaterialIndex,
+    pub operation: PmxMorphMaterialOperation,
     pub diffuse_color: PmxVec4,
     pub specular_color: PmxVec3,
     pub specular_strength: f32,
@@ -312,7 +416,7 @@ impl Parse for PmxMorphOffsetMaterial {
     fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         // since material morph offset has a fixed size, we don't need to check the size here
         let index = PmxMaterialIndex::parse(config, cursor)?;
-        let _unused = u8::parse(config, cursor)?;
+        let operation = PmxMorphMaterialOperation::parse(config, cursor)?;
         let diffuse_color = PmxVec4::parse(config, cursor)?;
         let specular_color = PmxVec3::parse(config, cursor)?;
         let specular_strength = f32::parse(config, cursor)?;
@@ -325,6 +429,7 @@ impl Parse for PmxMorphOffsetMaterial {
 
         Ok(Self {
             index,
+            operation,
             diffuse_color,
             specular_color,
             specular_strength,
@@ -338,7 +443,55 @@ impl Parse for PmxMorphOffsetMaterial {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphOffsetMaterial {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.operation.write(config, buffer)?;
+        self.diffuse_color.write(config, buffer)?;
+        self.specular_color.write(config, buffer)?;
+        self.specular_strength.write(config, buffer)?;
+        self.ambient_color.write(config, buffer)?;
+        self.edge_color.write(config, buffer)?;
+        self.edge_size.write(config, buffer)?;
+        self.texture_tint_color.write(config, buffer)?;
+        self.environment_tint_color.write(config, buffer)?;
+        self.toon_tint_color.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PmxMorphMaterialOperation {
+    Multiply,
+    Add,
+}
+
+impl Parse for PmxMorphMaterialOperation {
+    type Error = PmxMorphParseError;
+
+    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        let operation = u8::parse(config, cursor)?;
+
+        match operation {
+            0 => Ok(Self::Multiply),
+            1 => Ok(Self::Add),
+            operation => Err(PmxMorphParseError::InvalidMaterialOperation { operation }),
+        }
+    }
+}
+
+impl Write for PmxMorphMaterialOperation {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let operation: u8 = match self {
+            Self::Multiply => 0,
+            Self::Add => 1,
+        };
+
+        operation.write(config, buffer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetFlip {
     pub index: PmxMorphIndex,
     pub coefficient: f32,
@@ -362,7 +515,15 @@ impl Parse for PmxMorphOffsetFlip {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Write for PmxMorphOffsetFlip {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.coefficient.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxMorphOffsetImpulse {
     pub index: PmxRigidbodyIndex,
     /// `true` if `velocity` and `torque` is in local coordinate otherwise `false`.
@@ -395,3 +556,13 @@ impl Parse for PmxMorphOffsetImpulse {
         })
     }
 }
+
+impl Write for PmxMorphOffsetImpulse {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.index.write(config, buffer)?;
+        self.is_local.write(config, buffer)?;
+        self.velocity.write(config, buffer)?;
+        self.torque.write(config, buffer)?;
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     cursor::Cursor,
     parse::{Parse, ParseError},
     pmx_header::{PmxConfig, PmxIndexSize},
+    write::{PmxWriteError, Write},
 };
 use std::ops::Deref;
 use thiserror::Error;
@@ -148,16 +149,98 @@ impl Parse for PmxRigidbodyIndex {
     }
 }
 
+impl Write for PmxVertexIndex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let size = config.vertex_index_size;
+        let out_of_range = || PmxWriteError::IndexOutOfRange {
+            kind: "vertex",
+            index: self.0 as i64,
+            size,
+        };
+
+        // vertex indices are unsigned, unlike the other indices
+        match size {
+            PmxIndexSize::U8 => u8::try_from(self.0)
+                .map_err(|_| out_of_range())?
+                .write(config, buffer),
+            PmxIndexSize::U16 => u16::try_from(self.0)
+                .map_err(|_| out_of_range())?
+                .write(config, buffer),
+            PmxIndexSize::U32 => self.0.write(config, buffer),
+        }
+    }
+}
+
+/// Writes a signed index, where `-1` means none.
+fn write_signed_index(
+    kind: &'static str,
+    index: i32,
+    size: PmxIndexSize,
+    buffer: &mut Vec<u8>,
+) -> Result<(), PmxWriteError> {
+    let out_of_range = || PmxWriteError::IndexOutOfRange {
+        kind,
+        index: index as i64,
+        size,
+    };
+
+    match size {
+        PmxIndexSize::U8 => {
+            let index = i8::try_from(index).map_err(|_| out_of_range())?;
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+        PmxIndexSize::U16 => {
+            let index = i16::try_from(index).map_err(|_| out_of_range())?;
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+        PmxIndexSize::U32 => {
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+impl Write for PmxTextureIndex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        write_signed_index("texture", self.0, config.texture_index_size, buffer)
+    }
+}
+
+impl Write for PmxMaterialIndex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        write_signed_index("material", self.0, config.material_index_size, buffer)
+    }
+}
+
+impl Write for PmxBoneIndex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        write_signed_index("bone", self.0, config.bone_index_size, buffer)
+    }
+}
+
+impl Write for PmxMorphIndex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        write_signed_index("morph", self.0, config.morph_index_size, buffer)
+    }
+}
+
+impl Write for PmxRigidbodyIndex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        write_signed_index("rigidbody", self.0, config.rigidbody_index_size, buffer)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PmxVec2 {
     pub x: f32,
     pub y: f32,
 }
 
-impl Parse for PmxVec2 {
+impl<C> Parse<C> for PmxVec2 {
     type Error = PmxPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         let bytes = cursor.read::<Self::Error, 8>()?;
         let x = f32::from_le_bytes(bytes[0..4].try_into().unwrap());
         let y = f32::from_le_bytes(bytes[4..8].try_into().unwrap());
@@ -166,6 +249,14 @@ impl Parse for PmxVec2 {
     }
 }
 
+impl<C> Write<C> for PmxVec2 {
+    fn write(&self, config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.x.write(config, buffer)?;
+        self.y.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PmxVec3 {
     pub x: f32,
@@ -173,10 +264,10 @@ pub struct PmxVec3 {
     pub z: f32,
 }
 
-impl Parse for PmxVec3 {
+impl<C> Parse<C> for PmxVec3 {
     type Error = PmxPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         let bytes = cursor.read::<Self::Error, 12>()?;
         let x = f32::from_le_bytes(bytes[0..4].try_into().unwrap());
         let y = f32::from_le_bytes(bytes[4..8].try_into().unwrap());
@@ -186,6 +277,15 @@ impl Parse for PmxVec3 {
     }
 }
 
+impl<C> Write<C> for PmxVec3 {
+    fn write(&self, config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.x.write(config, buffer)?;
+        self.y.write(config, buffer)?;
+        self.z.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PmxVec4 {
     pub x: f32,
@@ -194,10 +294,10 @@ pub struct PmxVec4 {
     pub w: f32,
 }
 
-impl Parse for PmxVec4 {
+impl<C> Parse<C> for PmxVec4 {
     type Error = PmxPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         let bytes = cursor.read::<Self::Error, 16>()?;
         let x = f32::from_le_bytes(bytes[0..4].try_into().unwrap());
         let y = f32::from_le_bytes(bytes[4..8].try_into().unwrap());
@@ -207,3 +307,13 @@ impl Parse for PmxVec4 {
         Ok(Self { x, y, z, w })
     }
 }
+
+impl<C> Write<C> for PmxVec4 {
+    fn write(&self, config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.x.write(config, buffer)?;
+        self.y.write(config, buffer)?;
+        self.z.write(config, buffer)?;
+        self.w.write(config, buffer)?;
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
     pmx_primitives::{PmxBoneIndex, PmxVec3},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -26,7 +27,7 @@ impl ParseError for PmxRigidbodyParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxRigidbody {
     pub name_local: String,
     pub name_universal: String,
@@ -91,6 +92,24 @@ impl Parse for PmxRigidbody {
     }
 }
 
+impl Write for PmxRigidbody {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+        self.bone_index.write(config, buffer)?;
+        self.group_id.write(config, buffer)?;
+        self.non_collision_group.write(config, buffer)?;
+        self.shape.write(config, buffer)?;
+        self.mass.write(config, buffer)?;
+        self.linear_damping.write(config, buffer)?;
+        self.angular_damping.write(config, buffer)?;
+        self.restitution_coefficient.write(config, buffer)?;
+        self.friction_coefficient.write(config, buffer)?;
+        self.physics_mode.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxRigidbody> {
     type Error = PmxRigidbodyParseError;
 
@@ -110,7 +129,7 @@ impl Parse for Vec<PmxRigidbody> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxRigidbodyShape {
     pub kind: PmxRigidbodyShapeKind,
     pub size: PmxVec3,
@@ -138,6 +157,16 @@ impl Parse for PmxRigidbodyShape {
     }
 }
 
+impl Write for PmxRigidbodyShape {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.kind.write(config, buffer)?;
+        self.size.write(config, buffer)?;
+        self.position.write(config, buffer)?;
+        self.rotation.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PmxRigidbodyShapeKind {
     Sphere,
@@ -161,6 +190,18 @@ impl Parse for PmxRigidbodyShapeKind {
     }
 }
 
+impl Write for PmxRigidbodyShapeKind {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let kind: u8 = match self {
+            Self::Sphere => 0,
+            Self::Box => 1,
+            Self::Capsule => 2,
+        };
+
+        kind.write(config, buffer)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PmxRigidbodyPhysicsMode {
     Static,
@@ -183,3 +224,15 @@ impl Parse for PmxRigidbodyPhysicsMode {
         }
     }
 }
+
+impl Write for PmxRigidbodyPhysicsMode {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        let mode: u8 = match self {
+            Self::Static => 0,
+            Self::Dynamic => 1,
+            Self::DynamicWithBone => 2,
+        };
+
+        mode.write(config, buffer)
+    }
+}
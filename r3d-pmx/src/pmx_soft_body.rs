@@ -0,0 +1,414 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    pmx_header::PmxConfig,
+    pmx_primitives::{PmxMaterialIndex, PmxRigidbodyIndex, PmxVertexIndex},
+    write::{PmxWriteError, Write},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PmxSoftBodyParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+    #[error("failed to parse a PMX primitive: {0}")]
+    PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
+    #[error("soft body shape `{shape}` is invalid; it must be in the range of [0, 1]")]
+    InvalidShape { shape: u8 },
+    #[error("soft body aero model `{model}` is invalid; it must be in the range of [0, 4]")]
+    InvalidAeroModel { model: i32 },
+}
+
+impl ParseError for PmxSoftBodyParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+/// A soft body, added in PMX 2.1. Most of the parameters map to the ones of Bullet's `btSoftBody`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PmxSoftBody {
+    pub name_local: String,
+    pub name_universal: String,
+    pub shape: PmxSoftBodyShape,
+    pub material_index: PmxMaterialIndex,
+    pub group_id: u8,
+    pub non_collision_group: u16,
+    pub flags: PmxSoftBodyFlags,
+    pub bending_link_distance: i32,
+    pub cluster_count: i32,
+    pub total_mass: f32,
+    pub collision_margin: f32,
+    pub aero_model: PmxSoftBodyAeroModel,
+    pub config: PmxSoftBodyConfig,
+    pub cluster: PmxSoftBodyCluster,
+    pub iteration: PmxSoftBodyIteration,
+    pub material: PmxSoftBodyMaterial,
+    pub anchors: Vec<PmxSoftBodyAnchor>,
+    pub pin_vertex_indices: Vec<PmxVertexIndex>,
+}
+
+impl Parse for PmxSoftBody {
+    type Error = PmxSoftBodyParseError;
+
+    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // dynamic size
+        let name_local = String::parse(config, cursor)?;
+        let name_universal = String::parse(config, cursor)?;
+
+        // shape (1 byte)
+        // material index (N bytes)
+        // group id (1 byte)
+        // non collision group (2 bytes)
+        // flags (1 byte)
+        // bending link distance (4 bytes)
+        // cluster count (4 bytes)
+        // total mass (4 bytes)
+        // collision margin (4 bytes)
+        // aero model (4 bytes)
+        // config (12 * 4 bytes)
+        // cluster (6 * 4 bytes)
+        // iteration (4 * 4 bytes)
+        // material (3 * 4 bytes)
+        let size = 1
+            + config.material_index_size.size()
+            + 1
+            + 2
+            + 1
+            + 4 * 5
+            + 12 * 4
+            + 6 * 4
+            + 4 * 4
+            + 3 * 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let shape = match u8::parse(config, cursor)? {
+            0 => PmxSoftBodyShape::TriMesh,
+            1 => PmxSoftBodyShape::Rope,
+            shape => return Err(PmxSoftBodyParseError::InvalidShape { shape }),
+        };
+        let material_index = PmxMaterialIndex::parse(config, cursor)?;
+        let group_id = u8::parse(config, cursor)?;
+        let non_collision_group = u16::parse(config, cursor)?;
+
+        let flags = u8::parse(config, cursor)?;
+        let flags = PmxSoftBodyFlags {
+            generates_bending_links: flags & 0b0000_0001 != 0,
+            generates_clusters: flags & 0b0000_0010 != 0,
+            randomizes_links: flags & 0b0000_0100 != 0,
+        };
+
+        let bending_link_distance = i32::parse(config, cursor)?;
+        let cluster_count = i32::parse(config, cursor)?;
+        let total_mass = f32::parse(config, cursor)?;
+        let collision_margin = f32::parse(config, cursor)?;
+        let aero_model = match i32::parse(config, cursor)? {
+            0 => PmxSoftBodyAeroModel::VertexPoint,
+            1 => PmxSoftBodyAeroModel::VertexTwoSided,
+            2 => PmxSoftBodyAeroModel::VertexOneSided,
+            3 => PmxSoftBodyAeroModel::FaceTwoSided,
+            4 => PmxSoftBodyAeroModel::FaceOneSided,
+            model => return Err(PmxSoftBodyParseError::InvalidAeroModel { model }),
+        };
+
+        let soft_body_config = PmxSoftBodyConfig {
+            velocity_correction: f32::parse(config, cursor)?,
+            damping: f32::parse(config, cursor)?,
+            drag: f32::parse(config, cursor)?,
+            lift: f32::parse(config, cursor)?,
+            pressure: f32::parse(config, cursor)?,
+            volume_conservation: f32::parse(config, cursor)?,
+            dynamic_friction: f32::parse(config, cursor)?,
+            pose_matching: f32::parse(config, cursor)?,
+            rigid_contact_hardness: f32::parse(config, cursor)?,
+            kinetic_contact_hardness: f32::parse(config, cursor)?,
+            soft_contact_hardness: f32::parse(config, cursor)?,
+            anchor_hardness: f32::parse(config, cursor)?,
+        };
+        let cluster = PmxSoftBodyCluster {
+            soft_rigid_hardness: f32::parse(config, cursor)?,
+            soft_kinetic_hardness: f32::parse(config, cursor)?,
+            soft_soft_hardness: f32::parse(config, cursor)?,
+            soft_rigid_impulse_split: f32::parse(config, cursor)?,
+            soft_kinetic_impulse_split: f32::parse(config, cursor)?,
+            soft_soft_impulse_split: f32::parse(config, cursor)?,
+        };
+        let iteration = PmxSoftBodyIteration {
+            velocity: i32::parse(config, cursor)?,
+            position: i32::parse(config, cursor)?,
+            drift: i32::parse(config, cursor)?,
+            cluster: i32::parse(config, cursor)?,
+        };
+        let material = PmxSoftBodyMaterial {
+            linear_stiffness: f32::parse(config, cursor)?,
+            angular_stiffness: f32::parse(config, cursor)?,
+            volume_stiffness: f32::parse(config, cursor)?,
+        };
+
+        // dynamic size
+        let anchors = Vec::parse(config, cursor)?;
+
+        // pin vertex count (4 bytes)
+        let size = 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let count = u32::parse(config, cursor)? as usize;
+
+        // pin vertex indices (count * N bytes)
+        let size = count * config.vertex_index_size.size();
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let mut pin_vertex_indices = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            pin_vertex_indices.push(PmxVertexIndex::parse(config, cursor)?);
+        }
+
+        Ok(Self {
+            name_local,
+            name_universal,
+            shape,
+            material_index,
+            group_id,
+            non_collision_group,
+            flags,
+            bending_link_distance,
+            cluster_count,
+            total_mass,
+            collision_margin,
+            aero_model,
+            config: soft_body_config,
+            cluster,
+            iteration,
+            material,
+            anchors,
+            pin_vertex_indices,
+        })
+    }
+}
+
+impl Write for PmxSoftBody {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.name_local.write(config, buffer)?;
+        self.name_universal.write(config, buffer)?;
+
+        let shape: u8 = match self.shape {
+            PmxSoftBodyShape::TriMesh => 0,
+            PmxSoftBodyShape::Rope => 1,
+        };
+        shape.write(config, buffer)?;
+
+        self.material_index.write(config, buffer)?;
+        self.group_id.write(config, buffer)?;
+        self.non_collision_group.write(config, buffer)?;
+
+        let flags = (self.flags.generates_bending_links as u8)
+            | (self.flags.generates_clusters as u8) << 1
+            | (self.flags.randomizes_links as u8) << 2;
+        flags.write(config, buffer)?;
+
+        self.bending_link_distance.write(config, buffer)?;
+        self.cluster_count.write(config, buffer)?;
+        self.total_mass.write(config, buffer)?;
+        self.collision_margin.write(config, buffer)?;
+
+        let aero_model: i32 = match self.aero_model {
+            PmxSoftBodyAeroModel::VertexPoint => 0,
+            PmxSoftBodyAeroModel::VertexTwoSided => 1,
+            PmxSoftBodyAeroModel::VertexOneSided => 2,
+            PmxSoftBodyAeroModel::FaceTwoSided => 3,
+            PmxSoftBodyAeroModel::FaceOneSided => 4,
+        };
+        aero_model.write(config, buffer)?;
+
+        for value in [
+            self.config.velocity_correction,
+            self.config.damping,
+            self.config.drag,
+            self.config.lift,
+            self.config.pressure,
+            self.config.volume_conservation,
+            self.config.dynamic_friction,
+            self.config.pose_matching,
+            self.config.rigid_contact_hardness,
+            self.config.kinetic_contact_hardness,
+            self.config.soft_contact_hardness,
+            self.config.anchor_hardness,
+            self.cluster.soft_rigid_hardness,
+            self.cluster.soft_kinetic_hardness,
+            self.cluster.soft_soft_hardness,
+            self.cluster.soft_rigid_impulse_split,
+            self.cluster.soft_kinetic_impulse_split,
+            self.cluster.soft_soft_impulse_split,
+        ] {
+            value.write(config, buffer)?;
+        }
+
+        for value in [
+            self.iteration.velocity,
+            self.iteration.position,
+            self.iteration.drift,
+            self.iteration.cluster,
+        ] {
+            value.write(config, buffer)?;
+        }
+
+        for value in [
+            self.material.linear_stiffness,
+            self.material.angular_stiffness,
+            self.material.volume_stiffness,
+        ] {
+            value.write(config, buffer)?;
+        }
+
+        self.anchors.write(config, buffer)?;
+        self.pin_vertex_indices.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+impl Parse for Vec<PmxSoftBody> {
+    type Error = PmxSoftBodyParseError;
+
+    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // count (4 bytes)
+        let size = 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut soft_bodies = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            soft_bodies.push(PmxSoftBody::parse(config, cursor)?);
+        }
+
+        Ok(soft_bodies)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PmxSoftBodyShape {
+    TriMesh,
+    Rope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PmxSoftBodyFlags {
+    /// `true` if bending links should be generated otherwise `false`.
+    pub generates_bending_links: bool,
+    /// `true` if clusters should be generated otherwise `false`.
+    pub generates_clusters: bool,
+    /// `true` if links should be randomized otherwise `false`.
+    pub randomizes_links: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PmxSoftBodyAeroModel {
+    VertexPoint,
+    VertexTwoSided,
+    VertexOneSided,
+    FaceTwoSided,
+    FaceOneSided,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PmxSoftBodyConfig {
+    pub velocity_correction: f32,
+    pub damping: f32,
+    pub drag: f32,
+    pub lift: f32,
+    pub pressure: f32,
+    pub volume_conservation: f32,
+    pub dynamic_friction: f32,
+    pub pose_matching: f32,
+    pub rigid_contact_hardness: f32,
+    pub kinetic_contact_hardness: f32,
+    pub soft_contact_hardness: f32,
+    pub anchor_hardness: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PmxSoftBodyCluster {
+    pub soft_rigid_hardness: f32,
+    pub soft_kinetic_hardness: f32,
+    pub soft_soft_hardness: f32,
+    pub soft_rigid_impulse_split: f32,
+    pub soft_kinetic_impulse_split: f32,
+    pub soft_soft_impulse_split: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PmxSoftBodyIteration {
+    pub velocity: i32,
+    pub position: i32,
+    pub drift: i32,
+    pub cluster: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PmxSoftBodyMaterial {
+    pub linear_stiffness: f32,
+    pub angular_stiffness: f32,
+    pub volume_stiffness: f32,
+}
+
+/// Attaches a vertex of the soft body to a rigidbody.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PmxSoftBodyAnchor {
+    pub rigidbody_index: PmxRigidbodyIndex,
+    pub vertex_index: PmxVertexIndex,
+    pub is_near_mode: bool,
+}
+
+impl Parse for PmxSoftBodyAnchor {
+    type Error = PmxSoftBodyParseError;
+
+    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // since anchor has a fixed size, we don't need to check the size here
+        let rigidbody_index = PmxRigidbodyIndex::parse(config, cursor)?;
+        let vertex_index = PmxVertexIndex::parse(config, cursor)?;
+        let is_near_mode = bool::parse(config, cursor)?;
+
+        Ok(Self {
+            rigidbody_index,
+            vertex_index,
+            is_near_mode,
+        })
+    }
+}
+
+impl Write for PmxSoftBodyAnchor {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.rigidbody_index.write(config, buffer)?;
+        self.vertex_index.write(config, buffer)?;
+        self.is_near_mode.write(config, buffer)?;
+        Ok(())
+    }
+}
+
+impl Parse for Vec<PmxSoftBodyAnchor> {
+    type Error = PmxSoftBodyParseError;
+
+    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // count (4 bytes)
+        let size = 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let count = u32::parse(config, cursor)? as usize;
+
+        // anchor data (count * (N + N + 1) bytes)
+        let size =
+            count * (config.rigidbody_index_size.size() + config.vertex_index_size.size() + 1);
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let mut anchors = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            anchors.push(PmxSoftBodyAnchor::parse(config, cursor)?);
+        }
+
+        Ok(anchors)
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::{PmxConfig, PmxIndexSize},
     pmx_primitives::PmxVertexIndex,
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -24,7 +25,7 @@ impl ParseError for PmxSurfaceParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxSurface {
     /// vertex indices in CW order (DirectX style)
     pub vertex_indices: [PmxVertexIndex; 3],
@@ -125,3 +126,18 @@ impl Parse for Vec<PmxSurface> {
         Ok(surfaces)
     }
 }
+
+impl Write for Vec<PmxSurface> {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        // surface count is vertex count, not actual surface count in PMX
+        ((self.len() * 3) as u32).write(config, buffer)?;
+
+        for surface in self {
+            for vertex_index in &surface.vertex_indices {
+                vertex_index.write(config, buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
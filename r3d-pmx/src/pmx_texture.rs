@@ -2,6 +2,7 @@ use crate::{
     cursor::Cursor,
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -21,7 +22,7 @@ impl ParseError for PmxTextureParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxTexture {
     pub path: String,
 }
@@ -37,6 +38,12 @@ impl Parse for PmxTexture {
     }
 }
 
+impl Write for PmxTexture {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.path.write(config, buffer)
+    }
+}
+
 impl Parse for Vec<PmxTexture> {
     type Error = PmxTextureParseError;
 
@@ -3,6 +3,7 @@ use crate::{
     parse::{Parse, ParseError},
     pmx_header::PmxConfig,
     pmx_primitives::{PmxBoneIndex, PmxVec2, PmxVec3, PmxVec4},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -14,7 +15,7 @@ pub enum PmxVertexParseError {
     RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
     #[error("failed to parse a PMX primitive: {0}")]
     PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
-    #[error("deform kind `{kind}` is invalid; it must be in the range of [0, 3] in PMX 2.0")]
+    #[error("deform kind `{kind}` is invalid; it must be in the range of [0, 4] in PMX 2.1")]
     InvalidDeformKind { kind: u8 },
 }
 
@@ -24,7 +25,7 @@ impl ParseError for PmxVertexParseError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PmxVertex {
     pub position: PmxVec3,
     pub normal: PmxVec3,
@@ -80,6 +81,22 @@ impl Parse for PmxVertex {
     }
 }
 
+impl Write for PmxVertex {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        self.position.write(config, buffer)?;
+        self.normal.write(config, buffer)?;
+        self.uv.write(config, buffer)?;
+
+        for vec4 in &self.additional_vec4s[..config.additional_vec4_count] {
+            vec4.write(config, buffer)?;
+        }
+
+        self.deform_kind.write(config, buffer)?;
+        self.edge_size.write(config, buffer)?;
+        Ok(())
+    }
+}
+
 impl Parse for Vec<PmxVertex> {
     type Error = PmxVertexParseError;
 
@@ -99,7 +116,7 @@ impl Parse for Vec<PmxVertex> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PmxVertexDeformKind {
     Bdef1 {
         bone_index: PmxBoneIndex,
@@ -127,6 +144,17 @@ pub enum PmxVertexDeformKind {
         r0: PmxVec3,
         r1: PmxVec3,
     },
+    /// Dual quaternion skinning, added in PMX 2.1. Has the same layout as `Bdef4`.
+    Qdef {
+        bone_index_1: PmxBoneIndex,
+        bone_index_2: PmxBoneIndex,
+        bone_index_3: PmxBoneIndex,
+        bone_index_4: PmxBoneIndex,
+        bone_weight_1: f32,
+        bone_weight_2: f32,
+        bone_weight_3: f32,
+        bone_weight_4: f32,
+    },
 }
 
 impl Parse for PmxVertexDeformKind {
@@ -165,7 +193,7 @@ impl Parse for PmxVertexDeformKind {
                     bone_weight,
                 }
             }
-            2 => {
+            2 | 4 => {
                 // bone index (N bytes) * 4
                 // bone weight (4 bytes) * 4
                 let size = config.bone_index_size.size() * 4 + 4 * 4;
@@ -180,15 +208,27 @@ impl Parse for PmxVertexDeformKind {
                 let bone_weight_3 = f32::parse(config, cursor)?;
                 let bone_weight_4 = f32::parse(config, cursor)?;
 
-                PmxVertexDeformKind::Bdef4 {
-                    bone_index_1,
-                    bone_index_2,
-                    bone_index_3,
-                    bone_index_4,
-                    bone_weight_1,
-                    bone_weight_2,
-                    bone_weight_3,
-                    bone_weight_4,
+                match kind {
+                    2 => PmxVertexDeformKind::Bdef4 {
+                        bone_index_1,
+                        bone_index_2,
+                        bone_index_3,
+                        bone_index_4,
+                        bone_weight_1,
+                        bone_weight_2,
+                        bone_weight_3,
+                        bone_weight_4,
+                    },
+                    _ => PmxVertexDeformKind::Qdef {
+                        bone_index_1,
+                        bone_index_2,
+                        bone_index_3,
+                        bone_index_4,
+                        bone_weight_1,
+                        bone_weight_2,
+                        bone_weight_3,
+                        bone_weight_4,
+                    },
                 }
             }
             3 => {
@@ -220,3 +260,76 @@ impl Parse for PmxVertexDeformKind {
         })
     }
 }
+
+impl Write for PmxVertexDeformKind {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        match self {
+            PmxVertexDeformKind::Bdef1 { bone_index } => {
+                0u8.write(config, buffer)?;
+                bone_index.write(config, buffer)?;
+            }
+            PmxVertexDeformKind::Bdef2 {
+                bone_index_1,
+                bone_index_2,
+                bone_weight,
+            } => {
+                1u8.write(config, buffer)?;
+                bone_index_1.write(config, buffer)?;
+                bone_index_2.write(config, buffer)?;
+                bone_weight.write(config, buffer)?;
+            }
+            PmxVertexDeformKind::Bdef4 {
+                bone_index_1,
+                bone_index_2,
+                bone_index_3,
+                bone_index_4,
+                bone_weight_1,
+                bone_weight_2,
+                bone_weight_3,
+                bone_weight_4,
+            }
+            | PmxVertexDeformKind::Qdef {
+                bone_index_1,
+                bone_index_2,
+                bone_index_3,
+                bone_index_4,
+                bone_weight_1,
+                bone_weight_2,
+                bone_weight_3,
+                bone_weight_4,
+            } => {
+                let kind: u8 = match self {
+                    PmxVertexDeformKind::Bdef4 { .. } => 2,
+                    _ => 4,
+                };
+                kind.write(config, buffer)?;
+                bone_index_1.write(config, buffer)?;
+                bone_index_2.write(config, buffer)?;
+                bone_index_3.write(config, buffer)?;
+                bone_index_4.write(config, buffer)?;
+                bone_weight_1.write(config, buffer)?;
+                bone_weight_2.write(config, buffer)?;
+                bone_weight_3.write(config, buffer)?;
+                bone_weight_4.write(config, buffer)?;
+            }
+            PmxVertexDeformKind::Sdef {
+                bone_index_1,
+                bone_index_2,
+                bone_weight,
+                c,
+                r0,
+                r1,
+            } => {
+                3u8.write(config, buffer)?;
+                bone_index_1.write(config, buffer)?;
+                bone_index_2.write(config, buffer)?;
+                bone_weight.write(config, buffer)?;
+                c.write(config, buffer)?;
+                r0.write(config, buffer)?;
+                r1.write(config, buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
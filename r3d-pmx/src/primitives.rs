@@ -2,6 +2,7 @@ use crate::{
     cursor::Cursor,
     parse::{Parse, ParseError},
     pmx_header::{PmxConfig, PmxTextEncoding},
+    write::{PmxWriteError, Write},
 };
 use thiserror::Error;
 
@@ -23,78 +24,78 @@ impl ParseError for RustPrimitiveParseError {
     }
 }
 
-impl Parse for bool {
+impl<C> Parse<C> for bool {
     type Error = RustPrimitiveParseError;
 
-    fn parse(config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(u8::parse(config, cursor)? != 0)
     }
 }
 
-impl Parse for i8 {
+impl<C> Parse<C> for i8 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(i8::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 1>()?,
         ))
     }
 }
 
-impl Parse for i16 {
+impl<C> Parse<C> for i16 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(i16::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 2>()?,
         ))
     }
 }
 
-impl Parse for i32 {
+impl<C> Parse<C> for i32 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(i32::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 4>()?,
         ))
     }
 }
 
-impl Parse for u8 {
+impl<C> Parse<C> for u8 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(u8::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 1>()?,
         ))
     }
 }
 
-impl Parse for u16 {
+impl<C> Parse<C> for u16 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(u16::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 2>()?,
         ))
     }
 }
 
-impl Parse for u32 {
+impl<C> Parse<C> for u32 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(u32::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 4>()?,
         ))
     }
 }
 
-impl Parse for f32 {
+impl<C> Parse<C> for f32 {
     type Error = RustPrimitiveParseError;
 
-    fn parse(_config: &PmxConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+    fn parse(_config: &C, cursor: &mut Cursor) -> Result<Self, Self::Error> {
         Ok(f32::from_le_bytes(
             *cursor.read::<RustPrimitiveParseError, 4>()?,
         ))
@@ -138,3 +139,47 @@ impl Parse for String {
         }
     }
 }
+
+macro_rules! impl_write_for_number {
+    ($($ty:ty),*) => {
+        $(
+            impl<C> Write<C> for $ty {
+                fn write(&self, _config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+                    buffer.extend_from_slice(&self.to_le_bytes());
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_write_for_number!(i8, i16, i32, u8, u16, u32, f32);
+
+impl<C> Write<C> for bool {
+    fn write(&self, config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        (*self as u8).write(config, buffer)
+    }
+}
+
+impl Write for String {
+    fn write(&self, config: &PmxConfig, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        // string length (4 bytes)
+        // string data (len bytes)
+        match config.text_encoding {
+            PmxTextEncoding::Utf16le => {
+                let chars = Vec::from_iter(self.encode_utf16());
+                ((chars.len() * 2) as u32).write(config, buffer)?;
+
+                for char in chars {
+                    buffer.extend_from_slice(&char.to_le_bytes());
+                }
+            }
+            PmxTextEncoding::Utf8 => {
+                (self.len() as u32).write(config, buffer)?;
+                buffer.extend_from_slice(self.as_bytes());
+            }
+        }
+
+        Ok(())
+    }
+}
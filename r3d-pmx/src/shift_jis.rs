@@ -0,0 +1,75 @@
+use crate::{cursor::Cursor, parse::ParseError, shift_jis_table::DOUBLE_BYTE_TABLE};
+
+/// Decodes Shift-JIS (CP932) text. Invalid or truncated sequences are replaced with `U+FFFD`.
+pub fn decode_shift_jis(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+        index += 1;
+
+        let char = match byte {
+            0x00..=0x7F => byte as char,
+            // half-width katakana
+            0xA1..=0xDF => char::from_u32(0xFF61 + (byte - 0xA1) as u32).unwrap(),
+            0x81..=0x9F | 0xE0..=0xFC => {
+                let trail = match bytes.get(index) {
+                    Some(&trail @ 0x40..=0xFC) if trail != 0x7F => trail,
+                    _ => {
+                        string.push(char::REPLACEMENT_CHARACTER);
+                        continue;
+                    }
+                };
+                index += 1;
+
+                let lead = match byte {
+                    0x81..=0x9F => byte - 0x81,
+                    _ => byte - 0xE0 + 31,
+                };
+                let code = DOUBLE_BYTE_TABLE[lead as usize * 189 + (trail - 0x40) as usize];
+
+                match code {
+                    0 => char::REPLACEMENT_CHARACTER,
+                    code => char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+                }
+            }
+            _ => char::REPLACEMENT_CHARACTER,
+        };
+
+        string.push(char);
+    }
+
+    string
+}
+
+/// Parses a NUL-terminated Shift-JIS name of fixed length, as used by VMD and PMD.
+/// Bytes after the NUL are ignored.
+pub(crate) fn parse_shift_jis_name<E: ParseError, const L: usize>(
+    cursor: &mut Cursor,
+) -> Result<String, E> {
+    let bytes = cursor.read::<E, L>()?;
+    let len = bytes.iter().position(|&byte| byte == 0).unwrap_or(L);
+    Ok(decode_shift_jis(&bytes[..len]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_decode_shift_jis() {
+        // "センター" (center) and "左足ＩＫ" (left foot IK), common bone names
+        assert_eq!(
+            decode_shift_jis(&[0x83, 0x5A, 0x83, 0x93, 0x83, 0x5E, 0x81, 0x5B]),
+            "センター"
+        );
+        assert_eq!(
+            decode_shift_jis(&[0x8D, 0xB6, 0x91, 0xAB, 0x82, 0x68, 0x82, 0x6A]),
+            "左足ＩＫ"
+        );
+        assert_eq!(decode_shift_jis(b"abc\xB1"), "abcｱ");
+        // a name cut in the middle of a double-byte character
+        assert_eq!(decode_shift_jis(&[0x61, 0x8D]), "a\u{FFFD}");
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    cursor::Cursor,
+    parse::Parse,
+    vmd_bone_keyframe::{self, VmdBoneKeyframe},
+    vmd_camera_keyframe::{self, VmdCameraKeyframe},
+    vmd_header::{self, VmdHeader},
+    vmd_ik_keyframe::{self, VmdIKKeyframe},
+    vmd_light_keyframe::{self, VmdLightKeyframe, VmdShadowKeyframe},
+    vmd_morph_keyframe::{self, VmdMorphKeyframe},
+};
+use std::fmt::Display;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdParseError {
+    #[error("failed to parse VMD header: {0}")]
+    VmdHeaderParseError(#[from] vmd_header::VmdHeaderParseError),
+    #[error("failed to parse VMD bone keyframe: {0}")]
+    VmdBoneKeyframeParseError(#[from] vmd_bone_keyframe::VmdBoneKeyframeParseError),
+    #[error("failed to parse VMD morph keyframe: {0}")]
+    VmdMorphKeyframeParseError(#[from] vmd_morph_keyframe::VmdMorphKeyframeParseError),
+    #[error("failed to parse VMD camera keyframe: {0}")]
+    VmdCameraKeyframeParseError(#[from] vmd_camera_keyframe::VmdCameraKeyframeParseError),
+    #[error("failed to parse VMD light keyframe: {0}")]
+    VmdLightKeyframeParseError(#[from] vmd_light_keyframe::VmdLightKeyframeParseError),
+    #[error("failed to parse VMD IK keyframe: {0}")]
+    VmdIKKeyframeParseError(#[from] vmd_ik_keyframe::VmdIKKeyframeParseError),
+}
+
+/// A MikuMikuDance motion. Keyframes are in the order of the file, which is not necessarily sorted.
+#[derive(Debug, Clone)]
+pub struct Vmd {
+    pub header: VmdHeader,
+    pub bone_keyframes: Vec<VmdBoneKeyframe>,
+    pub morph_keyframes: Vec<VmdMorphKeyframe>,
+    pub camera_keyframes: Vec<VmdCameraKeyframe>,
+    pub light_keyframes: Vec<VmdLightKeyframe>,
+    pub shadow_keyframes: Vec<VmdShadowKeyframe>,
+    pub ik_keyframes: Vec<VmdIKKeyframe>,
+}
+
+impl Vmd {
+    pub fn parse(buf: impl AsRef<[u8]>) -> Result<Self, VmdParseError> {
+        let mut cursor = Cursor::new(buf.as_ref());
+
+        let header = VmdHeader::parse(&mut cursor)?;
+        let bone_keyframes = Vec::parse(&header.config, &mut cursor)?;
+        let morph_keyframes = Vec::parse(&header.config, &mut cursor)?;
+        let camera_keyframes = Vec::parse(&header.config, &mut cursor)?;
+        let light_keyframes = Vec::parse(&header.config, &mut cursor)?;
+        let shadow_keyframes = Vec::parse(&header.config, &mut cursor)?;
+        let ik_keyframes = Vec::parse(&header.config, &mut cursor)?;
+
+        Ok(Self {
+            header,
+            bone_keyframes,
+            morph_keyframes,
+            camera_keyframes,
+            light_keyframes,
+            shadow_keyframes,
+            ik_keyframes,
+        })
+    }
+}
+
+impl Display for Vmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "VMD {:?}", self.header.config.version)?;
+        writeln!(f, "  model name: {}", self.header.model_name)?;
+        writeln!(f, "  bone keyframes: {}", self.bone_keyframes.len())?;
+        writeln!(f, "  morph keyframes: {}", self.morph_keyframes.len())?;
+        writeln!(f, "  camera keyframes: {}", self.camera_keyframes.len())?;
+        writeln!(f, "  light keyframes: {}", self.light_keyframes.len())?;
+        writeln!(f, "  shadow keyframes: {}", self.shadow_keyframes.len())?;
+        writeln!(f, "  IK keyframes: {}", self.ik_keyframes.len())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VmdVersion;
+
+    fn push_name<const L: usize>(buf: &mut Vec<u8>, name: &[u8]) {
+        let mut bytes = [0u8; L];
+        bytes[..name.len()].copy_from_slice(name);
+        buf.extend_from_slice(&bytes);
+    }
+
+    fn push_f32s(buf: &mut Vec<u8>, values: &[f32]) {
+        for value in values {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn check_parse_vmd() {
+        let mut buf = Vec::new();
+        push_name::<30>(&mut buf, b"Vocaloid Motion Data 0002");
+        push_name::<20>(&mut buf, b"model");
+
+        // a bone keyframe of "センター" (center) with an ease-in translation along x
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_name::<15>(&mut buf, &[0x83, 0x5A, 0x83, 0x93, 0x83, 0x5E, 0x81, 0x5B]);
+        buf.extend_from_slice(&30u32.to_le_bytes());
+        push_f32s(&mut buf, &[1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 1.0]);
+        let mut interpolation = [20u8; 64];
+        interpolation[0] = 127; // x1 of translation x
+        interpolation[4] = 0; // y1 of translation x
+        buf.extend_from_slice(&interpolation);
+
+        // a morph keyframe
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_name::<15>(&mut buf, b"smile");
+        buf.extend_from_slice(&10u32.to_le_bytes());
+        push_f32s(&mut buf, &[0.5]);
+
+        // no camera, light or shadow keyframes
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+
+        // an IK keyframe disabling one IK
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.push(1);
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        push_name::<20>(&mut buf, b"leg IK");
+        buf.push(0);
+
+        let vmd = Vmd::parse(&buf).unwrap();
+        assert_eq!(vmd.header.config.version, VmdVersion::V2);
+        assert_eq!(vmd.header.model_name, "model");
+
+        let bone = &vmd.bone_keyframes[0];
+        assert_eq!(bone.bone_name, "センター");
+        assert_eq!(bone.frame, 30);
+        assert_eq!((bone.translation.x, bone.rotation.w), (1.0, 1.0));
+        assert_eq!(bone.interpolation.translation_x.x1, 127);
+        assert_eq!(bone.interpolation.translation_x.y1, 0);
+        assert!(bone.interpolation.rotation.is_linear());
+
+        assert_eq!(vmd.morph_keyframes[0].morph_name, "smile");
+        assert_eq!(vmd.morph_keyframes[0].weight, 0.5);
+        assert!(vmd.camera_keyframes.is_empty());
+
+        let ik = &vmd.ik_keyframes[0];
+        assert!(ik.is_visible);
+        assert_eq!(ik.iks[0].bone_name, "leg IK");
+        assert!(!ik.iks[0].is_enabled);
+    }
+
+    #[test]
+    fn check_parse_vmd_without_trailing_sections() {
+        let mut buf = Vec::new();
+        push_name::<30>(&mut buf, b"Vocaloid Motion Data 0002");
+        push_name::<20>(&mut buf, b"model");
+        buf.extend_from_slice(&0u32.to_le_bytes());
+
+        let vmd = Vmd::parse(&buf).unwrap();
+        assert!(vmd.bone_keyframes.is_empty());
+        assert!(vmd.ik_keyframes.is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    pmx_primitives::{PmxVec3, PmxVec4},
+    shift_jis::parse_shift_jis_name,
+    vmd_header::VmdConfig,
+    vmd_primitives::VmdBoneInterpolation,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdBoneKeyframeParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+    #[error("failed to parse a PMX primitive: {0}")]
+    PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
+    #[error("failed to parse a VMD primitive: {0}")]
+    VmdPrimitiveParseError(#[from] crate::vmd_primitives::VmdPrimitiveParseError),
+}
+
+impl ParseError for VmdBoneKeyframeParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmdBoneKeyframe {
+    pub bone_name: String,
+    pub frame: u32,
+    /// Translation from the rest position of the bone.
+    pub translation: PmxVec3,
+    /// Rotation from the rest pose of the bone, as a quaternion.
+    pub rotation: PmxVec4,
+    pub interpolation: VmdBoneInterpolation,
+}
+
+impl Parse<VmdConfig> for VmdBoneKeyframe {
+    type Error = VmdBoneKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // bone name (15 bytes)
+        // frame (4 bytes)
+        // translation (12 bytes)
+        // rotation (16 bytes)
+        // interpolation (64 bytes)
+        let size = 15 + 4 + 12 + 16 + 64;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let bone_name = parse_shift_jis_name::<Self::Error, 15>(cursor)?;
+        let frame = u32::parse(config, cursor)?;
+        let translation = PmxVec3::parse(config, cursor)?;
+        let rotation = PmxVec4::parse(config, cursor)?;
+        let interpolation = VmdBoneInterpolation::parse(config, cursor)?;
+
+        Ok(Self {
+            bone_name,
+            frame,
+            translation,
+            rotation,
+            interpolation,
+        })
+    }
+}
+
+impl Parse<VmdConfig> for Vec<VmdBoneKeyframe> {
+    type Error = VmdBoneKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // keyframe count (4 bytes); trailing sections may be omitted
+        let size = 4;
+        if !cursor.has_bytes(size) {
+            return Ok(Vec::new());
+        }
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut keyframes = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            keyframes.push(VmdBoneKeyframe::parse(config, cursor)?);
+        }
+
+        Ok(keyframes)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    pmx_primitives::PmxVec3,
+    vmd_header::VmdConfig,
+    vmd_primitives::VmdCameraInterpolation,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdCameraKeyframeParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+    #[error("failed to parse a PMX primitive: {0}")]
+    PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
+    #[error("failed to parse a VMD primitive: {0}")]
+    VmdPrimitiveParseError(#[from] crate::vmd_primitives::VmdPrimitiveParseError),
+}
+
+impl ParseError for VmdCameraKeyframeParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+/// The camera orbits around `target`, looking at it from `distance`.
+#[derive(Debug, Clone)]
+pub struct VmdCameraKeyframe {
+    pub frame: u32,
+    /// Signed distance from the target; negative values are in front of it.
+    pub distance: f32,
+    pub target: PmxVec3,
+    /// Euler angles in radians.
+    pub rotation: PmxVec3,
+    pub interpolation: VmdCameraInterpolation,
+    /// Vertical field of view in degrees.
+    pub view_angle: u32,
+    pub is_perspective: bool,
+}
+
+impl Parse<VmdConfig> for VmdCameraKeyframe {
+    type Error = VmdCameraKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // frame (4 bytes)
+        // distance (4 bytes)
+        // target (12 bytes)
+        // rotation (12 bytes)
+        // interpolation (24 bytes)
+        // view angle (4 bytes)
+        // perspective (1 byte)
+        let size = 4 + 4 + 12 + 12 + 24 + 4 + 1;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let frame = u32::parse(config, cursor)?;
+        let distance = f32::parse(config, cursor)?;
+        let target = PmxVec3::parse(config, cursor)?;
+        let rotation = PmxVec3::parse(config, cursor)?;
+        let interpolation = VmdCameraInterpolation::parse(config, cursor)?;
+        let view_angle = u32::parse(config, cursor)?;
+        // perspective is turned off by a non-zero value
+        let is_perspective = !bool::parse(config, cursor)?;
+
+        Ok(Self {
+            frame,
+            distance,
+            target,
+            rotation,
+            interpolation,
+            view_angle,
+            is_perspective,
+        })
+    }
+}
+
+impl Parse<VmdConfig> for Vec<VmdCameraKeyframe> {
+    type Error = VmdCameraKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // keyframe count (4 bytes); trailing sections may be omitted
+        let size = 4;
+        if !cursor.has_bytes(size) {
+            return Ok(Vec::new());
+        }
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut keyframes = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            keyframes.push(VmdCameraKeyframe::parse(config, cursor)?);
+        }
+
+        Ok(keyframes)
+    }
+}
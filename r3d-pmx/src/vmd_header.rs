@@ -0,0 +1,74 @@
+use crate::{
+    cursor::Cursor, parse::ParseError, shift_jis::parse_shift_jis_name,
+    vmd_primitives::VmdPrimitiveParseError,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdHeaderParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a VMD primitive: {0}")]
+    VmdPrimitiveParseError(#[from] VmdPrimitiveParseError),
+    #[error("`{signature}` is not a valid VMD signature")]
+    InvalidSignature { signature: String },
+}
+
+impl ParseError for VmdHeaderParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmdHeader {
+    pub config: VmdConfig,
+    pub model_name: String,
+}
+
+impl VmdHeader {
+    pub fn parse(cursor: &mut Cursor) -> Result<Self, VmdHeaderParseError> {
+        // signature (30 bytes)
+        let size = 30;
+        cursor.ensure_bytes::<VmdHeaderParseError>(size)?;
+
+        let signature = parse_shift_jis_name::<VmdHeaderParseError, 30>(cursor)?;
+        let version = match signature.as_str() {
+            "Vocaloid Motion Data 0002" => VmdVersion::V2,
+            "Vocaloid Motion Data file" => VmdVersion::V1,
+            _ => {
+                return Err(VmdHeaderParseError::InvalidSignature { signature });
+            }
+        };
+
+        // model name (10 or 20 bytes)
+        let model_name = match version {
+            VmdVersion::V1 => {
+                cursor.ensure_bytes::<VmdHeaderParseError>(10)?;
+                parse_shift_jis_name::<VmdHeaderParseError, 10>(cursor)?
+            }
+            VmdVersion::V2 => {
+                cursor.ensure_bytes::<VmdHeaderParseError>(20)?;
+                parse_shift_jis_name::<VmdHeaderParseError, 20>(cursor)?
+            }
+        };
+
+        Ok(Self {
+            config: VmdConfig { version },
+            model_name,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmdConfig {
+    pub version: VmdVersion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VmdVersion {
+    /// Motions saved by MikuMikuDance before 3.0, with 10 bytes of model name.
+    V1,
+    /// Motions saved by MikuMikuDance 3.0 or later, with 20 bytes of model name.
+    V2,
+}
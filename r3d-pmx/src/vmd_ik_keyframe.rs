@@ -0,0 +1,103 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    shift_jis::parse_shift_jis_name,
+    vmd_header::VmdConfig,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdIKKeyframeParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+}
+
+impl ParseError for VmdIKKeyframeParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+/// Model visibility and IK states; they hold until the next keyframe.
+#[derive(Debug, Clone)]
+pub struct VmdIKKeyframe {
+    pub frame: u32,
+    pub is_visible: bool,
+    pub iks: Vec<VmdIKState>,
+}
+
+impl Parse<VmdConfig> for VmdIKKeyframe {
+    type Error = VmdIKKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // frame (4 bytes)
+        // visibility (1 byte)
+        // IK count (4 bytes)
+        let size = 4 + 1 + 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let frame = u32::parse(config, cursor)?;
+        let is_visible = bool::parse(config, cursor)?;
+        let count = u32::parse(config, cursor)? as usize;
+        let mut iks = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            iks.push(VmdIKState::parse(config, cursor)?);
+        }
+
+        Ok(Self {
+            frame,
+            is_visible,
+            iks,
+        })
+    }
+}
+
+impl Parse<VmdConfig> for Vec<VmdIKKeyframe> {
+    type Error = VmdIKKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // keyframe count (4 bytes); trailing sections may be omitted
+        let size = 4;
+        if !cursor.has_bytes(size) {
+            return Ok(Vec::new());
+        }
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut keyframes = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            keyframes.push(VmdIKKeyframe::parse(config, cursor)?);
+        }
+
+        Ok(keyframes)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmdIKState {
+    /// Name of the IK bone.
+    pub bone_name: String,
+    pub is_enabled: bool,
+}
+
+impl Parse<VmdConfig> for VmdIKState {
+    type Error = VmdIKKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // bone name (20 bytes)
+        // enabled (1 byte)
+        let size = 20 + 1;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let bone_name = parse_shift_jis_name::<Self::Error, 20>(cursor)?;
+        let is_enabled = bool::parse(config, cursor)?;
+
+        Ok(Self {
+            bone_name,
+            is_enabled,
+        })
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    pmx_primitives::PmxVec3,
+    vmd_header::VmdConfig,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdLightKeyframeParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+    #[error("failed to parse a PMX primitive: {0}")]
+    PmxPrimitiveParseError(#[from] crate::pmx_primitives::PmxPrimitiveParseError),
+    #[error("shadow mode `{mode}` is invalid; it must be in the range of [0, 2]")]
+    InvalidShadowMode { mode: u8 },
+}
+
+impl ParseError for VmdLightKeyframeParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmdLightKeyframe {
+    pub frame: u32,
+    pub color: PmxVec3,
+    pub direction: PmxVec3,
+}
+
+impl Parse<VmdConfig> for VmdLightKeyframe {
+    type Error = VmdLightKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // frame (4 bytes)
+        // color (12 bytes)
+        // direction (12 bytes)
+        let size = 4 + 12 + 12;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let frame = u32::parse(config, cursor)?;
+        let color = PmxVec3::parse(config, cursor)?;
+        let direction = PmxVec3::parse(config, cursor)?;
+
+        Ok(Self {
+            frame,
+            color,
+            direction,
+        })
+    }
+}
+
+impl Parse<VmdConfig> for Vec<VmdLightKeyframe> {
+    type Error = VmdLightKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // keyframe count (4 bytes); trailing sections may be omitted
+        let size = 4;
+        if !cursor.has_bytes(size) {
+            return Ok(Vec::new());
+        }
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut keyframes = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            keyframes.push(VmdLightKeyframe::parse(config, cursor)?);
+        }
+
+        Ok(keyframes)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmdShadowKeyframe {
+    pub frame: u32,
+    pub mode: VmdShadowMode,
+    pub distance: f32,
+}
+
+impl Parse<VmdConfig> for VmdShadowKeyframe {
+    type Error = VmdLightKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // frame (4 bytes)
+        // mode (1 byte)
+        // distance (4 bytes)
+        let size = 4 + 1 + 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let frame = u32::parse(config, cursor)?;
+        let mode = VmdShadowMode::parse(config, cursor)?;
+        let distance = f32::parse(config, cursor)?;
+
+        Ok(Self {
+            frame,
+            mode,
+            distance,
+        })
+    }
+}
+
+impl Parse<VmdConfig> for Vec<VmdShadowKeyframe> {
+    type Error = VmdLightKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // keyframe count (4 bytes); trailing sections may be omitted
+        let size = 4;
+        if !cursor.has_bytes(size) {
+            return Ok(Vec::new());
+        }
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut keyframes = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            keyframes.push(VmdShadowKeyframe::parse(config, cursor)?);
+        }
+
+        Ok(keyframes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VmdShadowMode {
+    Off,
+    Mode1,
+    Mode2,
+}
+
+impl Parse<VmdConfig> for VmdShadowMode {
+    type Error = VmdLightKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        let mode = u8::parse(config, cursor)?;
+
+        match mode {
+            0 => Ok(Self::Off),
+            1 => Ok(Self::Mode1),
+            2 => Ok(Self::Mode2),
+            mode => Err(VmdLightKeyframeParseError::InvalidShadowMode { mode }),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    shift_jis::parse_shift_jis_name,
+    vmd_header::VmdConfig,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VmdMorphKeyframeParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+    #[error("failed to parse a Rust primitive: {0}")]
+    RustPrimitiveParseError(#[from] crate::primitives::RustPrimitiveParseError),
+}
+
+impl ParseError for VmdMorphKeyframeParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+/// Morph keyframes are interpolated linearly.
+#[derive(Debug, Clone)]
+pub struct VmdMorphKeyframe {
+    pub morph_name: String,
+    pub frame: u32,
+    pub weight: f32,
+}
+
+impl Parse<VmdConfig> for VmdMorphKeyframe {
+    type Error = VmdMorphKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // morph name (15 bytes)
+        // frame (4 bytes)
+        // weight (4 bytes)
+        let size = 15 + 4 + 4;
+        cursor.ensure_bytes::<Self::Error>(size)?;
+
+        let morph_name = parse_shift_jis_name::<Self::Error, 15>(cursor)?;
+        let frame = u32::parse(config, cursor)?;
+        let weight = f32::parse(config, cursor)?;
+
+        Ok(Self {
+            morph_name,
+            frame,
+            weight,
+        })
+    }
+}
+
+impl Parse<VmdConfig> for Vec<VmdMorphKeyframe> {
+    type Error = VmdMorphKeyframeParseError;
+
+    fn parse(config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // keyframe count (4 bytes); trailing sections may be omitted
+        let size = 4;
+        if !cursor.has_bytes(size) {
+            return Ok(Vec::new());
+        }
+
+        let count = u32::parse(config, cursor)? as usize;
+        let mut keyframes = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            keyframes.push(VmdMorphKeyframe::parse(config, cursor)?);
+        }
+
+        Ok(keyframes)
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    cursor::Cursor,
+    parse::{Parse, ParseError},
+    vmd_header::VmdConfig,
+};
+use thiserror::Error;
+
+/// Frames per second of VMD keyframes.
+pub const VMD_FRAME_RATE: f32 = 30.0;
+
+#[derive(Error, Debug)]
+pub enum VmdPrimitiveParseError {
+    #[error("unexpected EOF detected")]
+    UnexpectedEof,
+}
+
+impl ParseError for VmdPrimitiveParseError {
+    fn error_unexpected_eof() -> Self {
+        Self::UnexpectedEof
+    }
+}
+
+/// A cubic Bezier curve from `(0, 0)` to `(1, 1)` that maps the progress between two keyframes
+/// to the interpolation weight. Control points are stored as in VMD, in the range of [0, 127].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VmdBezierCurve {
+    pub x1: u8,
+    pub y1: u8,
+    pub x2: u8,
+    pub y2: u8,
+}
+
+impl VmdBezierCurve {
+    /// The default curve of MikuMikuDance, which is linear.
+    pub const LINEAR: Self = Self {
+        x1: 20,
+        y1: 20,
+        x2: 107,
+        y2: 107,
+    };
+
+    pub fn is_linear(&self) -> bool {
+        self.x1 == self.y1 && self.x2 == self.y2
+    }
+
+    /// Returns the interpolation weight at the given progress in the range of [0, 1].
+    pub fn evaluate(&self, progress: f32) -> f32 {
+        let progress = progress.clamp(0.0, 1.0);
+
+        if self.is_linear() {
+            return progress;
+        }
+
+        let x1 = self.x1.min(127) as f32 / 127.0;
+        let y1 = self.y1.min(127) as f32 / 127.0;
+        let x2 = self.x2.min(127) as f32 / 127.0;
+        let y2 = self.y2.min(127) as f32 / 127.0;
+        let bezier = |p1: f32, p2: f32, t: f32| {
+            let s = 1.0 - t;
+            3.0 * s * s * t * p1 + 3.0 * s * t * t * p2 + t * t * t
+        };
+
+        // x(t) is monotonic since the control points are in [0, 1]; find t by bisection.
+        let mut min = 0f32;
+        let mut max = 1f32;
+        let mut t = progress;
+
+        for _ in 0..24 {
+            let x = bezier(x1, x2, t);
+
+            if (x - progress).abs() < 1e-6 {
+                break;
+            }
+
+            if x < progress {
+                min = t;
+            } else {
+                max = t;
+            }
+
+            t = (min + max) * 0.5;
+        }
+
+        bezier(y1, y2, t)
+    }
+}
+
+impl Default for VmdBezierCurve {
+    fn default() -> Self {
+        Self::LINEAR
+    }
+}
+
+/// Interpolation curves of a bone keyframe, applied from the previous keyframe to this one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct VmdBoneInterpolation {
+    pub translation_x: VmdBezierCurve,
+    pub translation_y: VmdBezierCurve,
+    pub translation_z: VmdBezierCurve,
+    pub rotation: VmdBezierCurve,
+}
+
+impl Parse<VmdConfig> for VmdBoneInterpolation {
+    type Error = VmdPrimitiveParseError;
+
+    fn parse(_config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // The first 16 bytes hold `x1`, `y1`, `x2` and `y2` of each curve in turn;
+        // the rest are copies of them, shifted for compatibility.
+        let bytes = cursor.read::<Self::Error, 64>()?;
+        let curve = |index: usize| VmdBezierCurve {
+            x1: bytes[index],
+            y1: bytes[4 + index],
+            x2: bytes[8 + index],
+            y2: bytes[12 + index],
+        };
+
+        Ok(Self {
+            translation_x: curve(0),
+            translation_y: curve(1),
+            translation_z: curve(2),
+            rotation: curve(3),
+        })
+    }
+}
+
+/// Interpolation curves of a camera keyframe, applied from the previous keyframe to this one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct VmdCameraInterpolation {
+    pub translation_x: VmdBezierCurve,
+    pub translation_y: VmdBezierCurve,
+    pub translation_z: VmdBezierCurve,
+    pub rotation: VmdBezierCurve,
+    pub distance: VmdBezierCurve,
+    pub view_angle: VmdBezierCurve,
+}
+
+impl Parse<VmdConfig> for VmdCameraInterpolation {
+    type Error = VmdPrimitiveParseError;
+
+    fn parse(_config: &VmdConfig, cursor: &mut Cursor) -> Result<Self, Self::Error> {
+        // Each curve is stored as `x1`, `x2`, `y1` and `y2`.
+        let bytes = cursor.read::<Self::Error, 24>()?;
+        let curve = |index: usize| VmdBezierCurve {
+            x1: bytes[index * 4],
+            y1: bytes[index * 4 + 2],
+            x2: bytes[index * 4 + 1],
+            y2: bytes[index * 4 + 3],
+        };
+
+        Ok(Self {
+            translation_x: curve(0),
+            translation_y: curve(1),
+            translation_z: curve(2),
+            rotation: curve(3),
+            distance: curve(4),
+            view_angle: curve(5),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_bezier_curve() {
+        let linear = VmdBezierCurve::LINEAR;
+        assert_eq!(linear.evaluate(0.25), 0.25);
+
+        // ease-in: slow at the start, reaching the end at 1
+        let ease_in = VmdBezierCurve {
+            x1: 127,
+            y1: 0,
+            x2: 127,
+            y2: 127,
+        };
+        assert!(ease_in.evaluate(0.5) < 0.5);
+        assert!((ease_in.evaluate(0.0) - 0.0).abs() < 1e-4);
+        assert!((ease_in.evaluate(1.0) - 1.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::pmx_header::{PmxConfig, PmxIndexSize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PmxWriteError {
+    #[error("{kind} index `{index}` does not fit in the index size `{size:?}`")]
+    IndexOutOfRange {
+        kind: &'static str,
+        index: i64,
+        size: PmxIndexSize,
+    },
+    #[error(
+        "additional vec4 count `{count}` is invalid; it must be in the range of [0, 4] in PMX 2.0"
+    )]
+    InvalidAdditionalVec4Count { count: usize },
+    #[error("UV morph index `{uv_index}` is invalid; it must be in the range of [0, 4]")]
+    InvalidUvMorphIndex { uv_index: u8 },
+    #[error("soft bodies require PMX 2.1, but the version is `{version}`")]
+    SoftBodiesUnsupported { version: f32 },
+}
+
+/// Writes a value into the buffer, the inverse of [`crate::parse::Parse`].
+/// `C` is the configuration of the file format being written.
+pub trait Write<C = PmxConfig> {
+    fn write(&self, config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError>;
+}
+
+impl<C, T: Write<C>> Write<C> for Vec<T> {
+    fn write(&self, config: &C, buffer: &mut Vec<u8>) -> Result<(), PmxWriteError> {
+        // count (4 bytes)
+        buffer.extend_from_slice(&(self.len() as u32).to_le_bytes());
+
+        for item in self {
+            item.write(config, buffer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use crate::{
+    math::{Quat, Vec3},
+    transform::Transform,
+};
+use asset::assets::{AnimationClip, AnimationKeyframe, AnimationTrack};
+use specs::{prelude::*, Component};
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationWrapMode {
+    /// Restarts the clip from the beginning when it reaches the end.
+    Loop,
+    /// Holds the last pose when the clip reaches the end.
+    Clamp,
+}
+
+/// Plays animation clips on the object subtree it is attached to.
+/// Each track of the playing clip drives the [`Transform`] of the object in the subtree
+/// whose name matches the node name of the track. Morph tracks drive the morph weights
+/// of the [`SkinnedMeshRenderer`](crate::gfx::SkinnedMeshRenderer)s in the subtree by morph name.
+#[derive(Component)]
+#[storage(HashMapStorage)]
+pub struct Animator {
+    speed: f32,
+    current: Option<AnimationPlayback>,
+    previous: Option<AnimationPlayback>,
+    fade_duration: f32,
+    fade_elapsed: f32,
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self {
+            speed: 1.0,
+            current: None,
+            previous: None,
+            fade_duration: 0.0,
+            fade_elapsed: 0.0,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn clip(&self) -> Option<&Arc<AnimationClip>> {
+        self.current.as_ref().map(|playback| &playback.clip)
+    }
+
+    pub fn wrap_mode(&self) -> Option<AnimationWrapMode> {
+        self.current.as_ref().map(|playback| playback.wrap_mode)
+    }
+
+    /// Returns the playback position of the current clip, in seconds.
+    pub fn time(&self) -> f32 {
+        self.current.as_ref().map_or(0.0, |playback| playback.time)
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        if let Some(playback) = &mut self.current {
+            playback.time = 0.0;
+            playback.advance(time);
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// Returns `true` if a clamped clip has reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.current.as_ref().is_some_and(|playback| {
+            playback.wrap_mode == AnimationWrapMode::Clamp
+                && if 0.0 <= self.speed {
+                    playback.clip.duration <= playback.time
+                } else {
+                    playback.time <= 0.0
+                }
+        })
+    }
+
+    /// Plays the clip from the beginning, stopping any other clip immediately.
+    pub fn play(&mut self, clip: Arc<AnimationClip>, wrap_mode: AnimationWrapMode) {
+        self.current = Some(AnimationPlayback::new(clip, wrap_mode));
+        self.previous = None;
+    }
+
+    /// Plays the clip from the beginning, blending from the current pose over `duration` seconds.
+    pub fn cross_fade(
+        &mut self,
+        clip: Arc<AnimationClip>,
+        wrap_mode: AnimationWrapMode,
+        duration: f32,
+    ) {
+        if self.current.is_none() || duration <= 0.0 {
+            self.play(clip, wrap_mode);
+            return;
+        }
+
+        self.previous = self.current.take();
+        self.current = Some(AnimationPlayback::new(clip, wrap_mode));
+        self.fade_duration = duration;
+        self.fade_elapsed = 0.0;
+    }
+
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.previous = None;
+    }
+
+    /// Advances the playback by `delta` seconds. The cross-fade progresses regardless of the speed.
+    pub fn update(&mut self, delta: f32) {
+        if let Some(playback) = &mut self.current {
+            playback.advance(delta * self.speed);
+        }
+
+        if let Some(playback) = &mut self.previous {
+            playback.advance(delta * self.speed);
+            self.fade_elapsed += delta;
+
+            if self.fade_duration <= self.fade_elapsed {
+                self.previous = None;
+            }
+        }
+    }
+
+    /// Writes the pose of the given node into the transform.
+    /// Channels without keyframes keep the value of the transform.
+    /// Returns `false` if no playing clip animates the node.
+    pub fn sample(&self, node_name: &str, transform: &mut Transform) -> bool {
+        let current = match &self.current {
+            Some(current) => current,
+            None => return false,
+        };
+        let previous = match &self.previous {
+            Some(previous) => previous,
+            None => return current.sample(node_name, transform),
+        };
+
+        let mut from = transform.clone();
+        let is_previous_animated = previous.sample(node_name, &mut from);
+        let is_current_animated = current.sample(node_name, transform);
+
+        if !is_previous_animated && !is_current_animated {
+            return false;
+        }
+
+        let weight = self.fade_weight();
+        transform.position = Vec3::lerp(from.position, transform.position, weight);
+        transform.rotation = Quat::slerp(from.rotation, transform.rotation, weight);
+        transform.scale = Vec3::lerp(from.scale, transform.scale, weight);
+        true
+    }
+
+    /// Writes the weight of the given morph, leaving it as is if no playing clip animates the morph.
+    /// Returns `false` if no playing clip animates the morph.
+    pub fn sample_morph(&self, morph_name: &str, weight: &mut f32) -> bool {
+        let current = match &self.current {
+            Some(current) => current,
+            None => return false,
+        };
+        let previous = match &self.previous {
+            Some(previous) => previous,
+            None => return current.sample_morph(morph_name, weight),
+        };
+
+        let mut from = *weight;
+        let is_previous_animated = previous.sample_morph(morph_name, &mut from);
+        let is_current_animated = current.sample_morph(morph_name, weight);
+
+        if !is_previous_animated && !is_current_animated {
+            return false;
+        }
+
+        *weight = from + (*weight - from) * self.fade_weight();
+        true
+    }
+
+    fn fade_weight(&self) -> f32 {
+        (self.fade_elapsed / self.fade_duration).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct AnimationPlayback {
+    clip: Arc<AnimationClip>,
+    wrap_mode: AnimationWrapMode,
+    time: f32,
+    tracks: HashMap<String, usize>,
+    morph_tracks: HashMap<String, usize>,
+}
+
+impl AnimationPlayback {
+    fn new(clip: Arc<AnimationClip>, wrap_mode: AnimationWrapMode) -> Self {
+        let tracks = HashMap::from_iter(
+            clip.tracks
+                .iter()
+                .enumerate()
+                .map(|(index, track)| (track.node_name.clone(), index)),
+        );
+        let morph_tracks = HashMap::from_iter(
+            clip.morph_tracks
+                .iter()
+                .enumerate()
+                .map(|(index, track)| (track.morph_name.clone(), index)),
+        );
+
+        Self {
+            clip,
+            wrap_mode,
+            time: 0.0,
+            tracks,
+            morph_tracks,
+        }
+    }
+
+    fn advance(&mut self, delta: f32) {
+        let duration = self.clip.duration;
+        let time = self.time + delta;
+
+        self.time = match self.wrap_mode {
+            AnimationWrapMode::Loop if 0.0 < duration => time.rem_euclid(duration),
+            AnimationWrapMode::Loop => 0.0,
+            AnimationWrapMode::Clamp => time.clamp(0.0, duration.max(0.0)),
+        };
+    }
+
+    fn sample(&self, node_name: &str, transform: &mut Transform) -> bool {
+        let track = match self.tracks.get(node_name) {
+            Some(&index) => &self.clip.tracks[index],
+            None => return false,
+        };
+
+        sample_track(track, self.time, transform);
+        true
+    }
+
+    fn sample_morph(&self, morph_name: &str, weight: &mut f32) -> bool {
+        let track = match self.morph_tracks.get(morph_name) {
+            Some(&index) => &self.clip.morph_tracks[index],
+            None => return false,
+        };
+
+        match sample_keyframes(&track.weights, self.time, |from: f32, to, t| {
+            from + (to - from) * t
+        }) {
+            Some(sampled) => {
+                *weight = sampled;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn sample_track(track: &AnimationTrack, time: f32, transform: &mut Transform) {
+    if let Some(position) = sample_keyframes(&track.translations, time, Vec3::lerp) {
+        transform.position = position;
+    }
+
+    if let Some(rotation) = sample_keyframes(&track.rotations, time, Quat::slerp) {
+        transform.rotation = rotation;
+    }
+
+    if let Some(scale) = sample_keyframes(&track.scales, time, Vec3::lerp) {
+        transform.scale = scale;
+    }
+}
+
+fn sample_keyframes<T, U>(
+    keyframes: &[AnimationKeyframe<T>],
+    time: f32,
+    interpolate: impl Fn(U, U, f32) -> U,
+) -> Option<U>
+where
+    T: Copy + Into<U>,
+{
+    let index = keyframes.partition_point(|keyframe| keyframe.time <= time);
+    let (from, to) = match index {
+        _ if keyframes.is_empty() => return None,
+        0 => return Some(keyframes[0].value.into()),
+        index if index == keyframes.len() => return Some(keyframes[index - 1].value.into()),
+        index => (&keyframes[index - 1], &keyframes[index]),
+    };
+
+    let span = to.time - from.time;
+    let t = if 0.0 < span {
+        (time - from.time) / span
+    } else {
+        0.0
+    };
+
+    Some(interpolate(from.value.into(), to.value.into(), t))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use asset::assets::AnimationMorphTrack;
+
+    fn equals_float(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-5
+    }
+
+    fn create_clip() -> Arc<AnimationClip> {
+        Arc::new(AnimationClip {
+            name: "move".to_owned(),
+            duration: 2.0,
+            tracks: vec![AnimationTrack {
+                node_name: "bone".to_owned(),
+                translations: vec![
+                    AnimationKeyframe {
+                        time: 0.0,
+                        value: [0.0, 0.0, 0.0],
+                    },
+                    AnimationKeyframe {
+                        time: 2.0,
+                        value: [4.0, 0.0, 0.0],
+                    },
+                ],
+                rotations: vec![],
+                scales: vec![],
+            }],
+            morph_tracks: vec![AnimationMorphTrack {
+                morph_name: "smile".to_owned(),
+                weights: vec![
+                    AnimationKeyframe {
+                        time: 0.0,
+                        value: 0.0,
+                    },
+                    AnimationKeyframe {
+                        time: 2.0,
+                        value: 1.0,
+                    },
+                ],
+            }],
+        })
+    }
+
+    #[test]
+    fn check_animator_wrap_modes() {
+        let mut animator = Animator::new();
+        let mut transform = Transform::new();
+
+        animator.play(create_clip(), AnimationWrapMode::Loop);
+        animator.update(2.5);
+        assert!(animator.sample("bone", &mut transform));
+        assert!(equals_float(transform.position.x, 1.0));
+        assert!(!animator.is_finished());
+
+        animator.play(create_clip(), AnimationWrapMode::Clamp);
+        animator.update(2.5);
+        assert!(animator.sample("bone", &mut transform));
+        assert!(equals_float(transform.position.x, 4.0));
+        assert!(animator.is_finished());
+
+        assert!(!animator.sample("other", &mut transform));
+    }
+
+    #[test]
+    fn check_animator_morph_weights() {
+        let mut animator = Animator::new();
+        let mut weight = 0.25;
+
+        assert!(!animator.sample_morph("smile", &mut weight));
+
+        animator.play(create_clip(), AnimationWrapMode::Clamp);
+        animator.update(1.5);
+        assert!(animator.sample_morph("smile", &mut weight));
+        assert!(equals_float(weight, 0.75));
+
+        // Halfway between the end of the previous clip (1) and the current one (0.25).
+        animator.update(0.5);
+        animator.cross_fade(create_clip(), AnimationWrapMode::Clamp, 1.0);
+        animator.update(0.5);
+        assert!(animator.sample_morph("smile", &mut weight));
+        assert!(equals_float(weight, 0.625));
+
+        assert!(!animator.sample_morph("other", &mut weight));
+        assert!(equals_float(weight, 0.625));
+    }
+
+    #[test]
+    fn check_animator_cross_fade() {
+        let mut animator = Animator::new();
+        let mut transform = Transform::new();
+
+        animator.play(create_clip(), AnimationWrapMode::Clamp);
+        animator.update(2.0);
+        animator.cross_fade(create_clip(), AnimationWrapMode::Clamp, 1.0);
+        animator.update(0.5);
+
+        // Halfway between the end of the previous clip (x=4) and the current one (x=1).
+        assert!(animator.sample("bone", &mut transform));
+        assert!(equals_float(transform.position.x, 2.5));
+
+        animator.update(0.5);
+        assert!(!animator.is_fading());
+        assert!(animator.sample("bone", &mut transform));
+        assert!(equals_float(transform.position.x, 2.0));
+    }
+}
@@ -0,0 +1,5 @@
+mod animator;
+mod skeleton_pose;
+
+pub use animator::*;
+pub use skeleton_pose::*;
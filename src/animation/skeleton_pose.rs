@@ -0,0 +1,615 @@
+use super::Animator;
+use crate::{
+    math::{Mat4, Quat, Vec3, Vec4},
+    transform::Transform,
+};
+use asset::assets::{BoneAngleLimit, BoneIK, MeshSkin, Node, NodeBone};
+use specs::{prelude::*, Component};
+
+/// Evaluates the pose of a model skeleton the way MikuMikuDance does. Bones are deformed
+/// in ascending order of their transform layer, those deformed after physics last.
+/// Each bone applies its inherited (grant) rotation and translation, then solves its IK chain with CCD.
+#[derive(Component)]
+#[storage(HashMapStorage)]
+pub struct SkeletonPose {
+    nodes: Vec<PoseNode>,
+    /// Node indices in deform order.
+    deform_order: Vec<u32>,
+    /// Number of nodes in [`Self::deform_order`] deformed before physics.
+    before_physics_count: usize,
+}
+
+struct PoseNode {
+    name: String,
+    parent_index: Option<u32>,
+    children_indices: Vec<u32>,
+    bone: Option<NodeBone>,
+    rest: Transform,
+    local: Transform,
+    is_ik_enabled: bool,
+    ik_rotation: Quat,
+    grant_translation: Vec3,
+    grant_rotation: Quat,
+    local_matrix: Mat4,
+    matrix: Mat4,
+}
+
+impl SkeletonPose {
+    /// Creates a pose in the rest pose of the given nodes, usually [`asset::assets::ModelAsset::nodes`].
+    pub fn new(nodes: &[Node]) -> Self {
+        let nodes = Vec::from_iter(nodes.iter().map(|node| {
+            let rest = Transform::from_mat4(&Mat4::new(node.transform.matrix));
+
+            PoseNode {
+                name: node.name.clone(),
+                parent_index: node.parent_index,
+                children_indices: node.children_indices.clone(),
+                bone: node.bone.clone(),
+                local: rest.clone(),
+                rest,
+                is_ik_enabled: true,
+                ik_rotation: Quat::IDENTITY,
+                grant_translation: Vec3::ZERO,
+                grant_rotation: Quat::IDENTITY,
+                local_matrix: Mat4::identity(),
+                matrix: Mat4::identity(),
+            }
+        }));
+
+        let deform_key = |index: u32| {
+            let bone = nodes[index as usize].bone.as_ref();
+            (
+                bone.is_some_and(|bone| bone.deform_after_physics),
+                bone.map_or(0, |bone| bone.layer),
+                index,
+            )
+        };
+        let mut deform_order = Vec::from_iter(0..nodes.len() as u32);
+        deform_order.sort_by_key(|&index| deform_key(index));
+        let before_physics_count = deform_order
+            .iter()
+            .take_while(|&&index| !deform_key(index).0)
+            .count();
+
+        let mut pose = Self {
+            nodes,
+            deform_order,
+            before_physics_count,
+        };
+        pose.evaluate();
+        pose
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn node_name(&self, index: u32) -> &str {
+        &self.nodes[index as usize].name
+    }
+
+    pub fn find_node(&self, name: &str) -> Option<u32> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(|index| index as u32)
+    }
+
+    /// Returns the animated transform of the node, relative to its parent.
+    pub fn local_transform(&self, index: u32) -> &Transform {
+        &self.nodes[index as usize].local
+    }
+
+    pub fn local_transform_mut(&mut self, index: u32) -> &mut Transform {
+        &mut self.nodes[index as usize].local
+    }
+
+    pub fn is_ik_enabled(&self, index: u32) -> bool {
+        self.nodes[index as usize].is_ik_enabled
+    }
+
+    /// Enables or disables the IK chain driven by the node. IK is enabled by default.
+    pub fn set_ik_enabled(&mut self, index: u32, is_enabled: bool) {
+        self.nodes[index as usize].is_ik_enabled = is_enabled;
+    }
+
+    /// Resets the local transforms into the rest pose.
+    pub fn reset(&mut self) {
+        for node in &mut self.nodes {
+            node.local = node.rest.clone();
+        }
+    }
+
+    /// Resets the local transforms and samples the animator into the nodes of the same name.
+    pub fn sample(&mut self, animator: &Animator) {
+        for node in &mut self.nodes {
+            node.local = node.rest.clone();
+            animator.sample(&node.name, &mut node.local);
+        }
+    }
+
+    /// Returns the matrix of the node in model space, as of the last evaluation.
+    pub fn matrix(&self, index: u32) -> &Mat4 {
+        &self.nodes[index as usize].matrix
+    }
+
+    /// Overrides the matrix of the node in model space, carrying its descendants along.
+    /// The override lasts until the node is evaluated again.
+    pub fn set_matrix(&mut self, index: u32, matrix: Mat4) {
+        let local_matrix = match self.nodes[index as usize].parent_index {
+            Some(parent_index) => matrix * self.nodes[parent_index as usize].matrix.inversed(),
+            None => matrix,
+        };
+        self.nodes[index as usize].local_matrix = local_matrix;
+        self.update_matrices(index);
+    }
+
+    /// Returns the final bone matrix palette of the skin, transforming its vertices in model space.
+    pub fn bone_matrices(&self, skin: &MeshSkin) -> Vec<Mat4> {
+        Vec::from_iter(skin.joints.iter().zip(&skin.inverse_bind_matrices).map(
+            |(&joint, inverse_bind_matrix)| Mat4::new(*inverse_bind_matrix) * self.matrix(joint),
+        ))
+    }
+
+    pub fn evaluate(&mut self) {
+        self.evaluate_before_physics();
+        self.evaluate_after_physics();
+    }
+
+    /// Deforms the bones that are not deformed after physics.
+    pub fn evaluate_before_physics(&mut self) {
+        self.evaluate_range(0, self.before_physics_count);
+    }
+
+    /// Deforms the bones that are deformed after physics.
+    pub fn evaluate_after_physics(&mut self) {
+        self.evaluate_range(self.before_physics_count, self.deform_order.len());
+    }
+
+    fn evaluate_range(&mut self, start: usize, end: usize) {
+        for order in start..end {
+            let index = self.deform_order[order];
+            let node = &mut self.nodes[index as usize];
+            node.ik_rotation = Quat::IDENTITY;
+            node.grant_translation = Vec3::ZERO;
+            node.grant_rotation = Quat::IDENTITY;
+            self.update_local_matrix(index);
+        }
+
+        for order in start..end {
+            let index = self.deform_order[order];
+            // Descendants are updated along with the topmost node of this phase.
+            let is_parent_evaluated =
+                self.nodes[index as usize]
+                    .parent_index
+                    .is_some_and(|parent_index| {
+                        self.is_after_physics(parent_index) == self.is_after_physics(index)
+                    });
+
+            if !is_parent_evaluated {
+                self.update_matrices(index);
+            }
+        }
+
+        for order in start..end {
+            let index = self.deform_order[order];
+            let bone = match &self.nodes[index as usize].bone {
+                Some(bone) => bone,
+                None => continue,
+            };
+            let has_inheritance = bone.inheritance.is_some();
+            let ik = bone.ik.clone();
+
+            if has_inheritance {
+                self.update_grant(index);
+                self.update_local_matrix(index);
+                self.update_matrices(index);
+            }
+
+            if let Some(ik) = ik {
+                if self.nodes[index as usize].is_ik_enabled {
+                    self.solve_ik(index, &ik);
+                }
+            }
+        }
+    }
+
+    fn is_after_physics(&self, index: u32) -> bool {
+        self.nodes[index as usize]
+            .bone
+            .as_ref()
+            .is_some_and(|bone| bone.deform_after_physics)
+    }
+
+    fn update_grant(&mut self, index: u32) {
+        let inheritance = match self.nodes[index as usize]
+            .bone
+            .as_ref()
+            .and_then(|bone| bone.inheritance.as_ref())
+        {
+            Some(inheritance) => inheritance,
+            None => return,
+        };
+        let source = &self.nodes[inheritance.node_index as usize];
+        // Chained grants inherit what the source bone inherited, rather than its own animation.
+        let is_source_granted = source
+            .bone
+            .as_ref()
+            .is_some_and(|bone| bone.inheritance.is_some());
+
+        let grant_rotation = if inheritance.inherit_rotation {
+            let rotation = if is_source_granted {
+                source.grant_rotation
+            } else {
+                source.local.rotation
+            };
+            Quat::slerp_unclamped(
+                Quat::IDENTITY,
+                source.ik_rotation * rotation,
+                inheritance.coefficient,
+            )
+        } else {
+            Quat::IDENTITY
+        };
+        let grant_translation = if inheritance.inherit_translation {
+            let translation = if is_source_granted {
+                source.grant_translation
+            } else {
+                source.local.position - source.rest.position
+            };
+            translation * inheritance.coefficient
+        } else {
+            Vec3::ZERO
+        };
+
+        let node = &mut self.nodes[index as usize];
+        node.grant_rotation = grant_rotation;
+        node.grant_translation = grant_translation;
+    }
+
+    fn update_local_matrix(&mut self, index: u32) {
+        let node = &mut self.nodes[index as usize];
+        let position = node.local.position + node.grant_translation;
+        let rotation = node.ik_rotation * node.local.rotation * node.grant_rotation;
+        node.local_matrix = Mat4::srt(position, rotation, node.local.scale);
+    }
+
+    /// Updates the model space matrices of the node and its descendants.
+    fn update_matrices(&mut self, index: u32) {
+        let mut stack = vec![index];
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index as usize];
+            let matrix = match node.parent_index {
+                Some(parent_index) => {
+                    node.local_matrix.clone() * &self.nodes[parent_index as usize].matrix
+                }
+                None => node.local_matrix.clone(),
+            };
+
+            let node = &mut self.nodes[index as usize];
+            node.matrix = matrix;
+            stack.extend_from_slice(&node.children_indices);
+        }
+    }
+
+    fn position(&self, index: u32) -> Vec3 {
+        let elements = &self.nodes[index as usize].matrix.elements;
+        Vec3::new(elements[12], elements[13], elements[14])
+    }
+
+    /// Moves the IK target towards the IK bone by rotating the links of the chain, using CCD.
+    /// Stops early once an iteration no longer brings the target closer.
+    fn solve_ik(&mut self, index: u32, ik: &BoneIK) {
+        for link in &ik.links {
+            self.nodes[link.node_index as usize].ik_rotation = Quat::IDENTITY;
+            self.update_local_matrix(link.node_index);
+            self.update_matrices(link.node_index);
+        }
+
+        let mut link_states = vec![IKLinkState::default(); ik.links.len()];
+        let mut min_distance = f32::MAX;
+        let mut best_rotations = Vec::from_iter(
+            ik.links
+                .iter()
+                .map(|link| self.nodes[link.node_index as usize].ik_rotation),
+        );
+
+        for iteration in 0..ik.loop_count {
+            self.solve_ik_iteration(index, ik, iteration, &mut link_states);
+
+            let distance = Vec3::distance(self.position(ik.target_index), self.position(index));
+
+            if distance < min_distance {
+                min_distance = distance;
+
+                for (link, rotation) in ik.links.iter().zip(&mut best_rotations) {
+                    *rotation = self.nodes[link.node_index as usize].ik_rotation;
+                }
+            } else {
+                for (link, &rotation) in ik.links.iter().zip(&best_rotations) {
+                    self.nodes[link.node_index as usize].ik_rotation = rotation;
+                    self.update_local_matrix(link.node_index);
+                    self.update_matrices(link.node_index);
+                }
+
+                break;
+            }
+        }
+    }
+
+    fn solve_ik_iteration(
+        &mut self,
+        index: u32,
+        ik: &BoneIK,
+        iteration: u32,
+        link_states: &mut [IKLinkState],
+    ) {
+        let ik_position = self.position(index);
+
+        for (link, state) in ik.links.iter().zip(link_states.iter_mut()) {
+            if link.node_index == ik.target_index {
+                continue;
+            }
+
+            if let Some(limit) = link.angle_limit.as_ref().and_then(single_axis_limit) {
+                self.solve_ik_plane(link.node_index, ik, ik_position, iteration, limit, state);
+                continue;
+            }
+
+            let (to_ik, to_target) = self.local_directions(link.node_index, ik_position, ik);
+            let angle = Vec3::dot(to_target, to_ik).clamp(-1.0, 1.0).acos();
+
+            if angle < 1e-5 {
+                continue;
+            }
+
+            let axis = Vec3::cross(to_target, to_ik).normalized();
+            let rotation = Quat::from_axis_angle(axis, angle.min(ik.limit_angle));
+            let animated_rotation = self.nodes[link.node_index as usize].local.rotation;
+            let mut link_rotation =
+                self.nodes[link.node_index as usize].ik_rotation * animated_rotation * rotation;
+
+            if let Some(limit) = &link.angle_limit {
+                let euler = link_rotation.into_xyz_angles();
+                let mut clamped = [0f32; 3];
+
+                for axis in 0..3 {
+                    let previous = state.euler[axis];
+                    clamped[axis] = euler[axis]
+                        .clamp(limit.min[axis], limit.max[axis])
+                        .clamp(previous - ik.limit_angle, previous + ik.limit_angle);
+                }
+
+                state.euler = clamped;
+                link_rotation = Quat::from_xyz_angles(clamped);
+            }
+
+            let node = &mut self.nodes[link.node_index as usize];
+            node.ik_rotation = link_rotation * animated_rotation.inverted();
+            self.update_local_matrix(link.node_index);
+            self.update_matrices(link.node_index);
+        }
+    }
+
+    /// Solves a link that may only rotate around a single axis, such as a knee.
+    fn solve_ik_plane(
+        &mut self,
+        link_index: u32,
+        ik: &BoneIK,
+        ik_position: Vec3,
+        iteration: u32,
+        (axis, min, max): (Vec3, f32, f32),
+        state: &mut IKLinkState,
+    ) {
+        let (to_ik, to_target) = self.local_directions(link_index, ik_position, ik);
+        let angle = Vec3::dot(to_target, to_ik)
+            .clamp(-1.0, 1.0)
+            .acos()
+            .min(ik.limit_angle);
+
+        let dot_positive = Vec3::dot(Quat::from_axis_angle(axis, angle) * to_target, to_ik);
+        let dot_negative = Vec3::dot(Quat::from_axis_angle(axis, -angle) * to_target, to_ik);
+        let mut plane_angle = state.plane_angle
+            + if dot_negative < dot_positive {
+                angle
+            } else {
+                -angle
+            };
+
+        // At the first iteration, prefer bending towards the allowed range.
+        if iteration == 0 && (plane_angle < min || max < plane_angle) {
+            if min < -plane_angle && -plane_angle < max {
+                plane_angle = -plane_angle;
+            } else {
+                let half = (min + max) * 0.5;
+
+                if (half + plane_angle).abs() < (half - plane_angle).abs() {
+                    plane_angle = -plane_angle;
+                }
+            }
+        }
+
+        let plane_angle = plane_angle.clamp(min, max);
+        state.plane_angle = plane_angle;
+
+        let node = &mut self.nodes[link_index as usize];
+        node.ik_rotation =
+            Quat::from_axis_angle(axis, plane_angle) * node.local.rotation.inverted();
+        self.update_local_matrix(link_index);
+        self.update_matrices(link_index);
+    }
+
+    /// Returns the directions towards the IK bone and the IK target, in the local space of the link.
+    fn local_directions(&self, link_index: u32, ik_position: Vec3, ik: &BoneIK) -> (Vec3, Vec3) {
+        let inverse = self.nodes[link_index as usize].matrix.inversed();
+        let to_local = |position: Vec3| Vec3::from(Vec4::from_vec3(position, 1.0) * &inverse);
+        (
+            to_local(ik_position).normalized(),
+            to_local(self.position(ik.target_index)).normalized(),
+        )
+    }
+}
+
+#[derive(Default, Clone)]
+struct IKLinkState {
+    euler: [f32; 3],
+    plane_angle: f32,
+}
+
+/// Returns the axis and the range if the limit only allows rotation around a single axis.
+fn single_axis_limit(limit: &BoneAngleLimit) -> Option<(Vec3, f32, f32)> {
+    let is_free = |axis: usize| limit.min[axis] != 0.0 || limit.max[axis] != 0.0;
+
+    match (is_free(0), is_free(1), is_free(2)) {
+        (true, false, false) => Some((Vec3::new(1.0, 0.0, 0.0), limit.min[0], limit.max[0])),
+        (false, true, false) => Some((Vec3::new(0.0, 1.0, 0.0), limit.min[1], limit.max[1])),
+        (false, false, true) => Some((Vec3::new(0.0, 0.0, 1.0), limit.min[2], limit.max[2])),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use asset::assets::{BoneIKLink, BoneInheritance, NodeTransform};
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    fn create_node(
+        index: u32,
+        parent_index: Option<u32>,
+        children_indices: Vec<u32>,
+        translation: Vec3,
+        bone: NodeBone,
+    ) -> Node {
+        Node {
+            index,
+            parent_index,
+            children_indices,
+            name: format!("node{}", index),
+            transform: NodeTransform {
+                matrix: Mat4::translation(translation).elements,
+            },
+            mesh_indices: vec![],
+            bone: Some(bone),
+        }
+    }
+
+    fn create_bone() -> NodeBone {
+        NodeBone {
+            layer: 0,
+            deform_after_physics: false,
+            is_rotatable: true,
+            is_translatable: false,
+            is_visible: true,
+            ik: None,
+            inheritance: None,
+            fixed_axis: None,
+            local_coordinate: None,
+        }
+    }
+
+    fn position(pose: &SkeletonPose, index: u32) -> Vec3 {
+        Vec3::from(Vec4::new(0.0, 0.0, 0.0, 1.0) * pose.matrix(index))
+    }
+
+    #[test]
+    fn check_grant_bone() {
+        let nodes = vec![
+            create_node(0, None, vec![1, 2], Vec3::ZERO, create_bone()),
+            create_node(1, Some(0), vec![], Vec3::ZERO, create_bone()),
+            create_node(
+                2,
+                Some(0),
+                vec![],
+                Vec3::ZERO,
+                NodeBone {
+                    inheritance: Some(BoneInheritance {
+                        node_index: 1,
+                        coefficient: 0.5,
+                        inherit_rotation: true,
+                        inherit_translation: true,
+                    }),
+                    ..create_bone()
+                },
+            ),
+        ];
+        let mut pose = SkeletonPose::new(&nodes);
+
+        pose.local_transform_mut(1).position = Vec3::new(2.0, 0.0, 0.0);
+        pose.local_transform_mut(1).rotation =
+            Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), FRAC_PI_2);
+        pose.evaluate();
+
+        let expected =
+            Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), FRAC_PI_4) * Vec3::new(1.0, 0.0, 0.0);
+        let direction = Vec3::from(Vec4::new(1.0, 0.0, 0.0, 0.0) * pose.matrix(2));
+
+        assert!(Vec3::distance(direction, expected) < 1e-5);
+        assert!(Vec3::distance(position(&pose, 2), Vec3::new(1.0, 0.0, 0.0)) < 1e-5);
+    }
+
+    #[test]
+    fn check_ik_chain_reaches_target() {
+        let nodes = vec![
+            create_node(0, None, vec![1, 4], Vec3::ZERO, create_bone()),
+            create_node(1, Some(0), vec![2], Vec3::new(0.0, 2.0, 0.0), create_bone()),
+            create_node(
+                2,
+                Some(1),
+                vec![3],
+                Vec3::new(0.0, -1.0, 0.0),
+                create_bone(),
+            ),
+            create_node(3, Some(2), vec![], Vec3::new(0.0, -1.0, 0.0), create_bone()),
+            create_node(
+                4,
+                Some(0),
+                vec![],
+                Vec3::ZERO,
+                NodeBone {
+                    ik: Some(BoneIK {
+                        target_index: 3,
+                        loop_count: 40,
+                        limit_angle: 2.0,
+                        links: vec![
+                            BoneIKLink {
+                                node_index: 2,
+                                // a knee, bending around the X axis only
+                                angle_limit: Some(BoneAngleLimit {
+                                    min: [-PI, 0.0, 0.0],
+                                    max: [-0.01, 0.0, 0.0],
+                                }),
+                            },
+                            BoneIKLink {
+                                node_index: 1,
+                                angle_limit: None,
+                            },
+                        ],
+                    }),
+                    ..create_bone()
+                },
+            ),
+        ];
+        let mut pose = SkeletonPose::new(&nodes);
+        let target = Vec3::new(0.0, 0.8, 0.6);
+
+        pose.local_transform_mut(4).position = target;
+        pose.evaluate();
+        assert!(Vec3::distance(position(&pose, 3), target) < 1e-2);
+
+        // the knee is bent within its limit
+        let knee = position(&pose, 2);
+        let hip = position(&pose, 1);
+        let ankle = position(&pose, 3);
+        let bend = Vec3::dot(
+            Vec3::cross(knee - hip, ankle - knee),
+            Vec3::new(1.0, 0.0, 0.0),
+        );
+        assert!(bend < 0.0);
+
+        pose.set_ik_enabled(4, false);
+        pose.evaluate();
+        assert!(Vec3::distance(position(&pose, 3), Vec3::ZERO) < 1e-5);
+    }
+}
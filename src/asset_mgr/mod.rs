@@ -0,0 +1,182 @@
+use crate::{
+    event::event_types::{AssetReloadFailed, AssetReloaded},
+    gfx::{GfxContextHandle, ShaderManager},
+};
+use asset::{AssetKey, TypedAsset};
+use asset_loader::{
+    asset_loaders::RuntimeAssetLoader, AssetDatabase, AssetDatabaseError, AssetLoadError,
+    AssetLoadHandle, AssetLoadProgress, AssetLoader, AssetPackBuilder, AssetPackCompression,
+    AssetPackEntry, AssetPackError, AssetScanReport, AssetWatcher, CookedAssetCache,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AssetReloadError {
+    #[error("failed to reload asset metadata: {0}")]
+    DatabaseError(#[from] AssetDatabaseError),
+    #[error("failed to reload asset: {0}")]
+    LoadError(#[from] AssetLoadError),
+}
+
+#[derive(Error, Debug)]
+pub enum AssetPackBuildError {
+    #[error("failed to scan assets: {0}")]
+    DatabaseError(#[from] AssetDatabaseError),
+    #[error("failed to build asset pack: {0}")]
+    PackError(#[from] AssetPackError),
+}
+
+/// Result of [`build_asset_pack`].
+#[derive(Debug)]
+pub struct AssetPackSummary {
+    pub scan_report: AssetScanReport,
+    pub entries: Vec<AssetPackEntry>,
+}
+
+/// Scans `base_path` and packs all assets found into a single file at `output_path`.
+/// Assets are processed against the semantic tables of `shader_mgr`, so that semantics registered at runtime resolve too.
+pub fn build_asset_pack(
+    base_path: impl Into<PathBuf>,
+    output_path: impl AsRef<Path>,
+    compression: AssetPackCompression,
+    shader_mgr: &ShaderManager,
+) -> Result<AssetPackSummary, AssetPackBuildError> {
+    let mut database = AssetDatabase::new(base_path);
+    let scan_report = database.scan()?;
+    let pipeline_gfx_bridge = shader_mgr.pipeline_gfx_bridge();
+
+    let mut builder =
+        AssetPackBuilder::new(&database, &pipeline_gfx_bridge).with_compression(compression);
+    builder.add_all()?;
+    builder.write_to_file(output_path)?;
+
+    Ok(AssetPackSummary {
+        scan_report,
+        entries: Vec::from_iter(builder.entries().cloned()),
+    })
+}
+
+/// Owns the asset database and the loader that turns indexed assets into GPU-ready [`TypedAsset`]s.
+pub struct AssetManager {
+    database: AssetDatabase,
+    loader: RuntimeAssetLoader,
+    watcher: Option<AssetWatcher>,
+}
+
+impl AssetManager {
+    pub fn new(
+        base_path: impl Into<PathBuf>,
+        gfx_ctx: GfxContextHandle,
+        shader_mgr: &ShaderManager,
+    ) -> Self {
+        let base_path = base_path.into();
+        let cooked_cache = CookedAssetCache::next_to(&base_path);
+
+        Self {
+            database: AssetDatabase::new(base_path),
+            loader: RuntimeAssetLoader::new(gfx_ctx, shader_mgr.pipeline_gfx_bridge())
+                .with_cooked_cache(cooked_cache),
+            watcher: None,
+        }
+    }
+
+    pub fn database(&self) -> &AssetDatabase {
+        &self.database
+    }
+
+    pub fn database_mut(&mut self) -> &mut AssetDatabase {
+        &mut self.database
+    }
+
+    pub fn loader(&self) -> &RuntimeAssetLoader {
+        &self.loader
+    }
+
+    /// Scans the base path of the database for assets. See [`AssetDatabase::scan`].
+    pub fn scan(&mut self) -> Result<AssetScanReport, AssetDatabaseError> {
+        self.database.scan()
+    }
+
+    /// Loads an asset and all of its dependencies.
+    pub fn load(&self, key: &AssetKey) -> Result<TypedAsset, AssetLoadError> {
+        self.loader.load_asset(key, &self.database)
+    }
+
+    /// Starts loading an asset and its dependencies in the background. See [`RuntimeAssetLoader::load_asset_async`].
+    pub fn load_async(&self, key: &AssetKey) -> AssetLoadHandle {
+        self.loader.load_asset_async(key, &self.database)
+    }
+
+    /// Returns the aggregate progress of background loads, e.g. to drive a loading screen.
+    pub fn async_progress(&self) -> AssetLoadProgress {
+        self.loader.async_progress()
+    }
+
+    /// Finishes background loads that are ready to be uploaded, and returns their handles.
+    /// The engine calls this once per frame, so it is rarely needed to call this manually.
+    pub fn poll_async(&self) -> Vec<AssetLoadHandle> {
+        self.loader.poll_async(&self.database)
+    }
+
+    pub fn is_hot_reload_enabled(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Enables hot reload. Registered assets are checked for changes at most once per `interval`.
+    pub fn enable_hot_reload(&mut self, interval: Duration) {
+        match &mut self.watcher {
+            Some(watcher) => watcher.set_interval(interval),
+            None => self.watcher = Some(AssetWatcher::new(interval)),
+        }
+    }
+
+    pub fn disable_hot_reload(&mut self) {
+        self.watcher = None;
+    }
+
+    /// Reloads changed assets along with their alive dependents. Does nothing if hot reload is disabled.
+    /// The engine calls this once per frame, so it is rarely needed to call this manually.
+    pub fn poll_hot_reload(&mut self) -> Vec<Result<AssetReloaded, AssetReloadFailed>> {
+        let changed = match &mut self.watcher {
+            Some(watcher) => watcher.poll(&self.database),
+            None => return vec![],
+        };
+        let mut results = Vec::new();
+
+        for id in changed {
+            let key = AssetKey::Id(id);
+
+            if let Err(err) = self.database.reload(&id) {
+                results.push(Err(AssetReloadFailed {
+                    key,
+                    error: err.into(),
+                }));
+                continue;
+            }
+
+            match self.loader.reload_asset(&key, &self.database) {
+                Ok(reloaded) => {
+                    for reloaded in reloaded {
+                        results.push(Ok(AssetReloaded {
+                            key: reloaded.key,
+                            old: reloaded.old,
+                            new: reloaded.new,
+                        }));
+                    }
+                }
+                Err(err) => {
+                    results.push(Err(AssetReloadFailed {
+                        key,
+                        error: err.into(),
+                    }));
+                }
+            }
+        }
+
+        results
+    }
+}
@@ -1,6 +1,10 @@
 pub mod make_ui_scaler_dirty;
+pub mod reload_assets;
 pub mod render;
+pub mod update_animators;
 pub mod update_camera_transform_buffer;
+pub mod update_skeleton_poses;
+pub mod update_skinned_mesh_bones;
 pub mod update_ui_element;
 pub mod update_ui_raycast_grid;
 pub mod update_ui_scaler;
@@ -0,0 +1,407 @@
+use crate::{
+    event::event_types::{AssetReloadFailed, AssetReloaded},
+    gfx::{
+        BindGroupEntryResource, BindGroupLayoutCache, Material, MaterialHandle, MeshRenderer,
+        NinePatch, NinePatchHandle, ShaderHandle, SkinnedMeshRenderer, Sprite, SpriteHandle,
+        Texture, TextureHandle, UIElementRenderer, UIElementSprite, UITextRenderer,
+    },
+    ContextHandle,
+};
+use asset::{assets::TextureAsset, TypedAsset};
+use specs::prelude::*;
+use std::{collections::HashMap, sync::Arc};
+
+/// Polls hot reload and swaps reloaded shaders and textures into the materials used by renderers,
+/// and reloaded textures into the sprites of UI elements.
+/// The resulting events are kept until [`ReloadAssets::dispatch_events`] is called,
+/// since event handlers may want to access the world.
+pub struct ReloadAssets {
+    ctx: ContextHandle,
+    results: Vec<Result<AssetReloaded, AssetReloadFailed>>,
+}
+
+impl ReloadAssets {
+    pub fn new(ctx: ContextHandle) -> Self {
+        Self {
+            ctx,
+            results: Vec::new(),
+        }
+    }
+
+    pub fn dispatch_events(&mut self) {
+        for result in self.results.drain(..) {
+            match result {
+                Ok(reloaded) => self.ctx.event_mgr().dispatch(&reloaded),
+                Err(failed) => self.ctx.event_mgr().dispatch(&failed),
+            }
+        }
+    }
+}
+
+impl<'a> System<'a> for ReloadAssets {
+    type SystemData = (
+        ReadStorage<'a, MeshRenderer>,
+        ReadStorage<'a, SkinnedMeshRenderer>,
+        WriteStorage<'a, UIElementRenderer>,
+        ReadStorage<'a, UITextRenderer>,
+    );
+
+    fn run(
+        &mut self,
+        (mesh_renderers, skinned_mesh_renderers, mut ui_element_renderers, ui_text_renderers): Self::SystemData,
+    ) {
+        self.results
+            .extend(self.ctx.asset_mgr_mut().poll_hot_reload());
+
+        let reloaded = Vec::from_iter(
+            self.results
+                .iter()
+                .filter_map(|result| result.as_ref().ok())
+                .filter(|reloaded| reloaded.old.is_some()),
+        );
+
+        if reloaded.is_empty() {
+            return;
+        }
+
+        // Materials are often shared between renderers; visit each of them once.
+        let materials = HashMap::<_, MaterialHandle>::from_iter(
+            mesh_renderers
+                .join()
+                .filter_map(|renderer| renderer.material())
+                .chain(
+                    skinned_mesh_renderers
+                        .join()
+                        .filter_map(|renderer| renderer.material()),
+                )
+                .chain(
+                    ui_element_renderers
+                        .join()
+                        .filter_map(|renderer| renderer.material()),
+                )
+                .chain(
+                    ui_text_renderers
+                        .join()
+                        .filter_map(|renderer| renderer.material()),
+                )
+                .map(|material| (material.as_ptr(), material.clone())),
+        );
+        let mut shaders = HashMap::new();
+
+        for material in materials.values() {
+            for reloaded in &reloaded {
+                swap_material_asset(&self.ctx, material, reloaded, &mut shaders);
+            }
+        }
+
+        // Sprites refer to textures directly rather than through materials.
+        let device = &self.ctx.gfx_ctx().device;
+        let mut render_mgr = self.ctx.render_mgr_mut();
+
+        for renderer in (&mut ui_element_renderers).join() {
+            for reloaded in &reloaded {
+                swap_sprite_asset(
+                    renderer,
+                    reloaded,
+                    device,
+                    render_mgr.bind_group_layout_cache(),
+                );
+            }
+        }
+    }
+}
+
+fn swap_material_asset(
+    ctx: &ContextHandle,
+    material: &MaterialHandle,
+    reloaded: &AssetReloaded,
+    shaders: &mut HashMap<*const wgpu::ShaderModule, ShaderHandle>,
+) {
+    match (&reloaded.old, &reloaded.new) {
+        (Some(TypedAsset::Shader(old)), TypedAsset::Shader(new)) => {
+            if !Arc::ptr_eq(&material.read().shader.shader_module, old.handle()) {
+                return;
+            }
+
+            let mut render_mgr = ctx.render_mgr_mut();
+            let shader = shaders
+                .entry(Arc::as_ptr(old.handle()))
+                .or_insert_with(|| {
+                    ctx.shader_mgr()
+                        .create_shader_from_asset(render_mgr.bind_group_layout_cache(), &**new)
+                })
+                .clone();
+            let mut swapped = Material::new(shader, render_mgr.pipeline_layout_cache());
+            let mut material = material.write();
+
+            // Carry the properties over; the ones the new shader no longer has are dropped.
+            for (key, index) in &material.bind_properties {
+                let entry =
+                    &material.bind_group_holders[index.group_index].entries[index.entry_index];
+
+                if let Some(resource) = &entry.resource {
+                    swapped.set_bind_property(key, resource.clone());
+                }
+            }
+
+            for (name, property) in &material.instance_properties {
+                if let Some(value) = &property.value {
+                    swapped.set_per_instance_property(name, value.clone());
+                }
+            }
+
+            swapped.update_bind_group(&ctx.gfx_ctx().device);
+            *material = swapped;
+        }
+        (Some(TypedAsset::Texture(old)), TypedAsset::Texture(new)) => {
+            let mut material = material.write();
+            let mut is_swapped = false;
+
+            for bind_group_holder in &mut material.bind_group_holders {
+                for entry in &mut bind_group_holder.entries {
+                    let is_entry_swapped = match &mut entry.resource {
+                        Some(BindGroupEntryResource::TextureView { texture_view }) => {
+                            swap_arc(texture_view, old.view_handle(), new.view_handle())
+                        }
+                        Some(BindGroupEntryResource::TextureViewArray { texture_views }) => {
+                            let mut is_swapped = false;
+
+                            for texture_view in texture_views {
+                                is_swapped |=
+                                    swap_arc(texture_view, old.view_handle(), new.view_handle());
+                            }
+
+                            is_swapped
+                        }
+                        Some(BindGroupEntryResource::Sampler { sampler }) => {
+                            swap_arc(sampler, old.sampler_handle(), new.sampler_handle())
+                        }
+                        _ => false,
+                    };
+
+                    if is_entry_swapped {
+                        bind_group_holder.is_dirty = true;
+                        is_swapped = true;
+                    }
+                }
+            }
+
+            if is_swapped {
+                material.update_bind_group(&ctx.gfx_ctx().device);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn swap_sprite_asset(
+    renderer: &mut UIElementRenderer,
+    reloaded: &AssetReloaded,
+    device: &wgpu::Device,
+    bind_group_layout_cache: &mut BindGroupLayoutCache,
+) {
+    let (Some(TypedAsset::Texture(old)), TypedAsset::Texture(new)) = (&reloaded.old, &reloaded.new)
+    else {
+        return;
+    };
+    let sprite = match renderer.sprite() {
+        Some(sprite) if Arc::ptr_eq(&sprite.texture().texture, old.handle()) => sprite,
+        _ => return,
+    };
+    let texture = sprite.texture();
+
+    // Named sprites and nine-patches are recognized by their own samplers, so that they pick up new mappings.
+    // Others keep their mappings over the new texture.
+    let swapped = match sprite {
+        UIElementSprite::Sprite(sprite) => {
+            let swapped = old
+                .sprites()
+                .iter()
+                .find(|named| Arc::ptr_eq(&named.sampler_handle, &texture.sampler))
+                .and_then(|named| Sprite::from_asset(&**new, &named.name))
+                .unwrap_or_else(|| {
+                    Sprite::new(swap_texture(texture, &**old, &**new), sprite.mapping())
+                });
+            UIElementSprite::sprite(SpriteHandle::new(swapped))
+        }
+        UIElementSprite::NinePatch(nine_patch) => {
+            let swapped = old
+                .nine_patches()
+                .iter()
+                .find(|named| Arc::ptr_eq(&named.sampler_handle, &texture.sampler))
+                .and_then(|named| NinePatch::from_asset(&**new, &named.name))
+                .unwrap_or_else(|| {
+                    NinePatch::new(swap_texture(texture, &**old, &**new), nine_patch.mapping())
+                });
+            UIElementSprite::nine_patch(NinePatchHandle::new(swapped))
+        }
+    };
+
+    renderer.set_sprite(swapped, device, bind_group_layout_cache);
+}
+
+/// Creates a texture sharing the resources of the new texture asset, except for a custom sampler.
+fn swap_texture(
+    texture: &TextureHandle,
+    old: &dyn TextureAsset,
+    new: &dyn TextureAsset,
+) -> TextureHandle {
+    let mut sampler = texture.sampler.clone();
+    swap_arc(&mut sampler, old.sampler_handle(), new.sampler_handle());
+
+    TextureHandle::new(Texture {
+        sampler,
+        ..Texture::from_asset(new)
+    })
+}
+
+fn swap_arc<T>(target: &mut Arc<T>, old: &Arc<T>, new: &Arc<T>) -> bool {
+    if !Arc::ptr_eq(target, old) {
+        return false;
+    }
+
+    *target = new.clone();
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gfx::{
+            GfxContext, GfxContextHandle, NinePatchTexelMapping, SemanticPipelineGfxBridge,
+            SpriteTexelMapping,
+        },
+        test_utils::TempDir,
+    };
+    use asset::AssetKey;
+    use asset_loader::{asset_loaders::RuntimeAssetLoader, AssetDatabase, AssetLoader};
+    use image::RgbaImage;
+    use uuid::Uuid;
+    use winit::dpi::PhysicalSize;
+
+    const TEXTURE_METADATA: &str = r#"
+[texture]
+is_srgb = false
+has_alpha = true
+filter_mode = "point"
+address_mode_u = "clamp"
+address_mode_v = "clamp"
+
+[sprite.icon]
+x_min = 0
+x_max = 2
+y_min = 0
+y_max = 2
+
+[nine_patch.frame]
+x_min = 0
+x_mid_min = 1
+x_mid_max = 3
+x_max = 4
+y_min = 0
+y_mid_min = 1
+y_mid_max = 3
+y_max = 4
+"#;
+
+    fn sprite_texture(renderer: &UIElementRenderer) -> &TextureHandle {
+        renderer.sprite().unwrap().texture()
+    }
+
+    #[test]
+    fn check_reload_sprite_textures() {
+        // Any adapter will do, including software ones such as llvmpipe.
+        let gfx_ctx = GfxContextHandle::new(
+            pollster::block_on(GfxContext::new_headless(PhysicalSize::new(1, 1)))
+                .expect("the test requires a wgpu adapter"),
+        );
+        let mut bind_group_layout_cache = BindGroupLayoutCache::new(gfx_ctx.clone());
+
+        let dir = TempDir::new();
+        let base_path = dir.path();
+        let id = Uuid::new_v4();
+        RgbaImage::new(4, 4)
+            .save(base_path.join("texture.png"))
+            .unwrap();
+        std::fs::write(
+            base_path.join("texture.meta.toml"),
+            format!("[asset]\nid = \"{}\"\n{}", id, TEXTURE_METADATA),
+        )
+        .unwrap();
+
+        let mut database = AssetDatabase::new(base_path);
+        database.scan().unwrap();
+        let loader =
+            RuntimeAssetLoader::new(gfx_ctx.clone(), SemanticPipelineGfxBridge::built_in());
+        let key = AssetKey::Id(id);
+        let texture = loader.load_asset(&key, &database).unwrap();
+        let texture = texture.as_texture().unwrap();
+
+        // A named sprite, a named nine-patch, and a sprite made from the whole texture.
+        let sprites = [
+            UIElementSprite::sprite(SpriteHandle::new(
+                Sprite::from_asset(&**texture, "icon").unwrap(),
+            )),
+            UIElementSprite::nine_patch(NinePatchHandle::new(
+                NinePatch::from_asset(&**texture, "frame").unwrap(),
+            )),
+            UIElementSprite::sprite(SpriteHandle::new(Sprite::new(
+                TextureHandle::new(Texture::from_asset(&**texture)),
+                SpriteTexelMapping::new(0, 4, 0, 4),
+            ))),
+        ];
+        let mut renderers = sprites.map(|sprite| {
+            let mut renderer = UIElementRenderer::new();
+            renderer.set_sprite(sprite, &gfx_ctx.device, &mut bind_group_layout_cache);
+            renderer
+        });
+
+        let reloaded = loader.reload_asset(&key, &database).unwrap().remove(0);
+        let reloaded = AssetReloaded {
+            key: reloaded.key,
+            old: reloaded.old,
+            new: reloaded.new,
+        };
+
+        for renderer in &mut renderers {
+            swap_sprite_asset(
+                renderer,
+                &reloaded,
+                &gfx_ctx.device,
+                &mut bind_group_layout_cache,
+            );
+        }
+
+        let new = reloaded.new.as_texture().unwrap();
+
+        for renderer in &renderers {
+            assert!(Arc::ptr_eq(&sprite_texture(renderer).texture, new.handle()));
+            assert!(Arc::ptr_eq(
+                &sprite_texture(renderer).view,
+                new.view_handle()
+            ));
+        }
+
+        assert!(Arc::ptr_eq(
+            &sprite_texture(&renderers[0]).sampler,
+            &new.sprites()[0].sampler_handle
+        ));
+        assert!(Arc::ptr_eq(
+            &sprite_texture(&renderers[1]).sampler,
+            &new.nine_patches()[0].sampler_handle
+        ));
+        assert!(Arc::ptr_eq(
+            &sprite_texture(&renderers[2]).sampler,
+            new.sampler_handle()
+        ));
+
+        match renderers[1].sprite().unwrap() {
+            UIElementSprite::NinePatch(nine_patch) => assert_eq!(
+                nine_patch.mapping(),
+                NinePatchTexelMapping::new(0, 1, 3, 4, 0, 1, 3, 4)
+            ),
+            _ => panic!("expected a nine-patch"),
+        }
+    }
+}
@@ -1,56 +1,93 @@
 use crate::{
     gfx::{
-        BindGroupLayoutCache, Camera, MeshRenderer, Renderer, UIElementRenderer, UITextRenderer,
+        semantic_bindings, BindGroupLayoutCache, BuiltInShaderManager, Camera, Light, LightBuffer,
+        Material, MaterialHandle, MeshRenderer, RenderManager, Renderer, SkinnedMeshRenderer,
+        UIElementRenderer, UITextRenderer, BUILT_IN_SHADER_SHADOW_CASTER,
+        BUILT_IN_SHADER_SKINNED_SHADOW_CASTER,
     },
-    object::Object,
+    object::{Object, ObjectId},
     ui::UISize,
     use_context,
 };
 use image::EncodableLayout;
 use specs::prelude::*;
-use std::mem::size_of;
+use std::{collections::HashMap, mem::size_of};
 use wgpu::{
     BindGroup, BindGroupDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferAddress,
-    BufferBindingType, BufferDescriptor, BufferSize, BufferUsages, Device, ShaderStages,
+    BufferBindingType, BufferDescriptor, BufferSize, BufferUsages, Device, Queue, ShaderStages,
 };
 
 pub struct RenderSystem {
-    screen_size_buffer: Buffer,
-    screen_size_bind_group: BindGroup,
+    /// Cameras may render into targets of different sizes, so each camera has its own buffer.
+    screen_size_buffers: HashMap<ObjectId, ScreenSizeBuffer>,
+    /// Lights along with their shadow matrices differ by camera, so each camera has its own buffer.
+    light_buffers: HashMap<ObjectId, LightBuffer>,
+    shadow_caster_material: MaterialHandle,
+    skinned_shadow_caster_material: MaterialHandle,
 }
 
 impl RenderSystem {
-    pub fn new(device: &Device, bind_group_layout_cache: &mut BindGroupLayoutCache) -> Self {
-        let screen_size_buffer = device.create_buffer(&BufferDescriptor {
+    pub fn new(render_mgr: &mut RenderManager, built_in_shader_mgr: &BuiltInShaderManager) -> Self {
+        let shadow_caster_material = MaterialHandle::new(Material::new(
+            built_in_shader_mgr
+                .find_shader(BUILT_IN_SHADER_SHADOW_CASTER)
+                .unwrap(),
+            render_mgr.pipeline_layout_cache(),
+        ));
+        let skinned_shadow_caster_material = MaterialHandle::new(Material::new(
+            built_in_shader_mgr
+                .find_shader(BUILT_IN_SHADER_SKINNED_SHADOW_CASTER)
+                .unwrap(),
+            render_mgr.pipeline_layout_cache(),
+        ));
+
+        Self {
+            screen_size_buffers: HashMap::new(),
+            light_buffers: HashMap::new(),
+            shadow_caster_material,
+            skinned_shadow_caster_material,
+        }
+    }
+}
+
+/// The size of the render target of a camera, bound to UI shaders as `screen_size`.
+struct ScreenSizeBuffer {
+    buffer: Buffer,
+    bind_group: BindGroup,
+}
+
+impl ScreenSizeBuffer {
+    fn new(device: &Device, bind_group_layout_cache: &mut BindGroupLayoutCache) -> Self {
+        let buffer = device.create_buffer(&BufferDescriptor {
             label: None,
             size: size_of::<[f32; 4]>() as u64 as BufferAddress,
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
-        let screen_size_bind_group_layout =
-            bind_group_layout_cache.create_layout(vec![BindGroupLayoutEntry {
-                binding: 0,
-                visibility: ShaderStages::VERTEX_FRAGMENT,
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: Some(BufferSize::new(size_of::<[f32; 4]>() as u64).unwrap()),
-                },
-                count: None,
-            }]);
-        let screen_size_bind_group = device.create_bind_group(&BindGroupDescriptor {
+        let bind_group_layout = bind_group_layout_cache.create_layout(vec![BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: Some(BufferSize::new(size_of::<[f32; 4]>() as u64).unwrap()),
+            },
+            count: None,
+        }]);
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
             label: None,
-            layout: screen_size_bind_group_layout.as_ref(),
+            layout: bind_group_layout.as_ref(),
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: screen_size_buffer.as_entire_binding(),
+                resource: buffer.as_entire_binding(),
             }],
         });
 
-        Self {
-            screen_size_buffer,
-            screen_size_bind_group,
-        }
+        Self { buffer, bind_group }
+    }
+
+    fn update(&self, queue: &Queue, width: f32, height: f32) {
+        queue.write_buffer(&self.buffer, 0, [width, height, 0.0f32, 0.0f32].as_bytes());
     }
 }
 
@@ -58,7 +95,9 @@ impl<'a> System<'a> for RenderSystem {
     type SystemData = (
         ReadStorage<'a, Object>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, Light>,
         WriteStorage<'a, MeshRenderer>,
+        WriteStorage<'a, SkinnedMeshRenderer>,
         WriteStorage<'a, UIElementRenderer>,
         WriteStorage<'a, UITextRenderer>,
         ReadStorage<'a, UISize>,
@@ -69,7 +108,9 @@ impl<'a> System<'a> for RenderSystem {
         (
             objects,
             cameras,
+            lights,
             mut mesh_renderers,
+            mut skinned_mesh_renderers,
             mut ui_element_renderers,
             mut ui_text_renderers,
             ui_sizes,
@@ -79,30 +120,35 @@ impl<'a> System<'a> for RenderSystem {
         let mut glyph_mgr = context.glyph_mgr_mut();
         let mut render_mgr = context.render_mgr_mut();
         let shader_mgr = context.shader_mgr();
+        let screen_mgr = context.screen_mgr();
         let world_mgr = context.object_mgr();
         let object_hierarchy = world_mgr.object_hierarchy();
 
-        context
-            .gfx_ctx()
-            .queue
-            .write_buffer(&self.screen_size_buffer, 0, {
-                let screen_mgr = context.screen_mgr();
-                [
-                    screen_mgr.width() as f32,
-                    screen_mgr.height() as f32,
-                    0.0f32,
-                    0.0f32,
-                ]
-                .as_bytes()
-            });
-
-        let surface_texture = context.gfx_ctx().surface.get_current_texture().unwrap();
-        let surface_texture_view = surface_texture.texture.create_view(&Default::default());
+        let active_lights = Vec::from_iter(
+            (&objects, &lights)
+                .join()
+                .filter(|(object, _)| object_hierarchy.is_active(object.object_id()))
+                .map(|(object, light)| (light, object_hierarchy.matrix(object.object_id())))
+                .take(semantic_bindings::MAX_LIGHT_COUNT),
+        );
+
+        let frame = render_mgr.acquire_frame().unwrap();
         let mut encoder = render_mgr.create_encoder();
 
         let mut camera_objects = (&objects, &cameras).join().collect::<Vec<_>>();
         camera_objects.sort_unstable_by_key(|&(_, camera)| camera.depth);
 
+        self.screen_size_buffers.retain(|object_id, _| {
+            camera_objects
+                .iter()
+                .any(|(object, _)| object.object_id() == *object_id)
+        });
+        self.light_buffers.retain(|object_id, _| {
+            camera_objects
+                .iter()
+                .any(|(object, _)| object.object_id() == *object_id)
+        });
+
         for (object, camera) in camera_objects {
             let standard_ui_vertex_buffer = render_mgr.standard_ui_vertex_buffer().clone();
             let (bind_group_layout_cache, pipeline_cache) = render_mgr.split_caches();
@@ -111,7 +157,46 @@ impl<'a> System<'a> for RenderSystem {
                 continue;
             }
 
+            let camera_matrix = object_hierarchy.matrix(object.object_id());
+            let view_matrix = camera_matrix.inversed();
+
+            let screen_size_buffer = self
+                .screen_size_buffers
+                .entry(object.object_id())
+                .or_insert_with(|| {
+                    ScreenSizeBuffer::new(&context.gfx_ctx().device, bind_group_layout_cache)
+                });
+            let (width, height) = camera.target.size(&screen_mgr);
+            screen_size_buffer.update(&context.gfx_ctx().queue, width, height);
+
+            let light_buffer = self
+                .light_buffers
+                .entry(object.object_id())
+                .or_insert_with(|| {
+                    LightBuffer::new(&context.gfx_ctx().device, bind_group_layout_cache)
+                });
+            let shadow_matrices = Vec::from_iter(active_lights.iter().map(|(light, matrix)| {
+                light.shadow_matrices(
+                    matrix,
+                    camera_matrix,
+                    &camera.projection,
+                    camera.aspect(&screen_mgr),
+                )
+            }));
+            let shadow_map_count = light_buffer.update(
+                &context.gfx_ctx().queue,
+                camera_matrix,
+                active_lights.iter().zip(&shadow_matrices).map(
+                    |(&(light, matrix), shadow_matrices)| {
+                        (light, matrix, shadow_matrices.as_slice())
+                    },
+                ),
+            );
+
             let mut mesh_sub_renderers = Vec::with_capacity(1024);
+            let mut shadow_sub_renderers = Vec::with_capacity(1024);
+            let mut skinned_mesh_sub_renderers = Vec::with_capacity(1024);
+            let mut skinned_shadow_sub_renderers = Vec::with_capacity(1024);
 
             let mut ui_element_sub_renderers = Vec::with_capacity(1024);
             let mut ui_text_sub_renderers = Vec::with_capacity(1024);
@@ -123,6 +208,28 @@ impl<'a> System<'a> for RenderSystem {
                     continue;
                 }
 
+                // Meshes hidden from the camera by its mask still cast shadows into its cascades.
+                if let Some((center, radius)) =
+                    mesh_renderer.bounding_sphere(object_hierarchy.matrix(object_id))
+                {
+                    mesh_renderer.select_lod(camera.projected_size(
+                        &screen_mgr,
+                        &view_matrix,
+                        center,
+                        radius,
+                    ));
+                }
+
+                if shadow_map_count != 0 {
+                    if let Some(renderer) = mesh_renderer.shadow_sub_renderer(
+                        &self.shadow_caster_material,
+                        shader_mgr,
+                        pipeline_cache,
+                    ) {
+                        shadow_sub_renderers.push((object_id, renderer));
+                    }
+                }
+
                 if mesh_renderer.mask() & camera.mask == 0 {
                     continue;
                 }
@@ -138,6 +245,38 @@ impl<'a> System<'a> for RenderSystem {
                 mesh_sub_renderers.push((object_id, renderer));
             }
 
+            for (object, skinned_mesh_renderer) in (&objects, &mut skinned_mesh_renderers).join() {
+                let object_id = object.object_id();
+
+                if !object_hierarchy.is_active(object.object_id()) {
+                    continue;
+                }
+
+                if shadow_map_count != 0 {
+                    if let Some(renderer) = skinned_mesh_renderer.shadow_sub_renderer(
+                        &self.skinned_shadow_caster_material,
+                        shader_mgr,
+                        pipeline_cache,
+                    ) {
+                        skinned_shadow_sub_renderers.push((object_id, renderer));
+                    }
+                }
+
+                if skinned_mesh_renderer.mask() & camera.mask == 0 {
+                    continue;
+                }
+
+                let renderer = if let Some(renderer) =
+                    skinned_mesh_renderer.sub_renderer(shader_mgr, pipeline_cache)
+                {
+                    renderer
+                } else {
+                    continue;
+                };
+
+                skinned_mesh_sub_renderers.push((object_id, renderer));
+            }
+
             for (object, ui_element_renderer, ui_size) in
                 (&objects, &mut ui_element_renderers, &ui_sizes).join()
             {
@@ -218,8 +357,11 @@ impl<'a> System<'a> for RenderSystem {
 
             ui_sub_renderers.sort_unstable_by_key(|&(index, _, _)| index);
 
-            let mut commands =
-                Vec::with_capacity(mesh_sub_renderers.len() + ui_sub_renderers.len());
+            let mut commands = Vec::with_capacity(
+                mesh_sub_renderers.len()
+                    + skinned_mesh_sub_renderers.len()
+                    + ui_sub_renderers.len(),
+            );
 
             for (object_id, renderer) in &mesh_sub_renderers {
                 let command =
@@ -227,16 +369,57 @@ impl<'a> System<'a> for RenderSystem {
                 commands.push(command);
             }
 
+            for (object_id, renderer) in &skinned_mesh_sub_renderers {
+                let command =
+                    render_mgr.build_rendering_command(*object_id, object_hierarchy, renderer);
+                commands.push(command);
+            }
+
             for (_, object_id, renderer) in &ui_sub_renderers {
                 let command =
                     render_mgr.build_rendering_command(*object_id, object_hierarchy, *renderer);
                 commands.push(command);
             }
 
+            let mut shadow_commands =
+                Vec::with_capacity(shadow_sub_renderers.len() + skinned_shadow_sub_renderers.len());
+
+            for (object_id, renderer) in &shadow_sub_renderers {
+                let command =
+                    render_mgr.build_rendering_command(*object_id, object_hierarchy, renderer);
+                shadow_commands.push(command);
+            }
+
+            for (object_id, renderer) in &skinned_shadow_sub_renderers {
+                let command =
+                    render_mgr.build_rendering_command(*object_id, object_hierarchy, renderer);
+                shadow_commands.push(command);
+            }
+
+            for layer in 0..shadow_map_count {
+                let mut render_pass = render_mgr
+                    .begin_shadow_map_render_pass(&mut encoder, layer)
+                    .unwrap();
+                // Casters are rendered from the light, through the light space matrix of the layer.
+                let shadow_caster_bind_group =
+                    light_buffer.shadow_caster_bind_group(layer).unwrap();
+
+                for cmd in &shadow_commands {
+                    cmd.render(
+                        &mut render_pass,
+                        shadow_caster_bind_group,
+                        &screen_size_buffer.bind_group,
+                        light_buffer.bind_group(),
+                        render_mgr.shadow_maps().bind_group(),
+                    );
+                }
+            }
+
             let mut render_pass = render_mgr
                 .begin_frame_buffer_render_pass(
                     &mut encoder,
-                    &surface_texture_view,
+                    frame.view(),
+                    &camera.target,
                     &camera.clear_mode,
                 )
                 .unwrap();
@@ -245,12 +428,368 @@ impl<'a> System<'a> for RenderSystem {
                 cmd.render(
                     &mut render_pass,
                     &camera.bind_group,
-                    &self.screen_size_bind_group,
+                    &screen_size_buffer.bind_group,
+                    light_buffer.bind_group(),
+                    render_mgr.shadow_maps().bind_group(),
                 );
             }
         }
 
         render_mgr.finish_frame(vec![encoder.finish()]);
-        surface_texture.present();
+        frame.present();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        gfx::{
+            Camera, CameraClearMode, CameraProjection, Color, Font, FontHandle, Material,
+            MaterialHandle, RenderTarget, Sprite, SpriteHandle, SpriteTexelMapping,
+            UIElementRenderer, UIElementSprite, UITextRenderer, BUILT_IN_SHADER_UI_ELEMENT_NORMAL,
+            BUILT_IN_SHADER_UI_TEXT_NORMAL,
+        },
+        math::Vec2,
+        object::ObjectId,
+        test_utils::headless_engine,
+        ui::{UIAnchor, UIElement, UIMargin, UIScaleMode, UIScaler, UISize},
+        ContextHandle,
+    };
+    use image::{Rgba, RgbaImage};
+    use specs::prelude::*;
+
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 48;
+
+    fn spawn_camera(
+        ctx: &ContextHandle,
+        mask: u32,
+        depth: u32,
+        color: Color,
+        target: RenderTarget,
+    ) {
+        let camera = Camera::new(
+            mask,
+            depth,
+            CameraClearMode::all(color, 1.0, 0),
+            CameraProjection::orthographic(10.0, -50.0, 50.0),
+            &ctx.gfx_ctx().device,
+            ctx.render_mgr_mut().bind_group_layout_cache(),
+        )
+        .with_target(target);
+        let mut world = ctx.world_mut();
+        let (_, builder) = ctx
+            .object_mgr_mut()
+            .create_object_builder(&mut world, None, None);
+        builder.with(camera).build();
+    }
+
+    /// Spawns a UI element covering `min` to `max` of the screen, in fractions from the left bottom.
+    fn spawn_ui_element(ctx: &ContextHandle, min: Vec2, max: Vec2) -> ObjectId {
+        spawn_scaled_ui_element(
+            ctx,
+            UIScaler {
+                mode: UIScaleMode::Stretch,
+                reference_size: Vec2::new(WIDTH as f32, HEIGHT as f32),
+            },
+            min,
+            max,
+        )
+    }
+
+    /// Spawns a UI element covering `min` to `max` of the area given by `scaler`, in fractions from the left bottom.
+    fn spawn_scaled_ui_element(
+        ctx: &ContextHandle,
+        scaler: UIScaler,
+        min: Vec2,
+        max: Vec2,
+    ) -> ObjectId {
+        let mut object_mgr = ctx.object_mgr_mut();
+        let mut world = ctx.world_mut();
+        let (root, builder) = object_mgr.create_object_builder(&mut world, None, None);
+        builder
+            .with(scaler)
+            .with(UISize {
+                width: 0.0,
+                height: 0.0,
+            })
+            .build();
+        let (element, builder) = object_mgr.create_object_builder(&mut world, None, None);
+        builder
+            .with(UIElement::new(
+                UIAnchor::new(min, max),
+                UIMargin::zero(),
+                false,
+            ))
+            .with(UISize {
+                width: 0.0,
+                height: 0.0,
+            })
+            .build();
+        object_mgr
+            .object_hierarchy_mut()
+            .set_parent(element.object_id, Some(root.object_id));
+        element.object_id
+    }
+
+    fn add_ui_element_renderer(
+        ctx: &ContextHandle,
+        object_id: ObjectId,
+        sprite: UIElementSprite,
+        color: Color,
+        mask: u32,
+    ) {
+        let mut render_mgr = ctx.render_mgr_mut();
+        let mut renderer = UIElementRenderer::new();
+        renderer.set_mask(mask);
+        renderer.set_color(color);
+        renderer.set_material(MaterialHandle::new(Material::new(
+            ctx.built_in_shader_mgr()
+                .find_shader(BUILT_IN_SHADER_UI_ELEMENT_NORMAL)
+                .unwrap(),
+            render_mgr.pipeline_layout_cache(),
+        )));
+        renderer.set_sprite(
+            sprite,
+            &ctx.gfx_ctx().device,
+            render_mgr.bind_group_layout_cache(),
+        );
+
+        let world = ctx.world();
+        let entity = ctx.object_mgr().object_hierarchy().entity(object_id);
+        world
+            .write_component::<UIElementRenderer>()
+            .insert(entity, renderer)
+            .unwrap();
+    }
+
+    fn to_rgba(color: Color) -> Rgba<u8> {
+        Rgba([color.r, color.g, color.b, color.a].map(|value| (value * 255.0).round() as u8))
+    }
+
+    /// Builds the expected frame: `background` overlaid by rectangles of `(x, y, width, height, color)`,
+    /// in pixels from the left top.
+    fn golden_image(background: Color, rects: &[(u32, u32, u32, u32, Color)]) -> RgbaImage {
+        let mut image = RgbaImage::from_pixel(WIDTH, HEIGHT, to_rgba(background));
+
+        for &(x, y, width, height, color) in rects {
+            for (_, _, pixel) in image
+                .enumerate_pixels_mut()
+                .filter(|(px, py, _)| (x..x + width).contains(px) && (y..y + height).contains(py))
+            {
+                *pixel = to_rgba(color);
+            }
+        }
+
+        image
+    }
+
+    fn assert_golden_image(actual: &RgbaImage, expected: &RgbaImage) {
+        assert_eq!(actual.dimensions(), expected.dimensions());
+
+        let mismatches = Vec::from_iter(
+            actual
+                .enumerate_pixels()
+                .zip(expected.pixels())
+                .filter(|((_, _, actual), expected)| {
+                    actual
+                        .0
+                        .iter()
+                        .zip(expected.0)
+                        .any(|(a, e)| a.abs_diff(e) > 2)
+                })
+                .map(|((x, y, actual), expected)| (x, y, *actual, *expected)),
+        );
+
+        assert!(
+            mismatches.is_empty(),
+            "{} pixels differ from the golden image, first at {:?}",
+            mismatches.len(),
+            mismatches[0]
+        );
+    }
+
+    #[test]
+    fn check_render_clear_color() {
+        let (mut engine, _guard) = headless_engine(WIDTH, HEIGHT);
+        let ctx = engine.context();
+        let background = Color::from_rgb(0.2, 0.4, 0.6);
+        spawn_camera(&ctx, 0xFFFF_FFFF, 0, background, RenderTarget::Surface);
+
+        engine.step();
+
+        assert_golden_image(
+            &engine.capture_frame().unwrap(),
+            &golden_image(background, &[]),
+        );
+    }
+
+    #[test]
+    fn check_render_ui_element() {
+        let (mut engine, _guard) = headless_engine(WIDTH, HEIGHT);
+        let ctx = engine.context();
+        let background = Color::black();
+        spawn_camera(&ctx, 0xFFFF_FFFF, 0, background, RenderTarget::Surface);
+        // the left bottom quarter of the screen
+        let element = spawn_ui_element(&ctx, Vec2::new(0.0, 0.0), Vec2::new(0.5, 0.5));
+        let white = ctx.render_mgr().white_texture().clone();
+        add_ui_element_renderer(
+            &ctx,
+            element,
+            UIElementSprite::sprite(SpriteHandle::new(Sprite::new(
+                white,
+                SpriteTexelMapping::new(0, 1, 0, 1),
+            ))),
+            Color::red(),
+            0xFFFF_FFFF,
+        );
+
+        engine.step();
+
+        assert_golden_image(
+            &engine.capture_frame().unwrap(),
+            &golden_image(
+                background,
+                &[(0, HEIGHT / 2, WIDTH / 2, HEIGHT / 2, Color::red())],
+            ),
+        );
+    }
+
+    #[test]
+    fn check_render_target_as_sprite() {
+        let (mut engine, _guard) = headless_engine(WIDTH, HEIGHT);
+        let ctx = engine.context();
+        let target = ctx.render_mgr().create_render_target(16, 16);
+        // The first camera only fills the target, which the second one shows on the right half.
+        spawn_camera(
+            &ctx,
+            0x1,
+            0,
+            Color::green(),
+            RenderTarget::Texture(target.clone()),
+        );
+        spawn_camera(&ctx, 0x2, 1, Color::blue(), RenderTarget::Surface);
+        let element = spawn_ui_element(&ctx, Vec2::new(0.5, 0.0), Vec2::new(1.0, 1.0));
+        add_ui_element_renderer(
+            &ctx,
+            element,
+            UIElementSprite::sprite(target.sprite()),
+            Color::white(),
+            0x2,
+        );
+
+        engine.step();
+
+        assert_golden_image(
+            &engine.capture_frame().unwrap(),
+            &golden_image(
+                Color::blue(),
+                &[(WIDTH / 2, 0, WIDTH / 2, HEIGHT, Color::green())],
+            ),
+        );
+    }
+
+    #[test]
+    fn check_render_ui_element_into_target() {
+        let (mut engine, _guard) = headless_engine(WIDTH, HEIGHT);
+        let ctx = engine.context();
+        let target = ctx.render_mgr().create_render_target(16, 16);
+        // The element is exactly as large as the target, so it only fills the target when it is
+        // laid out against the size of the target rather than the surface.
+        spawn_camera(
+            &ctx,
+            0x1,
+            0,
+            Color::green(),
+            RenderTarget::Texture(target.clone()),
+        );
+        spawn_camera(&ctx, 0x2, 1, Color::blue(), RenderTarget::Surface);
+        let inner = spawn_scaled_ui_element(
+            &ctx,
+            UIScaler {
+                mode: UIScaleMode::Constant,
+                reference_size: Vec2::new(16.0, 16.0),
+            },
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+        );
+        let white = ctx.render_mgr().white_texture().clone();
+        add_ui_element_renderer(
+            &ctx,
+            inner,
+            UIElementSprite::sprite(SpriteHandle::new(Sprite::new(
+                white,
+                SpriteTexelMapping::new(0, 1, 0, 1),
+            ))),
+            Color::red(),
+            0x1,
+        );
+        let element = spawn_ui_element(&ctx, Vec2::new(0.5, 0.0), Vec2::new(1.0, 1.0));
+        add_ui_element_renderer(
+            &ctx,
+            element,
+            UIElementSprite::sprite(target.sprite()),
+            Color::white(),
+            0x2,
+        );
+
+        engine.step();
+
+        assert_golden_image(
+            &engine.capture_frame().unwrap(),
+            &golden_image(
+                Color::blue(),
+                &[(WIDTH / 2, 0, WIDTH / 2, HEIGHT, Color::red())],
+            ),
+        );
+    }
+
+    #[test]
+    fn check_render_ui_text() {
+        // Glyph shapes depend on the font, so only the coverage of the text is checked.
+        let font = fontdue::Font::from_bytes(
+            include_bytes!("../test_data/DejaVuSansMono.ttf").as_slice(),
+            Default::default(),
+        )
+        .unwrap();
+        let (mut engine, _guard) = headless_engine(WIDTH, HEIGHT);
+        let ctx = engine.context();
+        spawn_camera(&ctx, 0xFFFF_FFFF, 0, Color::black(), RenderTarget::Surface);
+        // the right half of the screen
+        let element = spawn_ui_element(&ctx, Vec2::new(0.5, 0.0), Vec2::new(1.0, 1.0));
+        let mut renderer = UITextRenderer::new();
+        renderer.set_color(Color::white());
+        renderer.set_font_size_with_recommended_values(24.0);
+        renderer.set_font(FontHandle::new(Font::with_default(font)));
+        renderer.set_text("H".to_owned());
+        renderer.set_material(MaterialHandle::new(Material::new(
+            ctx.built_in_shader_mgr()
+                .find_shader(BUILT_IN_SHADER_UI_TEXT_NORMAL)
+                .unwrap(),
+            ctx.render_mgr_mut().pipeline_layout_cache(),
+        )));
+        {
+            let world = ctx.world();
+            let entity = ctx.object_mgr().object_hierarchy().entity(element);
+            world
+                .write_component::<UITextRenderer>()
+                .insert(entity, renderer)
+                .unwrap();
+        }
+
+        engine.step();
+
+        let frame = engine.capture_frame().unwrap();
+        let lit = Vec::from_iter(
+            frame
+                .enumerate_pixels()
+                .filter(|(_, _, pixel)| pixel[0] > 128),
+        );
+        assert!(!lit.is_empty());
+        assert!(lit.iter().all(|&(x, _, _)| WIDTH / 2 <= x));
+        // white text over black blends into grays only
+        assert!(frame
+            .pixels()
+            .all(|pixel| pixel[0].abs_diff(pixel[1]) <= 2 && pixel[1].abs_diff(pixel[2]) <= 2));
     }
 }
@@ -0,0 +1,82 @@
+use crate::{
+    animation::Animator, gfx::SkinnedMeshRenderer, object::Object, transform::Transform,
+    ContextHandle,
+};
+use specs::prelude::*;
+
+/// Advances animators and writes the sampled poses into the transforms of their object subtrees,
+/// and the sampled morph weights into the skinned mesh renderers of the subtrees.
+pub struct UpdateAnimators {
+    ctx: ContextHandle,
+}
+
+impl UpdateAnimators {
+    pub fn new(ctx: ContextHandle) -> Self {
+        Self { ctx }
+    }
+}
+
+impl<'a> System<'a> for UpdateAnimators {
+    type SystemData = (
+        ReadStorage<'a, Object>,
+        WriteStorage<'a, Animator>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, SkinnedMeshRenderer>,
+    );
+
+    fn run(
+        &mut self,
+        (objects, mut animators, mut transforms, mut skinned_mesh_renderers): Self::SystemData,
+    ) {
+        let delta = self.ctx.time_mgr().delta_time().as_secs_f32();
+        let mut object_mgr = self.ctx.object_mgr_mut();
+        let mut animated = Vec::new();
+
+        {
+            let object_name_registry = object_mgr.object_name_registry();
+            let object_hierarchy = object_mgr.object_hierarchy();
+
+            for (object, animator) in (&objects, &mut animators).join() {
+                if !object_hierarchy.is_active(object.object_id()) {
+                    continue;
+                }
+
+                animator.update(delta);
+
+                for &target in object_hierarchy.object_and_children(object.object_id()) {
+                    let entity = object_hierarchy.entity(target);
+
+                    if let Some(skinned_mesh_renderer) = skinned_mesh_renderers.get_mut(entity) {
+                        for index in 0..skinned_mesh_renderer.morph_count() {
+                            let mut weight = skinned_mesh_renderer.morph_weight(index);
+                            let morph_name = skinned_mesh_renderer.morph_name(index).unwrap_or("");
+
+                            if animator.sample_morph(morph_name, &mut weight) {
+                                skinned_mesh_renderer.set_morph_weight(index, weight);
+                            }
+                        }
+                    }
+
+                    let name = match object_name_registry.name(target) {
+                        Some(name) => name,
+                        None => continue,
+                    };
+                    let transform = match transforms.get_mut(entity) {
+                        Some(transform) => transform,
+                        None => continue,
+                    };
+
+                    if animator.sample(name, transform) {
+                        animated.push(target);
+                    }
+                }
+            }
+        }
+
+        let object_hierarchy = object_mgr.object_hierarchy_mut();
+
+        for target in animated {
+            object_hierarchy.set_dirty(target);
+        }
+    }
+}
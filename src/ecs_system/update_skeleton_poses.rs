@@ -0,0 +1,96 @@
+use crate::{
+    animation::{Animator, SkeletonPose},
+    gfx::SkinnedMeshRenderer,
+    object::Object,
+    physics::ModelPhysics,
+    ContextHandle,
+};
+use specs::prelude::*;
+
+/// Evaluates skeleton poses, sampling the animator and simulating the physics of the same object if any,
+/// and passes the resulting joint transforms to the skinned mesh renderers in the subtree that do not follow objects.
+pub struct UpdateSkeletonPoses {
+    ctx: ContextHandle,
+}
+
+impl UpdateSkeletonPoses {
+    pub fn new(ctx: ContextHandle) -> Self {
+        Self { ctx }
+    }
+}
+
+impl<'a> System<'a> for UpdateSkeletonPoses {
+    type SystemData = (
+        ReadStorage<'a, Object>,
+        ReadStorage<'a, Animator>,
+        WriteStorage<'a, SkeletonPose>,
+        WriteStorage<'a, ModelPhysics>,
+        WriteStorage<'a, SkinnedMeshRenderer>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            objects,
+            animators,
+            mut skeleton_poses,
+            mut model_physics,
+            mut skinned_mesh_renderers,
+        ): Self::SystemData,
+    ) {
+        let object_mgr = self.ctx.object_mgr();
+        let object_hierarchy = object_mgr.object_hierarchy();
+        let delta = self.ctx.time_mgr().delta_time().as_secs_f32();
+
+        for (object, skeleton_pose, animator, physics) in (
+            &objects,
+            &mut skeleton_poses,
+            animators.maybe(),
+            (&mut model_physics).maybe(),
+        )
+            .join()
+        {
+            if !object_hierarchy.is_active(object.object_id()) {
+                continue;
+            }
+
+            if let Some(animator) = animator {
+                skeleton_pose.sample(animator);
+            }
+
+            match physics {
+                Some(physics) => {
+                    skeleton_pose.evaluate_before_physics();
+                    physics.update(delta, skeleton_pose);
+                    skeleton_pose.evaluate_after_physics();
+                }
+                None => skeleton_pose.evaluate(),
+            }
+
+            let matrix = object_hierarchy.matrix(object.object_id());
+
+            for &target in object_hierarchy.object_and_children(object.object_id()) {
+                let skinned_mesh_renderer =
+                    match skinned_mesh_renderers.get_mut(object_hierarchy.entity(target)) {
+                        Some(skinned_mesh_renderer) => skinned_mesh_renderer,
+                        None => continue,
+                    };
+
+                if !skinned_mesh_renderer.joint_objects().is_empty() {
+                    continue;
+                }
+
+                // Joint transforms are relative to the sub mesh, which is placed at its object.
+                let relative_matrix = matrix * object_hierarchy.matrix(target).inversed();
+                let transforms = Vec::from_iter(
+                    skinned_mesh_renderer
+                        .joint_nodes()
+                        .iter()
+                        .map(|&joint| skeleton_pose.matrix(joint) * relative_matrix.clone()),
+                );
+
+                skinned_mesh_renderer.set_joint_transforms(&transforms);
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{gfx::SkinnedMeshRenderer, object::Object, ContextHandle};
+use specs::prelude::*;
+
+/// Updates the bone matrices of skinned mesh renderers whose joints follow objects,
+/// and uploads them along with the morph weights.
+pub struct UpdateSkinnedMeshBones {
+    ctx: ContextHandle,
+}
+
+impl UpdateSkinnedMeshBones {
+    pub fn new(ctx: ContextHandle) -> Self {
+        Self { ctx }
+    }
+}
+
+impl<'a> System<'a> for UpdateSkinnedMeshBones {
+    type SystemData = (
+        ReadStorage<'a, Object>,
+        WriteStorage<'a, SkinnedMeshRenderer>,
+    );
+
+    fn run(&mut self, (objects, mut skinned_mesh_renderers): Self::SystemData) {
+        let world_mgr = self.ctx.object_mgr();
+        let object_hierarchy = world_mgr.object_hierarchy();
+
+        for (object, skinned_mesh_renderer) in (&objects, &mut skinned_mesh_renderers).join() {
+            if !object_hierarchy.is_active(object.object_id()) {
+                continue;
+            }
+
+            if !skinned_mesh_renderer.joint_objects().is_empty() {
+                // Joint transforms are relative to the sub mesh, which is placed at the object.
+                let inverse_matrix = object_hierarchy.matrix(object.object_id()).inversed();
+                let transforms = Vec::from_iter(
+                    skinned_mesh_renderer
+                        .joint_objects()
+                        .iter()
+                        .map(|&joint| object_hierarchy.matrix(joint) * inverse_matrix.clone()),
+                );
+
+                skinned_mesh_renderer.set_joint_transforms(&transforms);
+            }
+
+            skinned_mesh_renderer.upload_bone_matrices(&self.ctx.gfx_ctx.queue);
+            skinned_mesh_renderer.upload_morphs(&self.ctx.gfx_ctx.queue);
+        }
+    }
+}
@@ -1,5 +1,24 @@
+use crate::asset_mgr::AssetReloadError;
+use asset::{AssetKey, TypedAsset};
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Update;
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LateUpdate;
+
+/// Dispatched when an asset has been hot-reloaded. Materials used by renderers have already been updated.
+#[derive(Clone)]
+pub struct AssetReloaded {
+    pub key: AssetKey,
+    /// The previous version, if it was still alive.
+    pub old: Option<TypedAsset>,
+    pub new: TypedAsset,
+}
+
+/// Dispatched when an asset has changed on disk but could not be reloaded. The previous version stays in use.
+#[derive(Debug)]
+pub struct AssetReloadFailed {
+    pub key: AssetKey,
+    pub error: AssetReloadError,
+}
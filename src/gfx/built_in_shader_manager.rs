@@ -14,6 +14,23 @@ pub const BUILT_IN_SHADER_UI_ELEMENT_NORMAL: BuiltInShaderKey =
     BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(1) });
 pub const BUILT_IN_SHADER_UI_TEXT_NORMAL: BuiltInShaderKey =
     BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(11) });
+pub const BUILT_IN_SHADER_SKINNED_MESH_NORMAL: BuiltInShaderKey =
+    BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(21) });
+/// Shades [`MeshRenderer`](super::MeshRenderer)s with the [`Light`](super::Light)s of the frame.
+/// Takes `diffuse_texture` and `diffuse_sampler` properties, like [`BUILT_IN_SHADER_SKINNED_MESH_NORMAL`].
+pub const BUILT_IN_SHADER_MESH_LIT: BuiltInShaderKey =
+    BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(31) });
+/// Shades [`MeshRenderer`](super::MeshRenderer)s with metallic-roughness materials, lit by the [`Light`](super::Light)s
+/// of the frame and an [`EnvironmentMap`](super::EnvironmentMap). See [`create_pbr_material`](super::create_pbr_material).
+pub const BUILT_IN_SHADER_MESH_PBR: BuiltInShaderKey =
+    BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(32) });
+/// Renders [`MeshRenderer`](super::MeshRenderer)s into the [`ShadowMaps`](super::ShadowMaps) through depth-only pipelines.
+pub const BUILT_IN_SHADER_SHADOW_CASTER: BuiltInShaderKey =
+    BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(41) });
+/// Renders [`SkinnedMeshRenderer`](super::SkinnedMeshRenderer)s into the [`ShadowMaps`](super::ShadowMaps),
+/// deformed by their morphs and bones like [`BUILT_IN_SHADER_SKINNED_MESH_NORMAL`].
+pub const BUILT_IN_SHADER_SKINNED_SHADOW_CASTER: BuiltInShaderKey =
+    BuiltInShaderKey::new(unsafe { NonZeroU64::new_unchecked(42) });
 
 pub struct BuiltInShaderManager {
     shaders: HashMap<BuiltInShaderKey, ShaderHandle>,
@@ -43,6 +60,36 @@ impl BuiltInShaderManager {
             BUILT_IN_SHADER_UI_TEXT_NORMAL,
             include_str!("./built_in_shaders/ui_text.normal.wgsl"),
         );
+        self.add_shader(
+            shader_mgr,
+            bind_group_layout_cache,
+            BUILT_IN_SHADER_SKINNED_MESH_NORMAL,
+            include_str!("./built_in_shaders/skinned_mesh.normal.wgsl"),
+        );
+        self.add_shader(
+            shader_mgr,
+            bind_group_layout_cache,
+            BUILT_IN_SHADER_MESH_LIT,
+            include_str!("./built_in_shaders/mesh.lit.wgsl"),
+        );
+        self.add_shader(
+            shader_mgr,
+            bind_group_layout_cache,
+            BUILT_IN_SHADER_MESH_PBR,
+            include_str!("./built_in_shaders/mesh.pbr.wgsl"),
+        );
+        self.add_shader(
+            shader_mgr,
+            bind_group_layout_cache,
+            BUILT_IN_SHADER_SHADOW_CASTER,
+            include_str!("./built_in_shaders/shadow_caster.wgsl"),
+        );
+        self.add_shader(
+            shader_mgr,
+            bind_group_layout_cache,
+            BUILT_IN_SHADER_SKINNED_SHADOW_CASTER,
+            include_str!("./built_in_shaders/shadow_caster.skinned.wgsl"),
+        );
     }
 
     fn add_shader(
@@ -0,0 +1,142 @@
+struct Light {
+  position: vec4<f32>,
+  direction: vec4<f32>,
+  color: vec4<f32>,
+  spot: vec4<f32>,
+};
+
+struct Lights {
+  count: u32,
+  lights: array<Light, 16>,
+  shadow_matrices: array<mat4x4<f32>, 8>,
+  camera_position: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera_transform: mat4x4<f32>;
+@group(1) @binding(0) var<uniform> lights: Lights;
+@group(2) @binding(0) var shadow_maps: texture_depth_2d_array;
+@group(3) @binding(0) var diffuse_texture: texture_2d<f32>;
+@group(3) @binding(1) var diffuse_sampler: sampler;
+
+struct InstanceInput {
+  @location(0) transform_row_0: vec4<f32>,
+  @location(1) transform_row_1: vec4<f32>,
+  @location(2) transform_row_2: vec4<f32>,
+  @location(3) transform_row_3: vec4<f32>,
+  @location(4) receive_shadows: f32,
+};
+
+struct VertexInput {
+  @location(5) position: vec3<f32>,
+  @location(6) normal: vec3<f32>,
+  @location(7) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) world_position: vec3<f32>,
+  @location(1) normal: vec3<f32>,
+  @location(2) uv: vec2<f32>,
+  @location(3) @interpolate(flat) receive_shadows: f32,
+};
+
+struct FragmentOutput {
+  @location(0) color: vec4<f32>,
+};
+
+const AMBIENT: f32 = 0.1;
+const SHADOW_MAP_SIZE: i32 = 1024;
+
+// Smoothly reaches zero at the range, on top of the inverse square falloff.
+fn attenuation(distance: f32, range: f32) -> f32 {
+  let ratio = distance / max(range, 0.0001);
+  let window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+  return window * window / (distance * distance + 1.0);
+}
+
+// Fraction of the light passing the shadow casters, filtered over 2x2 texels.
+// Cascades are ordered nearest first, so the first one containing the position is used.
+fn shadow(light: Light, world_position: vec3<f32>) -> f32 {
+  let first_layer = i32(light.spot.z);
+  let layer_count = i32(light.spot.w);
+
+  for (var layer = first_layer; layer < first_layer + layer_count; layer = layer + 1) {
+    let clip = lights.shadow_matrices[layer] * vec4<f32>(world_position, 1.0);
+    let ndc = clip.xyz / clip.w;
+
+    if (clip.w <= 0.0 || any(abs(ndc.xy) > vec2<f32>(1.0)) || ndc.z < 0.0 || 1.0 < ndc.z) {
+      continue;
+    }
+
+    let texel = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5) * f32(SHADOW_MAP_SIZE) - 0.5;
+    let base = vec2<i32>(floor(texel));
+    let weight = fract(texel);
+    let depth = ndc.z - light.color.a;
+    var lit = vec4<f32>(0.0);
+
+    for (var index = 0; index < 4; index = index + 1) {
+      let coords = clamp(base + vec2<i32>(index & 1, index >> 1u), vec2<i32>(0), vec2<i32>(SHADOW_MAP_SIZE - 1));
+      lit[index] = select(0.0, 1.0, depth <= textureLoad(shadow_maps, coords, layer, 0));
+    }
+
+    return mix(mix(lit.x, lit.y, weight.x), mix(lit.z, lit.w, weight.x), weight.y);
+  }
+
+  return 1.0;
+}
+
+// Lambertian contribution of a light to a surface.
+fn shade(light: Light, world_position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+  let kind = u32(light.position.w);
+
+  if (kind == 0u) {
+    return light.color.rgb * max(dot(normal, -light.direction.xyz), 0.0);
+  }
+
+  let to_light = light.position.xyz - world_position;
+  let distance = length(to_light);
+  let direction = to_light / max(distance, 0.0001);
+  var intensity = attenuation(distance, light.direction.w);
+
+  if (kind == 2u) {
+    let cos_angle = dot(-direction, light.direction.xyz);
+    intensity = intensity * smoothstep(light.spot.y, light.spot.x, cos_angle);
+  }
+
+  return light.color.rgb * max(dot(normal, direction), 0.0) * intensity;
+}
+
+@vertex
+fn vs_main(instance: InstanceInput, vertex: VertexInput) -> VertexOutput {
+  var out: VertexOutput;
+  let transform = mat4x4<f32>(instance.transform_row_0, instance.transform_row_1, instance.transform_row_2, instance.transform_row_3);
+  let world_position = transform * vec4<f32>(vertex.position, 1.0);
+  out.position = camera_transform * world_position;
+  out.world_position = world_position.xyz;
+  out.normal = normalize((transform * vec4<f32>(vertex.normal, 0.0)).xyz);
+  out.uv = vertex.uv;
+  out.receive_shadows = instance.receive_shadows;
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+  var out: FragmentOutput;
+  let normal = normalize(in.normal);
+  var light = vec3<f32>(AMBIENT);
+
+  for (var index = 0u; index < min(lights.count, 16u); index = index + 1u) {
+    let current = lights.lights[index];
+    var visibility = 1.0;
+
+    if (0.5 < in.receive_shadows) {
+      visibility = shadow(current, in.world_position);
+    }
+
+    light = light + shade(current, in.world_position, normal) * visibility;
+  }
+
+  let diffuse = textureSample(diffuse_texture, diffuse_sampler, in.uv);
+  out.color = vec4<f32>(light * diffuse.rgb, diffuse.a);
+  return out;
+}
@@ -0,0 +1,249 @@
+struct Light {
+  position: vec4<f32>,
+  direction: vec4<f32>,
+  color: vec4<f32>,
+  spot: vec4<f32>,
+};
+
+struct Lights {
+  count: u32,
+  lights: array<Light, 16>,
+  shadow_matrices: array<mat4x4<f32>, 8>,
+  camera_position: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera_transform: mat4x4<f32>;
+@group(1) @binding(0) var<uniform> lights: Lights;
+@group(2) @binding(0) var shadow_maps: texture_depth_2d_array;
+@group(3) @binding(0) var<uniform> base_color_factor: vec4<f32>;
+@group(3) @binding(1) var<uniform> surface_factors: vec4<f32>;
+@group(3) @binding(2) var<uniform> emissive_factor: vec4<f32>;
+@group(3) @binding(3) var base_color_texture: texture_2d<f32>;
+@group(3) @binding(4) var base_color_sampler: sampler;
+@group(3) @binding(5) var metallic_roughness_texture: texture_2d<f32>;
+@group(3) @binding(6) var metallic_roughness_sampler: sampler;
+@group(3) @binding(7) var normal_texture: texture_2d<f32>;
+@group(3) @binding(8) var normal_sampler: sampler;
+@group(3) @binding(9) var occlusion_texture: texture_2d<f32>;
+@group(3) @binding(10) var occlusion_sampler: sampler;
+@group(3) @binding(11) var emissive_texture: texture_2d<f32>;
+@group(3) @binding(12) var emissive_sampler: sampler;
+@group(3) @binding(13) var environment_texture: texture_cube<f32>;
+@group(3) @binding(14) var environment_sampler: sampler;
+
+struct InstanceInput {
+  @location(0) transform_row_0: vec4<f32>,
+  @location(1) transform_row_1: vec4<f32>,
+  @location(2) transform_row_2: vec4<f32>,
+  @location(3) transform_row_3: vec4<f32>,
+  @location(4) receive_shadows: f32,
+};
+
+struct VertexInput {
+  @location(5) position: vec3<f32>,
+  @location(6) normal: vec3<f32>,
+  @location(7) uv: vec2<f32>,
+  @location(8) tangent: vec3<f32>,
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) world_position: vec3<f32>,
+  @location(1) normal: vec3<f32>,
+  @location(2) tangent: vec3<f32>,
+  @location(3) uv: vec2<f32>,
+  @location(4) @interpolate(flat) receive_shadows: f32,
+};
+
+struct FragmentOutput {
+  @location(0) color: vec4<f32>,
+};
+
+// Direction towards a light and the light arriving from it.
+struct LightSample {
+  direction: vec3<f32>,
+  radiance: vec3<f32>,
+};
+
+const PI: f32 = 3.14159265;
+const SHADOW_MAP_SIZE: i32 = 1024;
+
+// Smoothly reaches zero at the range, on top of the inverse square falloff.
+fn attenuation(distance: f32, range: f32) -> f32 {
+  let ratio = distance / max(range, 0.0001);
+  let window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+  return window * window / (distance * distance + 1.0);
+}
+
+// Fraction of the light passing the shadow casters, filtered over 2x2 texels.
+// Cascades are ordered nearest first, so the first one containing the position is used.
+fn shadow(light: Light, world_position: vec3<f32>) -> f32 {
+  let first_layer = i32(light.spot.z);
+  let layer_count = i32(light.spot.w);
+
+  for (var layer = first_layer; layer < first_layer + layer_count; layer = layer + 1) {
+    let clip = lights.shadow_matrices[layer] * vec4<f32>(world_position, 1.0);
+    let ndc = clip.xyz / clip.w;
+
+    if (clip.w <= 0.0 || any(abs(ndc.xy) > vec2<f32>(1.0)) || ndc.z < 0.0 || 1.0 < ndc.z) {
+      continue;
+    }
+
+    let texel = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5) * f32(SHADOW_MAP_SIZE) - 0.5;
+    let base = vec2<i32>(floor(texel));
+    let weight = fract(texel);
+    let depth = ndc.z - light.color.a;
+    var lit = vec4<f32>(0.0);
+
+    for (var index = 0; index < 4; index = index + 1) {
+      let coords = clamp(base + vec2<i32>(index & 1, index >> 1u), vec2<i32>(0), vec2<i32>(SHADOW_MAP_SIZE - 1));
+      lit[index] = select(0.0, 1.0, depth <= textureLoad(shadow_maps, coords, layer, 0));
+    }
+
+    return mix(mix(lit.x, lit.y, weight.x), mix(lit.z, lit.w, weight.x), weight.y);
+  }
+
+  return 1.0;
+}
+
+// Light intensities are scaled by PI, so that a white light of intensity 1 fully lights a white surface
+// facing it, as with the Lambertian shader.
+fn sample_light(light: Light, world_position: vec3<f32>) -> LightSample {
+  let kind = u32(light.position.w);
+  var incoming: LightSample;
+
+  if (kind == 0u) {
+    incoming.direction = -light.direction.xyz;
+    incoming.radiance = light.color.rgb * PI;
+    return incoming;
+  }
+
+  let to_light = light.position.xyz - world_position;
+  let distance = length(to_light);
+  incoming.direction = to_light / max(distance, 0.0001);
+  var intensity = attenuation(distance, light.direction.w);
+
+  if (kind == 2u) {
+    let cos_angle = dot(-incoming.direction, light.direction.xyz);
+    intensity = intensity * smoothstep(light.spot.y, light.spot.x, cos_angle);
+  }
+
+  incoming.radiance = light.color.rgb * intensity * PI;
+  return incoming;
+}
+
+// Textures are stored without sRGB formats, and so is the frame buffer.
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+  return pow(color, vec3<f32>(2.2));
+}
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+  return pow(color, vec3<f32>(1.0 / 2.2));
+}
+
+// Trowbridge-Reitz (GGX) normal distribution.
+fn distribution_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+  let alpha2 = alpha * alpha;
+  let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+  return alpha2 / (PI * d * d);
+}
+
+// Height-correlated Smith visibility, including the 1 / (4 n.l n.v) term of the specular BRDF.
+fn visibility_smith(n_dot_v: f32, n_dot_l: f32, alpha: f32) -> f32 {
+  let alpha2 = alpha * alpha;
+  let ggx_v = n_dot_l * sqrt(n_dot_v * n_dot_v * (1.0 - alpha2) + alpha2);
+  let ggx_l = n_dot_v * sqrt(n_dot_l * n_dot_l * (1.0 - alpha2) + alpha2);
+  return 0.5 / max(ggx_v + ggx_l, 0.0001);
+}
+
+fn fresnel_schlick(v_dot_h: f32, f0: vec3<f32>) -> vec3<f32> {
+  return f0 + (vec3<f32>(1.0) - f0) * pow(1.0 - v_dot_h, 5.0);
+}
+
+// Analytic fit of the pre-integrated specular BRDF of the split sum approximation, by Karis.
+fn environment_brdf(f0: vec3<f32>, roughness: f32, n_dot_v: f32) -> vec3<f32> {
+  let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+  let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+  let r = roughness * c0 + c1;
+  let a004 = min(r.x * r.x, exp2(-9.28 * n_dot_v)) * r.x + r.y;
+  let ab = vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+  return f0 * ab.x + ab.y;
+}
+
+@vertex
+fn vs_main(instance: InstanceInput, vertex: VertexInput) -> VertexOutput {
+  var out: VertexOutput;
+  let transform = mat4x4<f32>(instance.transform_row_0, instance.transform_row_1, instance.transform_row_2, instance.transform_row_3);
+  let world_position = transform * vec4<f32>(vertex.position, 1.0);
+  out.position = camera_transform * world_position;
+  out.world_position = world_position.xyz;
+  out.normal = normalize((transform * vec4<f32>(vertex.normal, 0.0)).xyz);
+  out.tangent = (transform * vec4<f32>(vertex.tangent, 0.0)).xyz;
+  out.uv = vertex.uv;
+  out.receive_shadows = instance.receive_shadows;
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+  var out: FragmentOutput;
+  let base_color_texel = textureSample(base_color_texture, base_color_sampler, in.uv);
+  let metallic_roughness_texel = textureSample(metallic_roughness_texture, metallic_roughness_sampler, in.uv);
+  let normal_texel = textureSample(normal_texture, normal_sampler, in.uv);
+  let occlusion_texel = textureSample(occlusion_texture, occlusion_sampler, in.uv);
+  let emissive_texel = textureSample(emissive_texture, emissive_sampler, in.uv);
+
+  let base_color = vec4<f32>(srgb_to_linear(base_color_texel.rgb), base_color_texel.a) * base_color_factor;
+  let metallic = clamp(surface_factors.x * metallic_roughness_texel.b, 0.0, 1.0);
+  let roughness = clamp(surface_factors.y * metallic_roughness_texel.g, 0.04, 1.0);
+  let alpha = roughness * roughness;
+  let occlusion = mix(1.0, occlusion_texel.r, surface_factors.w);
+  let emissive = srgb_to_linear(emissive_texel.rgb) * emissive_factor.rgb;
+
+  // The handedness of the tangent space is not imported, so the bitangent is assumed to follow the right hand.
+  let geometric_normal = normalize(in.normal);
+  let tangent = normalize(in.tangent - geometric_normal * dot(geometric_normal, in.tangent));
+  let bitangent = cross(geometric_normal, tangent);
+  let tangent_normal = normal_texel.xyz * 2.0 - 1.0;
+  let normal = normalize(mat3x3<f32>(tangent, bitangent, geometric_normal) * vec3<f32>(tangent_normal.xy * surface_factors.z, tangent_normal.z));
+
+  let view = normalize(lights.camera_position.xyz - in.world_position);
+  let n_dot_v = max(dot(normal, view), 0.0001);
+  let f0 = mix(vec3<f32>(0.04), base_color.rgb, metallic);
+  let diffuse_color = base_color.rgb * (1.0 - metallic);
+  var color = emissive;
+
+  for (var index = 0u; index < min(lights.count, 16u); index = index + 1u) {
+    let current = lights.lights[index];
+    let incoming = sample_light(current, in.world_position);
+    let n_dot_l = dot(normal, incoming.direction);
+
+    if (n_dot_l <= 0.0) {
+      continue;
+    }
+
+    var visibility = 1.0;
+
+    if (0.5 < in.receive_shadows) {
+      visibility = shadow(current, in.world_position);
+    }
+
+    let half_vector = normalize(view + incoming.direction);
+    let n_dot_h = max(dot(normal, half_vector), 0.0);
+    let v_dot_h = max(dot(view, half_vector), 0.0);
+    let fresnel = fresnel_schlick(v_dot_h, f0);
+    let specular = fresnel * distribution_ggx(n_dot_h, alpha) * visibility_smith(n_dot_v, n_dot_l, alpha);
+    let diffuse = (vec3<f32>(1.0) - fresnel) * diffuse_color / PI;
+    color = color + (diffuse + specular) * incoming.radiance * n_dot_l * visibility;
+  }
+
+  // Mip levels of the environment map stand in for prefiltered radiance; the smallest one for irradiance.
+  let max_level = f32(textureNumLevels(environment_texture) - 1u);
+  let irradiance = textureSampleLevel(environment_texture, environment_sampler, normal, max_level).rgb;
+  let reflection = reflect(-view, normal);
+  let radiance = textureSampleLevel(environment_texture, environment_sampler, reflection, roughness * max_level).rgb;
+  color = color + (diffuse_color * irradiance + radiance * environment_brdf(f0, roughness, n_dot_v)) * occlusion;
+
+  out.color = vec4<f32>(linear_to_srgb(color), base_color.a);
+  return out;
+}
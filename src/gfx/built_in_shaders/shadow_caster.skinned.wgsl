@@ -0,0 +1,72 @@
+@group(0) @binding(0) var<uniform> camera_transform: mat4x4<f32>;
+@group(1) @binding(0) var<uniform> bone_matrices: array<mat4x4<f32>, 256>;
+@group(2) @binding(0) var morph_targets: texture_2d<u32>;
+
+struct InstanceInput {
+  @location(0) transform_row_0: vec4<f32>,
+  @location(1) transform_row_1: vec4<f32>,
+  @location(2) transform_row_2: vec4<f32>,
+  @location(3) transform_row_3: vec4<f32>,
+};
+
+struct VertexInput {
+  @location(4) position: vec3<f32>,
+  @location(5) joints: vec4<u32>,
+  @location(6) weights: vec4<f32>,
+};
+
+// Morph targets are laid out in rows of 1024 texels, as in skinned_mesh.normal.wgsl.
+fn load_morph_texel(index: u32) -> vec4<u32> {
+  return textureLoad(morph_targets, vec2<i32>(i32(index % 1024u), i32(index / 1024u)), 0);
+}
+
+fn apply_morphs(vertex_index: u32, position: vec3<f32>) -> vec3<f32> {
+  var out = position;
+
+  // A single row means that there are no morph targets.
+  if (textureDimensions(morph_targets).y <= 1u) {
+    return out;
+  }
+
+  let header = load_morph_texel(1024u + vertex_index);
+
+  for (var index = 0u; index < header.y; index = index + 1u) {
+    let offset = load_morph_texel(header.x + index * 2u);
+    let weights = load_morph_texel(offset.w / 4u);
+    let weight = bitcast<f32>(weights[offset.w % 4u]);
+
+    if (weight != 0.0) {
+      out = out + bitcast<vec3<f32>>(offset.xyz) * weight;
+    }
+  }
+
+  return out;
+}
+
+// Linear blend skinning. Vertices without any weight are left in bind pose.
+fn skin_matrix(joints: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+  if (dot(weights, vec4<f32>(1.0)) <= 0.0) {
+    return mat4x4<f32>(
+      vec4<f32>(1.0, 0.0, 0.0, 0.0),
+      vec4<f32>(0.0, 1.0, 0.0, 0.0),
+      vec4<f32>(0.0, 0.0, 1.0, 0.0),
+      vec4<f32>(0.0, 0.0, 0.0, 1.0),
+    );
+  }
+
+  return bone_matrices[joints.x] * weights.x
+    + bone_matrices[joints.y] * weights.y
+    + bone_matrices[joints.z] * weights.z
+    + bone_matrices[joints.w] * weights.w;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: InstanceInput, vertex: VertexInput) -> @builtin(position) vec4<f32> {
+  let transform = mat4x4<f32>(instance.transform_row_0, instance.transform_row_1, instance.transform_row_2, instance.transform_row_3) * skin_matrix(vertex.joints, vertex.weights);
+  return camera_transform * transform * vec4<f32>(apply_morphs(vertex_index, vertex.position), 1.0);
+}
+
+// Only depth is written; pipelines of this shader have no fragment stage.
+@fragment
+fn fs_main() {
+}
@@ -0,0 +1,23 @@
+@group(0) @binding(0) var<uniform> camera_transform: mat4x4<f32>;
+
+struct InstanceInput {
+  @location(0) transform_row_0: vec4<f32>,
+  @location(1) transform_row_1: vec4<f32>,
+  @location(2) transform_row_2: vec4<f32>,
+  @location(3) transform_row_3: vec4<f32>,
+};
+
+struct VertexInput {
+  @location(4) position: vec3<f32>,
+};
+
+@vertex
+fn vs_main(instance: InstanceInput, vertex: VertexInput) -> @builtin(position) vec4<f32> {
+  let transform = mat4x4<f32>(instance.transform_row_0, instance.transform_row_1, instance.transform_row_2, instance.transform_row_3);
+  return camera_transform * transform * vec4<f32>(vertex.position, 1.0);
+}
+
+// Only depth is written; pipelines of this shader have no fragment stage.
+@fragment
+fn fs_main() {
+}
@@ -0,0 +1,108 @@
+@group(0) @binding(0) var<uniform> camera_transform: mat4x4<f32>;
+@group(1) @binding(0) var<uniform> bone_matrices: array<mat4x4<f32>, 256>;
+@group(2) @binding(0) var morph_targets: texture_2d<u32>;
+@group(3) @binding(0) var diffuse_texture: texture_2d<f32>;
+@group(3) @binding(1) var diffuse_sampler: sampler;
+
+struct InstanceInput {
+  @location(0) transform_row_0: vec4<f32>,
+  @location(1) transform_row_1: vec4<f32>,
+  @location(2) transform_row_2: vec4<f32>,
+  @location(3) transform_row_3: vec4<f32>,
+  @location(4) morph_diffuse_multiply: vec4<f32>,
+  @location(5) morph_diffuse_add: vec4<f32>,
+};
+
+struct VertexInput {
+  @location(6) position: vec3<f32>,
+  @location(7) normal: vec3<f32>,
+  @location(8) uv: vec2<f32>,
+  @location(9) joints: vec4<u32>,
+  @location(10) weights: vec4<f32>,
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) normal: vec3<f32>,
+  @location(1) uv: vec2<f32>,
+  @location(2) diffuse: vec4<f32>,
+};
+
+struct FragmentOutput {
+  @location(0) color: vec4<f32>,
+};
+
+struct MorphedVertex {
+  position: vec3<f32>,
+  uv: vec2<f32>,
+};
+
+// Morph targets are laid out in rows of 1024 texels.
+fn load_morph_texel(index: u32) -> vec4<u32> {
+  return textureLoad(morph_targets, vec2<i32>(i32(index % 1024u), i32(index / 1024u)), 0);
+}
+
+fn apply_morphs(vertex_index: u32, position: vec3<f32>, uv: vec2<f32>) -> MorphedVertex {
+  var out: MorphedVertex;
+  out.position = position;
+  out.uv = uv;
+
+  // A single row means that there are no morph targets.
+  if (textureDimensions(morph_targets).y <= 1u) {
+    return out;
+  }
+
+  let header = load_morph_texel(1024u + vertex_index);
+
+  for (var index = 0u; index < header.y; index = index + 1u) {
+    let offset = load_morph_texel(header.x + index * 2u);
+    let weights = load_morph_texel(offset.w / 4u);
+    let weight = bitcast<f32>(weights[offset.w % 4u]);
+
+    if (weight != 0.0) {
+      let uv_offset = load_morph_texel(header.x + index * 2u + 1u);
+      out.position = out.position + bitcast<vec3<f32>>(offset.xyz) * weight;
+      out.uv = out.uv + bitcast<vec2<f32>>(uv_offset.xy) * weight;
+    }
+  }
+
+  return out;
+}
+
+// Linear blend skinning. Vertices without any weight are left in bind pose.
+fn skin_matrix(joints: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+  if (dot(weights, vec4<f32>(1.0)) <= 0.0) {
+    return mat4x4<f32>(
+      vec4<f32>(1.0, 0.0, 0.0, 0.0),
+      vec4<f32>(0.0, 1.0, 0.0, 0.0),
+      vec4<f32>(0.0, 0.0, 1.0, 0.0),
+      vec4<f32>(0.0, 0.0, 0.0, 1.0),
+    );
+  }
+
+  return bone_matrices[joints.x] * weights.x
+    + bone_matrices[joints.y] * weights.y
+    + bone_matrices[joints.z] * weights.z
+    + bone_matrices[joints.w] * weights.w;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: InstanceInput, vertex: VertexInput) -> VertexOutput {
+  var out: VertexOutput;
+  let morphed = apply_morphs(vertex_index, vertex.position, vertex.uv);
+  let transform = mat4x4<f32>(instance.transform_row_0, instance.transform_row_1, instance.transform_row_2, instance.transform_row_3) * skin_matrix(vertex.joints, vertex.weights);
+  out.position = camera_transform * transform * vec4<f32>(morphed.position, 1.0);
+  out.normal = normalize((transform * vec4<f32>(vertex.normal, 0.0)).xyz);
+  out.uv = morphed.uv;
+  out.diffuse = instance.morph_diffuse_multiply + instance.morph_diffuse_add;
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+  var out: FragmentOutput;
+  let light = max(dot(normalize(in.normal), normalize(vec3<f32>(0.5, 1.0, 0.5))), 0.0);
+  let diffuse = in.diffuse * textureSample(diffuse_texture, diffuse_sampler, in.uv);
+  out.color = vec4<f32>(vec3<f32>(0.2 + 0.8 * light) * diffuse.rgb, diffuse.a);
+  return out;
+}
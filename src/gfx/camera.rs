@@ -1,5 +1,5 @@
-use super::{BindGroupLayoutCache, Color, ScreenManager};
-use crate::math::Mat4;
+use super::{BindGroupLayoutCache, Color, RenderTarget, ScreenManager};
+use crate::math::{Mat4, Vec3, Vec4};
 use specs::{prelude::*, Component};
 use std::{mem::size_of, sync::Arc};
 use wgpu::{
@@ -67,9 +67,14 @@ impl CameraProjection {
     }
 
     pub fn as_matrix(&self, screen_mgr: &ScreenManager) -> Mat4 {
+        self.as_matrix_with_aspect(screen_mgr.width() as f32 / screen_mgr.height() as f32)
+    }
+
+    /// Returns the projection matrix for a target of the given aspect ratio, width over height.
+    pub fn as_matrix_with_aspect(&self, aspect: f32) -> Mat4 {
         match self {
-            Self::Orthographic(projection) => projection.as_matrix(screen_mgr),
-            Self::Perspective(projection) => projection.as_matrix(screen_mgr),
+            Self::Orthographic(projection) => projection.as_matrix_with_aspect(aspect),
+            Self::Perspective(projection) => projection.as_matrix_with_aspect(aspect),
         }
     }
 }
@@ -83,7 +88,10 @@ pub struct CamereOrthographicProjection {
 
 impl CamereOrthographicProjection {
     pub fn as_matrix(&self, screen_mgr: &ScreenManager) -> Mat4 {
-        let aspect = screen_mgr.width() as f32 / screen_mgr.height() as f32;
+        self.as_matrix_with_aspect(screen_mgr.width() as f32 / screen_mgr.height() as f32)
+    }
+
+    pub fn as_matrix_with_aspect(&self, aspect: f32) -> Mat4 {
         Mat4::orthographic(
             self.width * -0.5,
             self.width * 0.5,
@@ -105,12 +113,15 @@ pub struct CameraPerspectiveProjection {
 
 impl CameraPerspectiveProjection {
     pub fn as_matrix(&self, screen_mgr: &ScreenManager) -> Mat4 {
+        self.as_matrix_with_aspect(screen_mgr.width() as f32 / screen_mgr.height() as f32)
+    }
+
+    /// `aspect` is the aspect ratio of the render target, used unless the aspect is fixed.
+    pub fn as_matrix_with_aspect(&self, aspect: f32) -> Mat4 {
         Mat4::perspective(
             self.fov,
             match self.aspect {
-                CameraPerspectiveProjectionAspect::Screen => {
-                    screen_mgr.width() as f32 / screen_mgr.height() as f32
-                }
+                CameraPerspectiveProjectionAspect::Screen => aspect,
                 CameraPerspectiveProjectionAspect::Fixed(aspect) => aspect,
             },
             self.near,
@@ -121,6 +132,7 @@ impl CameraPerspectiveProjection {
 
 #[derive(Debug, Clone, Copy)]
 pub enum CameraPerspectiveProjectionAspect {
+    /// Follows the aspect ratio of the render target of the camera.
     Screen,
     Fixed(f32),
 }
@@ -132,6 +144,7 @@ pub struct Camera {
     pub depth: u32,
     pub clear_mode: CameraClearMode,
     pub projection: CameraProjection,
+    pub target: RenderTarget,
     pub buffer: Arc<Buffer>,
     pub bind_group: Arc<BindGroup>,
 }
@@ -184,11 +197,29 @@ impl Camera {
             depth,
             clear_mode,
             projection,
+            target: RenderTarget::Surface,
             buffer,
             bind_group,
         }
     }
 
+    /// Makes the camera render into the given target instead of the surface.
+    pub fn with_target(mut self, target: RenderTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Returns the width of the render target divided by its height.
+    pub fn aspect(&self, screen_mgr: &ScreenManager) -> f32 {
+        self.target.aspect(screen_mgr)
+    }
+
+    /// Returns the projection matrix, fitted to the render target.
+    pub fn projection_matrix(&self, screen_mgr: &ScreenManager) -> Mat4 {
+        self.projection
+            .as_matrix_with_aspect(self.aspect(screen_mgr))
+    }
+
     pub fn update_buffer(
         &self,
         screen_mgr: &ScreenManager,
@@ -198,7 +229,62 @@ impl Camera {
         queue.write_buffer(
             &self.buffer,
             0,
-            (transform_matrix.inversed() * self.projection.as_matrix(screen_mgr)).as_bytes(),
+            (transform_matrix.inversed() * self.projection_matrix(screen_mgr)).as_bytes(),
+        );
+    }
+
+    /// Returns the fraction of the viewport height covered by a sphere in world space.
+    /// `view_matrix` is the inverse of the transform matrix of the camera.
+    /// Spheres containing the camera or behind it are treated as covering the whole viewport.
+    pub fn projected_size(
+        &self,
+        screen_mgr: &ScreenManager,
+        view_matrix: &Mat4,
+        center: Vec3,
+        radius: f32,
+    ) -> f32 {
+        // Element 5 scales view space heights into the NDC range of `[-1, 1]`.
+        let scale = self.projection_matrix(screen_mgr).row(1).y;
+
+        match &self.projection {
+            CameraProjection::Orthographic(..) => radius * scale,
+            CameraProjection::Perspective(..) => {
+                let depth = -(Vec4::from_vec3(center, 1.0) * view_matrix).z;
+
+                if depth <= radius {
+                    return f32::INFINITY;
+                }
+
+                radius * scale / depth
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_projection_aspect() {
+        let perspective = |aspect| {
+            CameraProjection::perspective(1.0, aspect, 0.1, 100.0).as_matrix_with_aspect(2.0)
+        };
+        let following = perspective(CameraPerspectiveProjectionAspect::Screen);
+        let fixed = perspective(CameraPerspectiveProjectionAspect::Fixed(1.0));
+
+        // Following the target halves the horizontal scale of a square view, keeping the vertical one.
+        assert!((following.row(0).x * 2.0 - fixed.row(0).x).abs() < 1e-5);
+        assert!((following.row(1).y - fixed.row(1).y).abs() < 1e-5);
+        assert_eq!(
+            perspective(CameraPerspectiveProjectionAspect::Screen),
+            CameraProjection::perspective(
+                1.0,
+                CameraPerspectiveProjectionAspect::Screen,
+                0.1,
+                100.0
+            )
+            .as_matrix(&ScreenManager::new(200, 100))
         );
     }
 }
@@ -80,7 +80,7 @@ impl DepthStencil {
     }
 }
 
-fn create_texture_and_view(
+pub(super) fn create_texture_and_view(
     device: &Device,
     mode: DepthStencilMode,
     size: PhysicalSize<u32>,
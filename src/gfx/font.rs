@@ -1,3 +1,4 @@
+use asset::assets::FontAsset;
 use codegen::Handle;
 use fontdue::Font as FontDueFont;
 
@@ -20,4 +21,15 @@ impl Font {
             sdf_cutoff: 0.45f32,
         }
     }
+
+    /// Creates a font from the given font asset, using its SDF parameters.
+    pub fn from_asset(asset: &dyn FontAsset) -> Self {
+        Self {
+            data: asset.data().clone(),
+            sdf_font_size: asset.sdf_font_size(),
+            sdf_inset: asset.sdf_inset() as usize,
+            sdf_radius: asset.sdf_radius() as usize,
+            sdf_cutoff: asset.sdf_cutoff(),
+        }
+    }
 }
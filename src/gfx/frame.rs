@@ -0,0 +1,114 @@
+use image::RgbaImage;
+use std::sync::mpsc;
+use thiserror::Error;
+use wgpu::{
+    BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device,
+    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Maintain, MapMode, Origin3d, Queue,
+    SurfaceTexture, Texture, TextureAspect, TextureFormat, TextureView,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+#[derive(Error, Debug)]
+pub enum FrameCaptureError {
+    #[error("frames presented to a surface cannot be read back")]
+    SurfaceFrame,
+    #[error("unsupported texture format: {0:?}")]
+    UnsupportedFormat(TextureFormat),
+    #[error("failed to map the readback buffer: {0}")]
+    BufferAsyncError(#[from] BufferAsyncError),
+}
+
+/// The frame buffer being rendered into during a frame; either a texture of the surface or the offscreen frame.
+pub struct Frame {
+    surface_texture: Option<SurfaceTexture>,
+    view: TextureView,
+}
+
+impl Frame {
+    pub fn new(surface_texture: Option<SurfaceTexture>, view: TextureView) -> Self {
+        Self {
+            surface_texture,
+            view,
+        }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    /// Presents the frame to the surface, if any.
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+/// Copies a 2D texture back from the GPU, blocking until it is done.
+/// The texture must have been created with [`wgpu::TextureUsages::COPY_SRC`], in an 8-bit RGBA or BGRA format.
+pub fn capture_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+) -> Result<RgbaImage, FrameCaptureError> {
+    let is_bgra = match texture.format() {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+        format => return Err(FrameCaptureError::UnsupportedFormat(format)),
+    };
+    let width = texture.width();
+    let height = texture.height();
+    // Rows of the buffer must be aligned, so they are padded and stripped afterwards.
+    let row_size = width * 4;
+    let padded_row_size = row_size.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("frame capture buffer"),
+        size: (padded_row_size * height) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+    encoder.copy_texture_to_buffer(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_size),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(Maintain::Wait);
+    receiver.recv().unwrap_or(Err(BufferAsyncError))?;
+
+    let mut pixels = Vec::with_capacity((row_size * height) as usize);
+
+    for row in slice.get_mapped_range().chunks(padded_row_size as usize) {
+        pixels.extend_from_slice(&row[..row_size as usize]);
+    }
+
+    buffer.unmap();
+
+    if is_bgra {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Ok(RgbaImage::from_raw(width, height, pixels).unwrap())
+}
@@ -0,0 +1,181 @@
+use super::GfxContextHandle;
+use asset::{
+    assets::{TextureAddressMode, TextureFilterMode, TextureFormat},
+    GfxBridge, GfxBuffer, GfxSampler, GfxShaderModule, GfxTexture, GfxTextureView,
+};
+use std::borrow::Cow;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    AddressMode, BufferUsages, Extent3d, FilterMode, ImageCopyTexture, ImageDataLayout, Origin3d,
+    SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, TextureAspect, TextureDescriptor,
+    TextureDimension, TextureUsages,
+};
+
+impl GfxBridge for GfxContextHandle {
+    fn upload_vertex_buffer(&self, usage: BufferUsages, content: &[u8]) -> GfxBuffer {
+        self.device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: None,
+                contents: content,
+                usage,
+            })
+            .into()
+    }
+
+    fn compile_shader(&self, source: ShaderSource) -> GfxShaderModule {
+        self.device
+            .create_shader_module(ShaderModuleDescriptor {
+                label: None,
+                source,
+            })
+            .into()
+    }
+
+    fn upload_texture(
+        &self,
+        width: u16,
+        height: u16,
+        format: TextureFormat,
+        generate_mipmaps: bool,
+        texels: &[u8],
+    ) -> GfxTexture {
+        // There is no 3-channel texture format on the GPU side; expand it to RGBA.
+        let texels = match format {
+            TextureFormat::RGB8 => Cow::Owned(Vec::from_iter(
+                texels
+                    .chunks_exact(3)
+                    .flat_map(|texel| [texel[0], texel[1], texel[2], u8::MAX]),
+            )),
+            TextureFormat::RGBA8 => Cow::Borrowed(texels),
+        };
+        let mip_level_count = if generate_mipmaps {
+            u16::max(width, height).max(1).ilog2() + 1
+        } else {
+            1
+        };
+        let texture = self.device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+        });
+
+        let mut level_width = width as u32;
+        let mut level_height = height as u32;
+        let mut level_texels = texels;
+
+        for mip_level in 0..mip_level_count {
+            self.queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &level_texels,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(level_width * 4),
+                    rows_per_image: Some(level_height),
+                },
+                Extent3d {
+                    width: level_width,
+                    height: level_height,
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            if mip_level + 1 < mip_level_count {
+                let (next_width, next_height, next_texels) =
+                    downsample_rgba8(level_width, level_height, &level_texels);
+                level_width = next_width;
+                level_height = next_height;
+                level_texels = Cow::Owned(next_texels);
+            }
+        }
+
+        texture.into()
+    }
+
+    fn create_texture_view(&self, texture: &wgpu::Texture) -> GfxTextureView {
+        texture.create_view(&Default::default()).into()
+    }
+
+    fn create_sampler(
+        &self,
+        filter_mode: TextureFilterMode,
+        address_mode: (TextureAddressMode, TextureAddressMode),
+    ) -> GfxSampler {
+        let (mag_filter, min_filter, mipmap_filter) = match filter_mode {
+            TextureFilterMode::Point => (
+                FilterMode::Nearest,
+                FilterMode::Nearest,
+                FilterMode::Nearest,
+            ),
+            TextureFilterMode::Bilinear => {
+                (FilterMode::Linear, FilterMode::Linear, FilterMode::Nearest)
+            }
+            TextureFilterMode::Trilinear => {
+                (FilterMode::Linear, FilterMode::Linear, FilterMode::Linear)
+            }
+        };
+
+        self.device
+            .create_sampler(&SamplerDescriptor {
+                label: None,
+                address_mode_u: convert_address_mode(address_mode.0),
+                address_mode_v: convert_address_mode(address_mode.1),
+                address_mode_w: AddressMode::ClampToEdge,
+                mag_filter,
+                min_filter,
+                mipmap_filter,
+                lod_min_clamp: 0.0,
+                lod_max_clamp: 32.0,
+                compare: None,
+                anisotropy_clamp: 1,
+                border_color: None,
+            })
+            .into()
+    }
+}
+
+fn convert_address_mode(address_mode: TextureAddressMode) -> AddressMode {
+    match address_mode {
+        TextureAddressMode::Clamp => AddressMode::ClampToEdge,
+        TextureAddressMode::Repeat => AddressMode::Repeat,
+    }
+}
+
+/// Halves the given RGBA8 image with a box filter. Odd edges are clamped.
+pub(super) fn downsample_rgba8(width: u32, height: u32, texels: &[u8]) -> (u32, u32, Vec<u8>) {
+    let next_width = u32::max(width / 2, 1);
+    let next_height = u32::max(height / 2, 1);
+    let mut next_texels = Vec::with_capacity((next_width * next_height * 4) as usize);
+
+    for y in 0..next_height {
+        for x in 0..next_width {
+            let x0 = u32::min(x * 2, width - 1);
+            let x1 = u32::min(x * 2 + 1, width - 1);
+            let y0 = u32::min(y * 2, height - 1);
+            let y1 = u32::min(y * 2 + 1, height - 1);
+
+            for channel in 0..4 {
+                let sum = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+                    .iter()
+                    .map(|&(x, y)| texels[((y * width + x) * 4 + channel) as usize] as u32)
+                    .sum::<u32>();
+                next_texels.push(((sum + 2) / 4) as u8);
+            }
+        }
+    }
+
+    (next_width, next_height, next_texels)
+}
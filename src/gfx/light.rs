@@ -0,0 +1,373 @@
+use super::{
+    directional_shadow_matrices, semantic_bindings, spot_shadow_matrix, BindGroupLayoutCache,
+    CameraProjection, Color, SHADOW_CASCADE_COUNT,
+};
+use crate::math::{Mat4, Vec3};
+use specs::{prelude::*, Component};
+use std::{mem::size_of, ops::Range};
+use wgpu::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutEntry, Buffer, BufferAddress,
+    BufferDescriptor, BufferUsages, Device, Queue, ShaderStages,
+};
+use zerocopy::AsBytes;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /// Lights everything along the forward direction of the object, like the sun.
+    Directional,
+    /// Lights all directions from the position of the object, fading out until `range`.
+    Point { range: f32 },
+    /// Lights a cone along the forward direction of the object, fading out until `range`.
+    /// Angles are the full angles of the cone in radians; the light fades out between them.
+    Spot {
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+/// Shadow settings of a light. Point lights do not cast shadows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightShadow {
+    /// Depth offset in the shadow maps, preventing surfaces from shadowing themselves.
+    pub bias: f32,
+    /// How far from the camera directional lights cast shadows, split into [`SHADOW_CASCADE_COUNT`] cascades.
+    /// Also how far towards the light casters are kept. Unused by spot lights, which cover their range.
+    pub distance: f32,
+}
+
+impl Default for LightShadow {
+    fn default() -> Self {
+        Self {
+            bias: 0.002,
+            distance: 50.0,
+        }
+    }
+}
+
+/// A light placed at its object. See [`semantic_bindings::LIGHTS`] for how lights reach shaders.
+#[derive(Debug, Clone, Component)]
+#[storage(HashMapStorage)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+    pub shadow: Option<LightShadow>,
+}
+
+impl Light {
+    pub fn directional(color: Color, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            color,
+            intensity,
+            shadow: None,
+        }
+    }
+
+    pub fn point(color: Color, intensity: f32, range: f32) -> Self {
+        Self {
+            kind: LightKind::Point { range },
+            color,
+            intensity,
+            shadow: None,
+        }
+    }
+
+    pub fn spot(
+        color: Color,
+        intensity: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            kind: LightKind::Spot {
+                range,
+                inner_angle,
+                outer_angle,
+            },
+            color,
+            intensity,
+            shadow: None,
+        }
+    }
+
+    /// Returns the light with shadows enabled.
+    pub fn with_shadow(mut self, shadow: LightShadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Returns the number of shadow map layers the light takes.
+    pub fn shadow_map_count(&self) -> usize {
+        match (self.shadow, self.kind) {
+            (None, _) | (_, LightKind::Point { .. }) => 0,
+            (_, LightKind::Directional) => SHADOW_CASCADE_COUNT,
+            (_, LightKind::Spot { .. }) => 1,
+        }
+    }
+
+    /// Returns the light space matrices of the shadow map layers of the light placed by `matrix`,
+    /// as seen by the camera placed by `camera_matrix`, rendering into a target of the given `aspect`.
+    pub fn shadow_matrices(
+        &self,
+        matrix: &Mat4,
+        camera_matrix: &Mat4,
+        projection: &CameraProjection,
+        aspect: f32,
+    ) -> Vec<Mat4> {
+        let shadow = match self.shadow {
+            Some(shadow) => shadow,
+            None => return vec![],
+        };
+
+        match self.kind {
+            LightKind::Directional => directional_shadow_matrices(
+                matrix,
+                camera_matrix,
+                projection,
+                aspect,
+                shadow.distance,
+            )
+            .to_vec(),
+            LightKind::Point { .. } => vec![],
+            LightKind::Spot {
+                range, outer_angle, ..
+            } => vec![spot_shadow_matrix(matrix, range, outer_angle)],
+        }
+    }
+
+    /// Encodes the light placed by the given transform matrix, in the layout of `Light` in [`semantic_bindings::LIGHTS`].
+    /// `shadow_layers` is the range of the shadow map layers of the light, if any.
+    fn to_raw(&self, matrix: &Mat4, shadow_layers: Option<Range<usize>>) -> [f32; 16] {
+        let position = Vec3::from_vec4(matrix.row(3));
+        let direction = -Vec3::from_vec4(matrix.row(2)).normalized();
+        let (kind, range, inner_cos, outer_cos) = match self.kind {
+            LightKind::Directional => (0.0, 0.0, 0.0, 0.0),
+            LightKind::Point { range } => (1.0, range, 0.0, 0.0),
+            LightKind::Spot {
+                range,
+                inner_angle,
+                outer_angle,
+            } => (
+                2.0,
+                range,
+                (inner_angle * 0.5).cos(),
+                (outer_angle * 0.5).cos(),
+            ),
+        };
+        let bias = self.shadow.map_or(0.0, |shadow| shadow.bias);
+        let (first_layer, layer_count) = match shadow_layers {
+            Some(layers) => (layers.start as f32, layers.len() as f32),
+            None => (-1.0, 0.0),
+        };
+
+        [
+            position.x,
+            position.y,
+            position.z,
+            kind,
+            direction.x,
+            direction.y,
+            direction.z,
+            range,
+            self.color.r * self.intensity,
+            self.color.g * self.intensity,
+            self.color.b * self.intensity,
+            bias,
+            inner_cos,
+            outer_cos,
+            first_layer,
+            layer_count,
+        ]
+    }
+}
+
+/// Uniform buffer holding the lights of a frame as seen by a camera, bound to [`semantic_bindings::LIGHTS`].
+/// Also holds the light space matrix of each shadow map layer, bound to [`semantic_bindings::CAMERA_TRANSFORM`]
+/// while rendering shadow casters into the layer.
+pub struct LightBuffer {
+    buffer: Buffer,
+    bind_group: BindGroup,
+    shadow_caster_buffers: Vec<Buffer>,
+    shadow_caster_bind_groups: Vec<BindGroup>,
+}
+
+impl LightBuffer {
+    pub fn new(device: &Device, bind_group_layout_cache: &mut BindGroupLayoutCache) -> Self {
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("light buffer"),
+            size: semantic_bindings::LIGHTS_SIZE as BufferAddress,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("light bind group"),
+            layout: bind_group_layout_cache
+                .create_layout(vec![BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: semantic_bindings::LIGHTS.ty,
+                    count: None,
+                }])
+                .as_ref(),
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        let shadow_caster_layout =
+            bind_group_layout_cache.create_layout(vec![BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: semantic_bindings::CAMERA_TRANSFORM.ty,
+                count: None,
+            }]);
+        let shadow_caster_buffers =
+            Vec::from_iter((0..semantic_bindings::MAX_SHADOW_MAP_COUNT).map(|_| {
+                device.create_buffer(&BufferDescriptor {
+                    label: Some("shadow caster transform buffer"),
+                    size: size_of::<[f32; 4 * 4]>() as BufferAddress,
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            }));
+        let shadow_caster_bind_groups =
+            Vec::from_iter(shadow_caster_buffers.iter().map(|buffer| {
+                device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("shadow caster transform bind group"),
+                    layout: shadow_caster_layout.as_ref(),
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                })
+            }));
+
+        Self {
+            buffer,
+            bind_group,
+            shadow_caster_buffers,
+            shadow_caster_bind_groups,
+        }
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    /// Returns the bind group to render shadow casters into the given layer of the shadow maps with.
+    pub fn shadow_caster_bind_group(&self, layer: usize) -> Option<&BindGroup> {
+        self.shadow_caster_bind_groups.get(layer)
+    }
+
+    /// Writes the given lights along with the transform matrices of their objects and their shadow matrices,
+    /// as returned by [`Light::shadow_matrices`], for the camera placed by `camera_matrix`.
+    /// Shadow map layers are assigned in order. Lights beyond [`semantic_bindings::MAX_LIGHT_COUNT`] are ignored,
+    /// and so are shadows not fitting in [`semantic_bindings::MAX_SHADOW_MAP_COUNT`] layers.
+    /// Returns the number of shadow map layers in use.
+    pub fn update<'a>(
+        &self,
+        queue: &Queue,
+        camera_matrix: &Mat4,
+        lights: impl IntoIterator<Item = (&'a Light, &'a Mat4, &'a [Mat4])>,
+    ) -> usize {
+        let mut content = vec![0f32; semantic_bindings::LIGHTS_SIZE / size_of::<f32>()];
+        let (header, rest) = content.split_at_mut(4);
+        let (raw_lights, rest) = rest.split_at_mut(16 * semantic_bindings::MAX_LIGHT_COUNT);
+        let (raw_shadow_matrices, raw_camera_position) =
+            rest.split_at_mut(16 * semantic_bindings::MAX_SHADOW_MAP_COUNT);
+        let mut count = 0u32;
+        let mut layer_count = 0;
+
+        for ((light, matrix, shadow_matrices), raw) in
+            lights.into_iter().zip(raw_lights.chunks_exact_mut(16))
+        {
+            let layers = layer_count..layer_count + shadow_matrices.len();
+            let shadow_layers =
+                if !layers.is_empty() && layers.end <= semantic_bindings::MAX_SHADOW_MAP_COUNT {
+                    for (layer, shadow_matrix) in layers.clone().zip(shadow_matrices) {
+                        raw_shadow_matrices[layer * 16..(layer + 1) * 16]
+                            .copy_from_slice(&shadow_matrix.elements);
+                        queue.write_buffer(
+                            &self.shadow_caster_buffers[layer],
+                            0,
+                            shadow_matrix.as_bytes(),
+                        );
+                    }
+
+                    layer_count = layers.end;
+                    Some(layers)
+                } else {
+                    None
+                };
+
+            raw.copy_from_slice(&light.to_raw(matrix, shadow_layers));
+            count += 1;
+        }
+
+        let camera_position = Vec3::from_vec4(camera_matrix.row(3));
+        raw_camera_position[..3].copy_from_slice(&[
+            camera_position.x,
+            camera_position.y,
+            camera_position.z,
+        ]);
+        header[0] = f32::from_bits(count);
+        queue.write_buffer(&self.buffer, 0, content.as_bytes());
+        layer_count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gfx::{is_binding_compatible, reflect_global_binding_kind},
+        math::Quat,
+    };
+
+    #[test]
+    fn check_light_layout() {
+        let module =
+            naga::front::wgsl::parse_str(include_str!("./built_in_shaders/mesh.lit.wgsl")).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+        let kind = reflect_global_binding_kind(&module, semantic_bindings::LIGHTS.name).unwrap();
+        assert!(is_binding_compatible(&semantic_bindings::LIGHTS.ty, &kind));
+        let kind =
+            reflect_global_binding_kind(&module, semantic_bindings::SHADOW_MAPS.name).unwrap();
+        assert!(is_binding_compatible(
+            &semantic_bindings::SHADOW_MAPS.ty,
+            &kind
+        ));
+
+        // a spot light at (1, 2, 3), turned to face +X
+        let matrix = Mat4::srt(
+            Vec3::new(1.0, 2.0, 3.0),
+            Quat::from_axis_angle(Vec3::UP, -std::f32::consts::FRAC_PI_2),
+            Vec3::ONE,
+        );
+        let light = Light::spot(Color::from_rgb(1.0, 0.5, 0.0), 2.0, 10.0, 0.5, 1.0);
+        let raw = light.to_raw(&matrix, None);
+        assert_eq!(&raw[0..4], &[1.0, 2.0, 3.0, 2.0]);
+        assert!((raw[4] - 1.0).abs() < 1e-5 && raw[5].abs() < 1e-5 && raw[6].abs() < 1e-5);
+        assert_eq!(raw[7], 10.0);
+        assert_eq!(&raw[8..11], &[2.0, 1.0, 0.0]);
+        assert_eq!(&raw[11..16], &[0.0, 0.25f32.cos(), 0.5f32.cos(), -1.0, 0.0]);
+
+        let light = light.with_shadow(LightShadow {
+            bias: 0.01,
+            distance: 20.0,
+        });
+        assert_eq!(light.shadow_map_count(), 1);
+        let raw = light.to_raw(&matrix, Some(2..3));
+        assert_eq!(raw[11], 0.01);
+        assert_eq!(&raw[14..16], &[2.0, 1.0]);
+    }
+}
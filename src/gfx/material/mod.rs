@@ -1,3 +1,5 @@
+use super::Texture;
+use asset::assets::{MaterialBindingKey, MaterialBindingProp, MaterialBindingValue, Mesh};
 use codegen::HandleMut;
 use std::{collections::HashMap, num::NonZeroU32, sync::Arc};
 use wgpu::{
@@ -9,12 +11,14 @@ use zerocopy::AsBytes;
 
 mod bind_group_layout_cache;
 mod pipeline_cache;
+mod pipeline_gfx_bridge;
 mod pipeline_layout_cache;
 mod shader;
 mod shader_reflection;
 
 pub use bind_group_layout_cache::*;
 pub use pipeline_cache::*;
+pub use pipeline_gfx_bridge::*;
 pub use pipeline_layout_cache::*;
 pub use shader::*;
 pub use shader_reflection::*;
@@ -187,6 +191,47 @@ impl Material {
         true
     }
 
+    /// Sets the binding properties of a material asset preset.
+    /// Returns `false` if any of them is missing from the shader or does not match it.
+    pub fn set_preset_bind_properties(&mut self, props: &[MaterialBindingProp]) -> bool {
+        let mut is_matched = true;
+
+        for prop in props {
+            let key = match &prop.key {
+                MaterialBindingKey::Semantic(key) => {
+                    BindingPropKey::SemanticKey(SemanticShaderBindingKey::new(key.get().get()))
+                }
+                MaterialBindingKey::Named(name) => BindingPropKey::StringKey(name.clone()),
+            };
+            let resource = match &prop.value {
+                MaterialBindingValue::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                } => BindGroupEntryResource::Buffer {
+                    buffer: buffer.clone(),
+                    offset: *offset,
+                    size: *size,
+                },
+                MaterialBindingValue::TextureView { view } => BindGroupEntryResource::TextureView {
+                    texture_view: view.clone(),
+                },
+                MaterialBindingValue::TextureViewArray { views } => {
+                    BindGroupEntryResource::TextureViewArray {
+                        texture_views: views.clone(),
+                    }
+                }
+                MaterialBindingValue::Sampler { sampler } => BindGroupEntryResource::Sampler {
+                    sampler: sampler.clone(),
+                },
+            };
+
+            is_matched &= self.set_bind_property(&key, resource);
+        }
+
+        is_matched
+    }
+
     pub fn set_per_instance_property(
         &mut self,
         name: impl AsRef<str>,
@@ -207,6 +252,41 @@ impl Material {
         true
     }
 
+    /// Creates a material of the given shader, e.g. [`BUILT_IN_SHADER_SKINNED_MESH_NORMAL`](crate::gfx::BUILT_IN_SHADER_SKINNED_MESH_NORMAL),
+    /// bound to the texture of the sub mesh material through `diffuse_texture` and `diffuse_sampler`.
+    /// Sub meshes without texture are bound to `fallback_texture`.
+    pub fn from_mesh(
+        mesh: &Mesh,
+        shader: ShaderHandle,
+        fallback_texture: &Texture,
+        device: &Device,
+        pipeline_layout_cache: &mut PipelineLayoutCache,
+    ) -> Self {
+        let (texture_view, sampler) = match mesh
+            .material
+            .as_ref()
+            .and_then(|material| material.texture.as_ref())
+        {
+            Some(texture) => (texture.view.clone(), texture.sampler.clone()),
+            None => (
+                fallback_texture.view.clone(),
+                fallback_texture.sampler.clone(),
+            ),
+        };
+        let mut material = Self::new(shader, pipeline_layout_cache);
+
+        material.set_bind_property(
+            &BindingPropKey::StringKey("diffuse_texture".to_owned()),
+            BindGroupEntryResource::TextureView { texture_view },
+        );
+        material.set_bind_property(
+            &BindingPropKey::StringKey("diffuse_sampler".to_owned()),
+            BindGroupEntryResource::Sampler { sampler },
+        );
+        material.update_bind_group(device);
+        material
+    }
+
     pub fn update_bind_group(&mut self, device: &Device) {
         for bind_group_holder in &mut self.bind_group_holders {
             if !bind_group_holder.is_dirty {
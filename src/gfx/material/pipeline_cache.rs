@@ -24,6 +24,8 @@ pub struct PipelineKey {
     pub buffer_layouts: Vec<BufferLayout>,
     pub primitive: PrimitiveState,
     pub depth_stencil: Option<DepthStencilState>,
+    /// Whether the pipeline only writes depth, leaving out the fragment stage.
+    pub depth_only: bool,
 }
 
 impl PipelineKey {
@@ -63,7 +65,7 @@ impl PipelineKey {
             primitive: self.primitive,
             depth_stencil: self.depth_stencil.clone(),
             multisample: Default::default(),
-            fragment: Some(FragmentState {
+            fragment: (!self.depth_only).then(|| FragmentState {
                 module: &self.shader.shader_module,
                 entry_point: &self.shader.reflected_shader.fragment_entry_point_name,
                 targets: &targets,
@@ -117,6 +119,7 @@ impl PipelineCache {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_pipeline(
         &mut self,
         shader_mgr: &ShaderManager,
@@ -125,6 +128,7 @@ impl PipelineCache {
         buffer_layouts: Vec<BufferLayout>,
         primitive: PrimitiveState,
         depth_stencil: Option<DepthStencilState>,
+        depth_only: bool,
     ) -> CachedPipeline {
         let key = PipelineKey {
             layout,
@@ -132,6 +136,7 @@ impl PipelineCache {
             buffer_layouts,
             primitive,
             depth_stencil,
+            depth_only,
         };
 
         if let Some(pipeline) = self.caches.get(&key).and_then(|weak| weak.upgrade()) {
@@ -0,0 +1,143 @@
+use super::{
+    is_binding_compatible, reflect_global_binding_kind, semantic_bindings, semantic_inputs,
+    semantic_outputs, SemanticShaderBinding, SemanticShaderInput, SemanticShaderOutput,
+    ShaderManager,
+};
+use asset::assets::{SemanticShaderBindingKey, SemanticShaderInputKey, SemanticShaderOutputKey};
+use asset_pipeline::PipelineGfxBridge;
+use naga::Module;
+use std::collections::HashMap;
+use wgpu::{VertexFormat, VertexStepMode};
+
+/// A [`PipelineGfxBridge`] backed by the semantic name tables of a [`ShaderManager`].
+/// Semantic keys are resolved with the same rules as [`ShaderManager::create_shader`].
+pub struct SemanticPipelineGfxBridge {
+    bindings: HashMap<&'static str, SemanticShaderBinding>,
+    inputs: HashMap<&'static str, SemanticShaderInput>,
+    outputs: HashMap<&'static str, SemanticShaderOutput>,
+    signature: String,
+}
+
+impl SemanticPipelineGfxBridge {
+    pub fn new(shader_mgr: &ShaderManager) -> Self {
+        Self::from_tables(
+            shader_mgr.semantic_bindings(),
+            shader_mgr.semantic_inputs(),
+            shader_mgr.semantic_outputs(),
+        )
+    }
+
+    /// Creates a bridge that knows the built-in semantics only. It does not require a GPU,
+    /// so it can be used to process assets offline, e.g. when building an asset pack.
+    pub fn built_in() -> Self {
+        Self::from_tables(
+            semantic_bindings::ALL.iter(),
+            semantic_inputs::ALL.iter(),
+            semantic_outputs::ALL.iter(),
+        )
+    }
+
+    fn from_tables<'a>(
+        bindings: impl Iterator<Item = &'a SemanticShaderBinding>,
+        inputs: impl Iterator<Item = &'a SemanticShaderInput>,
+        outputs: impl Iterator<Item = &'a SemanticShaderOutput>,
+    ) -> Self {
+        let bindings = HashMap::from_iter(bindings.map(|binding| (binding.name, binding.clone())));
+        let inputs = HashMap::from_iter(inputs.map(|input| (input.name, input.clone())));
+        let outputs = HashMap::from_iter(outputs.map(|output| (output.name, output.clone())));
+
+        // One line per semantic, sorted so that the order of registration does not matter.
+        let mut lines = Vec::from_iter(
+            bindings
+                .values()
+                .map(|binding| {
+                    format!(
+                        "binding {} {} {:?} {:?}",
+                        binding.name,
+                        binding.key.get(),
+                        binding.ty,
+                        binding.count
+                    )
+                })
+                .chain(inputs.values().map(|input| {
+                    format!(
+                        "input {} {} {:?} {:?}",
+                        input.name,
+                        input.key.get(),
+                        input.step_mode,
+                        input.format
+                    )
+                }))
+                .chain(outputs.values().map(|output| {
+                    format!(
+                        "output {} {} {}",
+                        output.name,
+                        output.key.get(),
+                        output.location
+                    )
+                })),
+        );
+        lines.sort_unstable();
+
+        Self {
+            bindings,
+            inputs,
+            outputs,
+            signature: lines.join("\n"),
+        }
+    }
+}
+
+impl PipelineGfxBridge for SemanticPipelineGfxBridge {
+    fn get_semantic_binding_key(
+        &self,
+        module: &Module,
+        name: &str,
+    ) -> Option<SemanticShaderBindingKey> {
+        let binding = self.bindings.get(name)?;
+        let element_kind = reflect_global_binding_kind(module, name)?;
+
+        if !is_binding_compatible(&binding.ty, &element_kind) {
+            return None;
+        }
+
+        Some(SemanticShaderBindingKey::new(binding.key.get()))
+    }
+
+    fn get_semantic_input_key(
+        &self,
+        step_mode: VertexStepMode,
+        format: VertexFormat,
+        name: &str,
+    ) -> Option<SemanticShaderInputKey> {
+        let input = self.inputs.get(name)?;
+
+        if input.step_mode != step_mode {
+            return None;
+        }
+
+        if input.format != format {
+            return None;
+        }
+
+        Some(SemanticShaderInputKey::new(input.key.get()))
+    }
+
+    fn get_semantic_output_key(
+        &self,
+        location: u32,
+        name: &str,
+    ) -> Option<SemanticShaderOutputKey> {
+        let output = self.outputs.get(name)?;
+
+        if output.location != location {
+            return None;
+        }
+
+        Some(SemanticShaderOutputKey::new(output.key.get()))
+    }
+
+    fn semantic_signature(&self) -> &str {
+        &self.signature
+    }
+}
@@ -1,10 +1,15 @@
-use super::{inspect_shader, BindGroupLayoutCache, CachedBindGroupLayout, ShaderInspectionError};
+use super::{
+    inspect_shader, BindGroupLayoutCache, CachedBindGroupLayout, SemanticPipelineGfxBridge,
+    ShaderInspectionError,
+};
 use crate::gfx::{GfxContextHandle, ReflectedShader};
+use asset::assets::ShaderAsset;
 use codegen::Handle;
 use std::{
     borrow::Cow,
     collections::{hash_map::Entry, HashMap},
     num::NonZeroU32,
+    sync::Arc,
 };
 use wgpu::{
     BindGroupLayoutEntry, BindingType, ColorTargetState, ShaderModule, ShaderModuleDescriptor,
@@ -45,6 +50,66 @@ pub mod semantic_bindings {
         count: None,
     };
 
+    /// The maximum number of lights affecting a frame.
+    pub const MAX_LIGHT_COUNT: usize = 16;
+    /// The maximum number of layers in [`SHADOW_MAPS`], shared by all shadow casting lights.
+    pub const MAX_SHADOW_MAP_COUNT: usize = 8;
+    /// Size of [`LIGHTS`] in bytes; a 16 bytes header, [`MAX_LIGHT_COUNT`] lights of 64 bytes each,
+    /// [`MAX_SHADOW_MAP_COUNT`] light space matrices and the camera position.
+    pub const LIGHTS_SIZE: usize = size_of::<[u32; 4]>()
+        + size_of::<[f32; 16]>() * MAX_LIGHT_COUNT
+        + size_of::<[f32; 4 * 4]>() * MAX_SHADOW_MAP_COUNT
+        + size_of::<[f32; 4]>();
+
+    pub const KEY_LIGHTS: SemanticShaderBindingKey = SemanticShaderBindingKey::new(3);
+    /// Lights of the current frame as seen by the current camera, written by [`LightBuffer`](crate::gfx::LightBuffer).
+    /// ```wgsl
+    /// struct Light {
+    ///   // xyz: world position, w: 0 for directional, 1 for point and 2 for spot lights
+    ///   position: vec4<f32>,
+    ///   // xyz: world direction the light travels, w: range
+    ///   direction: vec4<f32>,
+    ///   // rgb: color multiplied by intensity, a: depth bias of shadows
+    ///   color: vec4<f32>,
+    ///   // x: cosine of the inner half angle, y: cosine of the outer half angle,
+    ///   // z: first layer of the shadow maps, w: number of shadow map layers, which are cascades for directional lights
+    ///   spot: vec4<f32>,
+    /// };
+    ///
+    /// struct Lights {
+    ///   count: u32,
+    ///   lights: array<Light, MAX_LIGHT_COUNT>,
+    ///   // maps world positions into the clip space of each layer of the shadow maps
+    ///   shadow_matrices: array<mat4x4<f32>, MAX_SHADOW_MAP_COUNT>,
+    ///   // xyz: world position of the camera, w: unused
+    ///   camera_position: vec4<f32>,
+    /// };
+    /// ```
+    pub const LIGHTS: SemanticShaderBinding = SemanticShaderBinding {
+        key: KEY_LIGHTS,
+        name: "lights",
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: Some(unsafe { NonZeroU64::new_unchecked(LIGHTS_SIZE as u64) }),
+        },
+        count: None,
+    };
+
+    pub const KEY_SHADOW_MAPS: SemanticShaderBindingKey = SemanticShaderBindingKey::new(4);
+    /// `texture_depth_2d_array` of [`MAX_SHADOW_MAP_COUNT`] layers, written by the shadow pass.
+    /// Layers are located by [`LIGHTS`], and should be read with `textureLoad`.
+    pub const SHADOW_MAPS: SemanticShaderBinding = SemanticShaderBinding {
+        key: KEY_SHADOW_MAPS,
+        name: "shadow_maps",
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Depth,
+            view_dimension: TextureViewDimension::D2Array,
+            multisampled: false,
+        },
+        count: None,
+    };
+
     pub const KEY_SPRITE_TEXTURE: SemanticShaderBindingKey = SemanticShaderBindingKey::new(101);
     pub const SPRITE_TEXTURE: SemanticShaderBinding = SemanticShaderBinding {
         key: KEY_SPRITE_TEXTURE,
@@ -63,6 +128,53 @@ pub mod semantic_bindings {
         ty: BindingType::Sampler(SamplerBindingType::Filtering),
         count: None,
     };
+
+    /// The maximum number of joints a skinned mesh can have.
+    pub const MAX_BONE_COUNT: usize = 256;
+
+    pub const KEY_BONE_MATRICES: SemanticShaderBindingKey = SemanticShaderBindingKey::new(201);
+    /// `array<mat4x4<f32>, MAX_BONE_COUNT>`
+    pub const BONE_MATRICES: SemanticShaderBinding = SemanticShaderBinding {
+        key: KEY_BONE_MATRICES,
+        name: "bone_matrices",
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: Some(unsafe {
+                NonZeroU64::new_unchecked(size_of::<[f32; 4 * 4 * MAX_BONE_COUNT]>() as u64)
+            }),
+        },
+        count: None,
+    };
+
+    /// Width of [`MORPH_TARGETS`] in texels.
+    pub const MORPH_TEXTURE_WIDTH: u32 = 1024;
+
+    pub const KEY_MORPH_TARGETS: SemanticShaderBindingKey = SemanticShaderBindingKey::new(202);
+    /// `texture_2d<u32>`, holding the morph targets of a sub mesh along with their weights.
+    /// See [`SkinnedMeshRenderer`](crate::gfx::SkinnedMeshRenderer) for the layout.
+    pub const MORPH_TARGETS: SemanticShaderBinding = SemanticShaderBinding {
+        key: KEY_MORPH_TARGETS,
+        name: "morph_targets",
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Uint,
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+
+    /// All built-in bindings, registered by [`ShaderManager::new`](super::ShaderManager::new).
+    pub const ALL: &[SemanticShaderBinding] = &[
+        CAMERA_TRANSFORM,
+        SCREEN_SIZE,
+        LIGHTS,
+        SHADOW_MAPS,
+        SPRITE_TEXTURE,
+        SPRITE_SAMPLER,
+        BONE_MATRICES,
+        MORPH_TARGETS,
+    ];
 }
 
 pub mod semantic_inputs {
@@ -91,6 +203,30 @@ pub mod semantic_inputs {
         step_mode: VertexStepMode::Vertex,
     };
 
+    pub const KEY_JOINTS: SemanticShaderInputKey = SemanticShaderInputKey::new(4);
+    pub const JOINTS: SemanticShaderInput = SemanticShaderInput {
+        key: KEY_JOINTS,
+        name: "joints",
+        format: VertexFormat::Uint32x4,
+        step_mode: VertexStepMode::Vertex,
+    };
+    pub const KEY_WEIGHTS: SemanticShaderInputKey = SemanticShaderInputKey::new(5);
+    pub const WEIGHTS: SemanticShaderInput = SemanticShaderInput {
+        key: KEY_WEIGHTS,
+        name: "weights",
+        format: VertexFormat::Float32x4,
+        step_mode: VertexStepMode::Vertex,
+    };
+
+    pub const KEY_TANGENT: SemanticShaderInputKey = SemanticShaderInputKey::new(6);
+    /// Tangent in the direction of increasing U, for normal mapping.
+    pub const TANGENT: SemanticShaderInput = SemanticShaderInput {
+        key: KEY_TANGENT,
+        name: "tangent",
+        format: VertexFormat::Float32x3,
+        step_mode: VertexStepMode::Vertex,
+    };
+
     pub const KEY_TRANSFORM_ROW_0: SemanticShaderInputKey = SemanticShaderInputKey::new(101);
     pub const TRANSFORM_ROW_0: SemanticShaderInput = SemanticShaderInput {
         key: KEY_TRANSFORM_ROW_0,
@@ -170,6 +306,54 @@ pub mod semantic_inputs {
         format: VertexFormat::Float32,
         step_mode: VertexStepMode::Instance,
     };
+
+    pub const KEY_MORPH_DIFFUSE_MULTIPLY: SemanticShaderInputKey = SemanticShaderInputKey::new(401);
+    pub const MORPH_DIFFUSE_MULTIPLY: SemanticShaderInput = SemanticShaderInput {
+        key: KEY_MORPH_DIFFUSE_MULTIPLY,
+        name: "morph_diffuse_multiply",
+        format: VertexFormat::Float32x4,
+        step_mode: VertexStepMode::Instance,
+    };
+    pub const KEY_MORPH_DIFFUSE_ADD: SemanticShaderInputKey = SemanticShaderInputKey::new(402);
+    pub const MORPH_DIFFUSE_ADD: SemanticShaderInput = SemanticShaderInput {
+        key: KEY_MORPH_DIFFUSE_ADD,
+        name: "morph_diffuse_add",
+        format: VertexFormat::Float32x4,
+        step_mode: VertexStepMode::Instance,
+    };
+
+    pub const KEY_RECEIVE_SHADOWS: SemanticShaderInputKey = SemanticShaderInputKey::new(501);
+    /// `1.0` if the instance receives shadows, `0.0` otherwise.
+    pub const RECEIVE_SHADOWS: SemanticShaderInput = SemanticShaderInput {
+        key: KEY_RECEIVE_SHADOWS,
+        name: "receive_shadows",
+        format: VertexFormat::Float32,
+        step_mode: VertexStepMode::Instance,
+    };
+
+    /// All built-in inputs, registered by [`ShaderManager::new`](super::ShaderManager::new).
+    pub const ALL: &[SemanticShaderInput] = &[
+        POSITION,
+        NORMAL,
+        UV,
+        JOINTS,
+        WEIGHTS,
+        TANGENT,
+        TRANSFORM_ROW_0,
+        TRANSFORM_ROW_1,
+        TRANSFORM_ROW_2,
+        TRANSFORM_ROW_3,
+        SPRITE_SIZE,
+        SPRITE_OFFSET,
+        SPRITE_UV_MIN,
+        SPRITE_UV_MAX,
+        SPRITE_COLOR,
+        GLYPH_THICKNESS,
+        GLYPH_SMOOTHNESS,
+        MORPH_DIFFUSE_MULTIPLY,
+        MORPH_DIFFUSE_ADD,
+        RECEIVE_SHADOWS,
+    ];
 }
 
 pub mod semantic_outputs {
@@ -187,6 +371,9 @@ pub mod semantic_outputs {
         },
         location: 0,
     };
+
+    /// All built-in outputs, registered by [`ShaderManager::new`](super::ShaderManager::new).
+    pub const ALL: &[SemanticShaderOutput] = &[COLOR];
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -196,6 +383,10 @@ impl SemanticShaderBindingKey {
     pub const fn new(key: u32) -> Self {
         Self(unsafe { NonZeroU32::new_unchecked(key) })
     }
+
+    pub const fn get(self) -> NonZeroU32 {
+        self.0
+    }
 }
 
 #[derive(Debug, Clone, Hash)]
@@ -213,6 +404,10 @@ impl SemanticShaderInputKey {
     pub const fn new(key: u32) -> Self {
         Self(unsafe { NonZeroU32::new_unchecked(key) })
     }
+
+    pub const fn get(self) -> NonZeroU32 {
+        self.0
+    }
 }
 
 #[derive(Debug, Clone, Hash)]
@@ -230,6 +425,10 @@ impl SemanticShaderOutputKey {
     pub const fn new(key: u32) -> Self {
         Self(unsafe { NonZeroU32::new_unchecked(key) })
     }
+
+    pub const fn get(self) -> NonZeroU32 {
+        self.0
+    }
 }
 
 #[derive(Debug, Clone, Hash)]
@@ -242,7 +441,7 @@ pub struct SemanticShaderOutput {
 
 #[derive(Handle)]
 pub struct Shader {
-    pub shader_module: ShaderModule,
+    pub shader_module: Arc<ShaderModule>,
     pub bind_group_layouts: HashMap<u32, CachedBindGroupLayout>,
     pub reflected_shader: ReflectedShader,
 }
@@ -269,27 +468,17 @@ impl ShaderManager {
             outputs: HashMap::new(),
         };
 
-        this.register_binding(semantic_bindings::CAMERA_TRANSFORM);
-        this.register_binding(semantic_bindings::SCREEN_SIZE);
-        this.register_binding(semantic_bindings::SPRITE_TEXTURE);
-        this.register_binding(semantic_bindings::SPRITE_SAMPLER);
-
-        this.register_input(semantic_inputs::POSITION);
-        this.register_input(semantic_inputs::NORMAL);
-        this.register_input(semantic_inputs::UV);
-        this.register_input(semantic_inputs::TRANSFORM_ROW_0);
-        this.register_input(semantic_inputs::TRANSFORM_ROW_1);
-        this.register_input(semantic_inputs::TRANSFORM_ROW_2);
-        this.register_input(semantic_inputs::TRANSFORM_ROW_3);
-        this.register_input(semantic_inputs::SPRITE_SIZE);
-        this.register_input(semantic_inputs::SPRITE_OFFSET);
-        this.register_input(semantic_inputs::SPRITE_UV_MIN);
-        this.register_input(semantic_inputs::SPRITE_UV_MAX);
-        this.register_input(semantic_inputs::SPRITE_COLOR);
-        this.register_input(semantic_inputs::GLYPH_THICKNESS);
-        this.register_input(semantic_inputs::GLYPH_SMOOTHNESS);
-
-        this.register_output(semantic_outputs::COLOR);
+        for binding in semantic_bindings::ALL {
+            this.register_binding(binding.clone());
+        }
+
+        for input in semantic_inputs::ALL {
+            this.register_input(input.clone());
+        }
+
+        for output in semantic_outputs::ALL {
+            this.register_output(output.clone());
+        }
 
         this
     }
@@ -339,6 +528,23 @@ impl ShaderManager {
         self.outputs.get(&key)
     }
 
+    pub fn semantic_bindings(&self) -> impl Iterator<Item = &SemanticShaderBinding> {
+        self.bindings.values()
+    }
+
+    pub fn semantic_inputs(&self) -> impl Iterator<Item = &SemanticShaderInput> {
+        self.inputs.values()
+    }
+
+    pub fn semantic_outputs(&self) -> impl Iterator<Item = &SemanticShaderOutput> {
+        self.outputs.values()
+    }
+
+    /// Creates a [`PipelineGfxBridge`](asset_pipeline::PipelineGfxBridge) that resolves semantic keys from this manager.
+    pub fn pipeline_gfx_bridge(&self) -> SemanticPipelineGfxBridge {
+        SemanticPipelineGfxBridge::new(self)
+    }
+
     pub fn create_shader(
         &self,
         bind_group_layout_cache: &mut BindGroupLayoutCache,
@@ -349,10 +555,25 @@ impl ShaderManager {
         Ok(self.build_shader(bind_group_layout_cache, shader_module, reflected_shader))
     }
 
+    /// Creates a shader from a loaded shader asset. The asset must be processed with [`Self::pipeline_gfx_bridge`].
+    pub fn create_shader_from_asset(
+        &self,
+        bind_group_layout_cache: &mut BindGroupLayoutCache,
+        asset: &dyn ShaderAsset,
+    ) -> ShaderHandle {
+        let reflected_shader = ReflectedShader::from_asset(asset.reflection());
+
+        self.build_shader(
+            bind_group_layout_cache,
+            asset.handle().clone(),
+            reflected_shader,
+        )
+    }
+
     fn compile_shader(
         &self,
         source: impl AsRef<str>,
-    ) -> Result<(ReflectedShader, ShaderModule), ShaderInspectionError> {
+    ) -> Result<(ReflectedShader, Arc<ShaderModule>), ShaderInspectionError> {
         let source = source.as_ref();
         let reflected_shader = inspect_shader(self, source)?;
         let shader_module = self
@@ -363,13 +584,13 @@ impl ShaderManager {
                 source: ShaderSource::Wgsl(Cow::Borrowed(source)),
             });
 
-        Ok((reflected_shader, shader_module))
+        Ok((reflected_shader, shader_module.into()))
     }
 
     fn build_shader(
         &self,
         bind_group_layout_cache: &mut BindGroupLayoutCache,
-        shader_module: ShaderModule,
+        shader_module: Arc<ShaderModule>,
         reflected_shader: ReflectedShader,
     ) -> ShaderHandle {
         let mut bind_group_layout_entries = HashMap::<u32, Vec<_>>::new();
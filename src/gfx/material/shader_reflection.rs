@@ -2,6 +2,7 @@ use super::{
     shader::{SemanticShaderInputKey, ShaderManager},
     SemanticShaderBindingKey, SemanticShaderOutputKey,
 };
+use asset::assets::{ShaderGlobalItemKind, ShaderInput, ShaderReflection};
 use naga::{
     front::wgsl::{parse_str, ParseError},
     AddressSpace, ArraySize, Binding, Function, ImageClass, ImageDimension, Module, ScalarKind,
@@ -35,6 +36,61 @@ pub struct ReflectedShader {
     pub outputs: Vec<ReflectedShaderOutputElement>,
 }
 
+impl ReflectedShader {
+    /// Converts a reflection of a shader asset. Semantic keys are carried over as-is.
+    pub fn from_asset(reflection: &ShaderReflection) -> Self {
+        let mut bindings = Vec::from_iter(reflection.globals.iter().map(|item| {
+            ReflectedShaderBindingElement {
+                semantic_binding: item
+                    .sematic_key
+                    .map(|key| SemanticShaderBindingKey::new(key.get().get())),
+                name: item.name.clone(),
+                group: item.group,
+                binding: item.binding,
+                kind: match &item.kind {
+                    ShaderGlobalItemKind::Buffer { size } => {
+                        ReflectedShaderBindingElementKind::Buffer { size: *size }
+                    }
+                    ShaderGlobalItemKind::Texture {
+                        sample_type,
+                        view_dimension,
+                        multisampled,
+                        array_size,
+                    } => ReflectedShaderBindingElementKind::Texture {
+                        sample_type: *sample_type,
+                        view_dimension: *view_dimension,
+                        multisampled: *multisampled,
+                        array_size: *array_size,
+                    },
+                    ShaderGlobalItemKind::Sampler { binding_type } => {
+                        ReflectedShaderBindingElementKind::Sampler {
+                            binding_type: *binding_type,
+                        }
+                    }
+                },
+            }
+        }));
+        restrict_semantic_bindings(&mut bindings);
+
+        Self {
+            vertex_entry_point_name: reflection.vertex_entry_point.clone(),
+            fragment_entry_point_name: reflection.fragment_entry_point.clone(),
+            bindings,
+            per_instance_input: ReflectedShaderInput::from_asset(&reflection.instance_input),
+            per_vertex_input: ReflectedShaderInput::from_asset(&reflection.vertex_input),
+            outputs: Vec::from_iter(reflection.outputs.iter().map(|item| {
+                ReflectedShaderOutputElement {
+                    semantic_output: item
+                        .semantic_key
+                        .map(|key| SemanticShaderOutputKey::new(key.get().get())),
+                    name: item.name.clone(),
+                    location: item.location,
+                }
+            })),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ReflectedShaderBindingElement {
     pub semantic_binding: Option<SemanticShaderBindingKey>,
@@ -112,6 +168,22 @@ impl ReflectedShaderInput {
         }
     }
 
+    pub fn from_asset(input: &ShaderInput) -> Self {
+        Self {
+            step_mode: input.step_mode,
+            stride: input.stride,
+            elements: Vec::from_iter(input.fields.iter().map(|field| {
+                ReflectedShaderInputElement {
+                    semantic_input: field
+                        .semantic_key
+                        .map(|key| SemanticShaderInputKey::new(key.get().get())),
+                    name: field.name.clone(),
+                    attribute: field.attribute,
+                }
+            })),
+        }
+    }
+
     pub fn vertex_buffer_layout_builder(&self) -> ReflectedShaderInputVertexBufferLayoutBuilder {
         ReflectedShaderInputVertexBufferLayoutBuilder {
             step_mode: self.step_mode,
@@ -240,35 +312,8 @@ fn reflect_globals(
         let semantic_binding = shader_mgr.find_semantic_binding(name).and_then(|key| {
             let semantic_binding = shader_mgr.get_semantic_binding(key).unwrap();
 
-            match (&semantic_binding.ty, &element_kind) {
-                (
-                    BindingType::Buffer {
-                        min_binding_size, ..
-                    },
-                    ReflectedShaderBindingElementKind::Buffer { size },
-                ) if *min_binding_size == Some(*size) => {}
-                (
-                    BindingType::Texture {
-                        sample_type,
-                        view_dimension,
-                        multisampled,
-                    },
-                    ReflectedShaderBindingElementKind::Texture {
-                        sample_type: element_sample_type,
-                        view_dimension: element_view_dimension,
-                        multisampled: element_multisampled,
-                        ..
-                    },
-                ) if *sample_type == *element_sample_type
-                    && *view_dimension == *element_view_dimension
-                    && *multisampled == *element_multisampled => {}
-                (
-                    BindingType::Sampler(binding_type),
-                    ReflectedShaderBindingElementKind::Sampler {
-                        binding_type: element_binding_type,
-                    },
-                ) if *binding_type == *element_binding_type => {}
-                _ => return None,
+            if !is_binding_compatible(&semantic_binding.ty, &element_kind) {
+                return None;
             }
 
             Some(key)
@@ -283,6 +328,13 @@ fn reflect_globals(
         });
     }
 
+    restrict_semantic_bindings(&mut bindings);
+
+    bindings
+}
+
+/// Restricts semantic bindings to isolated binding groups.
+fn restrict_semantic_bindings(bindings: &mut [ReflectedShaderBindingElement]) {
     let isolated_binding_groups =
         Vec::from_iter(bindings.iter().enumerate().map(|(index, binding)| {
             binding.binding == 0
@@ -294,14 +346,68 @@ fn reflect_globals(
                     })
         }));
 
-    // Restrict semantic bindings to isolated binding groups.
     for (index, binding) in bindings.iter_mut().enumerate() {
         if !isolated_binding_groups[index] {
             binding.semantic_binding = None;
         }
     }
+}
 
-    bindings
+/// Checks whether a reflected binding element can be bound to a semantic binding of the given type.
+pub(crate) fn is_binding_compatible(
+    ty: &BindingType,
+    element_kind: &ReflectedShaderBindingElementKind,
+) -> bool {
+    match (ty, element_kind) {
+        (
+            BindingType::Buffer {
+                min_binding_size, ..
+            },
+            ReflectedShaderBindingElementKind::Buffer { size },
+        ) => *min_binding_size == Some(*size),
+        (
+            BindingType::Texture {
+                sample_type,
+                view_dimension,
+                multisampled,
+            },
+            ReflectedShaderBindingElementKind::Texture {
+                sample_type: element_sample_type,
+                view_dimension: element_view_dimension,
+                multisampled: element_multisampled,
+                ..
+            },
+        ) => {
+            *sample_type == *element_sample_type
+                && *view_dimension == *element_view_dimension
+                && *multisampled == *element_multisampled
+        }
+        (
+            BindingType::Sampler(binding_type),
+            ReflectedShaderBindingElementKind::Sampler {
+                binding_type: element_binding_type,
+            },
+        ) => *binding_type == *element_binding_type,
+        _ => false,
+    }
+}
+
+/// Reflects the binding element kind of a global variable with the given name.
+pub(crate) fn reflect_global_binding_kind(
+    module: &Module,
+    name: &str,
+) -> Option<ReflectedShaderBindingElementKind> {
+    let (_, global) = module
+        .global_variables
+        .iter()
+        .find(|(_, global)| global.name.as_deref() == Some(name) && global.binding.is_some())?;
+
+    match global.space {
+        AddressSpace::Uniform | AddressSpace::Handle => {
+            shader_ty_to_binding_element_kind(module, &module.types[global.ty])
+        }
+        _ => None,
+    }
 }
 
 fn reflect_vertex_entry_point(
@@ -527,7 +633,11 @@ fn shader_ty_to_binding_element_kind(
         TypeInner::Struct { span, .. } => Some(ReflectedShaderBindingElementKind::Buffer {
             size: unsafe { NonZeroU64::new_unchecked(*span as u64) },
         }),
-        TypeInner::Image { dim, class, .. } => {
+        TypeInner::Image {
+            dim,
+            arrayed,
+            class,
+        } => {
             let (sample_type, multisampled) = match *class {
                 ImageClass::Sampled { kind, multi } => {
                     let sample_type = match kind {
@@ -548,11 +658,13 @@ fn shader_ty_to_binding_element_kind(
 
             Some(ReflectedShaderBindingElementKind::Texture {
                 sample_type,
-                view_dimension: match *dim {
-                    ImageDimension::D1 => TextureViewDimension::D1,
-                    ImageDimension::D2 => TextureViewDimension::D2,
-                    ImageDimension::D3 => TextureViewDimension::D3,
-                    ImageDimension::Cube => TextureViewDimension::Cube,
+                view_dimension: match (*dim, *arrayed) {
+                    (ImageDimension::D1, _) => TextureViewDimension::D1,
+                    (ImageDimension::D2, false) => TextureViewDimension::D2,
+                    (ImageDimension::D2, true) => TextureViewDimension::D2Array,
+                    (ImageDimension::D3, _) => TextureViewDimension::D3,
+                    (ImageDimension::Cube, false) => TextureViewDimension::Cube,
+                    (ImageDimension::Cube, true) => TextureViewDimension::CubeArray,
                 },
                 multisampled,
                 array_size: None,
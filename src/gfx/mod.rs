@@ -14,13 +14,19 @@ mod camera;
 mod color;
 mod depth_stencil;
 mod font;
+mod frame;
+mod gfx_bridge;
 mod glyph;
+mod light;
 mod material;
 mod mesh;
 mod nine_patch;
+mod pbr;
 mod render_mgr;
+mod render_target;
 mod renderer;
 mod screen_mgr;
+mod shadow_maps;
 mod sprite;
 mod texture;
 
@@ -29,13 +35,18 @@ pub use camera::*;
 pub use color::*;
 pub use depth_stencil::*;
 pub use font::*;
+pub use frame::*;
 pub use glyph::*;
+pub use light::*;
 pub use material::*;
 pub use mesh::*;
 pub use nine_patch::*;
+pub use pbr::*;
 pub use render_mgr::*;
+pub use render_target::*;
 pub use renderer::*;
 pub use screen_mgr::*;
+pub use shadow_maps::*;
 pub use sprite::*;
 pub use texture::*;
 
@@ -54,7 +65,9 @@ pub struct GfxContext {
     pub instance: Instance,
     pub device: Device,
     pub queue: Queue,
-    pub surface: Surface,
+    /// The surface of the window; `None` for headless contexts, which render into an offscreen frame.
+    pub surface: Option<Surface>,
+    /// Describes the frame buffer, whether it is the surface or the offscreen frame.
     pub surface_config: RefCell<SurfaceConfiguration>,
 }
 
@@ -65,61 +78,104 @@ impl GfxContext {
         let adapters = instance
             .enumerate_adapters(Backends::all())
             .collect::<Vec<_>>();
-        let adapter = if let Some(adapter_index) = select_adapter(&surface, &adapters) {
+        let adapter = if let Some(adapter_index) = select_adapter(Some(&surface), &adapters) {
             &adapters[adapter_index]
         } else {
             return Err(GfxContextCreationError::AdapterNotFound);
         };
 
-        let (device, queue) = adapter
-            .request_device(
-                &DeviceDescriptor {
-                    label: None,
-                    features: Features::CLEAR_TEXTURE,
-                    limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                },
-                None,
-            )
-            .await?;
-
-        let window_inner_size = window.inner_size();
-        let surface_config = RefCell::new(SurfaceConfiguration {
-            usage: TextureUsages::RENDER_ATTACHMENT,
-            format: TextureFormat::Bgra8Unorm,
-            width: window_inner_size.width,
-            height: window_inner_size.height,
-            present_mode: PresentMode::Fifo,
-            alpha_mode: CompositeAlphaMode::Auto,
-            view_formats: vec![TextureFormat::Bgra8Unorm],
-        });
+        let (device, queue) = request_device(adapter).await?;
+        let surface_config = RefCell::new(frame_buffer_config(window.inner_size()));
         surface.configure(&device, &surface_config.borrow());
 
         Ok(GfxContext {
             instance,
             device,
             queue,
-            surface,
+            surface: Some(surface),
             surface_config,
         })
     }
 
+    /// Creates a context without a window, rendering frames of the given size into an offscreen texture.
+    /// Any adapter is accepted, including CPU ones, so that it works on machines without a GPU or display.
+    pub async fn new_headless(size: PhysicalSize<u32>) -> Result<Self, GfxContextCreationError> {
+        let instance = Instance::new(InstanceDescriptor::default());
+        let adapters = instance
+            .enumerate_adapters(Backends::all())
+            .collect::<Vec<_>>();
+        let adapter = if let Some(adapter_index) = select_adapter(None, &adapters) {
+            &adapters[adapter_index]
+        } else {
+            return Err(GfxContextCreationError::AdapterNotFound);
+        };
+
+        let (device, queue) = request_device(adapter).await?;
+        let surface_config = RefCell::new(frame_buffer_config(size));
+
+        Ok(GfxContext {
+            instance,
+            device,
+            queue,
+            surface: None,
+            surface_config,
+        })
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.surface.is_none()
+    }
+
     pub fn resize(&self, size: PhysicalSize<u32>) {
         let mut surface_config = self.surface_config.borrow_mut();
         surface_config.width = size.width;
         surface_config.height = size.height;
-        self.surface.configure(&self.device, &surface_config);
+
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &surface_config);
+        }
+    }
+}
+
+async fn request_device(adapter: &Adapter) -> Result<(Device, Queue), RequestDeviceError> {
+    adapter
+        .request_device(
+            &DeviceDescriptor {
+                label: None,
+                features: Features::CLEAR_TEXTURE,
+                limits: if cfg!(target_arch = "wasm32") {
+                    wgpu::Limits::downlevel_webgl2_defaults()
+                } else {
+                    wgpu::Limits::default()
+                },
+            },
+            None,
+        )
+        .await
+}
+
+fn frame_buffer_config(size: PhysicalSize<u32>) -> SurfaceConfiguration {
+    SurfaceConfiguration {
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        format: TextureFormat::Bgra8Unorm,
+        width: size.width,
+        height: size.height,
+        present_mode: PresentMode::Fifo,
+        alpha_mode: CompositeAlphaMode::Auto,
+        view_formats: vec![TextureFormat::Bgra8Unorm],
     }
 }
 
-fn select_adapter(surface: &Surface, adapters: impl AsRef<[Adapter]>) -> Option<usize> {
+/// Picks the best adapter; without a surface, every adapter is a candidate.
+fn select_adapter(surface: Option<&Surface>, adapters: impl AsRef<[Adapter]>) -> Option<usize> {
+    let supports_surface = |adapter: &Adapter| match surface {
+        Some(surface) => !surface.get_capabilities(adapter).formats.is_empty(),
+        None => true,
+    };
     let adapters = adapters
         .as_ref()
         .iter()
-        .filter(|adapter| !surface.get_capabilities(adapter).formats.is_empty())
+        .filter(|adapter| supports_surface(adapter))
         .collect::<Vec<_>>();
 
     if adapters.is_empty() {
@@ -129,7 +185,7 @@ fn select_adapter(surface: &Surface, adapters: impl AsRef<[Adapter]>) -> Option<
     let mut scores = adapters.iter().map(|_| 0).collect::<Vec<_>>();
 
     for (index, adapter) in adapters.iter().enumerate() {
-        if surface.get_capabilities(adapter).formats.is_empty() {
+        if !supports_surface(adapter) {
             continue;
         }
 
@@ -1,4 +1,5 @@
-use super::TextureHandle;
+use super::{Texture, TextureHandle};
+use asset::assets::TextureAsset;
 use codegen::Handle;
 
 #[derive(Handle)]
@@ -12,6 +13,26 @@ impl NinePatch {
         Self { texture, mapping }
     }
 
+    /// Creates a nine-patch from a named nine-patch of the given texture asset.
+    /// Returns `None` if the texture does not contain the nine-patch.
+    pub fn from_asset(texture: &dyn TextureAsset, name: &str) -> Option<Self> {
+        let nine_patch = texture
+            .nine_patches()
+            .iter()
+            .find(|nine_patch| nine_patch.name == name)?;
+        let (x, y) = nine_patch.texel_mapping;
+
+        Some(Self {
+            texture: TextureHandle::new(Texture {
+                sampler: nine_patch.sampler_handle.clone(),
+                ..Texture::from_asset(texture)
+            }),
+            mapping: NinePatchTexelMapping::new(
+                x.min, x.mid_min, x.mid_max, x.max, y.min, y.mid_min, y.mid_max, y.max,
+            ),
+        })
+    }
+
     pub fn texture(&self) -> &TextureHandle {
         &self.texture
     }
@@ -0,0 +1,346 @@
+use super::{
+    gfx_bridge::downsample_rgba8, BindGroupEntryResource, BindingPropKey, Color, Material,
+    PipelineLayoutCache, ShaderHandle, Texture,
+};
+use asset::assets::{pbr_bindings, MaterialPreset, MeshMaterialPbr, MeshMaterialTexture};
+use std::{borrow::Cow, mem::size_of, sync::Arc};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    AddressMode, BufferAddress, BufferSize, BufferUsages, Device, Extent3d, FilterMode,
+    ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Sampler, SamplerDescriptor, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+    TextureViewDescriptor, TextureViewDimension,
+};
+use zerocopy::AsBytes;
+
+/// A cube map of the surroundings, lighting materials of [`BUILT_IN_SHADER_MESH_PBR`](super::BUILT_IN_SHADER_MESH_PBR).
+/// Mip levels are box filtered, standing in for radiance prefiltered by roughness.
+pub struct EnvironmentMap {
+    pub texture: Arc<wgpu::Texture>,
+    pub view: Arc<TextureView>,
+    pub sampler: Arc<Sampler>,
+}
+
+impl EnvironmentMap {
+    pub const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+    /// Creates an environment map from 6 square RGBA8 sRGB images of `size` texels,
+    /// ordered +X, -X, +Y, -Y, +Z and -Z.
+    pub fn from_faces(size: u32, faces: [&[u8]; 6], device: &Device, queue: &Queue) -> Self {
+        let size = size.max(1);
+        let mip_level_count = size.ilog2() + 1;
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("environment map"),
+            size: Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[Self::FORMAT],
+        });
+
+        for (layer, face) in faces.into_iter().enumerate() {
+            let mut level_size = size;
+            let mut level_texels = Cow::Borrowed(face);
+
+            for mip_level in 0..mip_level_count {
+                queue.write_texture(
+                    ImageCopyTexture {
+                        texture: &texture,
+                        mip_level,
+                        origin: Origin3d {
+                            x: 0,
+                            y: 0,
+                            z: layer as u32,
+                        },
+                        aspect: TextureAspect::All,
+                    },
+                    &level_texels,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(level_size * 4),
+                        rows_per_image: Some(level_size),
+                    },
+                    Extent3d {
+                        width: level_size,
+                        height: level_size,
+                        depth_or_array_layers: 1,
+                    },
+                );
+
+                if mip_level + 1 < mip_level_count {
+                    let (next_size, _, next_texels) =
+                        downsample_rgba8(level_size, level_size, &level_texels);
+                    level_size = next_size;
+                    level_texels = Cow::Owned(next_texels);
+                }
+            }
+        }
+
+        let view = texture.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("environment map sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            compare: None,
+            anisotropy_clamp: 1,
+            border_color: None,
+        });
+
+        Self {
+            texture: texture.into(),
+            view: view.into(),
+            sampler: sampler.into(),
+        }
+    }
+
+    /// Creates an environment map of a single color, lighting surfaces evenly from all directions.
+    pub fn from_color(color: Color, device: &Device, queue: &Queue) -> Self {
+        let texel = [color.r, color.g, color.b, color.a]
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+
+        Self::from_faces(1, [&texel; 6], device, queue)
+    }
+
+    /// Binds the environment map to [`pbr_bindings::ENVIRONMENT_TEXTURE`] and [`pbr_bindings::ENVIRONMENT_SAMPLER`].
+    /// Call [`Material::update_bind_group`] afterwards.
+    pub fn bind(&self, material: &mut Material) {
+        material.set_bind_property(
+            &BindingPropKey::StringKey(pbr_bindings::ENVIRONMENT_TEXTURE.to_owned()),
+            BindGroupEntryResource::TextureView {
+                texture_view: self.view.clone(),
+            },
+        );
+        material.set_bind_property(
+            &BindingPropKey::StringKey(pbr_bindings::ENVIRONMENT_SAMPLER.to_owned()),
+            BindGroupEntryResource::Sampler {
+                sampler: self.sampler.clone(),
+            },
+        );
+    }
+}
+
+/// Creates a material of the given shader, usually [`BUILT_IN_SHADER_MESH_PBR`](super::BUILT_IN_SHADER_MESH_PBR),
+/// from the metallic-roughness parameters of a sub mesh material. Missing textures are bound to `fallback_texture`,
+/// which should be white. Meshes drawn with it need the `Tangent` vertex attribute.
+pub fn create_pbr_material(
+    pbr: &MeshMaterialPbr,
+    shader: ShaderHandle,
+    environment: &EnvironmentMap,
+    fallback_texture: &Texture,
+    device: &Device,
+    pipeline_layout_cache: &mut PipelineLayoutCache,
+) -> Material {
+    let mut material = Material::new(shader, pipeline_layout_cache);
+    bind_defaults(&mut material, environment, fallback_texture);
+
+    // Factors share a buffer, each at an offset aligned for uniform buffers.
+    let stride = device.limits().min_uniform_buffer_offset_alignment as usize;
+    let emissive_factor = [
+        pbr.emissive_factor[0],
+        pbr.emissive_factor[1],
+        pbr.emissive_factor[2],
+        1.0,
+    ];
+    let factors = [
+        (pbr_bindings::BASE_COLOR_FACTOR, pbr.base_color_factor),
+        (pbr_bindings::SURFACE_FACTORS, pbr.surface_factors()),
+        (pbr_bindings::EMISSIVE_FACTOR, emissive_factor),
+    ];
+    let mut content = vec![0u8; stride * factors.len()];
+
+    for (index, (_, factor)) in factors.iter().enumerate() {
+        content[index * stride..][..size_of::<[f32; 4]>()].copy_from_slice(factor.as_bytes());
+    }
+
+    let buffer = Arc::new(device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("pbr material factor buffer"),
+        contents: &content,
+        usage: BufferUsages::UNIFORM,
+    }));
+
+    for (index, (name, _)) in factors.iter().enumerate() {
+        material.set_bind_property(
+            &BindingPropKey::StringKey((*name).to_owned()),
+            BindGroupEntryResource::Buffer {
+                buffer: buffer.clone(),
+                offset: (index * stride) as BufferAddress,
+                size: BufferSize::new(size_of::<[f32; 4]>() as u64),
+            },
+        );
+    }
+
+    for (texture, texture_name, sampler_name) in [
+        (
+            &pbr.base_color_texture,
+            pbr_bindings::BASE_COLOR_TEXTURE,
+            pbr_bindings::BASE_COLOR_SAMPLER,
+        ),
+        (
+            &pbr.metallic_roughness_texture,
+            pbr_bindings::METALLIC_ROUGHNESS_TEXTURE,
+            pbr_bindings::METALLIC_ROUGHNESS_SAMPLER,
+        ),
+        (
+            &pbr.normal_texture,
+            pbr_bindings::NORMAL_TEXTURE,
+            pbr_bindings::NORMAL_SAMPLER,
+        ),
+        (
+            &pbr.occlusion_texture,
+            pbr_bindings::OCCLUSION_TEXTURE,
+            pbr_bindings::OCCLUSION_SAMPLER,
+        ),
+        (
+            &pbr.emissive_texture,
+            pbr_bindings::EMISSIVE_TEXTURE,
+            pbr_bindings::EMISSIVE_SAMPLER,
+        ),
+    ] {
+        if let Some(MeshMaterialTexture { view, sampler, .. }) = texture {
+            material.set_bind_property(
+                &BindingPropKey::StringKey(texture_name.to_owned()),
+                BindGroupEntryResource::TextureView {
+                    texture_view: view.clone(),
+                },
+            );
+            material.set_bind_property(
+                &BindingPropKey::StringKey(sampler_name.to_owned()),
+                BindGroupEntryResource::Sampler {
+                    sampler: sampler.clone(),
+                },
+            );
+        }
+    }
+
+    material.update_bind_group(device);
+    material
+}
+
+/// Creates a material from the preset of a material asset made of a [`PbrMaterialSource`](asset::assets::PbrMaterialSource).
+/// `shader` should be created from the shader of the preset. Textures left out of the preset are bound to
+/// `fallback_texture`, which should be white.
+pub fn create_pbr_material_from_preset(
+    preset: &MaterialPreset,
+    shader: ShaderHandle,
+    environment: &EnvironmentMap,
+    fallback_texture: &Texture,
+    device: &Device,
+    pipeline_layout_cache: &mut PipelineLayoutCache,
+) -> Material {
+    let mut material = Material::new(shader, pipeline_layout_cache);
+    bind_defaults(&mut material, environment, fallback_texture);
+    material.set_preset_bind_properties(&preset.binding_props);
+    material.update_bind_group(device);
+    material
+}
+
+/// Binds the environment map, and the fallback texture to every texture of the material.
+fn bind_defaults(
+    material: &mut Material,
+    environment: &EnvironmentMap,
+    fallback_texture: &Texture,
+) {
+    environment.bind(material);
+
+    for (texture_name, sampler_name) in [
+        (
+            pbr_bindings::BASE_COLOR_TEXTURE,
+            pbr_bindings::BASE_COLOR_SAMPLER,
+        ),
+        (
+            pbr_bindings::METALLIC_ROUGHNESS_TEXTURE,
+            pbr_bindings::METALLIC_ROUGHNESS_SAMPLER,
+        ),
+        (pbr_bindings::NORMAL_TEXTURE, pbr_bindings::NORMAL_SAMPLER),
+        (
+            pbr_bindings::OCCLUSION_TEXTURE,
+            pbr_bindings::OCCLUSION_SAMPLER,
+        ),
+        (
+            pbr_bindings::EMISSIVE_TEXTURE,
+            pbr_bindings::EMISSIVE_SAMPLER,
+        ),
+    ] {
+        material.set_bind_property(
+            &BindingPropKey::StringKey(texture_name.to_owned()),
+            BindGroupEntryResource::TextureView {
+                texture_view: fallback_texture.view.clone(),
+            },
+        );
+        material.set_bind_property(
+            &BindingPropKey::StringKey(sampler_name.to_owned()),
+            BindGroupEntryResource::Sampler {
+                sampler: fallback_texture.sampler.clone(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gfx::{is_binding_compatible, reflect_global_binding_kind, semantic_bindings};
+    use asset::assets::pbr_bindings;
+
+    #[test]
+    fn check_pbr_shader() {
+        let module =
+            naga::front::wgsl::parse_str(include_str!("./built_in_shaders/mesh.pbr.wgsl")).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+
+        for binding in [
+            semantic_bindings::CAMERA_TRANSFORM,
+            semantic_bindings::LIGHTS,
+            semantic_bindings::SHADOW_MAPS,
+        ] {
+            let kind = reflect_global_binding_kind(&module, binding.name).unwrap();
+            assert!(is_binding_compatible(&binding.ty, &kind));
+        }
+
+        for name in [
+            pbr_bindings::BASE_COLOR_FACTOR,
+            pbr_bindings::SURFACE_FACTORS,
+            pbr_bindings::EMISSIVE_FACTOR,
+            pbr_bindings::BASE_COLOR_TEXTURE,
+            pbr_bindings::BASE_COLOR_SAMPLER,
+            pbr_bindings::METALLIC_ROUGHNESS_TEXTURE,
+            pbr_bindings::METALLIC_ROUGHNESS_SAMPLER,
+            pbr_bindings::NORMAL_TEXTURE,
+            pbr_bindings::NORMAL_SAMPLER,
+            pbr_bindings::OCCLUSION_TEXTURE,
+            pbr_bindings::OCCLUSION_SAMPLER,
+            pbr_bindings::EMISSIVE_TEXTURE,
+            pbr_bindings::EMISSIVE_SAMPLER,
+            pbr_bindings::ENVIRONMENT_TEXTURE,
+            pbr_bindings::ENVIRONMENT_SAMPLER,
+        ] {
+            assert!(
+                module
+                    .global_variables
+                    .iter()
+                    .any(|(_, variable)| variable.name.as_deref() == Some(name)),
+                "{} is not bound",
+                name
+            );
+        }
+    }
+}
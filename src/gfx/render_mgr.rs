@@ -1,15 +1,18 @@
 use super::{
-    build_rendering_command, BindGroupLayoutCache, CameraClearMode, DepthStencil, DepthStencilMode,
-    FrameBufferAllocator, GenericBufferAllocation, GfxContextHandle, PipelineCache,
-    PipelineLayoutCache, Renderer, RenderingCommand,
+    build_rendering_command, capture_texture, BindGroupLayoutCache, CameraClearMode, DepthStencil,
+    DepthStencilMode, Frame, FrameBufferAllocator, FrameCaptureError, GenericBufferAllocation,
+    GfxContextHandle, PipelineCache, PipelineLayoutCache, RenderTarget, RenderTargetTexture,
+    RenderTargetTextureHandle, Renderer, RenderingCommand, ShadowMaps, Texture, TextureHandle,
 };
 use crate::object::{ObjectHierarchy, ObjectId};
+use image::{DynamicImage, Rgba, RgbaImage};
 use std::mem::size_of;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     Buffer, BufferSize, BufferUsages, Color, CommandBuffer, CommandEncoder,
-    CommandEncoderDescriptor, LoadOp, Operations, RenderPass, RenderPassColorAttachment,
-    RenderPassDepthStencilAttachment, SurfaceError, TextureView,
+    CommandEncoderDescriptor, Device, Extent3d, LoadOp, Operations, RenderPass,
+    RenderPassColorAttachment, RenderPassDepthStencilAttachment, SurfaceError, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureView,
 };
 use winit::dpi::PhysicalSize;
 use zerocopy::AsBytes;
@@ -17,11 +20,15 @@ use zerocopy::AsBytes;
 pub struct RenderManager {
     gfx_ctx: GfxContextHandle,
     depth_stencil: DepthStencil,
+    /// The frame buffer of headless contexts, which have no surface.
+    offscreen_frame: Option<wgpu::Texture>,
+    shadow_maps: ShadowMaps,
     bind_group_layout_cache: BindGroupLayoutCache,
     pipeline_layout_cache: PipelineLayoutCache,
     pipeline_cache: PipelineCache,
     frame_buffer_allocator: FrameBufferAllocator,
     standard_ui_vertex_buffer: GenericBufferAllocation<Buffer>,
+    white_texture: TextureHandle,
 }
 
 impl RenderManager {
@@ -31,7 +38,11 @@ impl RenderManager {
         depth_stencil_mode: DepthStencilMode,
     ) -> Self {
         let depth_stencil = DepthStencil::new(gfx_ctx.clone(), depth_stencil_mode, size).unwrap();
-        let bind_group_layout_cache = BindGroupLayoutCache::new(gfx_ctx.clone());
+        let offscreen_frame = gfx_ctx
+            .is_headless()
+            .then(|| create_offscreen_frame(&gfx_ctx.device, size));
+        let mut bind_group_layout_cache = BindGroupLayoutCache::new(gfx_ctx.clone());
+        let shadow_maps = ShadowMaps::new(&gfx_ctx.device, &mut bind_group_layout_cache);
         let pipeline_layout_cache = PipelineLayoutCache::new(gfx_ctx.clone());
         let pipeline_cache = PipelineCache::new(gfx_ctx.clone());
         let frame_buffer_allocator = FrameBufferAllocator::new(gfx_ctx.clone());
@@ -54,15 +65,24 @@ impl RenderManager {
             0,
             BufferSize::new((size_of::<f32>() * standard_ui_vertices.len()) as u64).unwrap(),
         );
+        let white_texture = TextureHandle::new(Texture::from_image(
+            TextureFormat::Rgba8Unorm,
+            &DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([255; 4]))),
+            &gfx_ctx.device,
+            &gfx_ctx.queue,
+        ));
 
         Self {
             gfx_ctx,
             depth_stencil,
+            offscreen_frame,
+            shadow_maps,
             bind_group_layout_cache,
             pipeline_layout_cache,
             pipeline_cache,
             frame_buffer_allocator,
             standard_ui_vertex_buffer,
+            white_texture,
         }
     }
 
@@ -86,8 +106,52 @@ impl RenderManager {
         &self.standard_ui_vertex_buffer
     }
 
+    /// A 1x1 white texture, bound in place of missing textures.
+    pub fn white_texture(&self) -> &TextureHandle {
+        &self.white_texture
+    }
+
+    pub fn shadow_maps(&self) -> &ShadowMaps {
+        &self.shadow_maps
+    }
+
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
         self.depth_stencil.resize(size);
+
+        if self.offscreen_frame.is_some() && size.width != 0 && size.height != 0 {
+            self.offscreen_frame = Some(create_offscreen_frame(&self.gfx_ctx.device, size));
+        }
+    }
+
+    /// Acquires the frame buffer to render the current frame into.
+    pub fn acquire_frame(&self) -> Result<Frame, SurfaceError> {
+        match (&self.gfx_ctx.surface, &self.offscreen_frame) {
+            (Some(surface), _) => {
+                let surface_texture = surface.get_current_texture()?;
+                let view = surface_texture.texture.create_view(&Default::default());
+                Ok(Frame::new(Some(surface_texture), view))
+            }
+            (None, Some(texture)) => Ok(Frame::new(None, texture.create_view(&Default::default()))),
+            (None, None) => Err(SurfaceError::Lost),
+        }
+    }
+
+    /// Reads the last rendered frame back. Only headless contexts support this.
+    pub fn capture_frame(&self) -> Result<RgbaImage, FrameCaptureError> {
+        match &self.offscreen_frame {
+            Some(texture) => capture_texture(&self.gfx_ctx.device, &self.gfx_ctx.queue, texture),
+            None => Err(FrameCaptureError::SurfaceFrame),
+        }
+    }
+
+    /// Creates a texture cameras can render into, with a depth buffer matching the frame buffer.
+    pub fn create_render_target(&self, width: u16, height: u16) -> RenderTargetTextureHandle {
+        RenderTargetTextureHandle::new(RenderTargetTexture::new(
+            &self.gfx_ctx.device,
+            width,
+            height,
+            self.depth_stencil.mode(),
+        ))
     }
 
     pub fn create_encoder(&self) -> CommandEncoder {
@@ -96,16 +160,22 @@ impl RenderManager {
             .create_command_encoder(&CommandEncoderDescriptor { label: None })
     }
 
+    /// Begins a render pass into the given target; `frame_view` is used for the surface.
     pub fn begin_frame_buffer_render_pass<'e>(
         &'e self,
         encoder: &'e mut CommandEncoder,
-        surface_texture_view: &'e TextureView,
+        frame_view: &'e TextureView,
+        target: &'e RenderTarget,
         clear_mode: &CameraClearMode,
     ) -> Result<RenderPass<'e>, SurfaceError> {
+        let (view, depth_stencil_view) = match target {
+            RenderTarget::Surface => (frame_view, self.depth_stencil.texture_view()),
+            RenderTarget::Texture(texture) => (texture.view(), texture.depth_stencil_view()),
+        };
         let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(RenderPassColorAttachment {
-                view: &surface_texture_view,
+                view,
                 resolve_target: None,
                 ops: Operations {
                     load: match clear_mode {
@@ -121,7 +191,7 @@ impl RenderManager {
                     store: true,
                 },
             })],
-            depth_stencil_attachment: self.depth_stencil.texture_view().map(|view| {
+            depth_stencil_attachment: depth_stencil_view.map(|view| {
                 RenderPassDepthStencilAttachment {
                     view,
                     depth_ops: Some(Operations {
@@ -146,6 +216,29 @@ impl RenderManager {
         Ok(render_pass)
     }
 
+    /// Begins a depth-only render pass into a layer of the shadow maps, clearing it.
+    /// Returns `None` if the layer is out of range.
+    pub fn begin_shadow_map_render_pass<'e>(
+        &'e self,
+        encoder: &'e mut CommandEncoder,
+        layer: usize,
+    ) -> Option<RenderPass<'e>> {
+        let view = self.shadow_maps.layer_view(layer)?;
+        let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("shadow map render pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        Some(render_pass)
+    }
+
     /// Constructs a rendering command for the given object by encoding per-instance data into a buffer.
     pub fn build_rendering_command<'r>(
         &mut self,
@@ -169,3 +262,20 @@ impl RenderManager {
         self.frame_buffer_allocator.recall();
     }
 }
+
+fn create_offscreen_frame(device: &Device, size: PhysicalSize<u32>) -> wgpu::Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some("offscreen frame texture"),
+        size: Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: RenderTargetTexture::FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[RenderTargetTexture::FORMAT],
+    })
+}
@@ -0,0 +1,161 @@
+use super::{
+    create_texture_and_view, semantic_outputs, DepthStencilMode, ScreenManager, Sprite,
+    SpriteHandle, SpriteTexelMapping, Texture, TextureHandle,
+};
+use codegen::Handle;
+use std::fmt::Debug;
+use wgpu::{
+    AddressMode, Device, Extent3d, FilterMode, SamplerDescriptor, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureView,
+};
+use winit::dpi::PhysicalSize;
+
+/// Where a camera renders into.
+#[derive(Default, Clone)]
+pub enum RenderTarget {
+    /// The frame buffer of the window.
+    #[default]
+    Surface,
+    /// A texture owned by the engine, which can be drawn as a sprite or bound to materials afterwards.
+    /// Cameras render in the order of their depth, so the camera rendering into the texture must have
+    /// a lower depth than the cameras showing it. A camera cannot show its own render target.
+    Texture(RenderTargetTextureHandle),
+}
+
+impl RenderTarget {
+    /// Returns the width and height of the target, in logical pixels for the surface and in texels for textures.
+    pub fn size(&self, screen_mgr: &ScreenManager) -> (f32, f32) {
+        match self {
+            Self::Surface => (screen_mgr.width() as f32, screen_mgr.height() as f32),
+            Self::Texture(texture) => (texture.width() as f32, texture.height() as f32),
+        }
+    }
+
+    /// Returns the width of the target divided by its height.
+    pub fn aspect(&self, screen_mgr: &ScreenManager) -> f32 {
+        let (width, height) = self.size(screen_mgr);
+        width / height
+    }
+}
+
+impl Debug for RenderTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Surface => write!(f, "Surface"),
+            Self::Texture(texture) => f
+                .debug_struct("Texture")
+                .field("width", &texture.width())
+                .field("height", &texture.height())
+                .finish(),
+        }
+    }
+}
+
+/// A color texture along with its own depth buffer, which cameras can render into.
+#[derive(Handle)]
+pub struct RenderTargetTexture {
+    texture: TextureHandle,
+    depth_stencil_texture: Option<wgpu::Texture>,
+    depth_stencil_view: Option<TextureView>,
+}
+
+impl RenderTargetTexture {
+    /// Matches the semantic color output, so that the same pipelines serve the frame buffer too.
+    pub const FORMAT: TextureFormat = semantic_outputs::COLOR.target.format;
+
+    /// Creates a render target of the given size; zero sizes are raised to 1.
+    pub fn new(
+        device: &Device,
+        width: u16,
+        height: u16,
+        depth_stencil_mode: DepthStencilMode,
+    ) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("render target texture"),
+            size: Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC,
+            view_formats: &[Self::FORMAT],
+        });
+        let view = texture.create_view(&Default::default());
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: None,
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            compare: None,
+            anisotropy_clamp: 1,
+            border_color: None,
+        });
+        let (depth_stencil_texture, depth_stencil_view) = create_texture_and_view(
+            device,
+            depth_stencil_mode,
+            PhysicalSize::new(width as u32, height as u32),
+        );
+
+        Self {
+            texture: TextureHandle::new(Texture {
+                texture: texture.into(),
+                view: view.into(),
+                sampler: sampler.into(),
+                width,
+                height,
+            }),
+            depth_stencil_texture,
+            depth_stencil_view,
+        }
+    }
+
+    /// The color texture, to be bound to materials or drawn as a sprite.
+    pub fn texture(&self) -> &TextureHandle {
+        &self.texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.width as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.height as u32
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width() as f32 / self.height() as f32
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.texture.view
+    }
+
+    pub fn depth_stencil_texture(&self) -> Option<&wgpu::Texture> {
+        self.depth_stencil_texture.as_ref()
+    }
+
+    pub fn depth_stencil_view(&self) -> Option<&TextureView> {
+        self.depth_stencil_view.as_ref()
+    }
+
+    /// Creates a sprite covering the whole texture, to be drawn by a `UIElementRenderer`.
+    pub fn sprite(&self) -> SpriteHandle {
+        SpriteHandle::new(Sprite::new(
+            self.texture.clone(),
+            SpriteTexelMapping::new(0, self.texture.width, 0, self.texture.height),
+        ))
+    }
+}
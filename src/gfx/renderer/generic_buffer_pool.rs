@@ -34,7 +34,7 @@ impl<T> GenericBufferAllocation<T>
 where
     T: GenericBuffer,
 {
-    pub fn new(buffer: T, offset: BufferAddress, size: BufferSize) -> Self {
+    pub fn new(buffer: impl Into<Arc<T>>, offset: BufferAddress, size: BufferSize) -> Self {
         Self {
             buffer: buffer.into(),
             offset,
@@ -41,6 +41,8 @@ impl<'r> RenderingCommand<'r> {
         render_pass: &mut RenderPass<'r>,
         camera_transform_bind_group: &'r BindGroup,
         screen_size_bind_group: &'r BindGroup,
+        light_bind_group: &'r BindGroup,
+        shadow_maps_bind_group: &'r BindGroup,
     ) {
         render_pass.set_pipeline(self.pipeline.as_ref());
 
@@ -58,6 +60,12 @@ impl<'r> RenderingCommand<'r> {
                 semantic_bindings::KEY_SCREEN_SIZE => {
                     render_pass.set_bind_group(binding.group, screen_size_bind_group, &[]);
                 }
+                semantic_bindings::KEY_LIGHTS => {
+                    render_pass.set_bind_group(binding.group, light_bind_group, &[]);
+                }
+                semantic_bindings::KEY_SHADOW_MAPS => {
+                    render_pass.set_bind_group(binding.group, shadow_maps_bind_group, &[]);
+                }
                 _ => {
                     // TODO: Since this bind group is required, we should notify the user if it's not present.
                     if let Some(bind_group) = self.bind_group_provider.bind_group(0, key) {
@@ -115,7 +123,15 @@ impl<'r> RenderingCommand<'r> {
             }
         }
 
-        render_pass.draw(0..self.vertex_count, 0..self.instance_count);
+        match self.vertex_buffer_provider.index_buffer() {
+            Some(IndexBuffer { buffer, format }) => {
+                render_pass.set_index_buffer(buffer.as_slice(), format);
+                render_pass.draw_indexed(0..self.vertex_count, 0, 0..self.instance_count);
+            }
+            None => {
+                render_pass.draw(0..self.vertex_count, 0..self.instance_count);
+            }
+        }
     }
 }
 
@@ -1,11 +1,20 @@
 use super::RendererVertexBufferLayout;
-use crate::gfx::{BufferLayout, CachedPipeline, MaterialHandle, PipelineCache, ShaderManager};
+use crate::gfx::{
+    BufferLayout, CachedPipeline, Material, MaterialHandle, PipelineCache, ShaderHandle,
+    ShaderManager,
+};
 use wgpu::{DepthStencilState, PrimitiveState, VertexAttribute, VertexStepMode};
 
 // TODO: Should we make buffer layouts and states to be shared across all renderer instances?
 pub struct PipelineProvider {
     is_dirty: bool,
     pipeline: Option<CachedPipeline>,
+    /// The shader the cached pipeline was built with. The shader of a material can be swapped by hot reload.
+    pipeline_shader: Option<ShaderHandle>,
+    is_depth_only_dirty: bool,
+    /// Depth-only pipeline for a material other than the one of the renderer, such as a shadow caster.
+    depth_only_pipeline: Option<CachedPipeline>,
+    depth_only_pipeline_shader: Option<ShaderHandle>,
     material: Option<MaterialHandle>,
     buffer_layouts: Vec<RendererVertexBufferLayout>,
     primitive: Option<PrimitiveState>,
@@ -17,6 +26,10 @@ impl PipelineProvider {
         Self {
             is_dirty: true,
             pipeline: None,
+            pipeline_shader: None,
+            is_depth_only_dirty: true,
+            depth_only_pipeline: None,
+            depth_only_pipeline_shader: None,
             material: None,
             buffer_layouts: Vec::new(),
             primitive: None,
@@ -35,11 +48,13 @@ impl PipelineProvider {
 
     pub fn set_buffer_layouts(&mut self, buffer_layouts: Vec<RendererVertexBufferLayout>) {
         self.is_dirty = true;
+        self.is_depth_only_dirty = true;
         self.buffer_layouts = buffer_layouts;
     }
 
     pub fn set_primitive(&mut self, primitive: PrimitiveState) {
         self.is_dirty = true;
+        self.is_depth_only_dirty = true;
         self.primitive = Some(primitive);
     }
 
@@ -53,18 +68,75 @@ impl PipelineProvider {
         shader_mgr: &ShaderManager,
         pipeline_cache: &mut PipelineCache,
     ) -> Option<CachedPipeline> {
-        if !self.is_dirty {
-            if let Some(pipeline) = self.pipeline.clone() {
-                return Some(pipeline);
-            }
-        }
-
         let material = if let Some(material) = &self.material {
             material.read()
         } else {
             return None;
         };
 
+        if !self.is_dirty && self.pipeline_shader.as_ref() == Some(&material.shader) {
+            if let Some(pipeline) = self.pipeline.clone() {
+                return Some(pipeline);
+            }
+        }
+
+        let pipeline = self.create_pipeline(
+            &material,
+            self.depth_stencil.clone(),
+            false,
+            shader_mgr,
+            pipeline_cache,
+        )?;
+
+        self.is_dirty = false;
+        self.pipeline = Some(pipeline.clone());
+        self.pipeline_shader = Some(material.shader.clone());
+
+        Some(pipeline)
+    }
+
+    /// Returns a pipeline rendering the buffers of the renderer with the given material into a depth buffer only,
+    /// leaving out the fragment stage. Used to render shadow casters.
+    pub fn obtain_depth_only_pipeline(
+        &mut self,
+        material: &MaterialHandle,
+        depth_stencil: DepthStencilState,
+        shader_mgr: &ShaderManager,
+        pipeline_cache: &mut PipelineCache,
+    ) -> Option<CachedPipeline> {
+        let material = material.read();
+
+        if !self.is_depth_only_dirty
+            && self.depth_only_pipeline_shader.as_ref() == Some(&material.shader)
+        {
+            if let Some(pipeline) = self.depth_only_pipeline.clone() {
+                return Some(pipeline);
+            }
+        }
+
+        let pipeline = self.create_pipeline(
+            &material,
+            Some(depth_stencil),
+            true,
+            shader_mgr,
+            pipeline_cache,
+        )?;
+
+        self.is_depth_only_dirty = false;
+        self.depth_only_pipeline = Some(pipeline.clone());
+        self.depth_only_pipeline_shader = Some(material.shader.clone());
+
+        Some(pipeline)
+    }
+
+    fn create_pipeline(
+        &self,
+        material: &Material,
+        depth_stencil: Option<DepthStencilState>,
+        depth_only: bool,
+        shader_mgr: &ShaderManager,
+        pipeline_cache: &mut PipelineCache,
+    ) -> Option<CachedPipeline> {
         if self.buffer_layouts.len() == 0 {
             return None;
         }
@@ -120,18 +192,14 @@ impl PipelineProvider {
             attributes: per_instance_attributes,
         });
 
-        let pipeline = pipeline_cache.create_pipeline(
+        Some(pipeline_cache.create_pipeline(
             shader_mgr,
             material.pipeline_layout.clone(),
             material.shader.clone(),
             buffer_layouts,
             primitive,
-            self.depth_stencil.clone(),
-        );
-
-        self.is_dirty = false;
-        self.pipeline = Some(pipeline.clone());
-
-        Some(pipeline)
+            depth_stencil,
+            depth_only,
+        ))
     }
 }
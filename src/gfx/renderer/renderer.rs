@@ -1,7 +1,7 @@
 use super::{GenericBufferAllocation, HostBuffer};
 use crate::gfx::{CachedPipeline, Material, SemanticShaderBindingKey, SemanticShaderInputKey};
 use parking_lot::RwLockReadGuard;
-use wgpu::{BindGroup, Buffer, BufferAddress};
+use wgpu::{BindGroup, Buffer, BufferAddress, IndexFormat};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RendererVertexBufferLayout {
@@ -40,9 +40,16 @@ pub struct VertexBuffer<'a> {
     pub buffer: &'a GenericBufferAllocation<Buffer>,
 }
 
+pub struct IndexBuffer<'a> {
+    pub buffer: &'a GenericBufferAllocation<Buffer>,
+    pub format: IndexFormat,
+}
+
 pub trait VertexBufferProvider {
     fn vertex_buffer_count(&self) -> u32;
     fn vertex_buffer(&self, key: SemanticShaderInputKey) -> Option<VertexBuffer>;
+    /// If an index buffer is provided, [`Renderer::vertex_count`] is treated as the index count.
+    fn index_buffer(&self) -> Option<IndexBuffer>;
 }
 
 pub trait InstanceDataProvider {
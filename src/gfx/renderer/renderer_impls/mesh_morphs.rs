@@ -0,0 +1,335 @@
+use crate::gfx::semantic_bindings::MORPH_TEXTURE_WIDTH;
+use asset::assets::{
+    GroupMorphOffset, MaterialMorphOffset, MaterialMorphOperation, MaterialMorphValues, Mesh,
+    ModelAsset, MorphKind,
+};
+
+/// The maximum depth of nested group morphs. Deeper morphs, including cyclic ones, are ignored.
+const MAX_GROUP_DEPTH: usize = 8;
+
+/// Material parameters accumulated from the material morphs of a sub mesh.
+/// A morphed parameter is `base * multiply + add`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaterialMorph {
+    pub multiply: MaterialMorphValues,
+    pub add: MaterialMorphValues,
+}
+
+impl MaterialMorph {
+    pub const IDENTITY: Self = Self {
+        multiply: MaterialMorphValues::ONE,
+        add: MaterialMorphValues::ZERO,
+    };
+
+    pub fn apply(&self, base: &MaterialMorphValues) -> MaterialMorphValues {
+        base.zip_with(&self.multiply, |base, multiply| base * multiply)
+            .zip_with(&self.add, |value, add| value + add)
+    }
+}
+
+/// Morph weights of a sub mesh renderer, along with the state derived from them.
+pub(crate) struct MeshMorphs {
+    morphs: Vec<MeshMorph>,
+    weights: Vec<f32>,
+    target_weights: Vec<f32>,
+    material: MaterialMorph,
+    is_dirty: bool,
+}
+
+struct MeshMorph {
+    name: String,
+    /// Index into [`Mesh::morph_targets`].
+    target_index: Option<usize>,
+    group: Vec<GroupMorphOffset>,
+    /// Material offsets affecting the sub mesh.
+    materials: Vec<MaterialMorphOffset>,
+}
+
+impl MeshMorphs {
+    pub fn new(model: &dyn ModelAsset, mesh: Option<&Mesh>) -> Self {
+        let morphs = Vec::from_iter(model.morphs().iter().enumerate().map(|(index, morph)| {
+            let target_index = mesh.and_then(|mesh| {
+                mesh.morph_targets
+                    .iter()
+                    .position(|target| target.morph_index as usize == index)
+            });
+            let (group, materials) = match &morph.kind {
+                MorphKind::Vertex => (vec![], vec![]),
+                MorphKind::Material(offsets) => (
+                    vec![],
+                    Vec::from_iter(offsets.iter().copied().filter(|offset| {
+                        offset.mesh_index.is_none()
+                            || mesh.map(|mesh| mesh.index) == offset.mesh_index
+                    })),
+                ),
+                MorphKind::Group(offsets) => (offsets.clone(), vec![]),
+            };
+
+            MeshMorph {
+                name: morph.name.clone(),
+                target_index,
+                group,
+                materials,
+            }
+        }));
+        let target_count = mesh.map_or(0, |mesh| mesh.morph_targets.len());
+
+        Self {
+            weights: vec![0.0; morphs.len()],
+            morphs,
+            target_weights: vec![0.0; target_count],
+            material: MaterialMorph::IDENTITY,
+            is_dirty: false,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            morphs: vec![],
+            weights: vec![],
+            target_weights: vec![],
+            material: MaterialMorph::IDENTITY,
+            is_dirty: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.morphs.len()
+    }
+
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.morphs.get(index).map(|morph| morph.name.as_str())
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.morphs.iter().position(|morph| morph.name == name)
+    }
+
+    pub fn weight(&self, index: usize) -> f32 {
+        self.weights.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_weight(&mut self, index: usize, weight: f32) {
+        if let Some(current) = self.weights.get_mut(index) {
+            if *current != weight {
+                *current = weight;
+                self.is_dirty = true;
+            }
+        }
+    }
+
+    /// Weight of each morph target of the sub mesh, with group morphs expanded.
+    pub fn target_weights(&self) -> &[f32] {
+        &self.target_weights
+    }
+
+    pub fn material(&self) -> &MaterialMorph {
+        &self.material
+    }
+
+    /// Re-evaluates the derived state if any weight has changed. Returns `true` if it has.
+    pub fn update(&mut self) -> bool {
+        if !self.is_dirty {
+            return false;
+        }
+
+        let mut weights = vec![0.0; self.morphs.len()];
+
+        for (index, &weight) in self.weights.iter().enumerate() {
+            if weight != 0.0 {
+                accumulate_weight(&self.morphs, index, weight, 0, &mut weights);
+            }
+        }
+
+        self.target_weights.fill(0.0);
+        self.material = MaterialMorph::IDENTITY;
+
+        for (morph, &weight) in self.morphs.iter().zip(&weights) {
+            if weight == 0.0 {
+                continue;
+            }
+
+            if let Some(target_index) = morph.target_index {
+                self.target_weights[target_index] += weight;
+            }
+
+            for offset in &morph.materials {
+                match offset.operation {
+                    MaterialMorphOperation::Multiply => {
+                        self.material.multiply = self
+                            .material
+                            .multiply
+                            .zip_with(&offset.values, |multiply, value| {
+                                multiply * (1.0 + (value - 1.0) * weight)
+                            });
+                    }
+                    MaterialMorphOperation::Add => {
+                        self.material.add = self
+                            .material
+                            .add
+                            .zip_with(&offset.values, |add, value| add + value * weight);
+                    }
+                }
+            }
+        }
+
+        self.is_dirty = false;
+        true
+    }
+}
+
+fn accumulate_weight(
+    morphs: &[MeshMorph],
+    index: usize,
+    weight: f32,
+    depth: usize,
+    weights: &mut [f32],
+) {
+    weights[index] += weight;
+
+    if MAX_GROUP_DEPTH <= depth {
+        return;
+    }
+
+    for offset in &morphs[index].group {
+        if (offset.morph_index as usize) < morphs.len() {
+            accumulate_weight(
+                morphs,
+                offset.morph_index as usize,
+                weight * offset.coefficient,
+                depth + 1,
+                weights,
+            );
+        }
+    }
+}
+
+/// Lays out the morph targets of the sub mesh as texels of [`MORPH_TEXTURE_WIDTH`] per row:
+/// 1. The first row holds the weight of each target, 4 per texel.
+/// 2. Each vertex has a texel from the second row on, holding the index of its first offset texel
+///    and the number of its offsets.
+/// 3. Each offset takes two texels; the position offset along with the target index, then the UV offset.
+///
+/// Floats are stored as their bits. Returns `None` if the sub mesh has no morph targets.
+pub(crate) fn build_morph_texels(mesh: &Mesh) -> Option<Vec<[u32; 4]>> {
+    if mesh.morph_targets.is_empty() {
+        return None;
+    }
+
+    let mut vertex_offsets = vec![Vec::new(); mesh.vertex_count as usize];
+
+    for (target_index, target) in mesh.morph_targets.iter().enumerate() {
+        for offset in &target.offsets {
+            if let Some(offsets) = vertex_offsets.get_mut(offset.vertex_index as usize) {
+                offsets.push((target_index as u32, offset));
+            }
+        }
+    }
+
+    let offset_base = (MORPH_TEXTURE_WIDTH + mesh.vertex_count) as usize;
+    let offset_count = vertex_offsets.iter().map(Vec::len).sum::<usize>();
+    let texel_count = offset_base + offset_count * 2;
+    let row_count = texel_count.div_ceil(MORPH_TEXTURE_WIDTH as usize);
+    let mut texels = vec![[0u32; 4]; row_count * MORPH_TEXTURE_WIDTH as usize];
+    let mut next_offset = offset_base;
+
+    for (vertex_index, offsets) in vertex_offsets.iter().enumerate() {
+        texels[MORPH_TEXTURE_WIDTH as usize + vertex_index] =
+            [next_offset as u32, offsets.len() as u32, 0, 0];
+
+        for (target_index, offset) in offsets {
+            texels[next_offset] = [
+                offset.position[0].to_bits(),
+                offset.position[1].to_bits(),
+                offset.position[2].to_bits(),
+                *target_index,
+            ];
+            texels[next_offset + 1] = [offset.uv[0].to_bits(), offset.uv[1].to_bits(), 0, 0];
+            next_offset += 2;
+        }
+    }
+
+    Some(texels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_morph(name: &str, target_index: Option<usize>, group: Vec<(u32, f32)>) -> MeshMorph {
+        MeshMorph {
+            name: name.to_owned(),
+            target_index,
+            group: Vec::from_iter(group.into_iter().map(|(morph_index, coefficient)| {
+                GroupMorphOffset {
+                    morph_index,
+                    coefficient,
+                }
+            })),
+            materials: vec![],
+        }
+    }
+
+    #[test]
+    fn check_group_morphs_expand_recursively() {
+        let mut morphs = MeshMorphs::empty();
+        morphs.morphs = vec![
+            create_morph("smile", Some(0), vec![]),
+            create_morph("blink", Some(1), vec![]),
+            create_morph("happy", None, vec![(0, 0.5), (1, 1.0)]),
+            create_morph("joy", None, vec![(2, 0.5)]),
+            // cyclic groups terminate at the maximum depth
+            create_morph("cycle", None, vec![(4, 1.0)]),
+        ];
+        morphs.weights = vec![0.0; morphs.morphs.len()];
+        morphs.target_weights = vec![0.0; 2];
+
+        morphs.set_weight(morphs.find("joy").unwrap(), 1.0);
+        morphs.set_weight(morphs.find("blink").unwrap(), 0.25);
+        morphs.set_weight(morphs.find("cycle").unwrap(), 1.0);
+        assert!(morphs.update());
+        assert_eq!(morphs.target_weights(), [0.25, 0.75]);
+        assert!(!morphs.update());
+    }
+
+    #[test]
+    fn check_material_morph_operations() {
+        let values = MaterialMorphValues {
+            diffuse_color: [0.5, 0.5, 0.5, 0.0],
+            ..MaterialMorphValues::ZERO
+        };
+        let mut morphs = MeshMorphs::empty();
+        morphs.morphs = vec![MeshMorph {
+            name: "fade".to_owned(),
+            target_index: None,
+            group: vec![],
+            materials: vec![
+                MaterialMorphOffset {
+                    mesh_index: None,
+                    operation: MaterialMorphOperation::Multiply,
+                    values: MaterialMorphValues {
+                        diffuse_color: [1.0, 1.0, 1.0, 0.0],
+                        ..MaterialMorphValues::ONE
+                    },
+                },
+                MaterialMorphOffset {
+                    mesh_index: None,
+                    operation: MaterialMorphOperation::Add,
+                    values,
+                },
+            ],
+        }];
+        morphs.weights = vec![0.0];
+
+        morphs.set_weight(0, 0.5);
+        morphs.update();
+
+        let base = MaterialMorphValues {
+            diffuse_color: [1.0, 0.0, 0.0, 1.0],
+            ..MaterialMorphValues::ONE
+        };
+        let morphed = morphs.material().apply(&base);
+        assert_eq!(morphed.diffuse_color, [1.25, 0.25, 0.25, 0.5]);
+        assert_eq!(morphed.edge_size, 1.0);
+    }
+}
@@ -1,9 +1,17 @@
-use crate::gfx::{
-    semantic_inputs::{self, KEY_NORMAL, KEY_POSITION, KEY_UV},
-    BindGroupProvider, CachedPipeline, GenericBufferAllocation, HostBuffer, InstanceDataProvider,
-    Material, MaterialHandle, MeshHandle, PipelineCache, PipelineProvider, Renderer,
-    RendererVertexBufferAttribute, RendererVertexBufferLayout, SemanticShaderBindingKey,
-    SemanticShaderInputKey, ShaderManager, VertexBuffer, VertexBufferProvider,
+use crate::{
+    gfx::{
+        semantic_inputs::{self, KEY_NORMAL, KEY_POSITION, KEY_TANGENT, KEY_UV},
+        BindGroupProvider, CachedPipeline, GenericBufferAllocation, HostBuffer, IndexBuffer,
+        InstanceDataProvider, Material, MaterialHandle, MeshHandle, PipelineCache,
+        PipelineProvider, Renderer, RendererVertexBufferAttribute, RendererVertexBufferLayout,
+        SemanticShaderBindingKey, SemanticShaderInputKey, ShaderManager, ShadowMaps, VertexBuffer,
+        VertexBufferProvider,
+    },
+    math::{Mat4, Vec3, Vec4},
+};
+use asset::{
+    assets::{ModelAsset, VertexAttributeKind, VertexIndexType},
+    GfxBuffer,
 };
 use parking_lot::RwLockReadGuard;
 use specs::{prelude::*, Component};
@@ -11,7 +19,8 @@ use std::mem::size_of;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     BindGroup, Buffer, BufferAddress, BufferSize, BufferUsages, CompareFunction, DepthStencilState,
-    Device, Face, FrontFace, PolygonMode, PrimitiveState, PrimitiveTopology, TextureFormat,
+    Device, Face, FrontFace, IndexFormat, PolygonMode, PrimitiveState, PrimitiveTopology,
+    TextureFormat,
 };
 use zerocopy::AsBytes;
 
@@ -20,31 +29,28 @@ use zerocopy::AsBytes;
 pub struct MeshRenderer {
     mask: u32,
     pipeline_provider: PipelineProvider,
-    mesh: Option<MeshHandle>,
+    vertex_count: u32,
     vertex_buffer: Option<GenericBufferAllocation<Buffer>>,
+    index_buffer: Option<(GenericBufferAllocation<Buffer>, IndexFormat)>,
+    /// Center and radius of the bounding sphere in local space, if the mesh has a [`MeshAABB`](asset::assets::MeshAABB).
+    bounding_sphere: Option<(Vec3, f32)>,
+    lods: Vec<MeshRendererLod>,
+    selected_lod: Option<usize>,
+    casts_shadows: bool,
+    receives_shadows: bool,
+}
+
+struct MeshRendererLod {
+    screen_size: f32,
+    vertex_count: u32,
+    index_buffer: (GenericBufferAllocation<Buffer>, IndexFormat),
 }
 
 impl MeshRenderer {
     pub fn new() -> Self {
         let mut pipeline_provider = PipelineProvider::new();
 
-        pipeline_provider.set_buffer_layouts(vec![RendererVertexBufferLayout {
-            array_stride: size_of::<[f32; 8]>() as BufferAddress,
-            attributes: vec![
-                RendererVertexBufferAttribute {
-                    key: KEY_POSITION,
-                    offset: 0,
-                },
-                RendererVertexBufferAttribute {
-                    key: KEY_NORMAL,
-                    offset: size_of::<[f32; 3]>() as BufferAddress,
-                },
-                RendererVertexBufferAttribute {
-                    key: KEY_UV,
-                    offset: size_of::<[f32; 6]>() as BufferAddress,
-                },
-            ],
-        }]);
+        pipeline_provider.set_buffer_layouts(vec![mesh_buffer_layout()]);
         pipeline_provider.set_primitive(PrimitiveState {
             topology: PrimitiveTopology::TriangleList,
             strip_index_format: None,
@@ -65,8 +71,14 @@ impl MeshRenderer {
         Self {
             mask: 0xFFFF_FFFF,
             pipeline_provider,
-            mesh: None,
+            vertex_count: 0,
             vertex_buffer: None,
+            index_buffer: None,
+            bounding_sphere: None,
+            lods: vec![],
+            selected_lod: None,
+            casts_shadows: true,
+            receives_shadows: true,
         }
     }
 
@@ -78,18 +90,45 @@ impl MeshRenderer {
         self.mask = mask;
     }
 
+    /// Whether the mesh is rendered into the shadow maps of shadow casting lights.
+    pub fn casts_shadows(&self) -> bool {
+        self.casts_shadows
+    }
+
+    pub fn set_casts_shadows(&mut self, casts_shadows: bool) {
+        self.casts_shadows = casts_shadows;
+    }
+
+    /// Whether shadows fall on the mesh, passed to the material as [`semantic_inputs::RECEIVE_SHADOWS`].
+    pub fn receives_shadows(&self) -> bool {
+        self.receives_shadows
+    }
+
+    pub fn set_receives_shadows(&mut self, receives_shadows: bool) {
+        self.receives_shadows = receives_shadows;
+    }
+
+    pub fn material(&self) -> Option<&MaterialHandle> {
+        self.pipeline_provider.material()
+    }
+
     pub fn set_material(&mut self, material: MaterialHandle) {
         self.pipeline_provider.set_material(material);
     }
 
     pub fn set_mesh(&mut self, mesh: MeshHandle, device: &Device) {
+        self.pipeline_provider
+            .set_buffer_layouts(vec![mesh_buffer_layout()]);
+        self.index_buffer = None;
+        self.clear_lods();
+
         if mesh.data.vertices.is_empty() {
-            self.mesh = None;
+            self.vertex_count = 0;
             self.vertex_buffer = None;
             return;
         }
 
-        self.mesh = Some(mesh.clone());
+        self.vertex_count = mesh.data.faces.len() as u32 * 3;
 
         let mut vertices = Vec::with_capacity(mesh.data.faces.len() * 3 * (3 + 3 + 2));
         let uvs = mesh.data.texture_coords[0].as_ref().unwrap();
@@ -123,6 +162,98 @@ impl MeshRenderer {
         ));
     }
 
+    /// Sets a sub mesh of a model asset. The vertex layout follows the attributes of the sub mesh.
+    /// Levels of detail of the sub mesh are selected by [`MeshRenderer::select_lod`].
+    pub fn set_model_mesh(&mut self, model: &dyn ModelAsset, mesh_index: u32) {
+        self.clear_lods();
+
+        let mesh = match model.meshes().get(mesh_index as usize) {
+            Some(mesh) if mesh.index_count != 0 => mesh,
+            _ => {
+                self.vertex_count = 0;
+                self.vertex_buffer = None;
+                self.index_buffer = None;
+                return;
+            }
+        };
+
+        self.pipeline_provider
+            .set_buffer_layouts(vec![RendererVertexBufferLayout {
+                array_stride: mesh.vertex_stride() as BufferAddress,
+                attributes: Vec::from_iter(mesh.vertex_attributes.iter().filter_map(|attribute| {
+                    let key = match attribute.kind {
+                        VertexAttributeKind::Position => KEY_POSITION,
+                        VertexAttributeKind::Normal => KEY_NORMAL,
+                        VertexAttributeKind::TexCoord { index: 0 } => KEY_UV,
+                        VertexAttributeKind::Tangent => KEY_TANGENT,
+                        _ => return None,
+                    };
+
+                    Some(RendererVertexBufferAttribute {
+                        key,
+                        offset: attribute.offset as BufferAddress,
+                    })
+                })),
+            }]);
+
+        self.vertex_count = mesh.index_count;
+        self.vertex_buffer = Some(GenericBufferAllocation::new(
+            mesh.vertex_buffer.clone(),
+            0,
+            BufferSize::new(mesh.vertex_buffer.size()).unwrap(),
+        ));
+        self.index_buffer = Some(index_buffer(&mesh.index_buffer, mesh.index_type));
+
+        let min = Vec3::new(mesh.aabb.min[0], mesh.aabb.min[1], mesh.aabb.min[2]);
+        let max = Vec3::new(mesh.aabb.max[0], mesh.aabb.max[1], mesh.aabb.max[2]);
+        self.bounding_sphere = Some(((min + max) * 0.5, (max - min).len() * 0.5));
+        self.lods = Vec::from_iter(mesh.lods.iter().filter(|lod| lod.index_count != 0).map(
+            |lod| MeshRendererLod {
+                screen_size: lod.screen_size,
+                vertex_count: lod.index_count,
+                index_buffer: index_buffer(&lod.index_buffer, lod.index_type),
+            },
+        ));
+    }
+
+    /// Returns the bounding sphere of the mesh in world space, given the transform matrix of the object.
+    pub fn bounding_sphere(&self, matrix: &Mat4) -> Option<(Vec3, f32)> {
+        let (center, radius) = self.bounding_sphere?;
+        let scale = (0..3)
+            .map(|index| Vec3::from_vec4(matrix.row(index)).len())
+            .fold(0f32, f32::max);
+
+        Some((
+            Vec3::from_vec4(Vec4::from_vec3(center, 1.0) * matrix),
+            radius * scale,
+        ))
+    }
+
+    /// Returns the number of levels of detail, excluding the full mesh.
+    pub fn lod_count(&self) -> usize {
+        self.lods.len()
+    }
+
+    /// Returns the index of the selected level of detail, or `None` if the full mesh is drawn.
+    pub fn selected_lod(&self) -> Option<usize> {
+        self.selected_lod
+    }
+
+    /// Selects the coarsest level of detail allowed at the given fraction of the viewport height
+    /// covered by the mesh, as computed by [`Camera::projected_size`](crate::gfx::Camera::projected_size).
+    pub fn select_lod(&mut self, screen_size: f32) {
+        self.selected_lod = self
+            .lods
+            .iter()
+            .rposition(|lod| screen_size <= lod.screen_size);
+    }
+
+    fn clear_lods(&mut self) {
+        self.bounding_sphere = None;
+        self.lods.clear();
+        self.selected_lod = None;
+    }
+
     pub fn sub_renderer(
         &mut self,
         shader_mgr: &ShaderManager,
@@ -132,16 +263,58 @@ impl MeshRenderer {
             .pipeline_provider
             .obtain_pipeline(shader_mgr, pipeline_cache)?;
         let material = self.pipeline_provider.material().cloned()?;
+
+        self.build_sub_renderer(pipeline, material)
+    }
+
+    /// Returns a sub renderer drawing the mesh into a layer of the [`ShadowMaps`] with the given caster material,
+    /// or `None` if the mesh does not cast shadows.
+    pub fn shadow_sub_renderer(
+        &mut self,
+        material: &MaterialHandle,
+        shader_mgr: &ShaderManager,
+        pipeline_cache: &mut PipelineCache,
+    ) -> Option<MeshSubRenderer> {
+        if !self.casts_shadows {
+            return None;
+        }
+
+        let pipeline = self.pipeline_provider.obtain_depth_only_pipeline(
+            material,
+            ShadowMaps::depth_stencil_state(),
+            shader_mgr,
+            pipeline_cache,
+        )?;
+
+        self.build_sub_renderer(pipeline, material.clone())
+    }
+
+    fn build_sub_renderer(
+        &self,
+        pipeline: CachedPipeline,
+        material: MaterialHandle,
+    ) -> Option<MeshSubRenderer> {
         let vertex_buffer = self.vertex_buffer.clone()?;
-        let mesh = self.mesh.as_ref()?;
+        let (vertex_count, index_buffer) = match self.selected_lod {
+            Some(index) => {
+                let lod = &self.lods[index];
+                (lod.vertex_count, Some(lod.index_buffer.clone()))
+            }
+            None => (self.vertex_count, self.index_buffer.clone()),
+        };
 
         Some(MeshSubRenderer {
             pipeline,
             material,
-            vertex_count: mesh.data.faces.len() as u32 * 3,
+            vertex_count,
             bind_group_provider: MeshRendererBindGroupProvider,
-            vertex_buffer_provider: MeshRendererVertexBufferProvider { vertex_buffer },
-            instance_data_provider: MeshRendererInstanceDataProvider,
+            vertex_buffer_provider: MeshRendererVertexBufferProvider {
+                vertex_buffer,
+                index_buffer,
+            },
+            instance_data_provider: MeshRendererInstanceDataProvider {
+                receives_shadows: self.receives_shadows,
+            },
         })
     }
 }
@@ -195,6 +368,7 @@ impl BindGroupProvider for MeshRendererBindGroupProvider {
 
 struct MeshRendererVertexBufferProvider {
     vertex_buffer: GenericBufferAllocation<Buffer>,
+    index_buffer: Option<(GenericBufferAllocation<Buffer>, IndexFormat)>,
 }
 
 impl VertexBufferProvider for MeshRendererVertexBufferProvider {
@@ -206,23 +380,72 @@ impl VertexBufferProvider for MeshRendererVertexBufferProvider {
         match key {
             semantic_inputs::KEY_POSITION
             | semantic_inputs::KEY_NORMAL
-            | semantic_inputs::KEY_UV => Some(VertexBuffer {
+            | semantic_inputs::KEY_UV
+            | semantic_inputs::KEY_TANGENT => Some(VertexBuffer {
                 slot: 0,
                 buffer: &self.vertex_buffer,
             }),
             _ => None,
         }
     }
+
+    fn index_buffer(&self) -> Option<IndexBuffer> {
+        self.index_buffer
+            .as_ref()
+            .map(|(buffer, format)| IndexBuffer {
+                buffer,
+                format: *format,
+            })
+    }
 }
 
-struct MeshRendererInstanceDataProvider;
+struct MeshRendererInstanceDataProvider {
+    receives_shadows: bool,
+}
 
 impl InstanceDataProvider for MeshRendererInstanceDataProvider {
     fn copy_per_instance_data(
         &self,
         _instance: u32,
-        _key: SemanticShaderInputKey,
-        _buffer: &mut GenericBufferAllocation<HostBuffer>,
+        key: SemanticShaderInputKey,
+        buffer: &mut GenericBufferAllocation<HostBuffer>,
     ) {
+        if key == semantic_inputs::KEY_RECEIVE_SHADOWS {
+            let receives_shadows: f32 = if self.receives_shadows { 1.0 } else { 0.0 };
+            buffer.copy_from_slice(receives_shadows.as_bytes());
+        }
+    }
+}
+
+fn index_buffer(
+    buffer: &GfxBuffer,
+    index_type: VertexIndexType,
+) -> (GenericBufferAllocation<Buffer>, IndexFormat) {
+    (
+        GenericBufferAllocation::new(buffer.clone(), 0, BufferSize::new(buffer.size()).unwrap()),
+        match index_type {
+            VertexIndexType::U8 | VertexIndexType::U16 => IndexFormat::Uint16,
+            VertexIndexType::U32 => IndexFormat::Uint32,
+        },
+    )
+}
+
+fn mesh_buffer_layout() -> RendererVertexBufferLayout {
+    RendererVertexBufferLayout {
+        array_stride: size_of::<[f32; 8]>() as BufferAddress,
+        attributes: vec![
+            RendererVertexBufferAttribute {
+                key: KEY_POSITION,
+                offset: 0,
+            },
+            RendererVertexBufferAttribute {
+                key: KEY_NORMAL,
+                offset: size_of::<[f32; 3]>() as BufferAddress,
+            },
+            RendererVertexBufferAttribute {
+                key: KEY_UV,
+                offset: size_of::<[f32; 6]>() as BufferAddress,
+            },
+        ],
     }
 }
@@ -1,7 +1,11 @@
+mod mesh_morphs;
 mod mesh_renderer;
+mod skinned_mesh_renderer;
 mod ui_element_renderer;
 mod ui_text_renderer;
 
+pub use mesh_morphs::*;
 pub use mesh_renderer::*;
+pub use skinned_mesh_renderer::*;
 pub use ui_element_renderer::*;
 pub use ui_text_renderer::*;
@@ -0,0 +1,600 @@
+use super::{build_morph_texels, MaterialMorph, MeshMorphs};
+use crate::{
+    gfx::{
+        semantic_bindings::{self, MAX_BONE_COUNT, MORPH_TEXTURE_WIDTH},
+        semantic_inputs::{self, KEY_JOINTS, KEY_NORMAL, KEY_POSITION, KEY_UV, KEY_WEIGHTS},
+        BindGroupLayoutCache, BindGroupProvider, CachedPipeline, GenericBufferAllocation,
+        HostBuffer, IndexBuffer, InstanceDataProvider, Material, MaterialHandle, PipelineCache,
+        PipelineProvider, Renderer, RendererVertexBufferAttribute, RendererVertexBufferLayout,
+        SemanticShaderBindingKey, SemanticShaderInputKey, ShaderManager, ShadowMaps, VertexBuffer,
+        VertexBufferProvider,
+    },
+    math::Mat4,
+    object::ObjectId,
+};
+use asset::assets::{ModelAsset, VertexAttributeKind, VertexIndexType};
+use parking_lot::RwLockReadGuard;
+use specs::{prelude::*, Component};
+use std::{mem::size_of, sync::Arc};
+use wgpu::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutEntry, BindingResource, Buffer,
+    BufferAddress, BufferDescriptor, BufferSize, BufferUsages, CompareFunction, DepthStencilState,
+    Device, Extent3d, Face, FrontFace, ImageCopyTexture, ImageDataLayout, IndexFormat, Origin3d,
+    PolygonMode, PrimitiveState, PrimitiveTopology, Queue, ShaderStages, Texture, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use zerocopy::AsBytes;
+
+/// Renders a skinned sub mesh of a model asset. Vertices are deformed on the GPU
+/// by a per-renderer bone matrix palette, bound to [`semantic_bindings::BONE_MATRICES`].
+/// Joints beyond [`MAX_BONE_COUNT`] are ignored.
+///
+/// Morph targets are applied on the GPU before skinning, by weights set per renderer.
+/// They are bound to [`semantic_bindings::MORPH_TARGETS`] in the layout described by [`build_morph_texels`];
+/// a texture with a single row means the sub mesh has no morph targets.
+///
+/// The diffuse color of the sub mesh material is modulated by material morphs, then multiplied with
+/// the texture bound to `diffuse_texture` and `diffuse_sampler`. See [`Material::from_mesh`].
+#[derive(Component)]
+#[storage(HashMapStorage)]
+pub struct SkinnedMeshRenderer {
+    mask: u32,
+    pipeline_provider: PipelineProvider,
+    vertex_count: u32,
+    vertex_buffer: Option<GenericBufferAllocation<Buffer>>,
+    index_buffer: Option<(GenericBufferAllocation<Buffer>, IndexFormat)>,
+    joint_nodes: Vec<u32>,
+    inverse_bind_matrices: Vec<Mat4>,
+    joint_objects: Vec<ObjectId>,
+    bone_matrices: Vec<Mat4>,
+    is_bone_matrices_dirty: bool,
+    bone_matrix_buffer: Option<Buffer>,
+    bone_matrix_bind_group: Option<Arc<BindGroup>>,
+    morphs: MeshMorphs,
+    morph_texture: Option<Texture>,
+    morph_texels: Option<Vec<[u32; 4]>>,
+    morph_bind_group: Option<Arc<BindGroup>>,
+    diffuse_color: [f32; 4],
+    casts_shadows: bool,
+}
+
+impl SkinnedMeshRenderer {
+    pub fn new() -> Self {
+        let mut pipeline_provider = PipelineProvider::new();
+
+        pipeline_provider.set_primitive(mesh_primitive_state());
+        pipeline_provider.set_depth_stencil(Some(DepthStencilState {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }));
+
+        Self {
+            mask: 0xFFFF_FFFF,
+            pipeline_provider,
+            vertex_count: 0,
+            vertex_buffer: None,
+            index_buffer: None,
+            joint_nodes: vec![],
+            inverse_bind_matrices: vec![],
+            joint_objects: vec![],
+            bone_matrices: vec![Mat4::identity(); MAX_BONE_COUNT],
+            is_bone_matrices_dirty: true,
+            bone_matrix_buffer: None,
+            bone_matrix_bind_group: None,
+            morphs: MeshMorphs::empty(),
+            morph_texture: None,
+            morph_texels: None,
+            morph_bind_group: None,
+            diffuse_color: [1.0; 4],
+            casts_shadows: true,
+        }
+    }
+
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    pub fn set_mask(&mut self, mask: u32) {
+        self.mask = mask;
+    }
+
+    /// Whether the mesh is rendered into the shadow maps of shadow casting lights.
+    pub fn casts_shadows(&self) -> bool {
+        self.casts_shadows
+    }
+
+    pub fn set_casts_shadows(&mut self, casts_shadows: bool) {
+        self.casts_shadows = casts_shadows;
+    }
+
+    pub fn material(&self) -> Option<&MaterialHandle> {
+        self.pipeline_provider.material()
+    }
+
+    pub fn set_material(&mut self, material: MaterialHandle) {
+        self.pipeline_provider.set_material(material);
+    }
+
+    /// Node index of each joint of the current sub mesh. See [`asset::assets::MeshSkin::joints`].
+    pub fn joint_nodes(&self) -> &[u32] {
+        &self.joint_nodes
+    }
+
+    /// Objects driving the joints, in the same order as [`SkinnedMeshRenderer::joint_nodes`].
+    pub fn joint_objects(&self) -> &[ObjectId] {
+        &self.joint_objects
+    }
+
+    /// Makes the joints follow the given objects. The bone matrices are then updated every frame.
+    /// Pass an empty list to drive the joints manually with [`SkinnedMeshRenderer::set_joint_transforms`].
+    pub fn set_joint_objects(&mut self, objects: Vec<ObjectId>) {
+        self.joint_objects = objects;
+    }
+
+    /// Number of morphs of the current model. See [`asset::assets::ModelAsset::morphs`].
+    pub fn morph_count(&self) -> usize {
+        self.morphs.len()
+    }
+
+    pub fn morph_name(&self, index: usize) -> Option<&str> {
+        self.morphs.name(index)
+    }
+
+    pub fn find_morph(&self, name: &str) -> Option<usize> {
+        self.morphs.find(name)
+    }
+
+    pub fn morph_weight(&self, index: usize) -> f32 {
+        self.morphs.weight(index)
+    }
+
+    /// Sets the weight of a morph of the current model, including morphs not affecting this sub mesh,
+    /// so that the same weights can be set on every sub mesh of the model.
+    pub fn set_morph_weight(&mut self, index: usize, weight: f32) {
+        self.morphs.set_weight(index, weight);
+    }
+
+    /// Material parameters modulated by material morphs, as of the last [`SkinnedMeshRenderer::upload_morphs`].
+    pub fn material_morph(&self) -> &MaterialMorph {
+        self.morphs.material()
+    }
+
+    /// Sets a sub mesh of a model asset, resetting the joints to bind pose and the morph weights to zero.
+    /// A sub mesh without skin is rendered as is.
+    pub fn set_model_mesh(
+        &mut self,
+        model: &dyn ModelAsset,
+        mesh_index: u32,
+        device: &Device,
+        bind_group_layout_cache: &mut BindGroupLayoutCache,
+    ) {
+        self.joint_nodes.clear();
+        self.inverse_bind_matrices.clear();
+        self.joint_objects.clear();
+        self.bone_matrices.fill(Mat4::identity());
+        self.is_bone_matrices_dirty = true;
+
+        if self.bone_matrix_buffer.is_none() {
+            let bone_matrix_buffer = device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: (size_of::<Mat4>() * MAX_BONE_COUNT) as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bone_matrix_bind_group_layout =
+                bind_group_layout_cache.create_layout(vec![BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: semantic_bindings::BONE_MATRICES.ty,
+                    count: None,
+                }]);
+
+            self.bone_matrix_bind_group =
+                Some(Arc::new(device.create_bind_group(&BindGroupDescriptor {
+                    label: None,
+                    layout: bone_matrix_bind_group_layout.as_ref(),
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: bone_matrix_buffer.as_entire_binding(),
+                    }],
+                })));
+            self.bone_matrix_buffer = Some(bone_matrix_buffer);
+        }
+
+        let mesh = model
+            .meshes()
+            .get(mesh_index as usize)
+            .filter(|mesh| mesh.index_count != 0);
+        self.morphs = MeshMorphs::new(model, mesh);
+        let material = mesh.and_then(|mesh| mesh.material.as_ref());
+        self.diffuse_color = material.map_or([1.0; 4], |material| material.diffuse_color);
+        self.pipeline_provider.set_primitive(PrimitiveState {
+            cull_mode: if material.is_some_and(|material| material.is_double_sided) {
+                None
+            } else {
+                Some(Face::Back)
+            },
+            ..mesh_primitive_state()
+        });
+        self.morph_texels = mesh.and_then(|mesh| {
+            // Morph targets not fitting in a texture are dropped.
+            build_morph_texels(mesh).filter(|texels| {
+                texels.len() / MORPH_TEXTURE_WIDTH as usize
+                    <= device.limits().max_texture_dimension_2d as usize
+            })
+        });
+
+        let (morph_texture_width, morph_texture_height) = match &self.morph_texels {
+            Some(texels) => (
+                MORPH_TEXTURE_WIDTH,
+                (texels.len() / MORPH_TEXTURE_WIDTH as usize) as u32,
+            ),
+            None => (1, 1),
+        };
+        let morph_texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: morph_texture_width,
+                height: morph_texture_height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba32Uint,
+            usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let morph_texture_view = morph_texture.create_view(&Default::default());
+        let morph_bind_group_layout =
+            bind_group_layout_cache.create_layout(vec![BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: semantic_bindings::MORPH_TARGETS.ty,
+                count: None,
+            }]);
+
+        self.morph_bind_group = Some(Arc::new(device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: morph_bind_group_layout.as_ref(),
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&morph_texture_view),
+            }],
+        })));
+        self.morph_texture = Some(morph_texture);
+
+        let mesh = match mesh {
+            Some(mesh) => mesh,
+            None => {
+                self.vertex_count = 0;
+                self.vertex_buffer = None;
+                self.index_buffer = None;
+                return;
+            }
+        };
+
+        if let Some(skin) = &mesh.skin {
+            self.joint_nodes = skin.joints.clone();
+            self.inverse_bind_matrices = Vec::from_iter(
+                skin.inverse_bind_matrices
+                    .iter()
+                    .map(|matrix| Mat4::new(*matrix)),
+            );
+        }
+
+        self.pipeline_provider
+            .set_buffer_layouts(vec![RendererVertexBufferLayout {
+                array_stride: mesh.vertex_stride() as BufferAddress,
+                attributes: Vec::from_iter(mesh.vertex_attributes.iter().filter_map(|attribute| {
+                    let key = match attribute.kind {
+                        VertexAttributeKind::Position => KEY_POSITION,
+                        VertexAttributeKind::Normal => KEY_NORMAL,
+                        VertexAttributeKind::TexCoord { index: 0 } => KEY_UV,
+                        VertexAttributeKind::Joints => KEY_JOINTS,
+                        VertexAttributeKind::Weights => KEY_WEIGHTS,
+                        _ => return None,
+                    };
+
+                    Some(RendererVertexBufferAttribute {
+                        key,
+                        offset: attribute.offset as BufferAddress,
+                    })
+                })),
+            }]);
+
+        self.vertex_count = mesh.index_count;
+        self.vertex_buffer = Some(GenericBufferAllocation::new(
+            mesh.vertex_buffer.clone(),
+            0,
+            BufferSize::new(mesh.vertex_buffer.size()).unwrap(),
+        ));
+        self.index_buffer = Some((
+            GenericBufferAllocation::new(
+                mesh.index_buffer.clone(),
+                0,
+                BufferSize::new(mesh.index_buffer.size()).unwrap(),
+            ),
+            match mesh.index_type {
+                VertexIndexType::U8 | VertexIndexType::U16 => IndexFormat::Uint16,
+                VertexIndexType::U32 => IndexFormat::Uint32,
+            },
+        ));
+    }
+
+    /// Sets the transform of each joint relative to the sub mesh, in the same order as
+    /// [`SkinnedMeshRenderer::joint_nodes`]. Joints without a transform are left as is.
+    pub fn set_joint_transforms(&mut self, transforms: &[Mat4]) {
+        for ((bone_matrix, inverse_bind_matrix), transform) in self
+            .bone_matrices
+            .iter_mut()
+            .zip(&self.inverse_bind_matrices)
+            .zip(transforms)
+        {
+            *bone_matrix = inverse_bind_matrix * transform.clone();
+        }
+
+        self.is_bone_matrices_dirty = true;
+    }
+
+    /// Uploads the bone matrices, if changed since the last upload.
+    pub fn upload_bone_matrices(&mut self, queue: &Queue) {
+        if !self.is_bone_matrices_dirty {
+            return;
+        }
+
+        let bone_matrix_buffer = if let Some(buffer) = &self.bone_matrix_buffer {
+            buffer
+        } else {
+            return;
+        };
+
+        queue.write_buffer(bone_matrix_buffer, 0, self.bone_matrices.as_bytes());
+        self.is_bone_matrices_dirty = false;
+    }
+
+    /// Evaluates the morph weights and uploads them, along with the morph targets of a new sub mesh.
+    pub fn upload_morphs(&mut self, queue: &Queue) {
+        let morph_texture = if let Some(texture) = &self.morph_texture {
+            texture
+        } else {
+            return;
+        };
+
+        let is_changed = self.morphs.update();
+        // Only the weights are written unless the morph targets are pending.
+        let mut texels = match self.morph_texels.take() {
+            Some(texels) => texels,
+            None if is_changed && 1 < morph_texture.height() => {
+                vec![[0u32; 4]; MORPH_TEXTURE_WIDTH as usize]
+            }
+            None => return,
+        };
+
+        for (index, weight) in self
+            .morphs
+            .target_weights()
+            .iter()
+            .take(MORPH_TEXTURE_WIDTH as usize * 4)
+            .enumerate()
+        {
+            texels[index / 4][index % 4] = weight.to_bits();
+        }
+
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: morph_texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            texels.as_bytes(),
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(MORPH_TEXTURE_WIDTH * size_of::<[u32; 4]>() as u32),
+                rows_per_image: None,
+            },
+            Extent3d {
+                width: MORPH_TEXTURE_WIDTH,
+                height: (texels.len() / MORPH_TEXTURE_WIDTH as usize) as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    pub fn sub_renderer(
+        &mut self,
+        shader_mgr: &ShaderManager,
+        pipeline_cache: &mut PipelineCache,
+    ) -> Option<SkinnedMeshSubRenderer> {
+        let pipeline = self
+            .pipeline_provider
+            .obtain_pipeline(shader_mgr, pipeline_cache)?;
+        let material = self.pipeline_provider.material().cloned()?;
+
+        self.build_sub_renderer(pipeline, material)
+    }
+
+    /// Returns a sub renderer drawing the deformed mesh into a layer of the [`ShadowMaps`] with the given caster material,
+    /// usually of [`BUILT_IN_SHADER_SKINNED_SHADOW_CASTER`](crate::gfx::BUILT_IN_SHADER_SKINNED_SHADOW_CASTER),
+    /// or `None` if the mesh does not cast shadows.
+    pub fn shadow_sub_renderer(
+        &mut self,
+        material: &MaterialHandle,
+        shader_mgr: &ShaderManager,
+        pipeline_cache: &mut PipelineCache,
+    ) -> Option<SkinnedMeshSubRenderer> {
+        if !self.casts_shadows {
+            return None;
+        }
+
+        let pipeline = self.pipeline_provider.obtain_depth_only_pipeline(
+            material,
+            ShadowMaps::depth_stencil_state(),
+            shader_mgr,
+            pipeline_cache,
+        )?;
+
+        self.build_sub_renderer(pipeline, material.clone())
+    }
+
+    fn build_sub_renderer(
+        &self,
+        pipeline: CachedPipeline,
+        material: MaterialHandle,
+    ) -> Option<SkinnedMeshSubRenderer> {
+        let vertex_buffer = self.vertex_buffer.clone()?;
+        let index_buffer = self.index_buffer.clone();
+        let bone_matrix_bind_group = self.bone_matrix_bind_group.clone()?;
+        let morph_bind_group = self.morph_bind_group.clone()?;
+        let material_morph = self.morphs.material();
+
+        Some(SkinnedMeshSubRenderer {
+            pipeline,
+            material,
+            vertex_count: self.vertex_count,
+            bind_group_provider: SkinnedMeshRendererBindGroupProvider {
+                bone_matrix_bind_group,
+                morph_bind_group,
+            },
+            vertex_buffer_provider: SkinnedMeshRendererVertexBufferProvider {
+                vertex_buffer,
+                index_buffer,
+            },
+            instance_data_provider: SkinnedMeshRendererInstanceDataProvider {
+                // Material morphs multiply the diffuse color of the material, then add to it.
+                morph_diffuse_multiply: std::array::from_fn(|index| {
+                    self.diffuse_color[index] * material_morph.multiply.diffuse_color[index]
+                }),
+                morph_diffuse_add: material_morph.add.diffuse_color,
+            },
+        })
+    }
+}
+
+fn mesh_primitive_state() -> PrimitiveState {
+    PrimitiveState {
+        topology: PrimitiveTopology::TriangleList,
+        strip_index_format: None,
+        front_face: FrontFace::Ccw,
+        cull_mode: Some(Face::Back),
+        unclipped_depth: false,
+        polygon_mode: PolygonMode::Fill,
+        conservative: false,
+    }
+}
+
+pub struct SkinnedMeshSubRenderer {
+    pipeline: CachedPipeline,
+    material: MaterialHandle,
+    vertex_count: u32,
+    bind_group_provider: SkinnedMeshRendererBindGroupProvider,
+    vertex_buffer_provider: SkinnedMeshRendererVertexBufferProvider,
+    instance_data_provider: SkinnedMeshRendererInstanceDataProvider,
+}
+
+impl Renderer for SkinnedMeshSubRenderer {
+    fn pipeline(&self) -> CachedPipeline {
+        self.pipeline.clone()
+    }
+
+    fn material(&self) -> RwLockReadGuard<Material> {
+        self.material.read()
+    }
+
+    fn instance_count(&self) -> u32 {
+        1
+    }
+
+    fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    fn bind_group_provider(&self) -> &dyn BindGroupProvider {
+        &self.bind_group_provider
+    }
+
+    fn vertex_buffer_provider(&self) -> &dyn VertexBufferProvider {
+        &self.vertex_buffer_provider
+    }
+
+    fn instance_data_provider(&self) -> &dyn InstanceDataProvider {
+        &self.instance_data_provider
+    }
+}
+
+struct SkinnedMeshRendererBindGroupProvider {
+    bone_matrix_bind_group: Arc<BindGroup>,
+    morph_bind_group: Arc<BindGroup>,
+}
+
+impl BindGroupProvider for SkinnedMeshRendererBindGroupProvider {
+    fn bind_group(&self, _instance: u32, key: SemanticShaderBindingKey) -> Option<&BindGroup> {
+        match key {
+            semantic_bindings::KEY_BONE_MATRICES => Some(&self.bone_matrix_bind_group),
+            semantic_bindings::KEY_MORPH_TARGETS => Some(&self.morph_bind_group),
+            _ => None,
+        }
+    }
+}
+
+struct SkinnedMeshRendererVertexBufferProvider {
+    vertex_buffer: GenericBufferAllocation<Buffer>,
+    index_buffer: Option<(GenericBufferAllocation<Buffer>, IndexFormat)>,
+}
+
+impl VertexBufferProvider for SkinnedMeshRendererVertexBufferProvider {
+    fn vertex_buffer_count(&self) -> u32 {
+        1
+    }
+
+    fn vertex_buffer(&self, key: SemanticShaderInputKey) -> Option<VertexBuffer> {
+        match key {
+            semantic_inputs::KEY_POSITION
+            | semantic_inputs::KEY_NORMAL
+            | semantic_inputs::KEY_UV
+            | semantic_inputs::KEY_JOINTS
+            | semantic_inputs::KEY_WEIGHTS => Some(VertexBuffer {
+                slot: 0,
+                buffer: &self.vertex_buffer,
+            }),
+            _ => None,
+        }
+    }
+
+    fn index_buffer(&self) -> Option<IndexBuffer> {
+        self.index_buffer
+            .as_ref()
+            .map(|(buffer, format)| IndexBuffer {
+                buffer,
+                format: *format,
+            })
+    }
+}
+
+struct SkinnedMeshRendererInstanceDataProvider {
+    morph_diffuse_multiply: [f32; 4],
+    morph_diffuse_add: [f32; 4],
+}
+
+impl InstanceDataProvider for SkinnedMeshRendererInstanceDataProvider {
+    fn copy_per_instance_data(
+        &self,
+        _instance: u32,
+        key: SemanticShaderInputKey,
+        buffer: &mut GenericBufferAllocation<HostBuffer>,
+    ) {
+        match key {
+            semantic_inputs::KEY_MORPH_DIFFUSE_MULTIPLY => {
+                buffer.copy_from_slice(self.morph_diffuse_multiply.as_bytes());
+            }
+            semantic_inputs::KEY_MORPH_DIFFUSE_ADD => {
+                buffer.copy_from_slice(self.morph_diffuse_add.as_bytes());
+            }
+            _ => {}
+        }
+    }
+}
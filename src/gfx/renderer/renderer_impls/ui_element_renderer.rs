@@ -3,10 +3,10 @@ use crate::{
         semantic_bindings,
         semantic_inputs::{self, KEY_POSITION},
         BindGroupLayoutCache, BindGroupProvider, CachedPipeline, Color, GenericBufferAllocation,
-        HostBuffer, InstanceDataProvider, Material, MaterialHandle, NinePatchHandle, PipelineCache,
-        PipelineProvider, Renderer, RendererVertexBufferAttribute, RendererVertexBufferLayout,
-        SemanticShaderBindingKey, SemanticShaderInputKey, ShaderManager, SpriteHandle,
-        TextureHandle, VertexBuffer, VertexBufferProvider,
+        HostBuffer, IndexBuffer, InstanceDataProvider, Material, MaterialHandle, NinePatchHandle,
+        PipelineCache, PipelineProvider, Renderer, RendererVertexBufferAttribute,
+        RendererVertexBufferLayout, SemanticShaderBindingKey, SemanticShaderInputKey,
+        ShaderManager, SpriteHandle, TextureHandle, VertexBuffer, VertexBufferProvider,
     },
     ui::UISize,
 };
@@ -109,10 +109,18 @@ impl UIElementRenderer {
         self.color = color;
     }
 
+    pub fn material(&self) -> Option<&MaterialHandle> {
+        self.pipeline_provider.material()
+    }
+
     pub fn set_material(&mut self, material: MaterialHandle) {
         self.pipeline_provider.set_material(material);
     }
 
+    pub fn sprite(&self) -> Option<&UIElementSprite> {
+        self.sprite.as_ref()
+    }
+
     pub fn set_sprite(
         &mut self,
         sprite: UIElementSprite,
@@ -269,6 +277,10 @@ impl VertexBufferProvider for UIElementRendererVertexBufferProvider {
             _ => None,
         }
     }
+
+    fn index_buffer(&self) -> Option<IndexBuffer> {
+        None
+    }
 }
 
 struct UIElementRendererInstanceDataProvider {
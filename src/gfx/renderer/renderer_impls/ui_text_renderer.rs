@@ -4,9 +4,10 @@ use crate::{
         semantic_inputs::{self, KEY_POSITION},
         BindGroupLayoutCache, BindGroupProvider, CachedPipeline, Color, FontHandle,
         GenericBufferAllocation, GlyphLayoutConfig, GlyphManager, GlyphSpriteHandle, HostBuffer,
-        InstanceDataProvider, Material, MaterialHandle, PipelineCache, PipelineProvider, Renderer,
-        RendererVertexBufferAttribute, RendererVertexBufferLayout, SemanticShaderBindingKey,
-        SemanticShaderInputKey, ShaderManager, VertexBuffer, VertexBufferProvider,
+        IndexBuffer, InstanceDataProvider, Material, MaterialHandle, PipelineCache,
+        PipelineProvider, Renderer, RendererVertexBufferAttribute, RendererVertexBufferLayout,
+        SemanticShaderBindingKey, SemanticShaderInputKey, ShaderManager, VertexBuffer,
+        VertexBufferProvider,
     },
     math::Vec2,
     ui::UISize,
@@ -158,6 +159,10 @@ impl UITextRenderer {
         self.smoothness = smoothness;
     }
 
+    pub fn material(&self) -> Option<&MaterialHandle> {
+        self.pipeline_provider.material()
+    }
+
     pub fn set_material(&mut self, material: MaterialHandle) {
         self.pipeline_provider.set_material(material);
     }
@@ -340,6 +345,10 @@ impl VertexBufferProvider for UITextRendererVertexBufferProvider {
             _ => None,
         }
     }
+
+    fn index_buffer(&self) -> Option<IndexBuffer> {
+        None
+    }
 }
 
 struct UITextRendererInstanceDataProvider {
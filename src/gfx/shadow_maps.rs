@@ -0,0 +1,340 @@
+use super::{semantic_bindings, BindGroupLayoutCache, CameraProjection};
+use crate::math::{Mat4, Vec3, Vec4};
+use wgpu::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutEntry, BindingResource,
+    CompareFunction, DepthBiasState, DepthStencilState, Device, Extent3d, ShaderStages, Texture,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+    TextureViewDescriptor, TextureViewDimension,
+};
+
+/// Width and height of each layer of the shadow maps.
+pub const SHADOW_MAP_SIZE: u32 = 1024;
+/// Number of cascades of a directional light, each taking a layer of the shadow maps.
+pub const SHADOW_CASCADE_COUNT: usize = 3;
+
+/// Depth textures the shadow casting lights render into, bound to [`semantic_bindings::SHADOW_MAPS`].
+/// Each light takes one or more layers, up to [`semantic_bindings::MAX_SHADOW_MAP_COUNT`] layers in total.
+pub struct ShadowMaps {
+    texture: Texture,
+    layer_views: Vec<TextureView>,
+    bind_group: BindGroup,
+}
+
+impl ShadowMaps {
+    pub const FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+    pub fn new(device: &Device, bind_group_layout_cache: &mut BindGroupLayoutCache) -> Self {
+        let layer_count = semantic_bindings::MAX_SHADOW_MAP_COUNT as u32;
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("shadow maps"),
+            size: Extent3d {
+                width: SHADOW_MAP_SIZE,
+                height: SHADOW_MAP_SIZE,
+                depth_or_array_layers: layer_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[Self::FORMAT],
+        });
+        let texture_view = texture.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let layer_views = Vec::from_iter((0..layer_count).map(|layer| {
+            texture.create_view(&TextureViewDescriptor {
+                dimension: Some(TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        }));
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("shadow maps bind group"),
+            layout: bind_group_layout_cache
+                .create_layout(vec![BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: semantic_bindings::SHADOW_MAPS.ty,
+                    count: None,
+                }])
+                .as_ref(),
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&texture_view),
+            }],
+        });
+
+        Self {
+            texture,
+            layer_views,
+            bind_group,
+        }
+    }
+
+    /// Returns the depth stencil state of pipelines rendering shadow casters.
+    /// Depths are biased by slope, keeping lit surfaces from shadowing themselves.
+    pub fn depth_stencil_state() -> DepthStencilState {
+        DepthStencilState {
+            format: Self::FORMAT,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: Default::default(),
+            bias: DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            },
+        }
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the view of a single layer, to be rendered into.
+    pub fn layer_view(&self, layer: usize) -> Option<&TextureView> {
+        self.layer_views.get(layer)
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
+
+/// Returns the light space matrices of the cascades of a directional light placed by `light_matrix`.
+/// The cascades split the view of the camera placed by `camera_matrix` up to `distance`, nearest first.
+/// `aspect` is the aspect ratio of the render target of the camera.
+pub(crate) fn directional_shadow_matrices(
+    light_matrix: &Mat4,
+    camera_matrix: &Mat4,
+    projection: &CameraProjection,
+    aspect: f32,
+    distance: f32,
+) -> [Mat4; SHADOW_CASCADE_COUNT] {
+    let projection_matrix = projection.as_matrix_with_aspect(aspect);
+    let (near, far, is_perspective) = match projection {
+        CameraProjection::Orthographic(projection) => (projection.near, projection.far, false),
+        CameraProjection::Perspective(projection) => (projection.near, projection.far, true),
+    };
+    let far = far.min(near + distance).max(near);
+    // Half extents of the view at a depth; constant for orthographic cameras.
+    let half_extents = |depth: f32| {
+        let scale = if is_perspective { depth } else { 1.0 };
+        (
+            scale / projection_matrix.row(0).x,
+            scale / projection_matrix.row(1).y,
+        )
+    };
+    // Blends uniform and logarithmic splits, keeping near cascades small for perspective cameras.
+    let split = |index: usize| {
+        let t = index as f32 / SHADOW_CASCADE_COUNT as f32;
+        let uniform = near + (far - near) * t;
+
+        if is_perspective && 0.0 < near {
+            (uniform + near * (far / near).powf(t)) * 0.5
+        } else {
+            uniform
+        }
+    };
+
+    let light_view = placement_matrix(light_matrix, false).inversed();
+    let camera_to_light = camera_matrix * light_view.clone();
+
+    std::array::from_fn(|cascade| {
+        let mut corners = [Vec3::ZERO; 8];
+
+        for (index, depth) in [split(cascade), split(cascade + 1)].into_iter().enumerate() {
+            let (width, height) = half_extents(depth);
+
+            for (corner, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+                .into_iter()
+                .enumerate()
+            {
+                let position = Vec4::new(x * width, y * height, -depth, 1.0) * &camera_to_light;
+                corners[index * 4 + corner] = Vec3::from_vec4(position);
+            }
+        }
+
+        let mut center = corners.iter().fold(Vec3::ZERO, |sum, &corner| sum + corner) * 0.125;
+        let radius = corners
+            .iter()
+            .map(|&corner| (corner - center).len())
+            .fold(0f32, f32::max)
+            .max(f32::EPSILON);
+
+        // Moves in whole texels, so that shadow edges do not shimmer while the camera moves.
+        let texel = radius * 2.0 / SHADOW_MAP_SIZE as f32;
+        center.x = (center.x / texel).floor() * texel;
+        center.y = (center.y / texel).floor() * texel;
+
+        // Casters up to `distance` towards the light are kept.
+        let depth = radius * 2.0 + distance;
+        let eye = Vec3::new(center.x, center.y, center.z + radius + distance);
+
+        &light_view * Mat4::translation(-eye) * orthographic(radius, radius, 0.0, depth)
+    })
+}
+
+/// Returns the light space matrix of a spot light placed by `light_matrix`.
+pub(crate) fn spot_shadow_matrix(light_matrix: &Mat4, range: f32, outer_angle: f32) -> Mat4 {
+    let fov = outer_angle.clamp(f32::EPSILON, std::f32::consts::PI * 0.99);
+    placement_matrix(light_matrix, true).inversed() * perspective(fov, range * 0.01, range)
+}
+
+/// Removes the scale from a transform matrix, optionally along with the translation.
+fn placement_matrix(matrix: &Mat4, with_translation: bool) -> Mat4 {
+    let axis = |index: usize| Vec4::from_vec3(Vec3::from_vec4(matrix.row(index)).normalized(), 0.0);
+    let position = if with_translation {
+        matrix.row(3)
+    } else {
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    };
+
+    Mat4::compose_rows(axis(0), axis(1), axis(2), position)
+}
+
+/// Orthographic projection looking along -Z, mapping depths into `[0, 1]`.
+fn orthographic(half_width: f32, half_height: f32, near: f32, far: f32) -> Mat4 {
+    Mat4::new([
+        1.0 / half_width,
+        0.0,
+        0.0,
+        0.0, //
+        0.0,
+        1.0 / half_height,
+        0.0,
+        0.0, //
+        0.0,
+        0.0,
+        1.0 / (near - far),
+        0.0, //
+        0.0,
+        0.0,
+        near / (near - far),
+        1.0, //
+    ])
+}
+
+/// Perspective projection looking along -Z, mapping depths into `[0, 1]`.
+fn perspective(fov: f32, near: f32, far: f32) -> Mat4 {
+    let f = (fov * 0.5).tan().recip();
+
+    Mat4::new([
+        f,
+        0.0,
+        0.0,
+        0.0, //
+        0.0,
+        f,
+        0.0,
+        0.0, //
+        0.0,
+        0.0,
+        far / (near - far),
+        -1.0, //
+        0.0,
+        0.0,
+        near * far / (near - far),
+        0.0, //
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gfx::{CameraPerspectiveProjection, CameraPerspectiveProjectionAspect},
+        math::Quat,
+    };
+
+    fn project(matrix: &Mat4, position: Vec3) -> Vec3 {
+        let clip = Vec4::from_vec3(position, 1.0) * matrix;
+        Vec3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+    }
+
+    fn is_inside(ndc: Vec3) -> bool {
+        ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && (0.0..=1.0).contains(&ndc.z)
+    }
+
+    #[test]
+    fn check_shadow_caster_shader() {
+        let module =
+            naga::front::wgsl::parse_str(include_str!("./built_in_shaders/shadow_caster.wgsl"))
+                .unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+
+    #[test]
+    fn check_skinned_shadow_caster_shader() {
+        let module = naga::front::wgsl::parse_str(include_str!(
+            "./built_in_shaders/shadow_caster.skinned.wgsl"
+        ))
+        .unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+
+    #[test]
+    fn check_spot_shadow_matrix() {
+        // a spot light at (0, 5, 0), looking down
+        let light = Mat4::srt(
+            Vec3::new(0.0, 5.0, 0.0),
+            Quat::from_axis_angle(Vec3::RIGHT, -std::f32::consts::FRAC_PI_2),
+            Vec3::new(2.0, 2.0, 2.0),
+        );
+        let matrix = spot_shadow_matrix(&light, 10.0, 1.0);
+
+        let below = project(&matrix, Vec3::new(0.0, 0.0, 0.0));
+        assert!(is_inside(below));
+        assert!(below.x.abs() < 1e-4 && below.y.abs() < 1e-4);
+        assert!(below.z < project(&matrix, Vec3::new(0.0, -2.0, 0.0)).z);
+
+        assert!(!is_inside(project(&matrix, Vec3::new(0.0, 6.0, 0.0))));
+        assert!(!is_inside(project(&matrix, Vec3::new(0.0, -6.0, 0.0))));
+        assert!(!is_inside(project(&matrix, Vec3::new(5.0, 4.0, 0.0))));
+    }
+
+    #[test]
+    fn check_directional_shadow_matrices() {
+        // a camera at (0, 1, 10), looking along -Z
+        let camera = Mat4::translation(Vec3::new(0.0, 1.0, 10.0));
+        let projection = CameraProjection::Perspective(CameraPerspectiveProjection {
+            fov: 1.0,
+            aspect: CameraPerspectiveProjectionAspect::Fixed(1.5),
+            near: 0.1,
+            far: 100.0,
+        });
+        let light = Mat4::rotation(Quat::from_axis_angle(Vec3::RIGHT, -1.0));
+        let matrices =
+            directional_shadow_matrices(&light, &camera, &projection, 1280.0 / 720.0, 30.0);
+
+        // Points in view are covered by a cascade; nearer points by nearer cascades.
+        let near = Vec3::new(0.0, 1.0, 9.0);
+        let middle = Vec3::new(2.0, 0.0, 0.0);
+        let far = Vec3::new(-5.0, 2.0, -18.0);
+        assert!(is_inside(project(&matrices[0], near)));
+        assert!(!is_inside(project(&matrices[0], far)));
+        assert!(matrices
+            .iter()
+            .any(|matrix| is_inside(project(matrix, middle))));
+        assert!(is_inside(project(&matrices[2], far)));
+
+        // Beyond the distance, nothing is covered.
+        let beyond = Vec3::new(0.0, 1.0, -100.0);
+        assert!(!matrices
+            .iter()
+            .any(|matrix| is_inside(project(matrix, beyond))));
+    }
+}
@@ -1,4 +1,5 @@
-use super::TextureHandle;
+use super::{Texture, TextureHandle};
+use asset::assets::TextureAsset;
 use codegen::Handle;
 
 #[derive(Handle)]
@@ -12,6 +13,24 @@ impl Sprite {
         Self { texture, mapping }
     }
 
+    /// Creates a sprite from a named sprite of the given texture asset.
+    /// Returns `None` if the texture does not contain the sprite.
+    pub fn from_asset(texture: &dyn TextureAsset, name: &str) -> Option<Self> {
+        let sprite = texture
+            .sprites()
+            .iter()
+            .find(|sprite| sprite.name == name)?;
+        let (x, y) = sprite.texel_mapping;
+
+        Some(Self {
+            texture: TextureHandle::new(Texture {
+                sampler: sprite.sampler_handle.clone(),
+                ..Texture::from_asset(texture)
+            }),
+            mapping: SpriteTexelMapping::new(x.min, x.max, y.min, y.max),
+        })
+    }
+
     pub fn texture(&self) -> &TextureHandle {
         &self.texture
     }
@@ -1,3 +1,4 @@
+use asset::assets::TextureAsset;
 use codegen::Handle;
 use image::{DynamicImage, GenericImageView};
 use std::sync::Arc;
@@ -16,6 +17,17 @@ pub struct Texture {
 }
 
 impl Texture {
+    /// Creates a texture that shares GPU resources with the given texture asset.
+    pub fn from_asset(asset: &dyn TextureAsset) -> Self {
+        Self {
+            texture: asset.handle().clone(),
+            view: asset.view_handle().clone(),
+            sampler: asset.sampler_handle().clone(),
+            width: asset.width(),
+            height: asset.height(),
+        }
+    }
+
     pub fn from_image(
         format: TextureFormat,
         image: &DynamicImage,
@@ -1,11 +1,17 @@
 use self::{
+    animation::{Animator, SkeletonPose},
+    asset_mgr::AssetManager,
     ecs_system::{
-        render::RenderSystem, update_camera_transform_buffer::UpdateCameraTransformBufferSystem,
+        reload_assets::ReloadAssets, render::RenderSystem, update_animators::UpdateAnimators,
+        update_camera_transform_buffer::UpdateCameraTransformBufferSystem,
+        update_skeleton_poses::UpdateSkeletonPoses,
+        update_skinned_mesh_bones::UpdateSkinnedMeshBones,
     },
     gfx::{
-        Camera, DepthStencilMode, GfxContext, GfxContextCreationError, GfxContextHandle,
-        RenderManager, ScreenManager, ShaderManager,
+        Camera, DepthStencilMode, FrameCaptureError, GfxContext, GfxContextCreationError,
+        GfxContextHandle, RenderManager, ScreenManager, ShaderManager,
     },
+    physics::ModelPhysics,
     time::TimeManager,
     vsync::TargetFrameInterval,
 };
@@ -15,7 +21,11 @@ use ecs_system::{
     update_ui_raycast_grid::UpdateUIRaycastGrid, update_ui_scaler::UpdateUIScaler,
 };
 use event::{event_types, EventManager};
-use gfx::{BuiltInShaderManager, GlyphManager, MeshRenderer, UIElementRenderer, UITextRenderer};
+use gfx::{
+    BuiltInShaderManager, GlyphManager, Light, MeshRenderer, SkinnedMeshRenderer,
+    UIElementRenderer, UITextRenderer,
+};
+use image::RgbaImage;
 use input::InputManager;
 use math::Vec2;
 use object::{Object, ObjectManager};
@@ -25,6 +35,7 @@ use std::{
     cell::{Ref, RefCell, RefMut},
     mem::MaybeUninit,
     num::NonZeroU32,
+    path::PathBuf,
     time::Instant,
 };
 use thiserror::Error;
@@ -38,6 +49,8 @@ use winit::{
     window::{Window, WindowBuilder},
 };
 
+pub mod animation;
+pub mod asset_mgr;
 pub mod ecs_system;
 pub mod event;
 pub mod gfx;
@@ -45,12 +58,19 @@ pub mod input;
 pub mod math;
 pub mod object;
 pub mod object_event;
+pub mod physics;
 pub mod time;
 pub mod transform;
 pub mod ui;
 pub mod vsync;
 
+#[cfg(test)]
+mod test_utils;
+
 // re-exports.
+pub use asset;
+pub use asset_loader;
+pub use asset_pipeline;
 pub use fontdue;
 pub use image;
 pub use russimp;
@@ -65,7 +85,8 @@ pub fn use_context() -> &'static ContextHandle {
 
 #[derive(Handle)]
 pub struct Context {
-    window: Window,
+    /// The window of the engine; `None` for a [`HeadlessEngine`].
+    window: Option<Window>,
     gfx_ctx: GfxContextHandle,
     world: RefCell<World>,
     object_mgr: RefCell<ObjectManager>,
@@ -74,6 +95,7 @@ pub struct Context {
     glyph_mgr: RefCell<GlyphManager>,
     shader_mgr: ShaderManager,
     built_in_shader_mgr: BuiltInShaderManager,
+    asset_mgr: RefCell<AssetManager>,
     ui_raycast_mgr: RefCell<UIRaycastManager>,
     ui_event_mgr: RefCell<UIEventManager>,
     time_mgr: RefCell<TimeManager>,
@@ -83,7 +105,13 @@ pub struct Context {
 }
 
 impl Context {
-    pub fn new(window: Window, gfx_ctx: GfxContext, screen_width: u32, screen_height: u32) -> Self {
+    pub fn new(
+        window: Option<Window>,
+        gfx_ctx: GfxContext,
+        screen_width: u32,
+        screen_height: u32,
+        asset_base_path: PathBuf,
+    ) -> Self {
         let gfx_ctx = GfxContextHandle::new(gfx_ctx);
         let world = World::new().into();
         let object_mgr = ObjectManager::new().into();
@@ -101,6 +129,7 @@ impl Context {
             &shader_mgr,
             render_mgr.borrow_mut().bind_group_layout_cache(),
         );
+        let asset_mgr = AssetManager::new(asset_base_path, gfx_ctx.clone(), &shader_mgr).into();
         let ui_raycast_mgr = UIRaycastManager::new().into();
         let ui_event_mgr = UIEventManager::new().into();
         let time_mgr = TimeManager::new().into();
@@ -118,6 +147,7 @@ impl Context {
             glyph_mgr,
             shader_mgr,
             built_in_shader_mgr: built_in_shader_mgr.into(),
+            asset_mgr,
             ui_raycast_mgr,
             ui_event_mgr,
             time_mgr,
@@ -127,8 +157,8 @@ impl Context {
         }
     }
 
-    pub fn window(&self) -> &Window {
-        &self.window
+    pub fn window(&self) -> Option<&Window> {
+        self.window.as_ref()
     }
 
     pub fn gfx_ctx(&self) -> &GfxContextHandle {
@@ -183,6 +213,14 @@ impl Context {
         &self.built_in_shader_mgr
     }
 
+    pub fn asset_mgr(&self) -> Ref<AssetManager> {
+        self.asset_mgr.borrow()
+    }
+
+    pub fn asset_mgr_mut(&self) -> RefMut<AssetManager> {
+        self.asset_mgr.borrow_mut()
+    }
+
     pub fn ui_raycast_mgr(&self) -> Ref<UIRaycastManager> {
         self.ui_raycast_mgr.borrow()
     }
@@ -240,29 +278,18 @@ impl Engine {
             .build(&event_loop)
             .unwrap();
         let gfx_ctx = GfxContext::new(&window).await?;
-        let ctx = ContextHandle::new(Context::new(window, gfx_ctx, config.width, config.height));
-
-        unsafe {
-            CONTEXT.write(ctx.clone());
-        }
+        let scale_factor = window.scale_factor();
+        let ctx = ContextHandle::new(Context::new(
+            Some(window),
+            gfx_ctx,
+            config.width,
+            config.height,
+            config.asset_base_path,
+        ));
 
-        {
-            let mut world = ctx.world_mut();
-            world.register::<Object>();
-            world.register::<Transform>();
-
-            world.register::<Camera>();
-            world.register::<MeshRenderer>();
-            world.register::<UIElementRenderer>();
-            world.register::<UITextRenderer>();
-
-            world.register::<UISize>();
-            world.register::<UIScaler>();
-            world.register::<UIElement>();
-        }
+        init_context(&ctx);
 
         {
-            let scale_factor = ctx.window.scale_factor();
             let physical_size =
                 LogicalSize::new(config.width, config.height).to_physical(scale_factor);
             let mut screen_mgr = ctx.screen_mgr_mut();
@@ -282,20 +309,12 @@ impl Engine {
         loop_mode: EngineLoopMode,
         target_fps: EngineTargetFps,
     ) -> Result<(), EngineExecError> {
-        let mut make_ui_scaler_dirty = MakeUIScalerDirty::new(self.ctx.clone());
-        let mut update_ui_scaler = UpdateUIScaler::new(self.ctx.clone());
-        let mut update_ui_element = UpdateUIElement::new(self.ctx.clone());
-        let mut update_ui_raycast_grid = UpdateUIRaycastGrid::new(self.ctx.clone());
-        let mut update_camera_transform_buffer_system =
-            UpdateCameraTransformBufferSystem::new(self.ctx.clone());
-        let mut render_system = RenderSystem::new(
-            &self.ctx.gfx_ctx.device,
-            self.ctx.render_mgr_mut().bind_group_layout_cache(),
-        );
+        let mut systems = FrameSystems::new(&self.ctx);
+        let window = self.ctx.window().unwrap();
 
-        self.ctx.window.set_visible(true);
+        window.set_visible(true);
 
-        let window_id = self.ctx.window.id();
+        let window_id = window.id();
         let mut window_occluded = false;
         let mut target_frame_interval = TargetFrameInterval::new(
             match target_fps {
@@ -303,7 +322,7 @@ impl Engine {
                 EngineTargetFps::MilliHertz(millihertz) => Some(millihertz),
                 EngineTargetFps::Unlimited => None,
             },
-            self.ctx.window(),
+            window,
         );
         let mut last_frame_time = Instant::now();
 
@@ -327,44 +346,13 @@ impl Engine {
 
                     last_frame_time = now;
 
-                    {
-                        let mut time_mgr = self.ctx.time_mgr_mut();
-                        time_mgr.update();
-                    }
-
-                    {
-                        let mut input_mgr = self.ctx.input_mgr_mut();
-                        input_mgr.poll();
-                    }
-
-                    self.ctx.event_mgr().dispatch(&event_types::Update);
-
-                    make_ui_scaler_dirty.run_now(&self.ctx.world());
-                    update_ui_scaler.run_now(&self.ctx.world());
-                    update_ui_element.run_now(&self.ctx.world());
-                    update_ui_raycast_grid.run_now(&self.ctx.world());
-
-                    self.ctx.ui_event_mgr_mut().handle_mouse_move();
-
-                    {
-                        let world = self.ctx.world();
-                        let mut object_mgr = self.ctx.object_mgr_mut();
-                        let object_hierarchy = object_mgr.object_hierarchy_mut();
-
-                        object_hierarchy.copy_dirty_to_current_frame();
-
-                        let transforms = world.read_component::<Transform>();
-                        object_hierarchy.update_object_matrices(|entity| transforms.get(entity));
-                    }
-
-                    self.ctx.event_mgr().dispatch(&event_types::LateUpdate);
+                    systems.update(&self.ctx);
 
                     if window_occluded {
                         return;
                     }
 
-                    update_camera_transform_buffer_system.run_now(&self.ctx.world());
-                    render_system.run_now(&self.ctx.world());
+                    systems.render(&self.ctx);
 
                     return;
                 }
@@ -373,40 +361,8 @@ impl Engine {
                         return;
                     }
 
-                    {
-                        let mut time_mgr = self.ctx.time_mgr_mut();
-                        time_mgr.update();
-                    }
-
-                    {
-                        let mut input_mgr = self.ctx.input_mgr_mut();
-                        input_mgr.poll();
-                    }
-
-                    self.ctx.event_mgr().dispatch(&event_types::Update);
-
-                    make_ui_scaler_dirty.run_now(&self.ctx.world());
-                    update_ui_scaler.run_now(&self.ctx.world());
-                    update_ui_element.run_now(&self.ctx.world());
-                    update_ui_raycast_grid.run_now(&self.ctx.world());
-
-                    self.ctx.ui_event_mgr_mut().handle_mouse_move();
-
-                    {
-                        let world = self.ctx.world();
-                        let mut object_mgr = self.ctx.object_mgr_mut();
-                        let object_hierarchy = object_mgr.object_hierarchy_mut();
-
-                        object_hierarchy.copy_dirty_to_current_frame();
-
-                        let transforms = world.read_component::<Transform>();
-                        object_hierarchy.update_object_matrices(|entity| transforms.get(entity));
-                    }
-
-                    self.ctx.event_mgr().dispatch(&event_types::LateUpdate);
-
-                    update_camera_transform_buffer_system.run_now(&self.ctx.world());
-                    render_system.run_now(&self.ctx.world());
+                    systems.update(&self.ctx);
+                    systems.render(&self.ctx);
 
                     return;
                 }
@@ -513,7 +469,7 @@ impl Engine {
                         },
                     window_id: id,
                 } if id == window_id => {
-                    target_frame_interval.update_window(&self.ctx.window);
+                    target_frame_interval.update_window(self.ctx.window().unwrap());
                     self.ctx
                         .screen_mgr_mut()
                         .update_scale_factor(scale_factor, *new_inner_size);
@@ -544,11 +500,167 @@ impl Engine {
     }
 }
 
+/// Runs the engine without a window, rendering frames into an offscreen texture on demand.
+/// Since any adapter is accepted, including CPU ones, it works on machines without a GPU or display.
+pub struct HeadlessEngine {
+    ctx: ContextHandle,
+    systems: FrameSystems,
+}
+
+impl HeadlessEngine {
+    pub async fn new(config: HeadlessEngineConfig) -> Result<Self, EngineInitError> {
+        let gfx_ctx =
+            GfxContext::new_headless(PhysicalSize::new(config.width, config.height)).await?;
+        let ctx = ContextHandle::new(Context::new(
+            None,
+            gfx_ctx,
+            config.width,
+            config.height,
+            config.asset_base_path,
+        ));
+
+        init_context(&ctx);
+
+        let systems = FrameSystems::new(&ctx);
+        Ok(Self { ctx, systems })
+    }
+
+    pub fn context(&self) -> ContextHandle {
+        self.ctx.clone()
+    }
+
+    /// Updates and renders a single frame.
+    pub fn step(&mut self) {
+        self.systems.update(&self.ctx);
+        self.systems.render(&self.ctx);
+    }
+
+    /// Reads the last rendered frame back.
+    pub fn capture_frame(&self) -> Result<RgbaImage, FrameCaptureError> {
+        self.ctx.render_mgr().capture_frame()
+    }
+}
+
+/// Publishes the context through [`use_context`] and registers the built-in components.
+fn init_context(ctx: &ContextHandle) {
+    unsafe {
+        CONTEXT.write(ctx.clone());
+    }
+
+    let mut world = ctx.world_mut();
+    world.register::<Object>();
+    world.register::<Transform>();
+    world.register::<Animator>();
+    world.register::<SkeletonPose>();
+    world.register::<ModelPhysics>();
+
+    world.register::<Camera>();
+    world.register::<Light>();
+    world.register::<MeshRenderer>();
+    world.register::<SkinnedMeshRenderer>();
+    world.register::<UIElementRenderer>();
+    world.register::<UITextRenderer>();
+
+    world.register::<UISize>();
+    world.register::<UIScaler>();
+    world.register::<UIElement>();
+}
+
+/// The systems run every frame, shared by [`Engine`] and [`HeadlessEngine`].
+struct FrameSystems {
+    reload_assets: ReloadAssets,
+    make_ui_scaler_dirty: MakeUIScalerDirty,
+    update_animators: UpdateAnimators,
+    update_ui_scaler: UpdateUIScaler,
+    update_ui_element: UpdateUIElement,
+    update_ui_raycast_grid: UpdateUIRaycastGrid,
+    update_camera_transform_buffer_system: UpdateCameraTransformBufferSystem,
+    update_skeleton_poses: UpdateSkeletonPoses,
+    update_skinned_mesh_bones: UpdateSkinnedMeshBones,
+    render_system: RenderSystem,
+}
+
+impl FrameSystems {
+    fn new(ctx: &ContextHandle) -> Self {
+        Self {
+            reload_assets: ReloadAssets::new(ctx.clone()),
+            make_ui_scaler_dirty: MakeUIScalerDirty::new(ctx.clone()),
+            update_animators: UpdateAnimators::new(ctx.clone()),
+            update_ui_scaler: UpdateUIScaler::new(ctx.clone()),
+            update_ui_element: UpdateUIElement::new(ctx.clone()),
+            update_ui_raycast_grid: UpdateUIRaycastGrid::new(ctx.clone()),
+            update_camera_transform_buffer_system: UpdateCameraTransformBufferSystem::new(
+                ctx.clone(),
+            ),
+            update_skeleton_poses: UpdateSkeletonPoses::new(ctx.clone()),
+            update_skinned_mesh_bones: UpdateSkinnedMeshBones::new(ctx.clone()),
+            render_system: RenderSystem::new(&mut ctx.render_mgr_mut(), ctx.built_in_shader_mgr()),
+        }
+    }
+
+    /// Advances the time and input, then runs the update events and the systems preparing the frame.
+    fn update(&mut self, ctx: &Context) {
+        {
+            let mut time_mgr = ctx.time_mgr_mut();
+            time_mgr.update();
+        }
+
+        {
+            let mut input_mgr = ctx.input_mgr_mut();
+            input_mgr.poll();
+        }
+
+        ctx.asset_mgr().poll_async();
+        self.reload_assets.run_now(&ctx.world());
+        self.reload_assets.dispatch_events();
+
+        ctx.event_mgr().dispatch(&event_types::Update);
+        self.update_animators.run_now(&ctx.world());
+
+        self.make_ui_scaler_dirty.run_now(&ctx.world());
+        self.update_ui_scaler.run_now(&ctx.world());
+        self.update_ui_element.run_now(&ctx.world());
+        self.update_ui_raycast_grid.run_now(&ctx.world());
+
+        ctx.ui_event_mgr_mut().handle_mouse_move();
+
+        {
+            let world = ctx.world();
+            let mut object_mgr = ctx.object_mgr_mut();
+            let object_hierarchy = object_mgr.object_hierarchy_mut();
+
+            object_hierarchy.copy_dirty_to_current_frame();
+
+            let transforms = world.read_component::<Transform>();
+            object_hierarchy.update_object_matrices(|entity| transforms.get(entity));
+        }
+
+        ctx.event_mgr().dispatch(&event_types::LateUpdate);
+    }
+
+    fn render(&mut self, ctx: &Context) {
+        self.update_camera_transform_buffer_system
+            .run_now(&ctx.world());
+        self.update_skeleton_poses.run_now(&ctx.world());
+        self.update_skinned_mesh_bones.run_now(&ctx.world());
+        self.render_system.run_now(&ctx.world());
+    }
+}
+
 pub struct EngineConfig {
     pub title: String,
     pub resizable: bool,
     pub width: u32,
     pub height: u32,
+    /// Base path of the asset database. It is not scanned until [`AssetManager::scan`] is called.
+    pub asset_base_path: PathBuf,
+}
+
+pub struct HeadlessEngineConfig {
+    pub width: u32,
+    pub height: u32,
+    /// Base path of the asset database. It is not scanned until [`AssetManager::scan`] is called.
+    pub asset_base_path: PathBuf,
 }
 
 #[derive(Error, Debug)]
@@ -139,6 +139,73 @@ impl Quat {
         result
     }
 
+    pub fn dot(lhs: Self, rhs: Self) -> f32 {
+        lhs.x * rhs.x + lhs.y * rhs.y + lhs.z * rhs.z + lhs.w * rhs.w
+    }
+
+    pub fn slerp(from: Self, to: Self, t: f32) -> Self {
+        match t {
+            t if t <= 0f32 => from,
+            t if 1f32 <= t => to,
+            t => Self::slerp_unclamped(from, to, t),
+        }
+    }
+
+    /// Interpolates along the shortest path between two unit quaternions.
+    pub fn slerp_unclamped(from: Self, to: Self, t: f32) -> Self {
+        let mut cos = Self::dot(from, to);
+        let to = if cos < 0f32 {
+            cos = -cos;
+            Self {
+                x: -to.x,
+                y: -to.y,
+                z: -to.z,
+                w: -to.w,
+            }
+        } else {
+            to
+        };
+
+        let (lhs, rhs) = if 1f32 - f32::EPSILON <= cos {
+            (1f32 - t, t)
+        } else {
+            let angle = cos.acos();
+            let inv_sin = angle.sin().recip();
+            (
+                (angle * (1f32 - t)).sin() * inv_sin,
+                (angle * t).sin() * inv_sin,
+            )
+        };
+
+        Self {
+            x: from.x * lhs + to.x * rhs,
+            y: from.y * lhs + to.y * rhs,
+            z: from.z * lhs + to.z * rhs,
+            w: from.w * lhs + to.w * rhs,
+        }
+        .normalized()
+    }
+
+    /// Rotates around the Z axis, then Y, then X; the inverse of [`Quat::into_xyz_angles`].
+    pub fn from_xyz_angles(angles: [f32; 3]) -> Self {
+        Self::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), angles[0])
+            * Self::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), angles[1])
+            * Self::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), angles[2])
+    }
+
+    /// Decomposes the rotation into angles around the X, Y and Z axes, in the order of [`Quat::from_xyz_angles`].
+    pub fn into_xyz_angles(self) -> [f32; 3] {
+        let x_axis = self * Vec3::new(1.0, 0.0, 0.0);
+        let y_axis = self * Vec3::new(0.0, 1.0, 0.0);
+        let z_axis = self * Vec3::new(0.0, 0.0, 1.0);
+
+        [
+            (-z_axis.y).atan2(z_axis.z),
+            z_axis.x.clamp(-1.0, 1.0).asin(),
+            (-y_axis.x).atan2(x_axis.x),
+        ]
+    }
+
     pub fn into_eular(self) -> Vec3 {
         let sinr_cosp = 2.0 * (self.w * self.x + self.y * self.z);
         let cosr_cosp = 1.0 - 2.0 * (self.x * self.x + self.y * self.y);
@@ -202,6 +269,18 @@ impl Default for Quat {
     }
 }
 
+/// Converts from `[x, y, z, w]`.
+impl From<[f32; 4]> for Quat {
+    fn from(value: [f32; 4]) -> Self {
+        Self {
+            x: value[0],
+            y: value[1],
+            z: value[2],
+            w: value[3],
+        }
+    }
+}
+
 impl Mul for Quat {
     type Output = Self;
 
@@ -323,6 +323,12 @@ impl From<Vec4> for Vec3 {
     }
 }
 
+impl From<[f32; 3]> for Vec3 {
+    fn from(value: [f32; 3]) -> Self {
+        Self::new(value[0], value[1], value[2])
+    }
+}
+
 impl Add for Vec3 {
     type Output = Self;
 
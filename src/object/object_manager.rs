@@ -1,7 +1,13 @@
 use super::{
     Object, ObjectHandle, ObjectHierarchy, ObjectId, ObjectIdAllocator, ObjectNameRegistry,
 };
-use crate::{transform::Transform, use_context};
+use crate::{
+    gfx::{Material, MaterialHandle, MeshRenderer, ShaderHandle, SkinnedMeshRenderer},
+    math::Mat4,
+    transform::Transform,
+    use_context,
+};
+use asset::assets::ModelAsset;
 use specs::prelude::*;
 
 pub struct ObjectManager {
@@ -80,6 +86,111 @@ impl ObjectManager {
         )
     }
 
+    /// Instantiates a model as a subtree of objects, one per node of [`ModelAsset::nodes`],
+    /// named after the node and placed at its rest transform. Returns the object of the root node, if any.
+    ///
+    /// Sub meshes are rendered on the object of their node, or on unnamed children of it if the node has several sub meshes.
+    /// Sub meshes with a skin or morph targets are rendered by [`SkinnedMeshRenderer`]s with materials of `skinned_mesh_shader`,
+    /// whose joints follow the objects of their nodes. Other sub meshes are rendered by [`MeshRenderer`]s with materials of `mesh_shader`.
+    /// Both shaders take `diffuse_texture` and `diffuse_sampler` properties, e.g. [`BUILT_IN_SHADER_MESH_LIT`](crate::gfx::BUILT_IN_SHADER_MESH_LIT)
+    /// and [`BUILT_IN_SHADER_SKINNED_MESH_NORMAL`](crate::gfx::BUILT_IN_SHADER_SKINNED_MESH_NORMAL).
+    pub fn instantiate_model(
+        &mut self,
+        world: &mut World,
+        model: &dyn ModelAsset,
+        mesh_shader: ShaderHandle,
+        skinned_mesh_shader: ShaderHandle,
+    ) -> Option<ObjectHandle> {
+        let ctx = use_context();
+        let device = &ctx.gfx_ctx().device;
+        let mut render_mgr = ctx.render_mgr_mut();
+        let fallback_texture = render_mgr.white_texture().clone();
+
+        let nodes = Vec::from_iter(model.nodes().iter().map(|node| {
+            let transform = Transform::from_mat4(&Mat4::new(node.transform.matrix));
+            let (handle, builder) =
+                self.create_object_builder(world, node.name.clone(), Some(transform));
+            builder.build();
+            handle
+        }));
+
+        for (node, handle) in model.nodes().iter().zip(&nodes) {
+            if let Some(parent) = node
+                .parent_index
+                .and_then(|parent_index| nodes.get(parent_index as usize))
+            {
+                self.object_hierarchy
+                    .set_parent(handle.object_id, Some(parent.object_id));
+            }
+        }
+
+        for (node, handle) in model.nodes().iter().zip(&nodes) {
+            for &mesh_index in &node.mesh_indices {
+                let mesh = match model.meshes().get(mesh_index as usize) {
+                    Some(mesh) => mesh,
+                    None => continue,
+                };
+
+                let entity = if node.mesh_indices.len() == 1 {
+                    handle.entity
+                } else {
+                    let (child, builder) = self.create_object_builder(world, None, None);
+                    builder.build();
+                    self.object_hierarchy
+                        .set_parent(child.object_id, Some(handle.object_id));
+                    child.entity
+                };
+
+                if mesh.skin.is_none() && mesh.morph_targets.is_empty() {
+                    let mut renderer = MeshRenderer::new();
+                    renderer.set_model_mesh(model, mesh_index);
+                    renderer.set_material(MaterialHandle::new(Material::from_mesh(
+                        mesh,
+                        mesh_shader.clone(),
+                        &fallback_texture,
+                        device,
+                        render_mgr.pipeline_layout_cache(),
+                    )));
+                    world
+                        .write_storage::<MeshRenderer>()
+                        .insert(entity, renderer)
+                        .unwrap();
+                    continue;
+                }
+
+                let mut renderer = SkinnedMeshRenderer::new();
+                renderer.set_model_mesh(
+                    model,
+                    mesh_index,
+                    device,
+                    render_mgr.bind_group_layout_cache(),
+                );
+                renderer.set_material(MaterialHandle::new(Material::from_mesh(
+                    mesh,
+                    skinned_mesh_shader.clone(),
+                    &fallback_texture,
+                    device,
+                    render_mgr.pipeline_layout_cache(),
+                )));
+                let joint_objects = renderer
+                    .joint_nodes()
+                    .iter()
+                    .map(|&joint| nodes.get(joint as usize).map(|joint| joint.object_id))
+                    .collect::<Option<Vec<_>>>();
+                renderer.set_joint_objects(joint_objects.unwrap_or_default());
+                world
+                    .write_storage::<SkinnedMeshRenderer>()
+                    .insert(entity, renderer)
+                    .unwrap();
+            }
+        }
+
+        model
+            .root_node_index()
+            .and_then(|root_node_index| nodes.get(root_node_index as usize))
+            .cloned()
+    }
+
     pub fn remove_object(&mut self, handle: &ObjectHandle) {
         use_context()
             .world_mut()
@@ -96,3 +207,174 @@ impl ObjectManager {
         use_context().ui_event_mgr_mut().remove_object(handle);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gfx::{BUILT_IN_SHADER_MESH_LIT, BUILT_IN_SHADER_SKINNED_MESH_NORMAL},
+        test_utils::headless_engine,
+    };
+    use asset::{
+        assets::{
+            MeshAABB, MeshSkin, MeshSource, ModelSource, Node, NodeTransform, VertexAttribute,
+            VertexAttributeKind, VertexIndexType,
+        },
+        AssetKey, AssetSource,
+    };
+    use std::collections::HashMap;
+
+    const IDENTITY: [f32; 16] = [
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 1.0, 0.0, //
+        0.0, 0.0, 0.0, 1.0, //
+    ];
+
+    /// A node placed at `index` on the x axis.
+    fn node(index: u32, parent_index: Option<u32>, name: &str, mesh_indices: Vec<u32>) -> Node {
+        let mut matrix = IDENTITY;
+        matrix[12] = index as f32;
+
+        Node {
+            index,
+            parent_index,
+            children_indices: vec![],
+            name: name.to_owned(),
+            transform: NodeTransform { matrix },
+            mesh_indices,
+            bone: None,
+        }
+    }
+
+    /// A single triangle, skinned to the node at index `1` if `is_skinned` is `true`.
+    fn mesh(index: u32, is_skinned: bool) -> MeshSource {
+        let mut vertex_attributes = vec![
+            VertexAttribute {
+                offset: 0,
+                kind: VertexAttributeKind::Position,
+            },
+            VertexAttribute {
+                offset: 12,
+                kind: VertexAttributeKind::Normal,
+            },
+            VertexAttribute {
+                offset: 24,
+                kind: VertexAttributeKind::TexCoord { index: 0 },
+            },
+        ];
+
+        if is_skinned {
+            vertex_attributes.push(VertexAttribute {
+                offset: 32,
+                kind: VertexAttributeKind::Joints,
+            });
+            vertex_attributes.push(VertexAttribute {
+                offset: 48,
+                kind: VertexAttributeKind::Weights,
+            });
+        }
+
+        let stride = if is_skinned { 64 } else { 32 };
+
+        MeshSource {
+            index,
+            aabb: MeshAABB {
+                min: [0.0; 3],
+                max: [1.0; 3],
+            },
+            index_type: VertexIndexType::U32,
+            index_buffer: Vec::from_iter([0u32, 1, 2].iter().flat_map(|index| index.to_le_bytes())),
+            vertex_attributes,
+            vertex_buffer: vec![0; stride * 3],
+            vertex_count: 3,
+            material: None,
+            skin: is_skinned.then(|| MeshSkin {
+                joints: vec![1],
+                inverse_bind_matrices: vec![IDENTITY],
+            }),
+            morph_targets: vec![],
+            lods: vec![],
+        }
+    }
+
+    #[test]
+    fn check_instantiate_model() {
+        let (engine, _guard) = headless_engine(1, 1);
+        let ctx = engine.context();
+        let model = ModelSource {
+            root_node_index: Some(0),
+            nodes: vec![
+                node(0, None, "root", vec![0]),
+                node(1, Some(0), "arm", vec![1]),
+                node(2, Some(1), "hand", vec![0, 1]),
+                node(3, Some(0), "arm", vec![]),
+            ],
+            meshes: vec![mesh(0, false), mesh(1, true)],
+            morphs: vec![],
+            rigid_bodies: vec![],
+            rigid_body_joints: vec![],
+        }
+        .load(
+            AssetKey::Path("model".to_owned()),
+            &HashMap::new(),
+            ctx.gfx_ctx(),
+        )
+        .unwrap();
+
+        let root = {
+            let mut world = ctx.world_mut();
+            let shaders = ctx.built_in_shader_mgr();
+            ctx.object_mgr_mut()
+                .instantiate_model(
+                    &mut world,
+                    model.as_ref(),
+                    shaders.find_shader(BUILT_IN_SHADER_MESH_LIT).unwrap(),
+                    shaders
+                        .find_shader(BUILT_IN_SHADER_SKINNED_MESH_NORMAL)
+                        .unwrap(),
+                )
+                .unwrap()
+        };
+
+        let object_mgr = ctx.object_mgr();
+        let hierarchy = object_mgr.object_hierarchy();
+        let registry = object_mgr.object_name_registry();
+        let world = ctx.world();
+        let transforms = world.read_storage::<Transform>();
+        let mesh_renderers = world.read_storage::<MeshRenderer>();
+        let skinned_mesh_renderers = world.read_storage::<SkinnedMeshRenderer>();
+
+        assert_eq!(registry.name(root.object_id).unwrap(), "root");
+        assert_eq!(hierarchy.parent(root.object_id), None);
+
+        // Nodes with the same name are all registered.
+        let arms = object_mgr.find_all("arm");
+        assert_eq!(arms.len(), 2);
+        assert!(arms
+            .iter()
+            .all(|arm| hierarchy.parent(arm.object_id) == Some(root.object_id)));
+
+        let hand = object_mgr.find("hand").unwrap();
+        let arm = hierarchy.parent(hand.object_id).unwrap();
+        assert_eq!(registry.name(arm).unwrap(), "arm");
+        assert_eq!(transforms.get(hand.entity).unwrap().position.x, 2.0);
+
+        // The unskinned mesh is rendered by a mesh renderer, the skinned one follows the arm.
+        assert!(mesh_renderers.contains(root.entity));
+        assert!(!skinned_mesh_renderers.contains(root.entity));
+        let skinned_mesh_renderer = skinned_mesh_renderers.get(hierarchy.entity(arm)).unwrap();
+        assert_eq!(skinned_mesh_renderer.joint_objects(), &[arm]);
+        assert!(!mesh_renderers.contains(hierarchy.entity(arm)));
+
+        // Several sub meshes are rendered on unnamed children.
+        let children = hierarchy
+            .direct_children_iter(hand.object_id)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(children.len(), 2);
+        assert!(children.iter().all(|&child| registry.name(child).is_none()));
+        assert!(mesh_renderers.contains(hierarchy.entity(children[0])));
+        assert!(skinned_mesh_renderers.contains(hierarchy.entity(children[1])));
+    }
+}
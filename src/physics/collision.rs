@@ -0,0 +1,398 @@
+use crate::math::{Quat, Vec3};
+use asset::assets::RigidBodyShape;
+
+/// The deepest point of contact between two shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ContactPoint {
+    /// Points from the second shape to the first one.
+    pub normal: Vec3,
+    /// The point of the first shape deepest inside the second one, in world space.
+    pub point_a: Vec3,
+    /// The point of the second shape deepest inside the first one, in world space.
+    pub point_b: Vec3,
+    pub depth: f32,
+}
+
+/// A shape placed in world space.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Collider {
+    pub shape: RigidBodyShape,
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+impl Collider {
+    /// Radius of the sphere around the position enclosing the shape.
+    pub fn bounding_radius(&self) -> f32 {
+        match self.shape {
+            RigidBodyShape::Sphere { radius } => radius,
+            RigidBodyShape::Box { half_extents } => Vec3::from(half_extents).len(),
+            RigidBodyShape::Capsule { radius, height } => radius + height * 0.5,
+        }
+    }
+
+    fn core(&self) -> Core {
+        match self.shape {
+            RigidBodyShape::Sphere { radius } => Core::Segment {
+                start: self.position,
+                end: self.position,
+                radius,
+            },
+            RigidBodyShape::Box { half_extents } => Core::Box {
+                half_extents: Vec3::from(half_extents),
+            },
+            RigidBodyShape::Capsule { radius, height } => {
+                let axis = self.rotation * Vec3::new(0.0, height * 0.5, 0.0);
+                Core::Segment {
+                    start: self.position - axis,
+                    end: self.position + axis,
+                    radius,
+                }
+            }
+        }
+    }
+
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation.inverted() * (point - self.position)
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation * point + self.position
+    }
+}
+
+/// Spheres and capsules are swept spheres along a segment, which is a single point for spheres.
+enum Core {
+    Segment { start: Vec3, end: Vec3, radius: f32 },
+    Box { half_extents: Vec3 },
+}
+
+/// Finds the deepest point of contact between the colliders, if they overlap.
+/// Box pairs only detect corners penetrating faces.
+pub(crate) fn collide(a: &Collider, b: &Collider) -> Option<ContactPoint> {
+    match (a.core(), b.core()) {
+        (
+            Core::Segment {
+                start: start_a,
+                end: end_a,
+                radius: radius_a,
+            },
+            Core::Segment {
+                start: start_b,
+                end: end_b,
+                radius: radius_b,
+            },
+        ) => {
+            let (closest_a, closest_b) =
+                closest_points_between_segments(start_a, end_a, start_b, end_b);
+            let delta = closest_a - closest_b;
+            let distance = delta.len();
+
+            if radius_a + radius_b <= distance {
+                return None;
+            }
+
+            let normal = if f32::EPSILON < distance {
+                delta / distance
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            };
+
+            Some(ContactPoint {
+                normal,
+                point_a: closest_a - normal * radius_a,
+                point_b: closest_b + normal * radius_b,
+                depth: radius_a + radius_b - distance,
+            })
+        }
+        (Core::Box { half_extents }, Core::Segment { start, end, radius }) => {
+            collide_box_segment(a, half_extents, start, end, radius).map(flip)
+        }
+        (Core::Segment { start, end, radius }, Core::Box { half_extents }) => {
+            collide_box_segment(b, half_extents, start, end, radius)
+        }
+        (
+            Core::Box {
+                half_extents: half_extents_a,
+            },
+            Core::Box {
+                half_extents: half_extents_b,
+            },
+        ) => {
+            let a_in_b = deepest_corner(a, half_extents_a, b, half_extents_b);
+            let b_in_a = deepest_corner(b, half_extents_b, a, half_extents_a).map(flip);
+
+            match (a_in_b, b_in_a) {
+                (Some(lhs), Some(rhs)) if lhs.depth < rhs.depth => Some(rhs),
+                (Some(lhs), _) => Some(lhs),
+                (None, rhs) => rhs,
+            }
+        }
+    }
+}
+
+fn flip(contact: ContactPoint) -> ContactPoint {
+    ContactPoint {
+        normal: -contact.normal,
+        point_a: contact.point_b,
+        point_b: contact.point_a,
+        depth: contact.depth,
+    }
+}
+
+/// Collides a swept sphere with a box. The contact is oriented from the box to the swept sphere.
+fn collide_box_segment(
+    collider: &Collider,
+    half_extents: Vec3,
+    start: Vec3,
+    end: Vec3,
+    radius: f32,
+) -> Option<ContactPoint> {
+    let start = collider.inverse_transform_point(start);
+    let end = collider.inverse_transform_point(end);
+    let direction = end - start;
+    let len_square = direction.len_square();
+    let clamp_to_box = |point: Vec3| Vec3::min(Vec3::max(point, -half_extents), half_extents);
+
+    // Alternates projections between the segment and the box, converging to their closest points.
+    let mut t = 0.5;
+
+    for _ in 0..8 {
+        if len_square <= f32::EPSILON {
+            t = 0.0;
+            break;
+        }
+
+        let on_box = clamp_to_box(start + direction * t);
+        t = (Vec3::dot(on_box - start, direction) / len_square).clamp(0.0, 1.0);
+    }
+
+    let on_segment = start + direction * t;
+    let on_box = clamp_to_box(on_segment);
+    let delta = on_segment - on_box;
+    let distance = delta.len();
+
+    let (normal, depth, on_box) = if f32::EPSILON < distance {
+        if radius <= distance {
+            return None;
+        }
+
+        (delta / distance, radius - distance, on_box)
+    } else {
+        // The segment is inside the box; pushes it out through the nearest face.
+        let (normal, face_distance) = nearest_face(on_segment, half_extents);
+        let mut on_face = on_segment;
+
+        match () {
+            _ if normal.x != 0.0 => on_face.x = half_extents.x * normal.x,
+            _ if normal.y != 0.0 => on_face.y = half_extents.y * normal.y,
+            _ => on_face.z = half_extents.z * normal.z,
+        }
+
+        (normal, face_distance + radius, on_face)
+    };
+
+    Some(ContactPoint {
+        normal: collider.rotation * normal,
+        point_a: collider.transform_point(on_segment - normal * radius),
+        point_b: collider.transform_point(on_box),
+        depth,
+    })
+}
+
+/// Finds the corner of the first box deepest inside the second one.
+fn deepest_corner(
+    a: &Collider,
+    half_extents_a: Vec3,
+    b: &Collider,
+    half_extents_b: Vec3,
+) -> Option<ContactPoint> {
+    let mut deepest: Option<ContactPoint> = None;
+
+    for corner in 0..8 {
+        let sign = |bit: u32| if corner & bit == 0 { -1.0 } else { 1.0 };
+        let corner = a.transform_point(Vec3::new(
+            half_extents_a.x * sign(1),
+            half_extents_a.y * sign(2),
+            half_extents_a.z * sign(4),
+        ));
+        let local = b.inverse_transform_point(corner);
+
+        if half_extents_b.x <= local.x.abs()
+            || half_extents_b.y <= local.y.abs()
+            || half_extents_b.z <= local.z.abs()
+        {
+            continue;
+        }
+
+        let (normal, depth) = nearest_face(local, half_extents_b);
+
+        if deepest.is_some_and(|deepest| depth <= deepest.depth) {
+            continue;
+        }
+
+        let normal = b.rotation * normal;
+        deepest = Some(ContactPoint {
+            normal,
+            point_a: corner,
+            point_b: corner + normal * depth,
+            depth,
+        });
+    }
+
+    deepest
+}
+
+/// Returns the outward normal of the box face nearest to the point inside it, and the distance to it.
+fn nearest_face(point: Vec3, half_extents: Vec3) -> (Vec3, f32) {
+    let distances = [
+        half_extents.x - point.x.abs(),
+        half_extents.y - point.y.abs(),
+        half_extents.z - point.z.abs(),
+    ];
+    let sign = |value: f32| if value < 0.0 { -1.0 } else { 1.0 };
+
+    if distances[0] <= distances[1] && distances[0] <= distances[2] {
+        (Vec3::new(sign(point.x), 0.0, 0.0), distances[0])
+    } else if distances[1] <= distances[2] {
+        (Vec3::new(0.0, sign(point.y), 0.0), distances[1])
+    } else {
+        (Vec3::new(0.0, 0.0, sign(point.z)), distances[2])
+    }
+}
+
+/// Returns the closest points on the segments `start_a..end_a` and `start_b..end_b`.
+fn closest_points_between_segments(
+    start_a: Vec3,
+    end_a: Vec3,
+    start_b: Vec3,
+    end_b: Vec3,
+) -> (Vec3, Vec3) {
+    let direction_a = end_a - start_a;
+    let direction_b = end_b - start_b;
+    let offset = start_a - start_b;
+    let len_square_a = direction_a.len_square();
+    let len_square_b = direction_b.len_square();
+    let f = Vec3::dot(direction_b, offset);
+
+    let (s, t) = if len_square_a <= f32::EPSILON && len_square_b <= f32::EPSILON {
+        (0.0, 0.0)
+    } else if len_square_a <= f32::EPSILON {
+        (0.0, (f / len_square_b).clamp(0.0, 1.0))
+    } else {
+        let c = Vec3::dot(direction_a, offset);
+
+        if len_square_b <= f32::EPSILON {
+            ((-c / len_square_a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = Vec3::dot(direction_a, direction_b);
+            let denominator = len_square_a * len_square_b - b * b;
+            let s = if denominator != 0.0 {
+                ((b * f - c * len_square_b) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / len_square_b;
+
+            if t < 0.0 {
+                ((-c / len_square_a).clamp(0.0, 1.0), 0.0)
+            } else if 1.0 < t {
+                (((b - c) / len_square_a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    (start_a + direction_a * s, start_b + direction_b * t)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn equals_float(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-5
+    }
+
+    fn collider(shape: RigidBodyShape, position: Vec3, rotation: Quat) -> Collider {
+        Collider {
+            shape,
+            position,
+            rotation,
+        }
+    }
+
+    #[test]
+    fn check_swept_sphere_contacts() {
+        let sphere = collider(
+            RigidBodyShape::Sphere { radius: 1.0 },
+            Vec3::new(0.0, 2.5, 0.0),
+            Quat::IDENTITY,
+        );
+        // lying along the X axis
+        let capsule = collider(
+            RigidBodyShape::Capsule {
+                radius: 1.0,
+                height: 4.0,
+            },
+            Vec3::new(1.5, 1.0, 0.0),
+            Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), FRAC_PI_2),
+        );
+
+        let contact = collide(&sphere, &capsule).unwrap();
+        assert!(equals_float(contact.depth, 0.5));
+        assert!(equals_float(contact.normal.y, 1.0));
+        assert!(equals_float(contact.point_a.y, 1.5));
+        assert!(equals_float(contact.point_b.y, 2.0));
+
+        let far_sphere = collider(
+            RigidBodyShape::Sphere { radius: 1.0 },
+            Vec3::new(0.0, 3.5, 0.0),
+            Quat::IDENTITY,
+        );
+        assert!(collide(&far_sphere, &capsule).is_none());
+    }
+
+    #[test]
+    fn check_box_contacts() {
+        let floor = collider(
+            RigidBodyShape::Box {
+                half_extents: [10.0, 1.0, 10.0],
+            },
+            Vec3::ZERO,
+            Quat::IDENTITY,
+        );
+        let sphere = collider(
+            RigidBodyShape::Sphere { radius: 0.5 },
+            Vec3::new(2.0, 1.25, 0.0),
+            Quat::IDENTITY,
+        );
+
+        let contact = collide(&floor, &sphere).unwrap();
+        assert!(equals_float(contact.depth, 0.25));
+        assert!(equals_float(contact.normal.y, -1.0));
+
+        // a sphere center inside the box is pushed out through the nearest face
+        let sunken = collider(
+            RigidBodyShape::Sphere { radius: 0.5 },
+            Vec3::new(9.5, 0.0, 0.0),
+            Quat::IDENTITY,
+        );
+        let contact = collide(&sunken, &floor).unwrap();
+        assert!(equals_float(contact.depth, 1.0));
+        assert!(equals_float(contact.normal.x, 1.0));
+
+        let cube = collider(
+            RigidBodyShape::Box {
+                half_extents: [0.5, 0.5, 0.5],
+            },
+            Vec3::new(0.0, 1.4, 0.0),
+            Quat::IDENTITY,
+        );
+        let contact = collide(&cube, &floor).unwrap();
+        assert!(equals_float(contact.depth, 0.1));
+        assert!(equals_float(contact.normal.y, 1.0));
+    }
+}
@@ -0,0 +1,5 @@
+mod collision;
+mod model_physics;
+mod physics_world;
+
+pub use model_physics::*;
@@ -0,0 +1,241 @@
+use super::physics_world::{rigid_body_rotation, PhysicsWorld, PHYSICS_TIMESTEP};
+use crate::{
+    animation::SkeletonPose,
+    math::{Mat4, Vec3},
+};
+use asset::assets::{Node, RigidBody, RigidBodyJoint, RigidBodyMode};
+use specs::{prelude::*, Component};
+
+/// Maximum number of simulation steps per update. Time beyond it is dropped to keep up with slow frames.
+const MAX_STEP_COUNT: u32 = 4;
+
+/// Simulates the rigid bodies of a model, such as hair and skirts, on a fixed timestep.
+/// Bodies following bones are moved by the [`SkeletonPose`] of the same object,
+/// while simulated bodies write back into it between the deformation before and after physics.
+#[derive(Component)]
+#[storage(HashMapStorage)]
+pub struct ModelPhysics {
+    world: PhysicsWorld,
+    bodies: Vec<BoundBody>,
+    /// Simulated bodies attached to bones, parents first.
+    write_order: Vec<usize>,
+    accumulated_time: f32,
+    is_reset_pending: bool,
+}
+
+struct BoundBody {
+    node_index: Option<u32>,
+    mode: RigidBodyMode,
+    /// The body matrix relative to the bone matrix.
+    offset: Mat4,
+    inverse_offset: Mat4,
+}
+
+impl ModelPhysics {
+    /// Creates the simulation of the given bodies in the rest pose of the nodes,
+    /// usually [`asset::assets::ModelAsset::rigid_bodies`], [`asset::assets::ModelAsset::rigid_body_joints`]
+    /// and [`asset::assets::ModelAsset::nodes`]. Joints referring to missing bodies are ignored.
+    pub fn new(nodes: &[Node], rigid_bodies: &[RigidBody], joints: &[RigidBodyJoint]) -> Self {
+        let rest_pose = SkeletonPose::new(nodes);
+        let mut world = PhysicsWorld::new();
+        let bodies = Vec::from_iter(rigid_bodies.iter().map(|body| {
+            let node_index = body
+                .node_index
+                .filter(|&index| (index as usize) < nodes.len());
+            let position = Vec3::from(body.position);
+            let rotation = rigid_body_rotation(body.rotation);
+            let matrix = Mat4::srt(position, rotation, Vec3::ONE);
+            let offset = match node_index {
+                Some(index) => matrix * rest_pose.matrix(index).inversed(),
+                None => matrix,
+            };
+            world.add_body(body, position, rotation);
+
+            BoundBody {
+                node_index,
+                mode: body.mode,
+                inverse_offset: offset.inversed(),
+                offset,
+            }
+        }));
+
+        for joint in joints {
+            if joint
+                .rigid_body_indices
+                .iter()
+                .all(|&index| (index as usize) < bodies.len())
+            {
+                world.add_joint(joint);
+            }
+        }
+
+        let node_depth = |mut index: u32| {
+            let mut depth = 0;
+
+            while let Some(parent_index) = nodes[index as usize].parent_index {
+                index = parent_index;
+                depth += 1;
+            }
+
+            depth
+        };
+        let mut write_order = Vec::from_iter(
+            (0..bodies.len())
+                .filter(|&index| bodies[index].node_index.is_some() && !world.is_kinematic(index)),
+        );
+        write_order.sort_by_key(|&index| (node_depth(bodies[index].node_index.unwrap()), index));
+
+        Self {
+            world,
+            bodies,
+            write_order,
+            accumulated_time: 0.0,
+            is_reset_pending: true,
+        }
+    }
+
+    /// Model space gravity. Defaults to `(0, -98, 0)`, matching MikuMikuDance.
+    pub fn gravity(&self) -> Vec3 {
+        self.world.gravity()
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vec3) {
+        self.world.set_gravity(gravity);
+    }
+
+    /// Snaps every body to its bone on the next update, such as after the model is teleported.
+    pub fn reset(&mut self) {
+        self.is_reset_pending = true;
+    }
+
+    /// Advances the simulation by `delta` seconds, then writes the simulated bodies into the pose.
+    /// The pose should have been deformed up to physics, see [`SkeletonPose::evaluate_before_physics`].
+    pub fn update(&mut self, delta: f32, pose: &mut SkeletonPose) {
+        if self.is_reset_pending {
+            self.is_reset_pending = false;
+            self.accumulated_time = 0.0;
+
+            for (index, body) in self.bodies.iter().enumerate() {
+                if let Some(node_index) = body.node_index {
+                    let (position, rotation, _) =
+                        (body.offset.clone() * pose.matrix(node_index)).split();
+                    self.world.teleport(index, position, rotation);
+                }
+            }
+        }
+
+        for (index, body) in self.bodies.iter().enumerate() {
+            if let Some(node_index) = body.node_index.filter(|_| self.world.is_kinematic(index)) {
+                let (position, rotation, _) =
+                    (body.offset.clone() * pose.matrix(node_index)).split();
+                self.world.set_kinematic_target(index, position, rotation);
+            }
+        }
+
+        self.accumulated_time += delta.max(0.0);
+        let step_count = (self.accumulated_time / PHYSICS_TIMESTEP) as u32;
+        self.accumulated_time -= step_count as f32 * PHYSICS_TIMESTEP;
+
+        if step_count != 0 {
+            self.world.simulate(step_count.min(MAX_STEP_COUNT));
+        }
+
+        for &index in &self.write_order {
+            let body = &self.bodies[index];
+            let node_index = body.node_index.unwrap();
+            let body_matrix = Mat4::srt(
+                self.world.position(index),
+                self.world.rotation(index),
+                Vec3::ONE,
+            );
+            let matrix = &body.inverse_offset * body_matrix;
+            let matrix = match body.mode {
+                RigidBodyMode::PhysicsWithBone => {
+                    let (_, rotation, _) = matrix.split();
+                    let (position, _, _) = pose.matrix(node_index).split();
+                    Mat4::srt(position, rotation, Vec3::ONE)
+                }
+                _ => matrix,
+            };
+
+            pose.set_matrix(node_index, matrix);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use asset::assets::{NodeTransform, RigidBodyShape};
+
+    fn create_node(index: u32, parent_index: Option<u32>, position: [f32; 3]) -> Node {
+        let mut matrix = Mat4::identity().elements;
+        matrix[12..15].copy_from_slice(&position);
+
+        Node {
+            index,
+            name: format!("bone{}", index),
+            transform: NodeTransform { matrix },
+            parent_index,
+            children_indices: if index == 0 { vec![1] } else { vec![] },
+            mesh_indices: vec![],
+            bone: None,
+        }
+    }
+
+    fn create_body(node_index: u32, position: [f32; 3], mode: RigidBodyMode) -> RigidBody {
+        RigidBody {
+            name: String::new(),
+            node_index: Some(node_index),
+            shape: RigidBodyShape::Sphere { radius: 0.5 },
+            position,
+            rotation: [0.0; 3],
+            group: 0,
+            collision_mask: 0xffff,
+            mass: 1.0,
+            linear_damping: 0.5,
+            angular_damping: 0.5,
+            restitution: 0.0,
+            friction: 0.5,
+            mode,
+        }
+    }
+
+    #[test]
+    fn check_simulated_bodies_drive_bones() {
+        // a tail sticking out along the X axis, hinged at its root
+        let nodes = [
+            create_node(0, None, [0.0; 3]),
+            create_node(1, Some(0), [2.0, 0.0, 0.0]),
+        ];
+        let bodies = [
+            create_body(0, [0.0; 3], RigidBodyMode::FollowBone),
+            create_body(1, [2.0, 0.0, 0.0], RigidBodyMode::Physics),
+        ];
+        let joints = [RigidBodyJoint {
+            name: String::new(),
+            rigid_body_indices: [0, 1],
+            position: [0.0; 3],
+            rotation: [0.0; 3],
+            translation_limit_min: [0.0; 3],
+            translation_limit_max: [0.0; 3],
+            rotation_limit_min: [1.0; 3],
+            rotation_limit_max: [-1.0; 3],
+            translation_stiffness: [0.0; 3],
+            rotation_stiffness: [0.0; 3],
+        }];
+        let mut pose = SkeletonPose::new(&nodes);
+        let mut physics = ModelPhysics::new(&nodes, &bodies, &joints);
+
+        for _ in 0..30 {
+            pose.evaluate_before_physics();
+            physics.update(1.0 / 60.0, &mut pose);
+            pose.evaluate_after_physics();
+        }
+
+        // the tail falls under gravity, staying attached to its root
+        let (position, _, _) = pose.matrix(1).split();
+        assert!(position.y < -0.5);
+        assert!((position.len() - 2.0).abs() < 0.05);
+    }
+}
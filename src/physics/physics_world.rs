@@ -0,0 +1,754 @@
+use super::collision::{collide, Collider};
+use crate::math::{Quat, Vec3};
+use asset::assets::{RigidBody, RigidBodyJoint, RigidBodyMode, RigidBodyShape};
+use std::collections::HashSet;
+
+/// Length of a simulation step, in seconds.
+pub const PHYSICS_TIMESTEP: f32 = 1.0 / 60.0;
+/// Number of substeps each simulation step is divided into.
+const SUBSTEP_COUNT: u32 = 8;
+
+/// A rigid body simulation based on extended position based dynamics (XPBD), in model space.
+/// Bodies are processed in the order they are added, so the simulation is deterministic.
+pub(crate) struct PhysicsWorld {
+    gravity: Vec3,
+    bodies: Vec<Body>,
+    joints: Vec<Joint>,
+    /// Pairs of jointed bodies, which do not collide with each other.
+    jointed_pairs: HashSet<(usize, usize)>,
+    contacts: Vec<Contact>,
+}
+
+struct Body {
+    collider: Collider,
+    bounding_radius: f32,
+    /// Kinematic bodies move toward their target instead of being simulated.
+    is_kinematic: bool,
+    inverse_mass: f32,
+    /// The diagonal of the inverse inertia tensor, in body space.
+    inverse_inertia: Vec3,
+    previous_position: Vec3,
+    previous_rotation: Quat,
+    linear_velocity: Vec3,
+    angular_velocity: Vec3,
+    linear_damping: f32,
+    angular_damping: f32,
+    restitution: f32,
+    friction: f32,
+    group: u8,
+    collision_mask: u16,
+    target_position: Vec3,
+    target_rotation: Quat,
+}
+
+impl Body {
+    fn position(&self) -> Vec3 {
+        self.collider.position
+    }
+
+    fn rotation(&self) -> Quat {
+        self.collider.rotation
+    }
+
+    fn apply_inverse_inertia(&self, vec: Vec3) -> Vec3 {
+        let rotation = self.rotation();
+        rotation * ((rotation.inverted() * vec) * self.inverse_inertia)
+    }
+
+    /// Inverse of the mass perceived at `offset` from the center along `normal`.
+    fn generalized_inverse_mass(&self, offset: Vec3, normal: Vec3) -> f32 {
+        if self.is_kinematic {
+            return 0.0;
+        }
+
+        let torque = Vec3::cross(offset, normal);
+        self.inverse_mass + Vec3::dot(torque, self.apply_inverse_inertia(torque))
+    }
+
+    fn apply_impulse(&mut self, offset: Vec3, impulse: Vec3) {
+        if self.is_kinematic {
+            return;
+        }
+
+        self.linear_velocity += impulse * self.inverse_mass;
+        self.angular_velocity += self.apply_inverse_inertia(Vec3::cross(offset, impulse));
+    }
+
+    fn apply_correction(&mut self, offset: Vec3, correction: Vec3) {
+        if self.is_kinematic {
+            return;
+        }
+
+        self.collider.position += correction * self.inverse_mass;
+        self.rotate(self.apply_inverse_inertia(Vec3::cross(offset, correction)));
+    }
+
+    fn apply_angular_correction(&mut self, correction: Vec3) {
+        if self.is_kinematic {
+            return;
+        }
+
+        self.rotate(self.apply_inverse_inertia(correction));
+    }
+
+    /// Rotates by the small rotation vector.
+    fn rotate(&mut self, rotation_vector: Vec3) {
+        let rotation = self.rotation();
+        let delta = Quat {
+            x: rotation_vector.x,
+            y: rotation_vector.y,
+            z: rotation_vector.z,
+            w: 0.0,
+        } * rotation;
+        self.collider.rotation = Quat {
+            x: rotation.x + delta.x * 0.5,
+            y: rotation.y + delta.y * 0.5,
+            z: rotation.z + delta.z * 0.5,
+            w: rotation.w + delta.w * 0.5,
+        }
+        .normalized();
+    }
+}
+
+/// A 6-DOF spring joint. Frames are relative to each body.
+struct Joint {
+    bodies: [usize; 2],
+    frame_positions: [Vec3; 2],
+    frame_rotations: [Quat; 2],
+    translation_limit_min: [f32; 3],
+    translation_limit_max: [f32; 3],
+    rotation_limit_min: [f32; 3],
+    rotation_limit_max: [f32; 3],
+    translation_stiffness: [f32; 3],
+    rotation_stiffness: [f32; 3],
+}
+
+struct Contact {
+    bodies: [usize; 2],
+    /// Points from the second body to the first one.
+    normal: Vec3,
+    /// Contact points relative to each body, in body space.
+    offsets: [Vec3; 2],
+    /// Relative normal velocity before the substep, for restitution.
+    normal_velocity: f32,
+    lambda: f32,
+}
+
+impl PhysicsWorld {
+    pub fn new() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -98.0, 0.0),
+            bodies: vec![],
+            joints: vec![],
+            jointed_pairs: HashSet::new(),
+            contacts: vec![],
+        }
+    }
+
+    pub fn gravity(&self) -> Vec3 {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vec3) {
+        self.gravity = gravity;
+    }
+
+    /// Adds a body at the given pose. Bodies following bones, or without mass, are kinematic.
+    pub fn add_body(&mut self, body: &RigidBody, position: Vec3, rotation: Quat) -> usize {
+        let is_kinematic = body.mode == RigidBodyMode::FollowBone || body.mass <= 0.0;
+        let collider = Collider {
+            shape: body.shape,
+            position,
+            rotation,
+        };
+        let inverse_inertia = if is_kinematic {
+            Vec3::ZERO
+        } else {
+            let inertia = shape_inertia(&body.shape, body.mass);
+            Vec3::new(
+                recip_or_zero(inertia.x),
+                recip_or_zero(inertia.y),
+                recip_or_zero(inertia.z),
+            )
+        };
+
+        self.bodies.push(Body {
+            bounding_radius: collider.bounding_radius(),
+            collider,
+            is_kinematic,
+            inverse_mass: if is_kinematic { 0.0 } else { body.mass.recip() },
+            inverse_inertia,
+            previous_position: position,
+            previous_rotation: rotation,
+            linear_velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+            linear_damping: body.linear_damping.clamp(0.0, 1.0),
+            angular_damping: body.angular_damping.clamp(0.0, 1.0),
+            restitution: body.restitution,
+            friction: body.friction,
+            group: body.group,
+            collision_mask: body.collision_mask,
+            target_position: position,
+            target_rotation: rotation,
+        });
+        self.bodies.len() - 1
+    }
+
+    /// Adds a joint between two bodies, taking their current poses as the rest pose.
+    pub fn add_joint(&mut self, joint: &RigidBodyJoint) {
+        let [a, b] = joint.rigid_body_indices.map(|index| index as usize);
+        let position = Vec3::from(joint.position);
+        let rotation = rigid_body_rotation(joint.rotation);
+        let frame = |body: &Body| {
+            let inverse = body.rotation().inverted();
+            (inverse * (position - body.position()), inverse * rotation)
+        };
+        let (frame_position_a, frame_rotation_a) = frame(&self.bodies[a]);
+        let (frame_position_b, frame_rotation_b) = frame(&self.bodies[b]);
+
+        self.joints.push(Joint {
+            bodies: [a, b],
+            frame_positions: [frame_position_a, frame_position_b],
+            frame_rotations: [frame_rotation_a, frame_rotation_b],
+            translation_limit_min: joint.translation_limit_min,
+            translation_limit_max: joint.translation_limit_max,
+            rotation_limit_min: joint.rotation_limit_min,
+            rotation_limit_max: joint.rotation_limit_max,
+            translation_stiffness: joint.translation_stiffness,
+            rotation_stiffness: joint.rotation_stiffness,
+        });
+        self.jointed_pairs.insert((a.min(b), a.max(b)));
+    }
+
+    pub fn position(&self, index: usize) -> Vec3 {
+        self.bodies[index].position()
+    }
+
+    pub fn rotation(&self, index: usize) -> Quat {
+        self.bodies[index].rotation()
+    }
+
+    pub fn is_kinematic(&self, index: usize) -> bool {
+        self.bodies[index].is_kinematic
+    }
+
+    /// Sets the pose the kinematic body reaches at the end of the next simulation.
+    pub fn set_kinematic_target(&mut self, index: usize, position: Vec3, rotation: Quat) {
+        let body = &mut self.bodies[index];
+        body.target_position = position;
+        body.target_rotation = rotation;
+    }
+
+    /// Moves the body to the pose immediately, stopping it.
+    pub fn teleport(&mut self, index: usize, position: Vec3, rotation: Quat) {
+        let body = &mut self.bodies[index];
+        body.collider.position = position;
+        body.collider.rotation = rotation;
+        body.previous_position = position;
+        body.previous_rotation = rotation;
+        body.target_position = position;
+        body.target_rotation = rotation;
+        body.linear_velocity = Vec3::ZERO;
+        body.angular_velocity = Vec3::ZERO;
+    }
+
+    /// Advances the simulation by the number of [`PHYSICS_TIMESTEP`] steps.
+    /// Kinematic bodies are interpolated toward their targets over the whole duration.
+    pub fn simulate(&mut self, step_count: u32) {
+        let substep_count = step_count * SUBSTEP_COUNT;
+        let h = PHYSICS_TIMESTEP / SUBSTEP_COUNT as f32;
+        let kinematic_starts = Vec::from_iter(
+            self.bodies
+                .iter()
+                .map(|body| (body.position(), body.rotation())),
+        );
+
+        for substep in 0..substep_count {
+            let t = (substep + 1) as f32 / substep_count as f32;
+
+            for (body, &(start_position, start_rotation)) in
+                self.bodies.iter_mut().zip(&kinematic_starts)
+            {
+                body.previous_position = body.position();
+                body.previous_rotation = body.rotation();
+
+                if body.is_kinematic {
+                    body.collider.position =
+                        Vec3::lerp_unclamped(start_position, body.target_position, t);
+                    body.collider.rotation = Quat::slerp(start_rotation, body.target_rotation, t);
+                    continue;
+                }
+
+                body.linear_velocity += self.gravity * h;
+                body.collider.position += body.linear_velocity * h;
+                body.rotate(body.angular_velocity * h);
+            }
+
+            self.find_contacts();
+
+            for index in 0..self.joints.len() {
+                self.solve_joint(index, h);
+            }
+
+            for index in 0..self.contacts.len() {
+                self.solve_contact(index, h);
+            }
+
+            for body in &mut self.bodies {
+                body.linear_velocity = (body.position() - body.previous_position) / h;
+
+                let delta = body.rotation() * body.previous_rotation.inverted();
+                let angular_velocity = Vec3::new(delta.x, delta.y, delta.z) * (2.0 / h);
+                body.angular_velocity = if delta.w < 0.0 {
+                    -angular_velocity
+                } else {
+                    angular_velocity
+                };
+            }
+
+            for index in 0..self.contacts.len() {
+                self.solve_contact_velocity(index, h);
+            }
+
+            for body in &mut self.bodies {
+                if !body.is_kinematic {
+                    body.linear_velocity *= (1.0 - body.linear_damping).powf(h);
+                    body.angular_velocity *= (1.0 - body.angular_damping).powf(h);
+                }
+            }
+        }
+    }
+
+    fn find_contacts(&mut self) {
+        self.contacts.clear();
+
+        for a in 0..self.bodies.len() {
+            for b in a + 1..self.bodies.len() {
+                let body_a = &self.bodies[a];
+                let body_b = &self.bodies[b];
+
+                if body_a.is_kinematic && body_b.is_kinematic
+                    || body_a.collision_mask & (1 << body_b.group) == 0
+                    || body_b.collision_mask & (1 << body_a.group) == 0
+                    || self.jointed_pairs.contains(&(a, b))
+                {
+                    continue;
+                }
+
+                let reach = body_a.bounding_radius + body_b.bounding_radius;
+
+                if reach * reach <= Vec3::distance_square(body_a.position(), body_b.position()) {
+                    continue;
+                }
+
+                let contact = match collide(&body_a.collider, &body_b.collider) {
+                    Some(contact) => contact,
+                    None => continue,
+                };
+                let offset_a = contact.point_a - body_a.position();
+                let offset_b = contact.point_b - body_b.position();
+                let velocity = (body_a.linear_velocity
+                    + Vec3::cross(body_a.angular_velocity, offset_a))
+                    - (body_b.linear_velocity + Vec3::cross(body_b.angular_velocity, offset_b));
+
+                self.contacts.push(Contact {
+                    bodies: [a, b],
+                    normal: contact.normal,
+                    offsets: [
+                        body_a.rotation().inverted() * offset_a,
+                        body_b.rotation().inverted() * offset_b,
+                    ],
+                    normal_velocity: Vec3::dot(contact.normal, velocity),
+                    lambda: 0.0,
+                });
+            }
+        }
+    }
+
+    fn solve_joint(&mut self, index: usize, h: f32) {
+        let joint = &self.joints[index];
+        let [a, b] = joint.bodies;
+        let translation_limit = (joint.translation_limit_min, joint.translation_limit_max);
+        let rotation_limit = (joint.rotation_limit_min, joint.rotation_limit_max);
+        let translation_stiffness = joint.translation_stiffness;
+        let rotation_stiffness = joint.rotation_stiffness;
+        let frame_positions = joint.frame_positions;
+        let frame_rotations = joint.frame_rotations;
+
+        let frame_rotation = |bodies: &[Body], body: usize, side: usize| {
+            bodies[body].rotation() * frame_rotations[side]
+        };
+        let anchor = |bodies: &[Body], body: usize, side: usize| {
+            bodies[body].position() + bodies[body].rotation() * frame_positions[side]
+        };
+
+        // Rotation limits, in euler angles of the second frame relative to the first one.
+        let relative_angles = |bodies: &[Body]| {
+            (frame_rotation(bodies, a, 0).inverted() * frame_rotation(bodies, b, 1))
+                .into_xyz_angles()
+        };
+        let angles = relative_angles(&self.bodies);
+        let clamped = clamp_limit(angles, rotation_limit);
+
+        if angles != clamped {
+            let target = frame_rotation(&self.bodies, a, 0) * Quat::from_xyz_angles(clamped);
+            let error = rotation_vector(frame_rotation(&self.bodies, b, 1) * target.inverted());
+            self.apply_angular_correction(b, a, error, 0.0, h);
+        }
+
+        for axis in 0..3 {
+            if rotation_stiffness[axis] <= 0.0 {
+                continue;
+            }
+
+            let angles = relative_angles(&self.bodies);
+            let mut rest = angles;
+            rest[axis] = 0.0;
+            let target = frame_rotation(&self.bodies, a, 0) * Quat::from_xyz_angles(rest);
+            let error = rotation_vector(frame_rotation(&self.bodies, b, 1) * target.inverted());
+            self.apply_angular_correction(b, a, error, rotation_stiffness[axis].recip(), h);
+        }
+
+        // Translation limits, along the axes of the first frame.
+        let anchor_a = anchor(&self.bodies, a, 0);
+        let anchor_b = anchor(&self.bodies, b, 1);
+        let rotation_a = frame_rotation(&self.bodies, a, 0);
+        let offset = rotation_a.inverted() * (anchor_b - anchor_a);
+        let clamped = clamp_limit([offset.x, offset.y, offset.z], translation_limit);
+
+        if [offset.x, offset.y, offset.z] != clamped {
+            let target = anchor_a + rotation_a * Vec3::from(clamped);
+            self.apply_correction(b, anchor_b, a, target, anchor_b - target, 0.0, h);
+        }
+
+        for axis in 0..3 {
+            if translation_stiffness[axis] <= 0.0 {
+                continue;
+            }
+
+            let anchor_a = anchor(&self.bodies, a, 0);
+            let anchor_b = anchor(&self.bodies, b, 1);
+            let mut direction = [0.0; 3];
+            direction[axis] = 1.0;
+            let direction = frame_rotation(&self.bodies, a, 0) * Vec3::from(direction);
+            let error = direction * Vec3::dot(anchor_b - anchor_a, direction);
+            self.apply_correction(
+                b,
+                anchor_b,
+                a,
+                anchor_a,
+                error,
+                translation_stiffness[axis].recip(),
+                h,
+            );
+        }
+    }
+
+    fn solve_contact(&mut self, index: usize, h: f32) {
+        let contact = &self.contacts[index];
+        let [a, b] = contact.bodies;
+        let normal = contact.normal;
+        let point_a = self.bodies[a].collider.transform_point(contact.offsets[0]);
+        let point_b = self.bodies[b].collider.transform_point(contact.offsets[1]);
+        let depth = Vec3::dot(point_b - point_a, normal);
+
+        if depth <= 0.0 {
+            return;
+        }
+
+        let lambda = self.apply_correction(a, point_a, b, point_b, -normal * depth, 0.0, h);
+        self.contacts[index].lambda = lambda;
+    }
+
+    fn solve_contact_velocity(&mut self, index: usize, h: f32) {
+        let contact = &self.contacts[index];
+
+        if contact.lambda == 0.0 {
+            return;
+        }
+
+        let [a, b] = contact.bodies;
+        let (body_a, body_b) = (&self.bodies[a], &self.bodies[b]);
+        let normal = contact.normal;
+        let offset_a = body_a.rotation() * contact.offsets[0];
+        let offset_b = body_b.rotation() * contact.offsets[1];
+        let velocity = (body_a.linear_velocity + Vec3::cross(body_a.angular_velocity, offset_a))
+            - (body_b.linear_velocity + Vec3::cross(body_b.angular_velocity, offset_b));
+        let normal_velocity = Vec3::dot(normal, velocity);
+        let tangent_velocity = velocity - normal * normal_velocity;
+        let tangent_speed = tangent_velocity.len();
+
+        let mut delta = Vec3::ZERO;
+
+        if f32::EPSILON < tangent_speed {
+            let friction = body_a.friction * body_b.friction;
+            delta -= tangent_velocity
+                * ((friction * contact.lambda.abs() / h).min(tangent_speed) / tangent_speed);
+        }
+
+        if normal_velocity < 0.0 {
+            // Resting contacts do not bounce, to avoid jittering under gravity.
+            let restitution = if contact.normal_velocity.abs() <= 2.0 * self.gravity.len() * h {
+                0.0
+            } else {
+                body_a.restitution * body_b.restitution
+            };
+            delta +=
+                normal * (-normal_velocity + (-restitution * contact.normal_velocity).max(0.0));
+        }
+
+        let len = delta.len();
+
+        if len <= f32::EPSILON {
+            return;
+        }
+
+        let direction = delta / len;
+        let inverse_mass = body_a.generalized_inverse_mass(offset_a, direction)
+            + body_b.generalized_inverse_mass(offset_b, direction);
+
+        if inverse_mass <= 0.0 {
+            return;
+        }
+
+        let impulse = delta / inverse_mass;
+        self.bodies[a].apply_impulse(offset_a, impulse);
+        self.bodies[b].apply_impulse(offset_b, -impulse);
+    }
+
+    /// Moves the point of `a` and the point of `b` to reduce `error`, the displacement of the
+    /// former from the latter. Returns the Lagrange multiplier of the correction.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_correction(
+        &mut self,
+        a: usize,
+        point_a: Vec3,
+        b: usize,
+        point_b: Vec3,
+        error: Vec3,
+        compliance: f32,
+        h: f32,
+    ) -> f32 {
+        let magnitude = error.len();
+
+        if magnitude <= f32::EPSILON {
+            return 0.0;
+        }
+
+        let normal = error / magnitude;
+        let offset_a = point_a - self.bodies[a].position();
+        let offset_b = point_b - self.bodies[b].position();
+        let inverse_mass = self.bodies[a].generalized_inverse_mass(offset_a, normal)
+            + self.bodies[b].generalized_inverse_mass(offset_b, normal);
+        let compliance = compliance / (h * h);
+
+        if inverse_mass + compliance <= 0.0 {
+            return 0.0;
+        }
+
+        let lambda = -magnitude / (inverse_mass + compliance);
+        let correction = normal * lambda;
+        self.bodies[a].apply_correction(offset_a, correction);
+        self.bodies[b].apply_correction(offset_b, -correction);
+        lambda
+    }
+
+    /// Rotates `a` and `b` to reduce `error`, the rotation vector of the former from the latter.
+    fn apply_angular_correction(
+        &mut self,
+        a: usize,
+        b: usize,
+        error: Vec3,
+        compliance: f32,
+        h: f32,
+    ) {
+        let magnitude = error.len();
+
+        if magnitude <= f32::EPSILON {
+            return;
+        }
+
+        let normal = error / magnitude;
+        let inverse_mass = [a, b]
+            .map(|index| {
+                let body = &self.bodies[index];
+
+                if body.is_kinematic {
+                    0.0
+                } else {
+                    Vec3::dot(normal, body.apply_inverse_inertia(normal))
+                }
+            })
+            .iter()
+            .sum::<f32>();
+        let compliance = compliance / (h * h);
+
+        if inverse_mass + compliance <= 0.0 {
+            return;
+        }
+
+        let correction = normal * (-magnitude / (inverse_mass + compliance));
+        self.bodies[a].apply_angular_correction(correction);
+        self.bodies[b].apply_angular_correction(-correction);
+    }
+}
+
+/// Converts euler angles of rigid bodies and joints, applied around the Z axis, then X, then Y.
+pub(crate) fn rigid_body_rotation(angles: [f32; 3]) -> Quat {
+    Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), angles[1])
+        * Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), angles[0])
+        * Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), angles[2])
+}
+
+/// Clamps each value into its limit. An axis whose minimum exceeds its maximum is free.
+fn clamp_limit(values: [f32; 3], (min, max): ([f32; 3], [f32; 3])) -> [f32; 3] {
+    std::array::from_fn(|axis| {
+        if max[axis] < min[axis] {
+            values[axis]
+        } else {
+            values[axis].clamp(min[axis], max[axis])
+        }
+    })
+}
+
+/// Returns the axis scaled by the angle of the rotation, along the shortest path.
+fn rotation_vector(rotation: Quat) -> Vec3 {
+    let rotation = if rotation.w < 0.0 {
+        Quat {
+            x: -rotation.x,
+            y: -rotation.y,
+            z: -rotation.z,
+            w: -rotation.w,
+        }
+    } else {
+        rotation
+    };
+    let sin = Vec3::new(rotation.x, rotation.y, rotation.z).len();
+
+    if sin <= f32::EPSILON {
+        return Vec3::new(rotation.x, rotation.y, rotation.z) * 2.0;
+    }
+
+    let angle = 2.0 * sin.atan2(rotation.w);
+    Vec3::new(rotation.x, rotation.y, rotation.z) * (angle / sin)
+}
+
+/// The diagonal of the inertia tensor. Capsules are approximated by their bounding boxes.
+fn shape_inertia(shape: &RigidBodyShape, mass: f32) -> Vec3 {
+    let box_inertia = |half_extents: Vec3| {
+        let square = half_extents * half_extents;
+        Vec3::new(
+            square.y + square.z,
+            square.x + square.z,
+            square.x + square.y,
+        ) * (mass / 3.0)
+    };
+
+    match *shape {
+        RigidBodyShape::Sphere { radius } => Vec3::ONE * (0.4 * mass * radius * radius),
+        RigidBodyShape::Box { half_extents } => box_inertia(Vec3::from(half_extents)),
+        RigidBodyShape::Capsule { radius, height } => {
+            box_inertia(Vec3::new(radius, radius + height * 0.5, radius))
+        }
+    }
+}
+
+fn recip_or_zero(value: f32) -> f32 {
+    if value <= 0.0 {
+        0.0
+    } else {
+        value.recip()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_body(mode: RigidBodyMode, group: u8, collision_mask: u16) -> RigidBody {
+        RigidBody {
+            name: String::new(),
+            node_index: None,
+            shape: RigidBodyShape::Sphere { radius: 1.0 },
+            position: [0.0; 3],
+            rotation: [0.0; 3],
+            group,
+            collision_mask,
+            mass: 1.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            restitution: 0.0,
+            friction: 0.5,
+            mode,
+        }
+    }
+
+    fn create_pendulum() -> PhysicsWorld {
+        let mut world = PhysicsWorld::new();
+        world.add_body(
+            &create_body(RigidBodyMode::FollowBone, 0, 0xffff),
+            Vec3::ZERO,
+            Quat::IDENTITY,
+        );
+        world.add_body(
+            &create_body(RigidBodyMode::Physics, 0, 0xffff),
+            Vec3::new(4.0, 0.0, 0.0),
+            Quat::IDENTITY,
+        );
+        // the joint pins the bodies together at the anchor, allowing any rotation
+        world.add_joint(&RigidBodyJoint {
+            name: String::new(),
+            rigid_body_indices: [0, 1],
+            position: [0.0; 3],
+            rotation: [0.0; 3],
+            translation_limit_min: [0.0; 3],
+            translation_limit_max: [0.0; 3],
+            rotation_limit_min: [1.0; 3],
+            rotation_limit_max: [-1.0; 3],
+            translation_stiffness: [0.0; 3],
+            rotation_stiffness: [0.0; 3],
+        });
+        world
+    }
+
+    #[test]
+    fn check_joint_holds_pendulum() {
+        let mut world = create_pendulum();
+        world.simulate(30);
+
+        let position = world.position(1);
+        assert!(position.y < -1.0);
+        assert!((position.len() - 4.0).abs() < 0.05);
+        assert_eq!(world.position(0), Vec3::ZERO);
+
+        // the simulation is deterministic
+        let mut other = create_pendulum();
+        other.simulate(30);
+        assert_eq!(other.position(1), position);
+        assert_eq!(other.rotation(1), world.rotation(1));
+    }
+
+    #[test]
+    fn check_collision_masks_filter_pairs() {
+        let simulate = |collision_mask: u16| {
+            let mut world = PhysicsWorld::new();
+            world.set_gravity(Vec3::ZERO);
+            world.add_body(
+                &create_body(RigidBodyMode::Physics, 0, 0xffff),
+                Vec3::ZERO,
+                Quat::IDENTITY,
+            );
+            world.add_body(
+                &create_body(RigidBodyMode::Physics, 1, collision_mask),
+                Vec3::new(1.0, 0.0, 0.0),
+                Quat::IDENTITY,
+            );
+            world.simulate(1);
+            Vec3::distance(world.position(0), world.position(1))
+        };
+
+        // overlapping bodies are pushed apart only if both accept each other
+        assert!(1.99 < simulate(0xffff));
+        assert_eq!(simulate(!0b1), 1.0);
+    }
+}
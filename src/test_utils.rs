@@ -0,0 +1,46 @@
+use crate::{HeadlessEngine, HeadlessEngineConfig};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+use uuid::Uuid;
+
+/// Engines publish themselves through the global context, so tests using them run one at a time.
+static ENGINE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Creates a headless engine. Any adapter will do, including software ones such as llvmpipe,
+/// but the calling test fails if there is none.
+pub fn headless_engine(width: u32, height: u32) -> (HeadlessEngine, MutexGuard<'static, ()>) {
+    let guard = ENGINE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let engine = pollster::block_on(HeadlessEngine::new(HeadlessEngineConfig {
+        width,
+        height,
+        asset_base_path: std::env::temp_dir(),
+    }));
+
+    match engine {
+        Ok(engine) => (engine, guard),
+        Err(err) => panic!("failed to create a headless engine: {}", err),
+    }
+}
+
+/// A uniquely named directory under the system temporary directory, removed along with its content on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}